@@ -15,6 +15,29 @@ mod m20250808_000001_add_task_recurrence_and_activities;
 mod m20250119_000001_create_projectmind_system;
 mod m20250820_025047_make_created_by_nullable_in_project_context_categories;
 mod m20250820_215425_add_context_id_to_tasks;
+mod m20261018_000001_add_login_protection;
+mod m20261018_000002_add_user_status;
+mod m20261018_000003_add_magic_link_login;
+mod m20261018_000004_add_password_policy;
+mod m20261018_000005_add_project_roles;
+mod m20261018_000006_sync_permission_catalog;
+mod m20261018_000007_add_time_bound_grants;
+mod m20261018_000008_add_role_hierarchy;
+mod m20261018_000009_add_sensitive_field_permissions;
+mod m20261018_000010_add_organizations;
+mod m20261018_000011_add_teams;
+mod m20261018_000012_add_project_invitations;
+mod m20261018_000013_add_email_outbox;
+mod m20261018_000014_add_email_branding_and_locales;
+mod m20261018_000015_add_email_replies;
+mod m20261018_000016_add_notifications;
+mod m20261018_000017_add_digest_preferences;
+mod m20261018_000018_add_task_watchers;
+mod m20261018_000019_add_webhooks;
+mod m20261018_000020_add_threaded_comments;
+mod m20261018_000021_deny_unscoped_organization_rows;
+mod m20261018_000022_scope_user_permissions_to_organizations;
+mod m20261018_000023_scope_role_names_to_organizations;
 
 pub struct Migrator;
 
@@ -35,6 +58,29 @@ impl MigratorTrait for Migrator {
             Box::new(m20250119_000001_create_projectmind_system::Migration),
             Box::new(m20250820_025047_make_created_by_nullable_in_project_context_categories::Migration),
             Box::new(m20250820_215425_add_context_id_to_tasks::Migration),
+            Box::new(m20261018_000001_add_login_protection::Migration),
+            Box::new(m20261018_000002_add_user_status::Migration),
+            Box::new(m20261018_000003_add_magic_link_login::Migration),
+            Box::new(m20261018_000004_add_password_policy::Migration),
+            Box::new(m20261018_000005_add_project_roles::Migration),
+            Box::new(m20261018_000006_sync_permission_catalog::Migration),
+            Box::new(m20261018_000007_add_time_bound_grants::Migration),
+            Box::new(m20261018_000008_add_role_hierarchy::Migration),
+            Box::new(m20261018_000009_add_sensitive_field_permissions::Migration),
+            Box::new(m20261018_000010_add_organizations::Migration),
+            Box::new(m20261018_000011_add_teams::Migration),
+            Box::new(m20261018_000012_add_project_invitations::Migration),
+            Box::new(m20261018_000013_add_email_outbox::Migration),
+            Box::new(m20261018_000014_add_email_branding_and_locales::Migration),
+            Box::new(m20261018_000015_add_email_replies::Migration),
+            Box::new(m20261018_000016_add_notifications::Migration),
+            Box::new(m20261018_000017_add_digest_preferences::Migration),
+            Box::new(m20261018_000018_add_task_watchers::Migration),
+            Box::new(m20261018_000019_add_webhooks::Migration),
+            Box::new(m20261018_000020_add_threaded_comments::Migration),
+            Box::new(m20261018_000021_deny_unscoped_organization_rows::Migration),
+            Box::new(m20261018_000022_scope_user_permissions_to_organizations::Migration),
+            Box::new(m20261018_000023_scope_role_names_to_organizations::Migration),
         ]
     }
 }
\ No newline at end of file
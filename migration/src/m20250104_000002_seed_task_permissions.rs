@@ -2,7 +2,7 @@ use sea_orm_migration::prelude::*;
 use sea_orm::{ActiveModelTrait, Set, EntityTrait, ColumnTrait, QueryFilter, DbErr};
 use chrono::Utc;
 use uuid::Uuid;
-use crate::rbac_helpers::create_resource_with_admin_permissions;
+use crate::rbac_helpers::{create_resource_with_admin_permissions, find_role_id};
 
 #[derive(DeriveMigrationName)]
 pub struct Migration;
@@ -50,9 +50,7 @@ impl MigrationTrait for Migration {
         ).await?;
 
         // Get user role for basic permissions
-        let user_role = freshapi::entities::role::Entity::find()
-            .filter(freshapi::entities::role::Column::Name.eq("user"))
-            .one(db)
+        let user_role_id = find_role_id(db, "user")
             .await?
             .ok_or_else(|| DbErr::Custom("User role not found".to_string()))?;
 
@@ -75,7 +73,7 @@ impl MigrationTrait for Migration {
             if let Some(permission) = all_permissions.iter().find(|p| p.action == *action) {
                 // Check if assignment already exists
                 let existing = freshapi::entities::role_permission::Entity::find()
-                    .filter(freshapi::entities::role_permission::Column::RoleId.eq(user_role.id))
+                    .filter(freshapi::entities::role_permission::Column::RoleId.eq(user_role_id))
                     .filter(freshapi::entities::role_permission::Column::PermissionId.eq(permission.id))
                     .one(db)
                     .await?;
@@ -83,7 +81,7 @@ impl MigrationTrait for Migration {
                 if existing.is_none() {
                     let role_permission = freshapi::entities::role_permission::ActiveModel {
                         id: Set(Uuid::new_v4()),
-                        role_id: Set(user_role.id),
+                        role_id: Set(user_role_id),
                         permission_id: Set(permission.id),
                         created_at: Set(Utc::now().into()),
                     };
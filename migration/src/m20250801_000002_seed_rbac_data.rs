@@ -1,5 +1,5 @@
 use sea_orm_migration::prelude::*;
-use sea_orm::{ActiveModelTrait, Set, EntityTrait, ColumnTrait, QueryFilter, ConnectionTrait, DbErr, Value};
+use sea_orm::{ActiveModelTrait, Set, EntityTrait, ColumnTrait, QueryFilter, QuerySelect, ConnectionTrait, DbErr, Value};
 use chrono::Utc;
 use uuid::Uuid;
 use std::env;
@@ -38,8 +38,9 @@ impl MigrationTrait for Migration {
             is_active: Set(true),
             created_at: Set(Utc::now().into()),
             updated_at: Set(Utc::now().into()),
+            ..Default::default()
         };
-        super_admin_role.insert(db).await?;
+        freshapi::entities::role::Entity::insert(super_admin_role).exec(db).await?;
         println!("✅ Created role: super_admin (level 100)");
 
         let admin_role_id = Uuid::new_v4();
@@ -51,8 +52,9 @@ impl MigrationTrait for Migration {
             is_active: Set(true),
             created_at: Set(Utc::now().into()),
             updated_at: Set(Utc::now().into()),
+            ..Default::default()
         };
-        admin_role.insert(db).await?;
+        freshapi::entities::role::Entity::insert(admin_role).exec(db).await?;
         println!("✅ Created role: admin (level 50)");
 
         let user_role_id = Uuid::new_v4();
@@ -64,8 +66,9 @@ impl MigrationTrait for Migration {
             is_active: Set(true),
             created_at: Set(Utc::now().into()),
             updated_at: Set(Utc::now().into()),
+            ..Default::default()
         };
-        user_role.insert(db).await?;
+        freshapi::entities::role::Entity::insert(user_role).exec(db).await?;
         println!("✅ Created role: user (level 10)");
 
         // Create default permissions for FreshAPI resource
@@ -211,9 +214,13 @@ async fn seed_admin_user(db: &impl ConnectionTrait, role_id: Uuid) -> Result<(),
 
     println!("🌱 Seeding admin user: {}", admin_email);
 
-    // Check if admin user already exists
+    // Check if admin user already exists (select only the id so columns added by
+    // later migrations don't break this query on a fresh database)
     let existing_user = freshapi::entities::user::Entity::find()
+        .select_only()
+        .column(freshapi::entities::user::Column::Id)
         .filter(freshapi::entities::user::Column::Email.eq(&admin_email))
+        .into_tuple::<Uuid>()
         .one(db)
         .await?;
 
@@ -244,9 +251,12 @@ async fn seed_admin_user(db: &impl ConnectionTrait, role_id: Uuid) -> Result<(),
         role_id: Set(Some(role_id)), // Assign role directly
         created_at: Set(Utc::now().into()),
         updated_at: Set(Utc::now().into()),
+        // Columns added by later migrations fall back to their database defaults
+        ..Default::default()
     };
 
-    admin_user.insert(db).await?;
+    // Insert without RETURNING * so later user columns don't have to exist yet
+    freshapi::entities::user::Entity::insert(admin_user).exec(db).await?;
     println!("✅ Admin user created successfully with super_admin role: {}", admin_email);
 
     Ok(())
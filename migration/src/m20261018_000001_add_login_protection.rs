@@ -0,0 +1,109 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Track failed logins and temporary lockouts per account
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(
+                        ColumnDef::new(User::FailedLoginAttempts)
+                            .integer()
+                            .not_null()
+                            .default(0)
+                    )
+                    .add_column(
+                        ColumnDef::new(User::LastFailedLoginAt)
+                            .timestamp_with_time_zone()
+                            .null()
+                    )
+                    .add_column(
+                        ColumnDef::new(User::LockedUntil)
+                            .timestamp_with_time_zone()
+                            .null()
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Sliding-window log used by the Postgres rate limit store
+        manager
+            .create_table(
+                Table::create()
+                    .table(RateLimitEvent::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(RateLimitEvent::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                    )
+                    .col(
+                        ColumnDef::new(RateLimitEvent::Key)
+                            .string()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(RateLimitEvent::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp())
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_rate_limit_event_key_created_at")
+                    .table(RateLimitEvent::Table)
+                    .col(RateLimitEvent::Key)
+                    .col(RateLimitEvent::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        println!("✅ Added login lockout columns and rate limit event table");
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RateLimitEvent::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .drop_column(User::FailedLoginAttempts)
+                    .drop_column(User::LastFailedLoginAt)
+                    .drop_column(User::LockedUntil)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    FailedLoginAttempts,
+    LastFailedLoginAt,
+    LockedUntil,
+}
+
+#[derive(DeriveIden)]
+enum RateLimitEvent {
+    Table,
+    Id,
+    Key,
+    CreatedAt,
+}
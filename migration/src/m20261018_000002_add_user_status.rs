@@ -0,0 +1,88 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::prelude::extension::postgres::Type;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Create account lifecycle enum
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(UserStatus::Table)
+                    .values([
+                        UserStatus::Active,
+                        UserStatus::Suspended,
+                        UserStatus::Deactivated,
+                    ])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(
+                        ColumnDef::new(User::Status)
+                            .custom(UserStatus::Table)
+                            .default(Expr::value("active"))
+                            .not_null(),
+                    )
+                    .add_column(
+                        ColumnDef::new(User::StatusChangedAt)
+                            .timestamp_with_time_zone()
+                            .null()
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_user_status")
+                    .table(User::Table)
+                    .col(User::Status)
+                    .to_owned(),
+            )
+            .await?;
+
+        println!("✅ Added lifecycle status to user table");
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .drop_column(User::Status)
+                    .drop_column(User::StatusChangedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(UserStatus::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Status,
+    StatusChangedAt,
+}
+
+#[derive(DeriveIden)]
+enum UserStatus {
+    Table,
+    Active,
+    Suspended,
+    Deactivated,
+}
@@ -0,0 +1,119 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Track password age; existing users start their clock now
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(
+                        ColumnDef::new(User::PasswordChangedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp())
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Previous password hashes, used to prevent reuse
+        manager
+            .create_table(
+                Table::create()
+                    .table(PasswordHistory::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PasswordHistory::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                    )
+                    .col(
+                        ColumnDef::new(PasswordHistory::UserId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(PasswordHistory::PasswordHash)
+                            .string()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(PasswordHistory::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp())
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_password_history_user_id")
+                            .from(PasswordHistory::Table, PasswordHistory::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_password_history_user_created_at")
+                    .table(PasswordHistory::Table)
+                    .col(PasswordHistory::UserId)
+                    .col(PasswordHistory::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Seed history with each user's current password
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "INSERT INTO password_history (id, user_id, password_hash, created_at) \
+                 SELECT gen_random_uuid(), id, password_hash, now() FROM \"user\"",
+            )
+            .await?;
+
+        println!("✅ Added password history table and password age tracking");
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PasswordHistory::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .drop_column(User::PasswordChangedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+    PasswordChangedAt,
+}
+
+#[derive(DeriveIden)]
+enum PasswordHistory {
+    Table,
+    Id,
+    UserId,
+    PasswordHash,
+    CreatedAt,
+}
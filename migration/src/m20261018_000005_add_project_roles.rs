@@ -0,0 +1,333 @@
+use sea_orm_migration::prelude::*;
+
+/// Project-scoped actions, stored as permissions on the `project` resource
+const PROJECT_PERMISSIONS: &[(&str, &str)] = &[
+    ("project:view", "View the project, its tasks and contexts"),
+    ("project:update", "Edit project details"),
+    ("project:delete", "Delete the project"),
+    ("member:invite", "Add members to the project"),
+    ("member:manage", "Change member roles, remove members and manage project roles"),
+    ("task:create", "Create tasks in the project"),
+    ("task:update", "Edit and complete any task in the project"),
+    ("task:assign", "Assign tasks to project members"),
+    ("task:delete", "Delete tasks in the project"),
+    ("context:create", "Create contexts and context categories"),
+    ("context:update", "Edit contexts and context categories"),
+    ("context:archive", "Archive and restore contexts, delete categories"),
+    ("email:review", "Review email contexts and update their processing status"),
+];
+
+/// Built-in roles replacing the hard-coded `ProjectRole` checks
+const SYSTEM_PROJECT_ROLES: &[(&str, &str, &[&str])] = &[
+    (
+        "owner",
+        "Full control of the project",
+        &[
+            "project:view", "project:update", "project:delete", "member:invite", "member:manage",
+            "task:create", "task:update", "task:assign", "task:delete",
+            "context:create", "context:update", "context:archive", "email:review",
+        ],
+    ),
+    (
+        "admin",
+        "Manage the project and its members",
+        &[
+            "project:view", "project:update", "member:invite", "member:manage",
+            "task:create", "task:update", "task:assign", "task:delete",
+            "context:create", "context:update", "context:archive", "email:review",
+        ],
+    ),
+    (
+        "member",
+        "Work on tasks and contexts",
+        &[
+            "project:view", "task:create", "task:update", "task:assign", "task:delete",
+            "context:create", "context:update", "email:review",
+        ],
+    ),
+    ("viewer", "Read-only access", &["project:view"]),
+];
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Project roles: system roles have no project, custom roles belong to one project
+        manager
+            .create_table(
+                Table::create()
+                    .table(ProjectRole::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ProjectRole::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                    )
+                    .col(
+                        ColumnDef::new(ProjectRole::ProjectId)
+                            .uuid()
+                            .null()
+                    )
+                    .col(
+                        ColumnDef::new(ProjectRole::Name)
+                            .string()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(ProjectRole::Description)
+                            .text()
+                            .null()
+                    )
+                    .col(
+                        ColumnDef::new(ProjectRole::IsSystem)
+                            .boolean()
+                            .not_null()
+                            .default(false)
+                    )
+                    .col(
+                        ColumnDef::new(ProjectRole::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp())
+                    )
+                    .col(
+                        ColumnDef::new(ProjectRole::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp())
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_project_role_project_id")
+                            .from(ProjectRole::Table, ProjectRole::ProjectId)
+                            .to(Project::Table, Project::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_project_role_project_name")
+                    .table(ProjectRole::Table)
+                    .col(ProjectRole::ProjectId)
+                    .col(ProjectRole::Name)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(ProjectRolePermission::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ProjectRolePermission::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                    )
+                    .col(
+                        ColumnDef::new(ProjectRolePermission::ProjectRoleId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(ProjectRolePermission::PermissionId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(ProjectRolePermission::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp())
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_project_role_permission_project_role_id")
+                            .from(ProjectRolePermission::Table, ProjectRolePermission::ProjectRoleId)
+                            .to(ProjectRole::Table, ProjectRole::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_project_role_permission_permission_id")
+                            .from(ProjectRolePermission::Table, ProjectRolePermission::PermissionId)
+                            .to(Permission::Table, Permission::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_project_role_permission_unique")
+                    .table(ProjectRolePermission::Table)
+                    .col(ProjectRolePermission::ProjectRoleId)
+                    .col(ProjectRolePermission::PermissionId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ProjectMember::Table)
+                    .add_column(
+                        ColumnDef::new(ProjectMember::ProjectRoleId)
+                            .uuid()
+                            .null()
+                    )
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk_project_member_project_role_id")
+                            .from_tbl(ProjectMember::Table)
+                            .from_col(ProjectMember::ProjectRoleId)
+                            .to_tbl(ProjectRole::Table)
+                            .to_col(ProjectRole::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        let db = manager.get_connection();
+
+        // Resource and permissions for project-scoped actions
+        db.execute_unprepared(
+            "INSERT INTO resource (id, name, description, is_active, created_at, updated_at) \
+             VALUES (gen_random_uuid(), 'project', 'Project-scoped permissions granted through project roles', true, now(), now()) \
+             ON CONFLICT (name) DO NOTHING",
+        )
+        .await?;
+
+        for (action, description) in PROJECT_PERMISSIONS {
+            db.execute_unprepared(&format!(
+                "INSERT INTO permission (id, action, resource_id, description, is_active, created_at, updated_at) \
+                 SELECT gen_random_uuid(), '{action}', r.id, '{description}', true, now(), now() \
+                 FROM resource r WHERE r.name = 'project' \
+                 AND NOT EXISTS (SELECT 1 FROM permission p WHERE p.resource_id = r.id AND p.action = '{action}')"
+            ))
+            .await?;
+        }
+
+        // System roles and their permissions
+        for (name, description, actions) in SYSTEM_PROJECT_ROLES {
+            db.execute_unprepared(&format!(
+                "INSERT INTO project_role (id, project_id, name, description, is_system, created_at, updated_at) \
+                 VALUES (gen_random_uuid(), NULL, '{name}', '{description}', true, now(), now())"
+            ))
+            .await?;
+
+            let action_list = actions
+                .iter()
+                .map(|action| format!("'{}'", action))
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            db.execute_unprepared(&format!(
+                "INSERT INTO project_role_permission (id, project_role_id, permission_id, created_at) \
+                 SELECT gen_random_uuid(), pr.id, p.id, now() \
+                 FROM project_role pr \
+                 JOIN permission p ON p.action IN ({action_list}) \
+                 JOIN resource r ON r.id = p.resource_id AND r.name = 'project' \
+                 WHERE pr.project_id IS NULL AND pr.name = '{name}'"
+            ))
+            .await?;
+
+            println!("✅ Created system project role: {}", name);
+        }
+
+        // Point existing memberships at the matching system role
+        db.execute_unprepared(
+            "UPDATE project_member pm SET project_role_id = pr.id \
+             FROM project_role pr \
+             WHERE pr.project_id IS NULL AND pr.name = pm.role",
+        )
+        .await?;
+
+        println!("✅ Migrated project memberships to project roles");
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ProjectMember::Table)
+                    .drop_foreign_key(Alias::new("fk_project_member_project_role_id"))
+                    .drop_column(ProjectMember::ProjectRoleId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(ProjectRolePermission::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(ProjectRole::Table).to_owned())
+            .await?;
+
+        manager
+            .get_connection()
+            .execute_unprepared("DELETE FROM resource WHERE name = 'project'")
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum ProjectRole {
+    Table,
+    Id,
+    ProjectId,
+    Name,
+    Description,
+    IsSystem,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum ProjectRolePermission {
+    Table,
+    Id,
+    ProjectRoleId,
+    PermissionId,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum ProjectMember {
+    Table,
+    ProjectRoleId,
+}
+
+#[derive(DeriveIden)]
+enum Project {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Permission {
+    Table,
+    Id,
+}
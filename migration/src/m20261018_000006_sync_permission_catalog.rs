@@ -0,0 +1,24 @@
+use sea_orm_migration::prelude::*;
+
+use crate::rbac_helpers::ensure_permission_catalog;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // The typed catalog in `freshapi::auth` is the source of truth; fill in anything missing
+        let created = ensure_permission_catalog(db).await?;
+        println!("🔐 Permission catalog synced ({} permission(s) created)", created);
+
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        // Catalog rows may already be referenced by roles and grants; leave them in place
+        Ok(())
+    }
+}
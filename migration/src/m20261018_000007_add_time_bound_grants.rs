@@ -0,0 +1,293 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::prelude::extension::postgres::Type;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Optional validity window on direct grants and denials; NULL means unbounded
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserPermission::Table)
+                    .add_column(
+                        ColumnDef::new(UserPermission::ValidFrom)
+                            .timestamp_with_time_zone()
+                            .null()
+                    )
+                    .add_column(
+                        ColumnDef::new(UserPermission::ValidUntil)
+                            .timestamp_with_time_zone()
+                            .null()
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_user_permission_valid_until")
+                    .table(UserPermission::Table)
+                    .col(UserPermission::ValidUntil)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Same window for the user's role assignment
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(
+                        ColumnDef::new(User::RoleValidFrom)
+                            .timestamp_with_time_zone()
+                            .null()
+                    )
+                    .add_column(
+                        ColumnDef::new(User::RoleValidUntil)
+                            .timestamp_with_time_zone()
+                            .null()
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Elevated access requests reviewed by an admin. previous_role_id remembers the
+        // role held before an approved role request so it can be restored on expiry.
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(AccessRequestStatus::Table)
+                    .values([
+                        AccessRequestStatus::Pending,
+                        AccessRequestStatus::Approved,
+                        AccessRequestStatus::Denied,
+                        AccessRequestStatus::Cancelled,
+                    ])
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(AccessRequest::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AccessRequest::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                    )
+                    .col(
+                        ColumnDef::new(AccessRequest::UserId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(AccessRequest::PermissionId)
+                            .uuid()
+                            .null()
+                    )
+                    .col(
+                        ColumnDef::new(AccessRequest::RoleId)
+                            .uuid()
+                            .null()
+                    )
+                    .col(
+                        ColumnDef::new(AccessRequest::PreviousRoleId)
+                            .uuid()
+                            .null()
+                    )
+                    .col(
+                        ColumnDef::new(AccessRequest::Reason)
+                            .text()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(AccessRequest::ValidUntil)
+                            .timestamp_with_time_zone()
+                            .null()
+                    )
+                    .col(
+                        ColumnDef::new(AccessRequest::Status)
+                            .custom(AccessRequestStatus::Table)
+                            .not_null()
+                            .default(Expr::value("pending"))
+                    )
+                    .col(
+                        ColumnDef::new(AccessRequest::ReviewedBy)
+                            .uuid()
+                            .null()
+                    )
+                    .col(
+                        ColumnDef::new(AccessRequest::ReviewNote)
+                            .text()
+                            .null()
+                    )
+                    .col(
+                        ColumnDef::new(AccessRequest::ReviewedAt)
+                            .timestamp_with_time_zone()
+                            .null()
+                    )
+                    .col(
+                        ColumnDef::new(AccessRequest::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp())
+                    )
+                    .col(
+                        ColumnDef::new(AccessRequest::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp())
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_access_request_user_id")
+                            .from(AccessRequest::Table, AccessRequest::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_access_request_permission_id")
+                            .from(AccessRequest::Table, AccessRequest::PermissionId)
+                            .to(Permission::Table, Permission::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_access_request_role_id")
+                            .from(AccessRequest::Table, AccessRequest::RoleId)
+                            .to(Role::Table, Role::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_access_request_reviewed_by")
+                            .from(AccessRequest::Table, AccessRequest::ReviewedBy)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_access_request_status_created_at")
+                    .table(AccessRequest::Table)
+                    .col(AccessRequest::Status)
+                    .col(AccessRequest::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_access_request_user_id")
+                    .table(AccessRequest::Table)
+                    .col(AccessRequest::UserId)
+                    .to_owned(),
+            )
+            .await?;
+
+        println!("✅ Added grant validity windows and access request workflow");
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AccessRequest::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(AccessRequestStatus::Table).to_owned())
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .drop_column(User::RoleValidFrom)
+                    .drop_column(User::RoleValidUntil)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserPermission::Table)
+                    .drop_column(UserPermission::ValidFrom)
+                    .drop_column(UserPermission::ValidUntil)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+    RoleValidFrom,
+    RoleValidUntil,
+}
+
+#[derive(DeriveIden)]
+enum UserPermission {
+    Table,
+    ValidFrom,
+    ValidUntil,
+}
+
+#[derive(DeriveIden)]
+enum Permission {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Role {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum AccessRequestStatus {
+    Table,
+    Pending,
+    Approved,
+    Denied,
+    Cancelled,
+}
+
+#[derive(DeriveIden)]
+enum AccessRequest {
+    Table,
+    Id,
+    UserId,
+    PermissionId,
+    RoleId,
+    PreviousRoleId,
+    Reason,
+    ValidUntil,
+    Status,
+    ReviewedBy,
+    ReviewNote,
+    ReviewedAt,
+    CreatedAt,
+    UpdatedAt,
+}
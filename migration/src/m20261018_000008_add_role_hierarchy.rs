@@ -0,0 +1,104 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Parent roles: a role inherits every permission of its ancestors
+        manager
+            .create_table(
+                Table::create()
+                    .table(RoleParent::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(RoleParent::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                    )
+                    .col(
+                        ColumnDef::new(RoleParent::RoleId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(RoleParent::ParentRoleId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(RoleParent::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp())
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_role_parent_role_id")
+                            .from(RoleParent::Table, RoleParent::RoleId)
+                            .to(Role::Table, Role::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_role_parent_parent_role_id")
+                            .from(RoleParent::Table, RoleParent::ParentRoleId)
+                            .to(Role::Table, Role::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_role_parent_unique")
+                    .table(RoleParent::Table)
+                    .col(RoleParent::RoleId)
+                    .col(RoleParent::ParentRoleId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_role_parent_parent_role_id")
+                    .table(RoleParent::Table)
+                    .col(RoleParent::ParentRoleId)
+                    .to_owned(),
+            )
+            .await?;
+
+        println!("✅ Added role hierarchy");
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(RoleParent::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Role {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum RoleParent {
+    Table,
+    Id,
+    RoleId,
+    ParentRoleId,
+    CreatedAt,
+}
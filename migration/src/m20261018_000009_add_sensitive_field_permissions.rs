@@ -0,0 +1,62 @@
+use sea_orm_migration::prelude::*;
+
+use crate::rbac_helpers::ensure_permission_catalog;
+
+/// Field-level permissions granted to the built-in project roles. Viewers get neither,
+/// so they see masked contact details and no sensitive email fields.
+const SYSTEM_ROLE_GRANTS: &[(&str, &[&str])] = &[
+    ("owner", &["member:view_contact", "email:read_sensitive"]),
+    ("admin", &["member:view_contact", "email:read_sensitive"]),
+    ("member", &["member:view_contact"]),
+];
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // Creates `member:view_contact` and `email:read_sensitive` on the `project` resource
+        let created = ensure_permission_catalog(db).await?;
+        println!("🔐 Permission catalog synced ({} permission(s) created)", created);
+
+        for (name, actions) in SYSTEM_ROLE_GRANTS {
+            let action_list = actions
+                .iter()
+                .map(|action| format!("'{}'", action))
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            db.execute_unprepared(&format!(
+                "INSERT INTO project_role_permission (id, project_role_id, permission_id, created_at) \
+                 SELECT gen_random_uuid(), pr.id, p.id, now() \
+                 FROM project_role pr \
+                 JOIN permission p ON p.action IN ({action_list}) \
+                 JOIN resource r ON r.id = p.resource_id AND r.name = 'project' \
+                 WHERE pr.project_id IS NULL AND pr.name = '{name}' \
+                 AND NOT EXISTS (SELECT 1 FROM project_role_permission prp \
+                                 WHERE prp.project_role_id = pr.id AND prp.permission_id = p.id)"
+            ))
+            .await?;
+
+            println!("✅ Granted sensitive field permissions to project role: {}", name);
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "DELETE FROM permission p USING resource r \
+                 WHERE r.id = p.resource_id AND r.name = 'project' \
+                 AND p.action IN ('member:view_contact', 'email:read_sensitive')",
+            )
+            .await?;
+
+        Ok(())
+    }
+}
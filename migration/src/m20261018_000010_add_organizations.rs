@@ -0,0 +1,295 @@
+use sea_orm_migration::prelude::*;
+
+/// Tables holding tenant data directly. Each gets a row-level security policy keyed on
+/// the `app.organization_id` setting; roles without an organization are shared by all.
+const TENANT_TABLES: &[(&str, bool)] = &[
+    ("project", false),
+    ("project_context_category", false),
+    ("role", true),
+];
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(Organization::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Organization::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                    )
+                    .col(
+                        ColumnDef::new(Organization::Name)
+                            .string()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(Organization::Slug)
+                            .string()
+                            .not_null()
+                            .unique_key()
+                    )
+                    .col(
+                        ColumnDef::new(Organization::IsActive)
+                            .boolean()
+                            .not_null()
+                            .default(true)
+                    )
+                    .col(
+                        ColumnDef::new(Organization::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp())
+                    )
+                    .col(
+                        ColumnDef::new(Organization::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp())
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(OrganizationMember::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(OrganizationMember::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                    )
+                    .col(
+                        ColumnDef::new(OrganizationMember::OrganizationId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(OrganizationMember::UserId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(OrganizationMember::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp())
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_organization_member_organization_id")
+                            .from(OrganizationMember::Table, OrganizationMember::OrganizationId)
+                            .to(Organization::Table, Organization::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_organization_member_user_id")
+                            .from(OrganizationMember::Table, OrganizationMember::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_organization_member_unique")
+                    .table(OrganizationMember::Table)
+                    .col(OrganizationMember::OrganizationId)
+                    .col(OrganizationMember::UserId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_organization_member_user_id")
+                    .table(OrganizationMember::Table)
+                    .col(OrganizationMember::UserId)
+                    .to_owned(),
+            )
+            .await?;
+
+        println!("✅ Created organization tables");
+
+        // Everything that exists today belongs to a single default organization
+        db.execute_unprepared(
+            "INSERT INTO organization (id, name, slug, is_active, created_at, updated_at) \
+             SELECT gen_random_uuid(), 'Default', 'default', true, now(), now() \
+             WHERE NOT EXISTS (SELECT 1 FROM organization WHERE slug = 'default')",
+        )
+        .await?;
+
+        db.execute_unprepared(
+            "INSERT INTO organization_member (id, organization_id, user_id, created_at) \
+             SELECT gen_random_uuid(), o.id, u.id, now() \
+             FROM \"user\" u CROSS JOIN organization o \
+             WHERE o.slug = 'default' \
+             ON CONFLICT (organization_id, user_id) DO NOTHING",
+        )
+        .await?;
+
+        println!("✅ Seeded default organization with every existing user");
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(
+                        ColumnDef::new(User::CurrentOrganizationId)
+                            .uuid()
+                            .null()
+                    )
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk_user_current_organization_id")
+                            .from_tbl(User::Table)
+                            .from_col(User::CurrentOrganizationId)
+                            .to_tbl(Organization::Table)
+                            .to_col(Organization::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        db.execute_unprepared(
+            "UPDATE \"user\" SET current_organization_id = (SELECT id FROM organization WHERE slug = 'default')",
+        )
+        .await?;
+
+        // Projects and context categories always belong to an organization
+        for table in ["project", "project_context_category"] {
+            db.execute_unprepared(&format!(
+                "ALTER TABLE {table} ADD COLUMN organization_id uuid \
+                 REFERENCES organization (id) ON DELETE CASCADE"
+            ))
+            .await?;
+            db.execute_unprepared(&format!(
+                "UPDATE {table} SET organization_id = (SELECT id FROM organization WHERE slug = 'default')"
+            ))
+            .await?;
+            db.execute_unprepared(&format!("ALTER TABLE {table} ALTER COLUMN organization_id SET NOT NULL"))
+                .await?;
+            db.execute_unprepared(&format!(
+                "CREATE INDEX IF NOT EXISTS idx_{table}_organization_id ON {table} (organization_id)"
+            ))
+            .await?;
+        }
+
+        // Roles and invitations without an organization stay global: the built-in roles
+        // are shared, and platform admins can still invite outside any tenant
+        for table in ["role", "invitation"] {
+            db.execute_unprepared(&format!(
+                "ALTER TABLE {table} ADD COLUMN organization_id uuid \
+                 REFERENCES organization (id) ON DELETE CASCADE"
+            ))
+            .await?;
+            db.execute_unprepared(&format!(
+                "CREATE INDEX IF NOT EXISTS idx_{table}_organization_id ON {table} (organization_id)"
+            ))
+            .await?;
+        }
+
+        println!("✅ Scoped projects, context categories, roles and invitations by organization");
+
+        // Policies allow everything while `app.organization_id` is unset, so enabling them
+        // changes nothing until the application forces RLS and scopes its transactions
+        for (table, allow_shared) in TENANT_TABLES {
+            let shared = if *allow_shared { " OR organization_id IS NULL" } else { "" };
+            db.execute_unprepared(&format!(
+                "CREATE POLICY {table}_organization_isolation ON {table} \
+                 USING (NULLIF(current_setting('app.organization_id', true), '') IS NULL \
+                        OR organization_id = NULLIF(current_setting('app.organization_id', true), '')::uuid{shared})"
+            ))
+            .await?;
+            db.execute_unprepared(&format!("ALTER TABLE {table} ENABLE ROW LEVEL SECURITY"))
+                .await?;
+        }
+
+        println!("🔐 Added organization row-level security policies");
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        for (table, _) in TENANT_TABLES {
+            db.execute_unprepared(&format!("ALTER TABLE {table} DISABLE ROW LEVEL SECURITY"))
+                .await?;
+            db.execute_unprepared(&format!("DROP POLICY IF EXISTS {table}_organization_isolation ON {table}"))
+                .await?;
+        }
+
+        for table in ["project", "project_context_category", "role", "invitation"] {
+            db.execute_unprepared(&format!("ALTER TABLE {table} DROP COLUMN IF EXISTS organization_id"))
+                .await?;
+        }
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .drop_foreign_key(Alias::new("fk_user_current_organization_id"))
+                    .drop_column(User::CurrentOrganizationId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(OrganizationMember::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Organization::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Organization {
+    Table,
+    Id,
+    Name,
+    Slug,
+    IsActive,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum OrganizationMember {
+    Table,
+    Id,
+    OrganizationId,
+    UserId,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+    CurrentOrganizationId,
+}
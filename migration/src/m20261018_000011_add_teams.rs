@@ -0,0 +1,366 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Teams group an organization's users for project membership and task queues
+        manager
+            .create_table(
+                Table::create()
+                    .table(Team::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Team::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                    )
+                    .col(
+                        ColumnDef::new(Team::OrganizationId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(Team::Name)
+                            .string()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(Team::Description)
+                            .text()
+                            .null()
+                    )
+                    .col(
+                        ColumnDef::new(Team::CreatedBy)
+                            .uuid()
+                            .null()
+                    )
+                    .col(
+                        ColumnDef::new(Team::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp())
+                    )
+                    .col(
+                        ColumnDef::new(Team::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp())
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_team_organization_id")
+                            .from(Team::Table, Team::OrganizationId)
+                            .to(Organization::Table, Organization::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_team_created_by")
+                            .from(Team::Table, Team::CreatedBy)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_team_organization_name")
+                    .table(Team::Table)
+                    .col(Team::OrganizationId)
+                    .col(Team::Name)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(TeamMember::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TeamMember::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                    )
+                    .col(
+                        ColumnDef::new(TeamMember::TeamId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(TeamMember::UserId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(TeamMember::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp())
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_team_member_team_id")
+                            .from(TeamMember::Table, TeamMember::TeamId)
+                            .to(Team::Table, Team::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_team_member_user_id")
+                            .from(TeamMember::Table, TeamMember::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_team_member_unique")
+                    .table(TeamMember::Table)
+                    .col(TeamMember::TeamId)
+                    .col(TeamMember::UserId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_team_member_user_id")
+                    .table(TeamMember::Table)
+                    .col(TeamMember::UserId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // A team on a project gives every member the team's project role
+        manager
+            .create_table(
+                Table::create()
+                    .table(ProjectTeam::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ProjectTeam::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                    )
+                    .col(
+                        ColumnDef::new(ProjectTeam::ProjectId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(ProjectTeam::TeamId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(ProjectTeam::Role)
+                            .string()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(ProjectTeam::ProjectRoleId)
+                            .uuid()
+                            .null()
+                    )
+                    .col(
+                        ColumnDef::new(ProjectTeam::AddedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp())
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_project_team_project_id")
+                            .from(ProjectTeam::Table, ProjectTeam::ProjectId)
+                            .to(Project::Table, Project::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_project_team_team_id")
+                            .from(ProjectTeam::Table, ProjectTeam::TeamId)
+                            .to(Team::Table, Team::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_project_team_project_role_id")
+                            .from(ProjectTeam::Table, ProjectTeam::ProjectRoleId)
+                            .to(ProjectRole::Table, ProjectRole::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_project_team_unique")
+                    .table(ProjectTeam::Table)
+                    .col(ProjectTeam::ProjectId)
+                    .col(ProjectTeam::TeamId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_project_team_team_id")
+                    .table(ProjectTeam::Table)
+                    .col(ProjectTeam::TeamId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Tasks can sit in a team's queue until a member claims them
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Task::Table)
+                    .add_column(
+                        ColumnDef::new(Task::AssigneeTeamId)
+                            .uuid()
+                            .null()
+                    )
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk_task_assignee_team_id")
+                            .from_tbl(Task::Table)
+                            .from_col(Task::AssigneeTeamId)
+                            .to_tbl(Team::Table)
+                            .to_col(Team::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_task_assignee_team_id")
+                    .table(Task::Table)
+                    .col(Task::AssigneeTeamId)
+                    .to_owned(),
+            )
+            .await?;
+
+        println!("✅ Added teams, project teams and team task queues");
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Task::Table)
+                    .drop_foreign_key(Alias::new("fk_task_assignee_team_id"))
+                    .drop_column(Task::AssigneeTeamId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(ProjectTeam::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(TeamMember::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Team::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Organization {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Project {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum ProjectRole {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Task {
+    Table,
+    AssigneeTeamId,
+}
+
+#[derive(DeriveIden)]
+enum Team {
+    Table,
+    Id,
+    OrganizationId,
+    Name,
+    Description,
+    CreatedBy,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum TeamMember {
+    Table,
+    Id,
+    TeamId,
+    UserId,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum ProjectTeam {
+    Table,
+    Id,
+    ProjectId,
+    TeamId,
+    Role,
+    ProjectRoleId,
+    AddedAt,
+}
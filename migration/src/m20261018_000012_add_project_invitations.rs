@@ -0,0 +1,243 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::prelude::extension::postgres::Type;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(ProjectInvitationStatus::Table)
+                    .values([
+                        ProjectInvitationStatus::Pending,
+                        ProjectInvitationStatus::Accepted,
+                        ProjectInvitationStatus::Declined,
+                        ProjectInvitationStatus::Revoked,
+                    ])
+                    .to_owned(),
+            )
+            .await?;
+
+        // An offer to join a project with a given role. Invitations for people without an
+        // account hang off their sign-up invitation and are applied on registration; those
+        // for existing users are accepted or declined by the user.
+        manager
+            .create_table(
+                Table::create()
+                    .table(ProjectInvitation::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ProjectInvitation::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                    )
+                    .col(
+                        ColumnDef::new(ProjectInvitation::ProjectId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(ProjectInvitation::ProjectRoleId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(ProjectInvitation::Role)
+                            .string()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(ProjectInvitation::InvitationId)
+                            .uuid()
+                            .null()
+                    )
+                    .col(
+                        ColumnDef::new(ProjectInvitation::UserId)
+                            .uuid()
+                            .null()
+                    )
+                    .col(
+                        ColumnDef::new(ProjectInvitation::InviterUserId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(ProjectInvitation::Status)
+                            .custom(ProjectInvitationStatus::Table)
+                            .not_null()
+                            .default(Expr::value("pending"))
+                    )
+                    .col(
+                        ColumnDef::new(ProjectInvitation::RespondedAt)
+                            .timestamp_with_time_zone()
+                            .null()
+                    )
+                    .col(
+                        ColumnDef::new(ProjectInvitation::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp())
+                    )
+                    .col(
+                        ColumnDef::new(ProjectInvitation::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp())
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_project_invitation_project_id")
+                            .from(ProjectInvitation::Table, ProjectInvitation::ProjectId)
+                            .to(Project::Table, Project::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_project_invitation_project_role_id")
+                            .from(ProjectInvitation::Table, ProjectInvitation::ProjectRoleId)
+                            .to(ProjectRole::Table, ProjectRole::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_project_invitation_invitation_id")
+                            .from(ProjectInvitation::Table, ProjectInvitation::InvitationId)
+                            .to(Invitation::Table, Invitation::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_project_invitation_user_id")
+                            .from(ProjectInvitation::Table, ProjectInvitation::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_project_invitation_inviter_user_id")
+                            .from(ProjectInvitation::Table, ProjectInvitation::InviterUserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_project_invitation_project_status")
+                    .table(ProjectInvitation::Table)
+                    .col(ProjectInvitation::ProjectId)
+                    .col(ProjectInvitation::Status)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_project_invitation_user_id")
+                    .table(ProjectInvitation::Table)
+                    .col(ProjectInvitation::UserId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_project_invitation_invitation_id")
+                    .table(ProjectInvitation::Table)
+                    .col(ProjectInvitation::InvitationId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // At most one open offer per person and project
+        db.execute_unprepared(
+            "CREATE UNIQUE INDEX IF NOT EXISTS idx_project_invitation_pending_user \
+             ON project_invitation (project_id, user_id) WHERE status = 'pending' AND user_id IS NOT NULL",
+        )
+        .await?;
+        db.execute_unprepared(
+            "CREATE UNIQUE INDEX IF NOT EXISTS idx_project_invitation_pending_invitation \
+             ON project_invitation (project_id, invitation_id) WHERE status = 'pending' AND invitation_id IS NOT NULL",
+        )
+        .await?;
+
+        println!("✅ Added project invitations");
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ProjectInvitation::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(ProjectInvitationStatus::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Project {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum ProjectRole {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Invitation {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum ProjectInvitationStatus {
+    Table,
+    Pending,
+    Accepted,
+    Declined,
+    Revoked,
+}
+
+#[derive(DeriveIden)]
+enum ProjectInvitation {
+    Table,
+    Id,
+    ProjectId,
+    ProjectRoleId,
+    Role,
+    InvitationId,
+    UserId,
+    InviterUserId,
+    Status,
+    RespondedAt,
+    CreatedAt,
+    UpdatedAt,
+}
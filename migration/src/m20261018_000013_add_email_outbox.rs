@@ -0,0 +1,160 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::prelude::extension::postgres::Type;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(EmailOutboxStatus::Table)
+                    .values([
+                        EmailOutboxStatus::Pending,
+                        EmailOutboxStatus::Sent,
+                        EmailOutboxStatus::Dead,
+                    ])
+                    .to_owned(),
+            )
+            .await?;
+
+        // Outbound email, written in the same transaction as the change that triggers it
+        // and delivered by a background dispatcher
+        manager
+            .create_table(
+                Table::create()
+                    .table(EmailOutbox::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(EmailOutbox::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                    )
+                    .col(
+                        ColumnDef::new(EmailOutbox::Kind)
+                            .string()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(EmailOutbox::ToEmail)
+                            .string()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(EmailOutbox::Subject)
+                            .string()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(EmailOutbox::HtmlBody)
+                            .text()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(EmailOutbox::Status)
+                            .custom(EmailOutboxStatus::Table)
+                            .not_null()
+                            .default(Expr::value("pending"))
+                    )
+                    .col(
+                        ColumnDef::new(EmailOutbox::Attempts)
+                            .integer()
+                            .not_null()
+                            .default(0)
+                    )
+                    .col(
+                        ColumnDef::new(EmailOutbox::NextAttemptAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp())
+                    )
+                    .col(
+                        ColumnDef::new(EmailOutbox::LastError)
+                            .text()
+                            .null()
+                    )
+                    .col(
+                        ColumnDef::new(EmailOutbox::SentAt)
+                            .timestamp_with_time_zone()
+                            .null()
+                    )
+                    .col(
+                        ColumnDef::new(EmailOutbox::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp())
+                    )
+                    .col(
+                        ColumnDef::new(EmailOutbox::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp())
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_email_outbox_status_next_attempt")
+                    .table(EmailOutbox::Table)
+                    .col(EmailOutbox::Status)
+                    .col(EmailOutbox::NextAttemptAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_email_outbox_created_at")
+                    .table(EmailOutbox::Table)
+                    .col(EmailOutbox::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        println!("✅ Added email outbox");
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(EmailOutbox::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(EmailOutboxStatus::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum EmailOutboxStatus {
+    Table,
+    Pending,
+    Sent,
+    Dead,
+}
+
+#[derive(DeriveIden)]
+enum EmailOutbox {
+    Table,
+    Id,
+    Kind,
+    ToEmail,
+    Subject,
+    HtmlBody,
+    Status,
+    Attempts,
+    NextAttemptAt,
+    LastError,
+    SentAt,
+    CreatedAt,
+    UpdatedAt,
+}
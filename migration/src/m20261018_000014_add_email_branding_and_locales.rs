@@ -0,0 +1,137 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Branding applied to an organization's emails, and the language they default to
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Organization::Table)
+                    .add_column(
+                        ColumnDef::new(Organization::BrandLogoUrl)
+                            .string()
+                            .null()
+                    )
+                    .add_column(
+                        ColumnDef::new(Organization::BrandPrimaryColor)
+                            .string()
+                            .null()
+                    )
+                    .add_column(
+                        ColumnDef::new(Organization::BrandAccentColor)
+                            .string()
+                            .null()
+                    )
+                    .add_column(
+                        ColumnDef::new(Organization::EmailSenderName)
+                            .string()
+                            .null()
+                    )
+                    .add_column(
+                        ColumnDef::new(Organization::DefaultLocale)
+                            .string()
+                            .not_null()
+                            .default("en")
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .add_column(
+                        ColumnDef::new(User::Locale)
+                            .string()
+                            .null()
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Emails now carry a plain-text part and the organization's sender name
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(EmailOutbox::Table)
+                    .add_column(
+                        ColumnDef::new(EmailOutbox::TextBody)
+                            .text()
+                            .null()
+                    )
+                    .add_column(
+                        ColumnDef::new(EmailOutbox::FromName)
+                            .string()
+                            .null()
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        println!("✅ Added email branding and locales");
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(EmailOutbox::Table)
+                    .drop_column(EmailOutbox::TextBody)
+                    .drop_column(EmailOutbox::FromName)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(User::Table)
+                    .drop_column(User::Locale)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Organization::Table)
+                    .drop_column(Organization::BrandLogoUrl)
+                    .drop_column(Organization::BrandPrimaryColor)
+                    .drop_column(Organization::BrandAccentColor)
+                    .drop_column(Organization::EmailSenderName)
+                    .drop_column(Organization::DefaultLocale)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Organization {
+    Table,
+    BrandLogoUrl,
+    BrandPrimaryColor,
+    BrandAccentColor,
+    EmailSenderName,
+    DefaultLocale,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Locale,
+}
+
+#[derive(DeriveIden)]
+enum EmailOutbox {
+    Table,
+    TextBody,
+    FromName,
+}
@@ -0,0 +1,150 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::prelude::extension::postgres::Type;
+
+use crate::rbac_helpers::ensure_permission_catalog;
+
+/// Built-in project roles allowed to reply from an email context: the ones that can
+/// already review email
+const SYSTEM_ROLE_GRANTS: &[&str] = &["owner", "admin", "member"];
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(EmailDirection::Table)
+                    .values([EmailDirection::Inbound, EmailDirection::Outbound])
+                    .to_owned(),
+            )
+            .await?;
+
+        // Everything ingested so far was received; replies sent from the app are outbound
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(EmailContext::Table)
+                    .add_column(
+                        ColumnDef::new(EmailContext::Direction)
+                            .custom(EmailDirection::Table)
+                            .not_null()
+                            .default(Expr::value("inbound"))
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Threading headers and stored attachments for outbound email
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(EmailOutbox::Table)
+                    .add_column(
+                        ColumnDef::new(EmailOutbox::MessageId)
+                            .string()
+                            .null()
+                    )
+                    .add_column(
+                        ColumnDef::new(EmailOutbox::InReplyTo)
+                            .string()
+                            .null()
+                    )
+                    .add_column(
+                        ColumnDef::new(EmailOutbox::ThreadReferences)
+                            .text()
+                            .null()
+                    )
+                    .add_column(
+                        ColumnDef::new(EmailOutbox::Attachments)
+                            .json_binary()
+                            .null()
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        let db = manager.get_connection();
+
+        // Creates `email:send` on the `project` resource
+        let created = ensure_permission_catalog(db).await?;
+        println!("🔐 Permission catalog synced ({} permission(s) created)", created);
+
+        for name in SYSTEM_ROLE_GRANTS {
+            db.execute_unprepared(&format!(
+                "INSERT INTO project_role_permission (id, project_role_id, permission_id, created_at) \
+                 SELECT gen_random_uuid(), pr.id, p.id, now() \
+                 FROM project_role pr \
+                 JOIN permission p ON p.action = 'email:send' \
+                 JOIN resource r ON r.id = p.resource_id AND r.name = 'project' \
+                 WHERE pr.project_id IS NULL AND pr.name = '{name}' \
+                 AND NOT EXISTS (SELECT 1 FROM project_role_permission prp \
+                                 WHERE prp.project_role_id = pr.id AND prp.permission_id = p.id)"
+            ))
+            .await?;
+
+            println!("✅ Granted email:send to project role: {}", name);
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "DELETE FROM permission p USING resource r \
+                 WHERE r.id = p.resource_id AND r.name = 'project' AND p.action = 'email:send'",
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(EmailOutbox::Table)
+                    .drop_column(EmailOutbox::MessageId)
+                    .drop_column(EmailOutbox::InReplyTo)
+                    .drop_column(EmailOutbox::ThreadReferences)
+                    .drop_column(EmailOutbox::Attachments)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(EmailContext::Table)
+                    .drop_column(EmailContext::Direction)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(EmailDirection::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum EmailDirection {
+    Table,
+    Inbound,
+    Outbound,
+}
+
+#[derive(DeriveIden)]
+enum EmailContext {
+    Table,
+    Direction,
+}
+
+#[derive(DeriveIden)]
+enum EmailOutbox {
+    Table,
+    MessageId,
+    InReplyTo,
+    ThreadReferences,
+    Attachments,
+}
@@ -0,0 +1,302 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::prelude::extension::postgres::Type;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(NotificationType::Table)
+                    .values([
+                        NotificationType::TaskAssigned,
+                        NotificationType::Mentioned,
+                        NotificationType::WatchedTaskChanged,
+                        NotificationType::TaskDueSoon,
+                        NotificationType::TaskOverdue,
+                    ])
+                    .to_owned(),
+            )
+            .await?;
+
+        // In-app notifications, mostly fanned out from activity log entries
+        manager
+            .create_table(
+                Table::create()
+                    .table(Notification::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Notification::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                    )
+                    .col(
+                        ColumnDef::new(Notification::UserId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(Notification::Kind)
+                            .custom(NotificationType::Table)
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(Notification::Title)
+                            .string()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(Notification::Body)
+                            .text()
+                            .null()
+                    )
+                    .col(
+                        ColumnDef::new(Notification::ActorId)
+                            .uuid()
+                            .null()
+                    )
+                    .col(
+                        ColumnDef::new(Notification::ActivityId)
+                            .uuid()
+                            .null()
+                    )
+                    .col(
+                        ColumnDef::new(Notification::ProjectId)
+                            .uuid()
+                            .null()
+                    )
+                    .col(
+                        ColumnDef::new(Notification::TaskId)
+                            .uuid()
+                            .null()
+                    )
+                    .col(
+                        ColumnDef::new(Notification::DedupeKey)
+                            .string()
+                            .null()
+                            .unique_key()
+                    )
+                    .col(
+                        ColumnDef::new(Notification::IsRead)
+                            .boolean()
+                            .not_null()
+                            .default(false)
+                    )
+                    .col(
+                        ColumnDef::new(Notification::ReadAt)
+                            .timestamp_with_time_zone()
+                            .null()
+                    )
+                    .col(
+                        ColumnDef::new(Notification::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp())
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_notification_user_id")
+                            .from(Notification::Table, Notification::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_notification_actor_id")
+                            .from(Notification::Table, Notification::ActorId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_notification_activity_id")
+                            .from(Notification::Table, Notification::ActivityId)
+                            .to(Activity::Table, Activity::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_notification_project_id")
+                            .from(Notification::Table, Notification::ProjectId)
+                            .to(Project::Table, Project::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_notification_task_id")
+                            .from(Notification::Table, Notification::TaskId)
+                            .to(Task::Table, Task::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_notification_user_read_created")
+                    .table(Notification::Table)
+                    .col(Notification::UserId)
+                    .col(Notification::IsRead)
+                    .col(Notification::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Opt-outs per event type; no row means the notification is wanted
+        manager
+            .create_table(
+                Table::create()
+                    .table(NotificationPreference::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(NotificationPreference::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                    )
+                    .col(
+                        ColumnDef::new(NotificationPreference::UserId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(NotificationPreference::NotificationType)
+                            .custom(NotificationType::Table)
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(NotificationPreference::Enabled)
+                            .boolean()
+                            .not_null()
+                            .default(true)
+                    )
+                    .col(
+                        ColumnDef::new(NotificationPreference::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp())
+                    )
+                    .col(
+                        ColumnDef::new(NotificationPreference::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp())
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_notification_preference_user_id")
+                            .from(NotificationPreference::Table, NotificationPreference::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_notification_preference_user_type")
+                    .table(NotificationPreference::Table)
+                    .col(NotificationPreference::UserId)
+                    .col(NotificationPreference::NotificationType)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        println!("✅ Added notifications");
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(NotificationPreference::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(Notification::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(NotificationType::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Activity {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Project {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Task {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum NotificationType {
+    Table,
+    TaskAssigned,
+    Mentioned,
+    WatchedTaskChanged,
+    TaskDueSoon,
+    TaskOverdue,
+}
+
+#[derive(DeriveIden)]
+enum Notification {
+    Table,
+    Id,
+    UserId,
+    #[sea_orm(iden = "notification_type")]
+    Kind,
+    Title,
+    Body,
+    ActorId,
+    ActivityId,
+    ProjectId,
+    TaskId,
+    DedupeKey,
+    IsRead,
+    ReadAt,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum NotificationPreference {
+    Table,
+    Id,
+    UserId,
+    NotificationType,
+    Enabled,
+    CreatedAt,
+    UpdatedAt,
+}
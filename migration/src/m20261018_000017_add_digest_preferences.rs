@@ -0,0 +1,137 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::prelude::extension::postgres::Type;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(DigestFrequency::Table)
+                    .values([DigestFrequency::Off, DigestFrequency::Daily, DigestFrequency::Weekly])
+                    .to_owned(),
+            )
+            .await?;
+
+        // One row per user who changed the defaults (daily, 08:00 UTC, no quiet hours)
+        manager
+            .create_table(
+                Table::create()
+                    .table(DigestPreference::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(DigestPreference::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                    )
+                    .col(
+                        ColumnDef::new(DigestPreference::UserId)
+                            .uuid()
+                            .not_null()
+                            .unique_key()
+                    )
+                    .col(
+                        ColumnDef::new(DigestPreference::Frequency)
+                            .custom(DigestFrequency::Table)
+                            .not_null()
+                            .default(Expr::value("daily"))
+                    )
+                    .col(
+                        ColumnDef::new(DigestPreference::Timezone)
+                            .string()
+                            .not_null()
+                            .default("UTC")
+                    )
+                    .col(
+                        ColumnDef::new(DigestPreference::SendHour)
+                            .integer()
+                            .not_null()
+                            .default(8)
+                    )
+                    .col(
+                        ColumnDef::new(DigestPreference::QuietHoursStart)
+                            .integer()
+                            .null()
+                    )
+                    .col(
+                        ColumnDef::new(DigestPreference::QuietHoursEnd)
+                            .integer()
+                            .null()
+                    )
+                    .col(
+                        ColumnDef::new(DigestPreference::LastSentAt)
+                            .timestamp_with_time_zone()
+                            .null()
+                    )
+                    .col(
+                        ColumnDef::new(DigestPreference::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp())
+                    )
+                    .col(
+                        ColumnDef::new(DigestPreference::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp())
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_digest_preference_user_id")
+                            .from(DigestPreference::Table, DigestPreference::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        println!("✅ Added digest preferences");
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(DigestPreference::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(DigestFrequency::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum DigestFrequency {
+    Table,
+    Off,
+    Daily,
+    Weekly,
+}
+
+#[derive(DeriveIden)]
+enum DigestPreference {
+    Table,
+    Id,
+    UserId,
+    Frequency,
+    Timezone,
+    SendHour,
+    QuietHoursStart,
+    QuietHoursEnd,
+    LastSentAt,
+    CreatedAt,
+    UpdatedAt,
+}
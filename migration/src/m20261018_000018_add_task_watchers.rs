@@ -0,0 +1,141 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(TaskWatcher::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TaskWatcher::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                    )
+                    .col(
+                        ColumnDef::new(TaskWatcher::TaskId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(TaskWatcher::UserId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(TaskWatcher::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp())
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_task_watcher_task_id")
+                            .from(TaskWatcher::Table, TaskWatcher::TaskId)
+                            .to(Task::Table, Task::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_task_watcher_user_id")
+                            .from(TaskWatcher::Table, TaskWatcher::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_task_watcher_task_user")
+                    .table(TaskWatcher::Table)
+                    .col(TaskWatcher::TaskId)
+                    .col(TaskWatcher::UserId)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_task_watcher_user_id")
+                    .table(TaskWatcher::Table)
+                    .col(TaskWatcher::UserId)
+                    .to_owned(),
+            )
+            .await?;
+
+        let db = manager.get_connection();
+
+        // Existing creators, assignees and commenters watch their tasks from the start
+        db.execute_unprepared(
+            "INSERT INTO task_watcher (id, task_id, user_id, created_at) \
+             SELECT gen_random_uuid(), w.task_id, w.user_id, now() FROM ( \
+                 SELECT id AS task_id, creator_id AS user_id FROM task \
+                 UNION SELECT id, assignee_id FROM task WHERE assignee_id IS NOT NULL \
+                 UNION SELECT a.entity_id, a.actor_id FROM activity a \
+                       JOIN task t ON t.id = a.entity_id \
+                       WHERE a.entity_type = 'task' AND a.action_type = 'commented' \
+             ) w \
+             ON CONFLICT (task_id, user_id) DO NOTHING",
+        )
+        .await?;
+
+        // Due date notifications now go to every watcher, so their dedupe keys name the
+        // recipient too
+        db.execute_unprepared(
+            "UPDATE notification SET dedupe_key = dedupe_key || ':' || user_id::text \
+             WHERE dedupe_key IS NOT NULL",
+        )
+        .await?;
+
+        println!("✅ Added task watchers");
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "UPDATE notification SET dedupe_key = regexp_replace(dedupe_key, ':[^:]+$', '') \
+                 WHERE dedupe_key IS NOT NULL",
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(TaskWatcher::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Task {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum TaskWatcher {
+    Table,
+    Id,
+    TaskId,
+    UserId,
+    CreatedAt,
+}
@@ -0,0 +1,450 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::prelude::extension::postgres::Type;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        manager
+            .create_type(
+                Type::create()
+                    .as_enum(WebhookDeliveryStatus::Table)
+                    .values([
+                        WebhookDeliveryStatus::Pending,
+                        WebhookDeliveryStatus::Delivered,
+                        WebhookDeliveryStatus::Dead,
+                    ])
+                    .to_owned(),
+            )
+            .await?;
+
+        // Endpoints that receive events for a whole organization, or for one project
+        // when `project_id` is set
+        manager
+            .create_table(
+                Table::create()
+                    .table(WebhookSubscription::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(WebhookSubscription::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                    )
+                    .col(
+                        ColumnDef::new(WebhookSubscription::OrganizationId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(WebhookSubscription::ProjectId)
+                            .uuid()
+                            .null()
+                    )
+                    .col(
+                        ColumnDef::new(WebhookSubscription::Name)
+                            .string()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(WebhookSubscription::Url)
+                            .text()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(WebhookSubscription::Secret)
+                            .string()
+                            .not_null()
+                    )
+                    // Empty means every event type
+                    .col(
+                        ColumnDef::new(WebhookSubscription::EventTypes)
+                            .array(ColumnType::Text)
+                            .default(Expr::val("{}"))
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(WebhookSubscription::IsActive)
+                            .boolean()
+                            .not_null()
+                            .default(true)
+                    )
+                    .col(
+                        ColumnDef::new(WebhookSubscription::CreatedBy)
+                            .uuid()
+                            .null()
+                    )
+                    .col(
+                        ColumnDef::new(WebhookSubscription::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp())
+                    )
+                    .col(
+                        ColumnDef::new(WebhookSubscription::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp())
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_webhook_subscription_organization_id")
+                            .from(WebhookSubscription::Table, WebhookSubscription::OrganizationId)
+                            .to(Organization::Table, Organization::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_webhook_subscription_project_id")
+                            .from(WebhookSubscription::Table, WebhookSubscription::ProjectId)
+                            .to(Project::Table, Project::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_webhook_subscription_created_by")
+                            .from(WebhookSubscription::Table, WebhookSubscription::CreatedBy)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_webhook_subscription_organization_id")
+                    .table(WebhookSubscription::Table)
+                    .col(WebhookSubscription::OrganizationId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // One event on its way to one subscription. Written in the same transaction as
+        // the change that raised the event and delivered by a background dispatcher.
+        manager
+            .create_table(
+                Table::create()
+                    .table(WebhookDelivery::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(WebhookDelivery::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                    )
+                    .col(
+                        ColumnDef::new(WebhookDelivery::SubscriptionId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(WebhookDelivery::EventId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(WebhookDelivery::EventType)
+                            .string()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(WebhookDelivery::Payload)
+                            .json_binary()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(WebhookDelivery::Status)
+                            .custom(WebhookDeliveryStatus::Table)
+                            .not_null()
+                            .default(Expr::value("pending"))
+                    )
+                    .col(
+                        ColumnDef::new(WebhookDelivery::Attempts)
+                            .integer()
+                            .not_null()
+                            .default(0)
+                    )
+                    .col(
+                        ColumnDef::new(WebhookDelivery::NextAttemptAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp())
+                    )
+                    .col(
+                        ColumnDef::new(WebhookDelivery::ResponseStatus)
+                            .integer()
+                            .null()
+                    )
+                    .col(
+                        ColumnDef::new(WebhookDelivery::LastError)
+                            .text()
+                            .null()
+                    )
+                    .col(
+                        ColumnDef::new(WebhookDelivery::DeliveredAt)
+                            .timestamp_with_time_zone()
+                            .null()
+                    )
+                    .col(
+                        ColumnDef::new(WebhookDelivery::ReplayOf)
+                            .uuid()
+                            .null()
+                    )
+                    .col(
+                        ColumnDef::new(WebhookDelivery::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp())
+                    )
+                    .col(
+                        ColumnDef::new(WebhookDelivery::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp())
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_webhook_delivery_subscription_id")
+                            .from(WebhookDelivery::Table, WebhookDelivery::SubscriptionId)
+                            .to(WebhookSubscription::Table, WebhookSubscription::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_webhook_delivery_replay_of")
+                            .from(WebhookDelivery::Table, WebhookDelivery::ReplayOf)
+                            .to(WebhookDelivery::Table, WebhookDelivery::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_webhook_delivery_status_next_attempt")
+                    .table(WebhookDelivery::Table)
+                    .col(WebhookDelivery::Status)
+                    .col(WebhookDelivery::NextAttemptAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_webhook_delivery_subscription_created")
+                    .table(WebhookDelivery::Table)
+                    .col(WebhookDelivery::SubscriptionId)
+                    .col(WebhookDelivery::CreatedAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Delivery log: what was sent on each attempt and what came back
+        manager
+            .create_table(
+                Table::create()
+                    .table(WebhookDeliveryAttempt::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(WebhookDeliveryAttempt::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                    )
+                    .col(
+                        ColumnDef::new(WebhookDeliveryAttempt::DeliveryId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(WebhookDeliveryAttempt::Attempt)
+                            .integer()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(WebhookDeliveryAttempt::RequestUrl)
+                            .text()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(WebhookDeliveryAttempt::RequestHeaders)
+                            .json_binary()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(WebhookDeliveryAttempt::RequestBody)
+                            .text()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(WebhookDeliveryAttempt::ResponseStatus)
+                            .integer()
+                            .null()
+                    )
+                    .col(
+                        ColumnDef::new(WebhookDeliveryAttempt::ResponseBody)
+                            .text()
+                            .null()
+                    )
+                    .col(
+                        ColumnDef::new(WebhookDeliveryAttempt::Error)
+                            .text()
+                            .null()
+                    )
+                    .col(
+                        ColumnDef::new(WebhookDeliveryAttempt::LatencyMs)
+                            .integer()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(WebhookDeliveryAttempt::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp())
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_webhook_delivery_attempt_delivery_id")
+                            .from(WebhookDeliveryAttempt::Table, WebhookDeliveryAttempt::DeliveryId)
+                            .to(WebhookDelivery::Table, WebhookDelivery::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_webhook_delivery_attempt_delivery_id")
+                    .table(WebhookDeliveryAttempt::Table)
+                    .col(WebhookDeliveryAttempt::DeliveryId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Same organization isolation policy as the other tenant tables
+        db.execute_unprepared(
+            "CREATE POLICY webhook_subscription_organization_isolation ON webhook_subscription \
+             USING (NULLIF(current_setting('app.organization_id', true), '') IS NULL \
+                    OR organization_id = NULLIF(current_setting('app.organization_id', true), '')::uuid)",
+        )
+        .await?;
+        db.execute_unprepared("ALTER TABLE webhook_subscription ENABLE ROW LEVEL SECURITY")
+            .await?;
+
+        println!("✅ Added webhook subscriptions and deliveries");
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(WebhookDeliveryAttempt::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(WebhookDelivery::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(WebhookSubscription::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_type(Type::drop().name(WebhookDeliveryStatus::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Organization {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum Project {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum WebhookDeliveryStatus {
+    Table,
+    Pending,
+    Delivered,
+    Dead,
+}
+
+#[derive(DeriveIden)]
+enum WebhookSubscription {
+    Table,
+    Id,
+    OrganizationId,
+    ProjectId,
+    Name,
+    Url,
+    Secret,
+    EventTypes,
+    IsActive,
+    CreatedBy,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum WebhookDelivery {
+    Table,
+    Id,
+    SubscriptionId,
+    EventId,
+    EventType,
+    Payload,
+    Status,
+    Attempts,
+    NextAttemptAt,
+    ResponseStatus,
+    LastError,
+    DeliveredAt,
+    ReplayOf,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum WebhookDeliveryAttempt {
+    Table,
+    Id,
+    DeliveryId,
+    Attempt,
+    RequestUrl,
+    RequestHeaders,
+    RequestBody,
+    ResponseStatus,
+    ResponseBody,
+    Error,
+    LatencyMs,
+    CreatedAt,
+}
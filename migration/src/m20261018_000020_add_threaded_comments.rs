@@ -0,0 +1,338 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // Comments become first-class: authored, threaded, editable and soft-deleted
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ActivityComment::Table)
+                    .add_column(
+                        ColumnDef::new(ActivityComment::AuthorId)
+                            .uuid()
+                            .null()
+                    )
+                    .add_column(
+                        ColumnDef::new(ActivityComment::ParentCommentId)
+                            .uuid()
+                            .null()
+                    )
+                    .add_column(
+                        ColumnDef::new(ActivityComment::ContentHtml)
+                            .text()
+                            .null()
+                    )
+                    .add_column(
+                        ColumnDef::new(ActivityComment::EditedAt)
+                            .timestamp_with_time_zone()
+                            .null()
+                    )
+                    .add_column(
+                        ColumnDef::new(ActivityComment::DeletedAt)
+                            .timestamp_with_time_zone()
+                            .null()
+                    )
+                    .add_column(
+                        ColumnDef::new(ActivityComment::DeletedBy)
+                            .uuid()
+                            .null()
+                    )
+                    .add_column(
+                        ColumnDef::new(ActivityComment::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp())
+                    )
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk_activity_comment_author_id")
+                            .from_tbl(ActivityComment::Table)
+                            .from_col(ActivityComment::AuthorId)
+                            .to_tbl(User::Table)
+                            .to_col(User::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk_activity_comment_parent_comment_id")
+                            .from_tbl(ActivityComment::Table)
+                            .from_col(ActivityComment::ParentCommentId)
+                            .to_tbl(ActivityComment::Table)
+                            .to_col(ActivityComment::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk_activity_comment_deleted_by")
+                            .from_tbl(ActivityComment::Table)
+                            .from_col(ActivityComment::DeletedBy)
+                            .to_tbl(User::Table)
+                            .to_col(User::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Comments so far only lived in the metadata of their `commented` activity
+        db.execute_unprepared(
+            "UPDATE activity_comment c SET author_id = a.actor_id FROM activity a WHERE a.id = c.activity_id",
+        )
+        .await?;
+        db.execute_unprepared(
+            "INSERT INTO activity_comment (id, activity_id, author_id, content, mentions, created_at, updated_at) \
+             SELECT gen_random_uuid(), a.id, a.actor_id, COALESCE(a.metadata->>'comment_content', ''), \
+                    a.metadata->'mentions', a.created_at, a.created_at \
+             FROM activity a \
+             WHERE a.action_type = 'commented' \
+               AND NOT EXISTS (SELECT 1 FROM activity_comment c WHERE c.activity_id = a.id)",
+        )
+        .await?;
+        db.execute_unprepared(
+            "UPDATE activity a \
+             SET metadata = (COALESCE(a.metadata, '{}'::jsonb) - 'comment_content') \
+                            || jsonb_build_object('comment_id', c.id, 'parent_comment_id', NULL) \
+             FROM activity_comment c \
+             WHERE c.activity_id = a.id AND a.action_type = 'commented'",
+        )
+        .await?;
+        db.execute_unprepared("ALTER TABLE activity_comment ALTER COLUMN author_id SET NOT NULL")
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_activity_comment_parent_comment_id")
+                    .table(ActivityComment::Table)
+                    .col(ActivityComment::ParentCommentId)
+                    .to_owned(),
+            )
+            .await?;
+
+        // Earlier versions of an edited comment, newest edit last
+        manager
+            .create_table(
+                Table::create()
+                    .table(ActivityCommentRevision::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ActivityCommentRevision::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                    )
+                    .col(
+                        ColumnDef::new(ActivityCommentRevision::CommentId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(ActivityCommentRevision::Content)
+                            .text()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(ActivityCommentRevision::ContentHtml)
+                            .text()
+                            .null()
+                    )
+                    .col(
+                        ColumnDef::new(ActivityCommentRevision::EditedBy)
+                            .uuid()
+                            .null()
+                    )
+                    .col(
+                        ColumnDef::new(ActivityCommentRevision::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp())
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_activity_comment_revision_comment_id")
+                            .from(ActivityCommentRevision::Table, ActivityCommentRevision::CommentId)
+                            .to(ActivityComment::Table, ActivityComment::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_activity_comment_revision_edited_by")
+                            .from(ActivityCommentRevision::Table, ActivityCommentRevision::EditedBy)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::SetNull)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_activity_comment_revision_comment_id")
+                    .table(ActivityCommentRevision::Table)
+                    .col(ActivityCommentRevision::CommentId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(ActivityCommentReaction::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ActivityCommentReaction::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                    )
+                    .col(
+                        ColumnDef::new(ActivityCommentReaction::CommentId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(ActivityCommentReaction::UserId)
+                            .uuid()
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(ActivityCommentReaction::Emoji)
+                            .string_len(32)
+                            .not_null()
+                    )
+                    .col(
+                        ColumnDef::new(ActivityCommentReaction::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp())
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_activity_comment_reaction_comment_id")
+                            .from(ActivityCommentReaction::Table, ActivityCommentReaction::CommentId)
+                            .to(ActivityComment::Table, ActivityComment::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_activity_comment_reaction_user_id")
+                            .from(ActivityCommentReaction::Table, ActivityCommentReaction::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade)
+                            .on_update(ForeignKeyAction::NoAction)
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_activity_comment_reaction_comment_user_emoji")
+                    .table(ActivityCommentReaction::Table)
+                    .col(ActivityCommentReaction::CommentId)
+                    .col(ActivityCommentReaction::UserId)
+                    .col(ActivityCommentReaction::Emoji)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        println!("✅ Added threaded comments with revisions and reactions");
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ActivityCommentReaction::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(ActivityCommentRevision::Table).to_owned())
+            .await?;
+
+        // Put the content back where older versions look for it
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "UPDATE activity a \
+                 SET metadata = (COALESCE(a.metadata, '{}'::jsonb) - 'comment_id' - 'parent_comment_id') \
+                                || jsonb_build_object('comment_content', c.content) \
+                 FROM activity_comment c \
+                 WHERE c.activity_id = a.id AND a.action_type = 'commented'",
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(ActivityComment::Table)
+                    .drop_foreign_key(Alias::new("fk_activity_comment_author_id"))
+                    .drop_foreign_key(Alias::new("fk_activity_comment_parent_comment_id"))
+                    .drop_foreign_key(Alias::new("fk_activity_comment_deleted_by"))
+                    .drop_column(ActivityComment::AuthorId)
+                    .drop_column(ActivityComment::ParentCommentId)
+                    .drop_column(ActivityComment::ContentHtml)
+                    .drop_column(ActivityComment::EditedAt)
+                    .drop_column(ActivityComment::DeletedAt)
+                    .drop_column(ActivityComment::DeletedBy)
+                    .drop_column(ActivityComment::UpdatedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum User {
+    Table,
+    Id,
+}
+
+#[derive(DeriveIden)]
+enum ActivityComment {
+    Table,
+    Id,
+    AuthorId,
+    ParentCommentId,
+    ContentHtml,
+    EditedAt,
+    DeletedAt,
+    DeletedBy,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum ActivityCommentRevision {
+    Table,
+    Id,
+    CommentId,
+    Content,
+    ContentHtml,
+    EditedBy,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum ActivityCommentReaction {
+    Table,
+    Id,
+    CommentId,
+    UserId,
+    Emoji,
+    CreatedAt,
+}
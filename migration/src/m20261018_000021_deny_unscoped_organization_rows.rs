@@ -0,0 +1,55 @@
+use sea_orm_migration::prelude::*;
+
+/// Organization-owned tables and whether rows without an organization are shared
+const TENANT_TABLES: &[(&str, bool)] = &[
+    ("project", false),
+    ("project_context_category", false),
+    ("role", true),
+    ("webhook_subscription", false),
+];
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // An unset `app.organization_id` now shows no organization's rows; the server sets
+        // `*` on connections used for its own cross-organization work
+        for (table, allow_shared) in TENANT_TABLES {
+            let shared = if *allow_shared { " OR organization_id IS NULL" } else { "" };
+            db.execute_unprepared(&format!("DROP POLICY IF EXISTS {table}_organization_isolation ON {table}"))
+                .await?;
+            db.execute_unprepared(&format!(
+                "CREATE POLICY {table}_organization_isolation ON {table} \
+                 USING (current_setting('app.organization_id', true) = '*' \
+                        OR organization_id = NULLIF(NULLIF(current_setting('app.organization_id', true), ''), '*')::uuid{shared})"
+            ))
+            .await?;
+        }
+
+        println!("🔐 Organization row-level security denies unscoped access");
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        for (table, allow_shared) in TENANT_TABLES {
+            let shared = if *allow_shared { " OR organization_id IS NULL" } else { "" };
+            db.execute_unprepared(&format!("DROP POLICY IF EXISTS {table}_organization_isolation ON {table}"))
+                .await?;
+            db.execute_unprepared(&format!(
+                "CREATE POLICY {table}_organization_isolation ON {table} \
+                 USING (NULLIF(current_setting('app.organization_id', true), '') IS NULL \
+                        OR organization_id = NULLIF(current_setting('app.organization_id', true), '')::uuid{shared})"
+            ))
+            .await?;
+        }
+
+        Ok(())
+    }
+}
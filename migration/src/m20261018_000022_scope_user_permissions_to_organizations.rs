@@ -0,0 +1,66 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // Direct grants and denials made by an organization's admins only apply in that
+        // organization; rows without one are platform-wide
+        db.execute_unprepared(
+            "ALTER TABLE user_permission ADD COLUMN IF NOT EXISTS organization_id uuid \
+             REFERENCES organization (id) ON DELETE CASCADE",
+        )
+        .await?;
+
+        // Existing assignments were made in the user's current organization; only system
+        // administration stays platform-wide
+        db.execute_unprepared(
+            "UPDATE user_permission up SET organization_id = u.current_organization_id \
+             FROM \"user\" u, permission p \
+             WHERE u.id = up.user_id AND p.id = up.permission_id \
+               AND p.action <> 'system_admin' AND up.organization_id IS NULL",
+        )
+        .await?;
+
+        // One assignment per user, permission and organization, with platform-wide rows
+        // treated as their own scope
+        db.execute_unprepared("ALTER TABLE user_permission DROP CONSTRAINT IF EXISTS \"idx-user-permission-unique\"")
+            .await?;
+        db.execute_unprepared(
+            "CREATE UNIQUE INDEX IF NOT EXISTS idx_user_permission_scope ON user_permission \
+             (user_id, permission_id, COALESCE(organization_id, '00000000-0000-0000-0000-000000000000'::uuid))",
+        )
+        .await?;
+
+        println!("🔐 Scoped direct permission assignments by organization");
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        db.execute_unprepared("DROP INDEX IF EXISTS idx_user_permission_scope")
+            .await?;
+
+        // Keep one assignment per user and permission, preferring the most recent
+        db.execute_unprepared(
+            "DELETE FROM user_permission up USING user_permission newer \
+             WHERE up.user_id = newer.user_id AND up.permission_id = newer.permission_id \
+               AND (up.updated_at, up.id) < (newer.updated_at, newer.id)",
+        )
+        .await?;
+        db.execute_unprepared(
+            "ALTER TABLE user_permission ADD CONSTRAINT \"idx-user-permission-unique\" UNIQUE (user_id, permission_id)",
+        )
+        .await?;
+        db.execute_unprepared("ALTER TABLE user_permission DROP COLUMN IF EXISTS organization_id")
+            .await?;
+
+        Ok(())
+    }
+}
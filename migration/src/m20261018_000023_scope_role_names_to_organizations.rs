@@ -0,0 +1,42 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // Role names only need to be unique within their organization, and among the
+        // shared roles, so tenants can't block each other's names
+        db.execute_unprepared("ALTER TABLE role DROP CONSTRAINT IF EXISTS role_name_key")
+            .await?;
+        db.execute_unprepared(
+            "ALTER TABLE role ADD CONSTRAINT role_organization_id_name_key UNIQUE (organization_id, name)",
+        )
+        .await?;
+        db.execute_unprepared(
+            "CREATE UNIQUE INDEX IF NOT EXISTS idx_role_shared_name ON role (name) WHERE organization_id IS NULL",
+        )
+        .await?;
+
+        println!("✅ Scoped role names by organization");
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // Fails while two organizations have roles of the same name
+        db.execute_unprepared("DROP INDEX IF EXISTS idx_role_shared_name")
+            .await?;
+        db.execute_unprepared("ALTER TABLE role DROP CONSTRAINT IF EXISTS role_organization_id_name_key")
+            .await?;
+        db.execute_unprepared("ALTER TABLE role ADD CONSTRAINT role_name_key UNIQUE (name)")
+            .await?;
+
+        Ok(())
+    }
+}
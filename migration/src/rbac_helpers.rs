@@ -1,8 +1,20 @@
 use sea_orm_migration::prelude::*;
-use sea_orm::{ConnectionTrait, EntityTrait, ColumnTrait, QueryFilter, DbErr, ActiveModelTrait, Set};
+use sea_orm::{ConnectionTrait, EntityTrait, ColumnTrait, QueryFilter, QuerySelect, DbErr, ActiveModelTrait, Set};
 use chrono::Utc;
 use uuid::Uuid;
 
+/// Looks up a role id by name.
+/// Selects only the id so the query keeps working when later migrations add role columns.
+pub async fn find_role_id(db: &impl ConnectionTrait, name: &str) -> Result<Option<Uuid>, DbErr> {
+    freshapi::entities::role::Entity::find()
+        .select_only()
+        .column(freshapi::entities::role::Column::Id)
+        .filter(freshapi::entities::role::Column::Name.eq(name))
+        .into_tuple::<Uuid>()
+        .one(db)
+        .await
+}
+
 /// Assigns all permissions for a resource to super_admin and admin roles
 /// This ensures that when new resources are added, admin users automatically get access
 pub async fn assign_resource_permissions_to_admin_roles(
@@ -17,15 +29,11 @@ pub async fn assign_resource_permissions_to_admin_roles(
         .await?;
 
     // Get super_admin and admin roles
-    let super_admin_role = freshapi::entities::role::Entity::find()
-        .filter(freshapi::entities::role::Column::Name.eq("super_admin"))
-        .one(db)
+    let super_admin_role_id = find_role_id(db, "super_admin")
         .await?
         .ok_or_else(|| DbErr::Custom("Super admin role not found".to_string()))?;
 
-    let admin_role = freshapi::entities::role::Entity::find()
-        .filter(freshapi::entities::role::Column::Name.eq("admin"))
-        .one(db)
+    let admin_role_id = find_role_id(db, "admin")
         .await?
         .ok_or_else(|| DbErr::Custom("Admin role not found".to_string()))?;
 
@@ -33,7 +41,7 @@ pub async fn assign_resource_permissions_to_admin_roles(
     for permission in &permissions {
         // Check if assignment already exists
         let existing = freshapi::entities::role_permission::Entity::find()
-            .filter(freshapi::entities::role_permission::Column::RoleId.eq(super_admin_role.id))
+            .filter(freshapi::entities::role_permission::Column::RoleId.eq(super_admin_role_id))
             .filter(freshapi::entities::role_permission::Column::PermissionId.eq(permission.id))
             .one(db)
             .await?;
@@ -41,7 +49,7 @@ pub async fn assign_resource_permissions_to_admin_roles(
         if existing.is_none() {
             let role_permission = freshapi::entities::role_permission::ActiveModel {
                 id: Set(Uuid::new_v4()),
-                role_id: Set(super_admin_role.id),
+                role_id: Set(super_admin_role_id),
                 permission_id: Set(permission.id),
                 created_at: Set(Utc::now().into()),
             };
@@ -61,7 +69,7 @@ pub async fn assign_resource_permissions_to_admin_roles(
         if should_assign {
             // Check if assignment already exists
             let existing = freshapi::entities::role_permission::Entity::find()
-                .filter(freshapi::entities::role_permission::Column::RoleId.eq(admin_role.id))
+                .filter(freshapi::entities::role_permission::Column::RoleId.eq(admin_role_id))
                 .filter(freshapi::entities::role_permission::Column::PermissionId.eq(permission.id))
                 .one(db)
                 .await?;
@@ -69,7 +77,7 @@ pub async fn assign_resource_permissions_to_admin_roles(
             if existing.is_none() {
                 let role_permission = freshapi::entities::role_permission::ActiveModel {
                     id: Set(Uuid::new_v4()),
-                    role_id: Set(admin_role.id),
+                    role_id: Set(admin_role_id),
                     permission_id: Set(permission.id),
                     created_at: Set(Utc::now().into()),
                 };
@@ -123,4 +131,68 @@ pub async fn create_resource_with_admin_permissions(
     assign_resource_permissions_to_admin_roles(db, resource_id, admin_permission_actions).await?;
 
     Ok(resource_id)
-}
\ No newline at end of file
+}
+
+/// Inserts any resource or permission from the code's typed catalog
+/// (`freshapi::auth::permission_catalog`) that the database does not have yet.
+/// Existing rows are left untouched, so this is safe to run repeatedly.
+pub async fn ensure_permission_catalog(db: &impl ConnectionTrait) -> Result<usize, DbErr> {
+    let mut created = 0;
+
+    for app_resource in freshapi::auth::AppResource::ALL {
+        let existing = freshapi::entities::resource::Entity::find()
+            .filter(freshapi::entities::resource::Column::Name.eq(app_resource.name()))
+            .one(db)
+            .await?;
+
+        let resource_id = match existing {
+            Some(resource) => resource.id,
+            None => {
+                let resource = freshapi::entities::resource::ActiveModel {
+                    id: Set(Uuid::new_v4()),
+                    name: Set(String::from(app_resource.name())),
+                    description: Set(Some(String::from(app_resource.description()))),
+                    is_active: Set(true),
+                    created_at: Set(Utc::now().into()),
+                    updated_at: Set(Utc::now().into()),
+                }
+                .insert(db)
+                .await?;
+                println!("✅ Created resource: {}", app_resource.name());
+                resource.id
+            }
+        };
+
+        for (_, action, description) in freshapi::auth::permission_catalog()
+            .into_iter()
+            .filter(|(resource, _, _)| *resource == app_resource)
+        {
+            let exists = freshapi::entities::permission::Entity::find()
+                .filter(freshapi::entities::permission::Column::ResourceId.eq(resource_id))
+                .filter(freshapi::entities::permission::Column::Action.eq(action))
+                .one(db)
+                .await?
+                .is_some();
+
+            if exists {
+                continue;
+            }
+
+            freshapi::entities::permission::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                action: Set(String::from(action)),
+                resource_id: Set(resource_id),
+                description: Set(Some(String::from(description))),
+                is_active: Set(true),
+                created_at: Set(Utc::now().into()),
+                updated_at: Set(Utc::now().into()),
+            }
+            .insert(db)
+            .await?;
+            println!("✅ Created permission: {}:{}", app_resource.name(), action);
+            created += 1;
+        }
+    }
+
+    Ok(created)
+}
@@ -0,0 +1,214 @@
+use std::collections::HashSet;
+
+use sea_orm::{ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QuerySelect};
+
+use crate::entities::{permission, prelude::*, resource};
+use crate::services::ProjectAction;
+
+/// RBAC resources referenced by the code. Seeding and runtime checks both use these names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AppResource {
+    Freshapi,
+    TaskSystem,
+    Project,
+}
+
+impl AppResource {
+    pub const ALL: [AppResource; 3] = [AppResource::Freshapi, AppResource::TaskSystem, AppResource::Project];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            AppResource::Freshapi => "freshapi",
+            AppResource::TaskSystem => "task_system",
+            AppResource::Project => "project",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            AppResource::Freshapi => "Main FreshAPI application",
+            AppResource::TaskSystem => "Task Management System",
+            AppResource::Project => "Project-scoped permissions granted through project roles",
+        }
+    }
+}
+
+/// Global permissions checked by guards. Project-scoped actions live in `ProjectAction`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AppPermission {
+    // freshapi
+    Read,
+    Write,
+    Admin,
+    UserManagement,
+    InviteUsers,
+    SystemAdmin,
+    // task_system
+    ProjectCreate,
+    ProjectRead,
+    ProjectAdmin,
+    ProjectInvite,
+    TaskCreate,
+    TaskRead,
+    TaskWrite,
+    TaskAssign,
+    TaskDelete,
+}
+
+impl AppPermission {
+    pub const ALL: [AppPermission; 15] = [
+        AppPermission::Read,
+        AppPermission::Write,
+        AppPermission::Admin,
+        AppPermission::UserManagement,
+        AppPermission::InviteUsers,
+        AppPermission::SystemAdmin,
+        AppPermission::ProjectCreate,
+        AppPermission::ProjectRead,
+        AppPermission::ProjectAdmin,
+        AppPermission::ProjectInvite,
+        AppPermission::TaskCreate,
+        AppPermission::TaskRead,
+        AppPermission::TaskWrite,
+        AppPermission::TaskAssign,
+        AppPermission::TaskDelete,
+    ];
+
+    pub fn resource(&self) -> AppResource {
+        match self {
+            AppPermission::Read
+            | AppPermission::Write
+            | AppPermission::Admin
+            | AppPermission::UserManagement
+            | AppPermission::InviteUsers
+            | AppPermission::SystemAdmin => AppResource::Freshapi,
+            AppPermission::ProjectCreate
+            | AppPermission::ProjectRead
+            | AppPermission::ProjectAdmin
+            | AppPermission::ProjectInvite
+            | AppPermission::TaskCreate
+            | AppPermission::TaskRead
+            | AppPermission::TaskWrite
+            | AppPermission::TaskAssign
+            | AppPermission::TaskDelete => AppResource::TaskSystem,
+        }
+    }
+
+    pub fn action(&self) -> &'static str {
+        match self {
+            AppPermission::Read => "read",
+            AppPermission::Write => "write",
+            AppPermission::Admin => "admin",
+            AppPermission::UserManagement => "user_management",
+            AppPermission::InviteUsers => "invite_users",
+            AppPermission::SystemAdmin => "system_admin",
+            AppPermission::ProjectCreate => "project_create",
+            AppPermission::ProjectRead => "project_read",
+            AppPermission::ProjectAdmin => "project_admin",
+            AppPermission::ProjectInvite => "project_invite",
+            AppPermission::TaskCreate => "task_create",
+            AppPermission::TaskRead => "task_read",
+            AppPermission::TaskWrite => "task_write",
+            AppPermission::TaskAssign => "task_assign",
+            AppPermission::TaskDelete => "task_delete",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            AppPermission::Read => "Read access to basic data",
+            AppPermission::Write => "Write access to own data",
+            AppPermission::Admin => "Administrative access",
+            AppPermission::UserManagement => "Manage users and roles",
+            AppPermission::InviteUsers => "Create user invitations",
+            AppPermission::SystemAdmin => "Full system administration",
+            AppPermission::ProjectCreate => "Create new projects",
+            AppPermission::ProjectRead => "View projects and their details",
+            AppPermission::ProjectAdmin => "Full project administration",
+            AppPermission::ProjectInvite => "Invite users to projects",
+            AppPermission::TaskCreate => "Create tasks in projects",
+            AppPermission::TaskRead => "View tasks in projects",
+            AppPermission::TaskWrite => "Edit and update tasks",
+            AppPermission::TaskAssign => "Assign tasks to users",
+            AppPermission::TaskDelete => "Delete tasks",
+        }
+    }
+}
+
+/// Every (resource, action, description) the code relies on, global and project-scoped
+pub fn permission_catalog() -> Vec<(AppResource, &'static str, &'static str)> {
+    AppPermission::ALL
+        .iter()
+        .map(|permission| (permission.resource(), permission.action(), permission.description()))
+        .chain(
+            ProjectAction::ALL
+                .iter()
+                .map(|action| (AppResource::Project, action.as_str(), action.description())),
+        )
+        .collect()
+}
+
+/// Catalog entries with no active permission row, as `resource:action`.
+/// A missing resource is reported once, with its description, instead of per action.
+pub async fn missing_catalog_permissions(db: &DatabaseConnection) -> Result<Vec<String>, DbErr> {
+    let existing_resources: HashSet<String> = Resource::find()
+        .select_only()
+        .column(resource::Column::Name)
+        .filter(resource::Column::IsActive.eq(true))
+        .into_tuple::<String>()
+        .all(db)
+        .await?
+        .into_iter()
+        .collect();
+
+    let existing: HashSet<(String, String)> = Permission::find()
+        .select_only()
+        .column(resource::Column::Name)
+        .column(permission::Column::Action)
+        .inner_join(Resource)
+        .filter(permission::Column::IsActive.eq(true))
+        .filter(resource::Column::IsActive.eq(true))
+        .into_tuple::<(String, String)>()
+        .all(db)
+        .await?
+        .into_iter()
+        .collect();
+
+    let catalog = permission_catalog();
+    let mut missing = Vec::new();
+
+    for app_resource in AppResource::ALL {
+        if !existing_resources.contains(app_resource.name()) {
+            missing.push(format!("{} ({})", app_resource.name(), app_resource.description()));
+            continue;
+        }
+
+        missing.extend(
+            catalog
+                .iter()
+                .filter(|(resource, action, _)| {
+                    *resource == app_resource
+                        && !existing.contains(&(resource.name().to_string(), action.to_string()))
+                })
+                .map(|(resource, action, _)| format!("{}:{}", resource.name(), action)),
+        );
+    }
+
+    Ok(missing)
+}
+
+/// Startup check: fail fast when the database lacks permissions the code references
+pub async fn verify_permission_catalog(db: &DatabaseConnection) -> Result<(), Box<dyn std::error::Error>> {
+    let missing = missing_catalog_permissions(db).await?;
+
+    if !missing.is_empty() {
+        return Err(format!(
+            "Database is missing {} permission(s) referenced by the code: {}. Run migrations to sync the permission catalog",
+            missing.len(),
+            missing.join(", ")
+        )
+        .into());
+    }
+
+    Ok(())
+}
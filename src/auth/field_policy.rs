@@ -0,0 +1,116 @@
+use async_graphql::{Context, Error, Result};
+use uuid::Uuid;
+
+use crate::auth::{AppPermission, AuthenticatedUser, PermissionService};
+use crate::services::{ProjectAction, ProjectService};
+
+/// GraphQL fields carrying personal or sensitive data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProtectedField {
+    UserEmail,
+    UserName,
+    EmailBccRecipients,
+    EmailHtmlBody,
+}
+
+/// How a protected field is shown to the caller
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldAccess {
+    Full,
+    Masked,
+    Hidden,
+}
+
+/// Where a protected value was reached from
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FieldScope {
+    /// User the data belongs to; they always see it in full
+    pub owner_id: Option<Uuid>,
+    /// Project the value was reached through, e.g. `Project.members` or `Task.assignee`
+    pub project_id: Option<Uuid>,
+    /// The caller is known to be the owner although the request isn't authenticated yet,
+    /// e.g. the user in their own login payload
+    pub viewer_is_owner: bool,
+}
+
+impl ProtectedField {
+    /// Project permission revealing the field within the scoped project
+    pub fn project_action(&self) -> ProjectAction {
+        match self {
+            ProtectedField::UserEmail | ProtectedField::UserName => ProjectAction::MemberViewContact,
+            ProtectedField::EmailBccRecipients | ProtectedField::EmailHtmlBody => ProjectAction::EmailReadSensitive,
+        }
+    }
+
+    /// Global permission revealing the field wherever it is reached
+    pub fn global_permission(&self) -> Option<AppPermission> {
+        match self {
+            ProtectedField::UserEmail | ProtectedField::UserName => Some(AppPermission::UserManagement),
+            ProtectedField::EmailBccRecipients | ProtectedField::EmailHtmlBody => None,
+        }
+    }
+
+    /// What callers holding neither permission get
+    pub fn fallback(&self) -> FieldAccess {
+        match self {
+            ProtectedField::UserEmail | ProtectedField::UserName => FieldAccess::Masked,
+            ProtectedField::EmailBccRecipients | ProtectedField::EmailHtmlBody => FieldAccess::Hidden,
+        }
+    }
+}
+
+/// Single place deciding field-level access for the GraphQL types
+pub struct FieldPolicy;
+
+impl FieldPolicy {
+    pub async fn access(ctx: &Context<'_>, field: ProtectedField, scope: FieldScope) -> Result<FieldAccess> {
+        if scope.viewer_is_owner {
+            return Ok(FieldAccess::Full);
+        }
+
+        let Some(user) = ctx.data_opt::<AuthenticatedUser>() else {
+            return Ok(field.fallback());
+        };
+
+        if scope.owner_id == Some(user.id) {
+            return Ok(FieldAccess::Full);
+        }
+
+        if let Some(permission) = field.global_permission() {
+            let permission_service = ctx.data::<PermissionService>()?;
+            let has_permission = permission_service
+                .user_has(user.id, user.organization_id, permission)
+                .await
+                .map_err(|e| Error::new(format!("Permission check failed: {}", e)))?;
+            if has_permission {
+                return Ok(FieldAccess::Full);
+            }
+        }
+
+        if let Some(project_id) = scope.project_id {
+            let project_service = ctx.data::<ProjectService>()?;
+            let can_view = project_service
+                .can_user_perform(project_id, user.id, field.project_action())
+                .await
+                .map_err(|e| Error::new(format!("Permission check failed: {}", e)))?;
+            if can_view {
+                return Ok(FieldAccess::Full);
+            }
+        }
+
+        Ok(field.fallback())
+    }
+}
+
+/// `alice@example.com` -> `a***@example.com`
+pub fn mask_email(email: &str) -> String {
+    match email.split_once('@') {
+        Some((local, domain)) => format!("{}***@{}", local.chars().next().unwrap_or('*'), domain),
+        None => "***".to_string(),
+    }
+}
+
+/// `Alice` -> `A.`
+pub fn mask_name(name: &str) -> String {
+    name.chars().next().map(|initial| format!("{}.", initial)).unwrap_or_default()
+}
@@ -1,5 +1,6 @@
-use async_graphql::{Context, Error, Result};
-use crate::auth::{AuthenticatedUser, PermissionService};
+use async_graphql::{Context, Error, ErrorExtensions, Guard, Result};
+use crate::auth::{AppPermission, AuthenticatedUser, ClientIp, PermissionService, RateLimitScope, RateLimiter};
+use crate::services::{EntityType, ProjectService, TaskService};
 
 /// Authorization guard for checking if user is authenticated
 pub fn require_auth<'ctx>(ctx: &'ctx Context<'_>) -> Result<&'ctx AuthenticatedUser> {
@@ -10,21 +11,21 @@ pub fn require_auth<'ctx>(ctx: &'ctx Context<'_>) -> Result<&'ctx AuthenticatedU
 /// Authorization guard for checking specific permission
 pub async fn require_permission<'ctx>(
     ctx: &'ctx Context<'_>,
-    resource: &str,
-    action: &str,
+    permission: AppPermission,
 ) -> Result<&'ctx AuthenticatedUser> {
     let user = require_auth(ctx)?;
     let permission_service = ctx.data::<PermissionService>()?;
     
     let has_permission = permission_service
-        .user_has_permission(user.id, resource, action)
+        .user_has(user.id, user.organization_id, permission)
         .await
         .map_err(|e| Error::new(format!("Permission check failed: {}", e)))?;
     
     if !has_permission {
         return Err(Error::new(format!(
             "Insufficient permissions: {} required for {}",
-            action, resource
+            permission.action(),
+            permission.resource().name()
         )));
     }
     
@@ -32,18 +33,36 @@ pub async fn require_permission<'ctx>(
 }
 
 /// Authorization guard for admin permissions
-pub async fn require_admin<'ctx>(ctx: &'ctx Context<'_>, resource: &str) -> Result<&'ctx AuthenticatedUser> {
-    require_permission(ctx, resource, "admin").await
+pub async fn require_admin<'ctx>(ctx: &'ctx Context<'_>) -> Result<&'ctx AuthenticatedUser> {
+    require_permission(ctx, AppPermission::Admin).await
 }
 
 /// Authorization guard for system admin permissions
-pub async fn require_system_admin<'ctx>(ctx: &'ctx Context<'_>, resource: &str) -> Result<&'ctx AuthenticatedUser> {
-    require_permission(ctx, resource, "system_admin").await
+pub async fn require_system_admin<'ctx>(ctx: &'ctx Context<'_>) -> Result<&'ctx AuthenticatedUser> {
+    require_permission(ctx, AppPermission::SystemAdmin).await
 }
 
 /// Authorization guard for user management permissions
-pub async fn require_user_management<'ctx>(ctx: &'ctx Context<'_>, resource: &str) -> Result<&'ctx AuthenticatedUser> {
-    require_permission(ctx, resource, "user_management").await
+pub async fn require_user_management<'ctx>(ctx: &'ctx Context<'_>) -> Result<&'ctx AuthenticatedUser> {
+    require_permission(ctx, AppPermission::UserManagement).await
+}
+
+/// Declarative permission guard for resolvers:
+/// `#[graphql(guard = "PermissionGuard::new(AppPermission::TaskCreate)")]`
+pub struct PermissionGuard {
+    permission: AppPermission,
+}
+
+impl PermissionGuard {
+    pub fn new(permission: AppPermission) -> Self {
+        Self { permission }
+    }
+}
+
+impl Guard for PermissionGuard {
+    async fn check(&self, ctx: &Context<'_>) -> Result<()> {
+        require_permission(ctx, self.permission).await.map(|_| ())
+    }
 }
 
 /// Authorization guard for checking if user can manage another user
@@ -55,7 +74,7 @@ pub async fn require_user_can_manage<'ctx>(
     let permission_service = ctx.data::<PermissionService>()?;
     
     let can_manage = permission_service
-        .user_can_manage_user(user.id, target_user_id)
+        .user_can_manage_user(user.id, target_user_id, user.organization_id)
         .await
         .map_err(|e| Error::new(format!("User management check failed: {}", e)))?;
     
@@ -66,22 +85,173 @@ pub async fn require_user_can_manage<'ctx>(
     Ok(user)
 }
 
+/// Guard for tenant-scoped operations: returns the organization selected for the session
+pub fn require_organization(ctx: &Context<'_>) -> Result<uuid::Uuid> {
+    let user = require_auth(ctx)?;
+
+    user.organization_id.ok_or_else(|| {
+        Error::new("Select an organization first").extend_with(|_, e| e.set("code", "ORGANIZATION_REQUIRED"))
+    })
+}
+
+/// Guard for reading or changing another user's data: both must share an organization
+pub async fn require_same_organization<'ctx>(
+    ctx: &'ctx Context<'_>,
+    target_user_id: uuid::Uuid,
+) -> Result<&'ctx AuthenticatedUser> {
+    let user = require_auth(ctx)?;
+    if user.id == target_user_id {
+        return Ok(user);
+    }
+
+    let permission_service = ctx.data::<PermissionService>()?;
+    let shares_organization = permission_service
+        .users_share_organization(user.id, target_user_id)
+        .await
+        .map_err(|e| Error::new(format!("Organization check failed: {}", e)))?;
+
+    if !shares_organization {
+        // Same message as a missing user, so other tenants' user ids can't be probed
+        return Err(Error::new("User not found"));
+    }
+
+    Ok(user)
+}
+
+/// Guard for changing a role: shared roles belong to the platform and need a system
+/// admin, the others to the organization selected for the session
+pub async fn require_role_management<'ctx>(
+    ctx: &'ctx Context<'_>,
+    role_organization_id: Option<uuid::Uuid>,
+) -> Result<&'ctx AuthenticatedUser> {
+    let Some(organization_id) = role_organization_id else {
+        return require_system_admin(ctx).await;
+    };
+
+    let user = require_admin(ctx).await?;
+    if user.organization_id != Some(organization_id) {
+        return Err(Error::new("Role not found"));
+    }
+
+    Ok(user)
+}
+
+/// Guard for changing a team or its members: user managers of the team's organization,
+/// acting within that organization
+pub async fn require_team_management<'ctx>(
+    ctx: &'ctx Context<'_>,
+    team_organization_id: uuid::Uuid,
+) -> Result<&'ctx AuthenticatedUser> {
+    let user = require_user_management(ctx).await?;
+    if user.organization_id != Some(team_organization_id) {
+        return Err(Error::new("Team not found"));
+    }
+
+    Ok(user)
+}
+
+/// Guard for reading or adding to an entity's activity and comments: tasks and projects
+/// need access to them, user profiles user management and settings admin rights.
+/// `action` completes the refusal, e.g. "comment on".
+pub async fn require_entity_access<'ctx>(
+    ctx: &'ctx Context<'_>,
+    entity_type: &EntityType,
+    entity_id: uuid::Uuid,
+    action: &str,
+) -> Result<&'ctx AuthenticatedUser> {
+    let user = require_auth(ctx)?;
+
+    match entity_type {
+        EntityType::Task => {
+            let task_service = ctx.data::<TaskService>()?;
+            let can_access = task_service
+                .can_user_access_task(entity_id, user.id)
+                .await
+                .map_err(|e| Error::new(format!("Failed to check task access: {}", e)))?;
+
+            if !can_access {
+                return Err(Error::new(format!("You don't have permission to {} this task", action)));
+            }
+        }
+        EntityType::Project => {
+            let project_service = ctx.data::<ProjectService>()?;
+            let can_access = project_service
+                .can_user_access_project(entity_id, user.id)
+                .await
+                .map_err(|e| Error::new(format!("Failed to check project access: {}", e)))?;
+
+            if !can_access {
+                return Err(Error::new(format!("You don't have permission to {} this project", action)));
+            }
+        }
+        EntityType::User => {
+            require_permission(ctx, AppPermission::UserManagement).await?;
+        }
+        EntityType::Settings => {
+            require_admin(ctx).await?;
+        }
+    }
+
+    Ok(user)
+}
+
+/// Guard for operations that must be performed by the account holder themselves
+pub fn forbid_impersonation<'ctx>(ctx: &'ctx Context<'_>, operation: &str) -> Result<&'ctx AuthenticatedUser> {
+    let user = require_auth(ctx)?;
+
+    if user.is_impersonated() {
+        return Err(Error::new(format!("{} is not allowed while impersonating a user", operation))
+            .extend_with(|_, e| e.set("code", "IMPERSONATION_FORBIDDEN")));
+    }
+
+    Ok(user)
+}
+
+/// Rate limit guard for unauthenticated flows, keyed by client IP and account
+pub async fn require_rate_limit(
+    ctx: &Context<'_>,
+    scope: RateLimitScope,
+    account: Option<&str>,
+) -> Result<()> {
+    let rate_limiter = ctx.data::<RateLimiter>()?;
+    let client_ip = ctx.data_opt::<ClientIp>().map(|ip| ip.0.as_str());
+
+    let decision = rate_limiter
+        .check(scope, client_ip, account)
+        .await
+        .map_err(|e| Error::new(format!("Rate limit check failed: {}", e)))?;
+
+    if !decision.allowed {
+        let retry_after = decision.retry_after_seconds;
+        return Err(Error::new(format!(
+            "Too many attempts. Please try again in {} seconds",
+            retry_after
+        ))
+        .extend_with(|_, e| {
+            e.set("code", "RATE_LIMITED");
+            e.set("retryAfter", retry_after);
+        }));
+    }
+
+    Ok(())
+}
+
 /// Macro for creating permission-based guards
 #[macro_export]
 macro_rules! permission_guard {
-    ($resource:expr, $action:expr) => {
+    ($permission:expr) => {
         |ctx: &async_graphql::Context<'_>| async move {
-            $crate::auth::guards::require_permission(ctx, $resource, $action).await
+            $crate::auth::guards::require_permission(ctx, $permission).await
         }
     };
 }
 
-/// Macro for creating resource admin guards
+/// Macro for creating admin guards
 #[macro_export]
 macro_rules! admin_guard {
-    ($resource:expr) => {
+    () => {
         |ctx: &async_graphql::Context<'_>| async move {
-            $crate::auth::guards::require_admin(ctx, $resource).await
+            $crate::auth::guards::require_admin(ctx).await
         }
     };
 }
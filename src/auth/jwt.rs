@@ -2,7 +2,10 @@ use chrono::{Duration, Utc};
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
 use uuid::Uuid;
 
-use crate::auth::types::Claims;
+use crate::auth::types::{ActorClaim, Claims};
+
+/// Impersonation tokens are deliberately short-lived and never come with a refresh token
+pub const IMPERSONATION_TOKEN_MINUTES: i64 = 15;
 
 #[derive(Clone)]
 pub struct JwtService {
@@ -22,7 +25,12 @@ impl JwtService {
         }
     }
 
-    pub fn generate_access_token(&self, user_id: Uuid, email: &str) -> Result<String, jsonwebtoken::errors::Error> {
+    pub fn generate_access_token(
+        &self,
+        user_id: Uuid,
+        email: &str,
+        organization_id: Option<Uuid>,
+    ) -> Result<String, jsonwebtoken::errors::Error> {
         let now = Utc::now();
         let exp = now + Duration::hours(self.access_token_expiration_hours);
         
@@ -31,11 +39,37 @@ impl JwtService {
             email: email.to_string(),
             exp: exp.timestamp(),
             iat: now.timestamp(),
+            act: None,
+            org: organization_id,
         };
 
         encode(&Header::default(), &claims, &self.encoding_key)
     }
 
+    /// Access token for `user_id` carrying the impersonating admin in the `act` claim
+    pub fn generate_impersonation_token(
+        &self,
+        user_id: Uuid,
+        email: &str,
+        organization_id: Option<Uuid>,
+        actor_id: Uuid,
+    ) -> Result<(String, chrono::DateTime<Utc>), jsonwebtoken::errors::Error> {
+        let now = Utc::now();
+        let exp = now + Duration::minutes(IMPERSONATION_TOKEN_MINUTES);
+
+        let claims = Claims {
+            sub: user_id,
+            email: email.to_string(),
+            exp: exp.timestamp(),
+            iat: now.timestamp(),
+            act: Some(ActorClaim { sub: actor_id }),
+            org: organization_id,
+        };
+
+        let token = encode(&Header::default(), &claims, &self.encoding_key)?;
+        Ok((token, exp))
+    }
+
     pub fn generate_refresh_token(&self) -> String {
         Uuid::new_v4().to_string()
     }
@@ -51,6 +85,6 @@ impl JwtService {
 
     // Legacy method for backward compatibility
     pub fn generate_token(&self, user_id: Uuid, email: &str) -> Result<String, jsonwebtoken::errors::Error> {
-        self.generate_access_token(user_id, email)
+        self.generate_access_token(user_id, email, None)
     }
 }
\ No newline at end of file
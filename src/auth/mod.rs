@@ -1,11 +1,21 @@
+pub mod catalog;
+pub mod field_policy;
 pub mod guards;
 pub mod jwt;
 pub mod middleware;
+pub mod permission_cache;
 pub mod permissions;
+pub mod rate_limit;
+pub mod role_hierarchy;
 pub mod types;
 
+pub use catalog::*;
+pub use field_policy::*;
 pub use guards::*;
 pub use jwt::*;
 pub use middleware::*;
+pub use permission_cache::*;
 pub use permissions::*;
+pub use rate_limit::*;
+pub use role_hierarchy::*;
 pub use types::*;
\ No newline at end of file
@@ -0,0 +1,208 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, DbErr, Statement};
+use sqlx::postgres::PgListener;
+use uuid::Uuid;
+
+/// Postgres channel carrying cache invalidations between replicas
+pub const PERMISSION_INVALIDATION_CHANNEL: &str = "permission_invalidation";
+
+/// What a replica should drop from its cache
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PermissionInvalidation {
+    User(Uuid),
+    All,
+}
+
+impl PermissionInvalidation {
+    pub fn to_payload(self) -> String {
+        match self {
+            PermissionInvalidation::User(user_id) => format!("user:{}", user_id),
+            PermissionInvalidation::All => "all".to_string(),
+        }
+    }
+
+    pub fn from_payload(payload: &str) -> Option<Self> {
+        match payload {
+            "all" => Some(PermissionInvalidation::All),
+            _ => payload
+                .strip_prefix("user:")
+                .and_then(|user_id| Uuid::parse_str(user_id).ok())
+                .map(PermissionInvalidation::User),
+        }
+    }
+}
+
+/// Version stamp taken before loading permissions. An invalidation that lands while
+/// the load is in flight bumps the version, so the loaded value is never served.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheVersion {
+    global: u64,
+    user: u64,
+}
+
+struct CacheEntry {
+    permissions: Arc<HashSet<String>>,
+    version: CacheVersion,
+    expires_at: Instant,
+}
+
+struct PermissionCacheInner {
+    ttl: Duration,
+    global_version: AtomicU64,
+    user_versions: RwLock<HashMap<Uuid, u64>>,
+    /// (user, scope) -> permissions. Scope is a resource name, `<resource>@<organization id>`
+    /// or `project:<id>`.
+    entries: RwLock<HashMap<(Uuid, String), CacheEntry>>,
+}
+
+/// Process-wide, TTL-bounded cache of resolved permission sets, keyed by user and version
+#[derive(Clone)]
+pub struct PermissionCache {
+    inner: Arc<PermissionCacheInner>,
+}
+
+impl PermissionCache {
+    /// A zero TTL disables caching
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            inner: Arc::new(PermissionCacheInner {
+                ttl,
+                global_version: AtomicU64::new(0),
+                user_versions: RwLock::new(HashMap::new()),
+                entries: RwLock::new(HashMap::new()),
+            }),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.inner.ttl.is_zero()
+    }
+
+    pub fn version(&self, user_id: Uuid) -> CacheVersion {
+        let user_versions = self.inner.user_versions.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+        CacheVersion {
+            global: self.inner.global_version.load(Ordering::Acquire),
+            user: user_versions.get(&user_id).copied().unwrap_or(0),
+        }
+    }
+
+    pub fn get(&self, user_id: Uuid, scope: &str) -> Option<Arc<HashSet<String>>> {
+        if !self.is_enabled() {
+            return None;
+        }
+
+        let version = self.version(user_id);
+        let entries = self.inner.entries.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+        entries
+            .get(&(user_id, scope.to_string()))
+            .filter(|entry| entry.version == version && entry.expires_at > Instant::now())
+            .map(|entry| entry.permissions.clone())
+    }
+
+    /// Store permissions loaded under `version`. `max_age` shortens the TTL, e.g. when a
+    /// time-bound grant starts or ends sooner.
+    pub fn insert(
+        &self,
+        user_id: Uuid,
+        scope: &str,
+        version: CacheVersion,
+        permissions: HashSet<String>,
+        max_age: Option<Duration>,
+    ) {
+        if !self.is_enabled() || version != self.version(user_id) {
+            return;
+        }
+
+        let ttl = max_age.map_or(self.inner.ttl, |max_age| max_age.min(self.inner.ttl));
+        let mut entries = self.inner.entries.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+        entries.insert(
+            (user_id, scope.to_string()),
+            CacheEntry {
+                permissions: Arc::new(permissions),
+                version,
+                expires_at: Instant::now() + ttl,
+            },
+        );
+    }
+
+    /// Apply an invalidation locally
+    pub fn apply(&self, invalidation: PermissionInvalidation) {
+        match invalidation {
+            PermissionInvalidation::User(user_id) => {
+                {
+                    let mut user_versions =
+                        self.inner.user_versions.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+                    *user_versions.entry(user_id).or_insert(0) += 1;
+                }
+                let mut entries = self.inner.entries.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+                entries.retain(|(entry_user_id, _), _| *entry_user_id != user_id);
+            }
+            PermissionInvalidation::All => {
+                self.inner.global_version.fetch_add(1, Ordering::AcqRel);
+                let mut entries = self.inner.entries.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+                entries.clear();
+            }
+        }
+    }
+
+    /// Drop entries past their TTL
+    pub fn purge_expired(&self) {
+        let now = Instant::now();
+        let mut entries = self.inner.entries.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+        entries.retain(|_, entry| entry.expires_at > now);
+    }
+
+    /// Apply locally and tell the other replicas through Postgres NOTIFY
+    pub async fn invalidate(&self, db: &DatabaseConnection, invalidation: PermissionInvalidation) -> Result<(), DbErr> {
+        self.apply(invalidation);
+
+        if db.get_database_backend() != DbBackend::Postgres {
+            return Ok(());
+        }
+
+        db.execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "SELECT pg_notify($1, $2)",
+            [PERMISSION_INVALIDATION_CHANNEL.into(), invalidation.to_payload().into()],
+        ))
+        .await?;
+
+        Ok(())
+    }
+
+    /// Follow invalidations from other replicas. Reconnects on failure and clears the
+    /// whole cache afterwards, since notifications sent while disconnected are lost.
+    pub fn spawn_listener(&self, db: DatabaseConnection) {
+        if !self.is_enabled() || db.get_database_backend() != DbBackend::Postgres {
+            return;
+        }
+
+        let cache = self.clone();
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = cache.listen(&db).await {
+                    tracing::warn!("Permission invalidation listener failed, reconnecting: {}", e);
+                }
+                cache.apply(PermissionInvalidation::All);
+                tokio::time::sleep(Duration::from_secs(5)).await;
+            }
+        });
+    }
+
+    async fn listen(&self, db: &DatabaseConnection) -> Result<(), sqlx::Error> {
+        let mut listener = PgListener::connect_with(db.get_postgres_connection_pool()).await?;
+        listener.listen(PERMISSION_INVALIDATION_CHANNEL).await?;
+
+        loop {
+            let notification = listener.recv().await?;
+            match PermissionInvalidation::from_payload(notification.payload()) {
+                Some(invalidation) => self.apply(invalidation),
+                None => tracing::warn!("Ignoring malformed permission invalidation: {}", notification.payload()),
+            }
+        }
+    }
+}
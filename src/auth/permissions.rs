@@ -1,393 +1,861 @@
-use sea_orm::{DatabaseConnection, EntityTrait, ColumnTrait, QueryFilter};
+use sea_orm::{prelude::DateTimeWithTimeZone, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, ColumnTrait, PaginatorTrait, QueryFilter, QuerySelect, RelationTrait};
 use uuid::Uuid;
+use chrono::{DateTime, Utc};
 use std::collections::{HashSet, HashMap};
 
-use crate::entities::{prelude::*, user_permission, resource};
+use crate::auth::{AppPermission, CacheVersion, PermissionCache, PermissionInvalidation, RoleGraph};
+use crate::entities::{organization_member, permission, prelude::*, role, user_permission, resource};
+use crate::services::is_role_visible;
+
+/// Cache scope holding a user's organization memberships
+const ORGANIZATIONS_SCOPE: &str = "organizations";
+
+/// Whether a grant with this validity window applies at `now`. Open ends are unbounded.
+pub fn is_within_window(
+    valid_from: Option<DateTimeWithTimeZone>,
+    valid_until: Option<DateTimeWithTimeZone>,
+    now: DateTime<Utc>,
+) -> bool {
+    valid_from.is_none_or(|from| from <= now) && valid_until.is_none_or(|until| until > now)
+}
+
+/// Where a rule in a permission decision comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PermissionRuleKind {
+    RoleGrant,
+    DirectGrant,
+    DirectDeny,
+    ResourceNotFound,
+    ResourceInactive,
+    PermissionNotFound,
+    NoGrant,
+}
+
+/// One input to a permission decision. `action` is `None` for resource-level rules.
+#[derive(Debug, Clone)]
+pub struct PermissionRule {
+    pub kind: PermissionRuleKind,
+    pub action: Option<String>,
+    pub permission_id: Option<Uuid>,
+    pub role_id: Option<Uuid>,
+    pub role_name: Option<String>,
+    pub valid_from: Option<DateTimeWithTimeZone>,
+    pub valid_until: Option<DateTimeWithTimeZone>,
+    /// Whether the rule took part in the merge
+    pub applied: bool,
+    pub detail: String,
+}
+
+impl PermissionRule {
+    fn resource(kind: PermissionRuleKind, detail: &str) -> Self {
+        Self {
+            kind,
+            action: None,
+            permission_id: None,
+            role_id: None,
+            role_name: None,
+            valid_from: None,
+            valid_until: None,
+            applied: false,
+            detail: detail.to_string(),
+        }
+    }
+}
+
+/// A permission a role holds, directly or through an ancestor
+#[derive(Debug, Clone)]
+pub struct EffectiveRolePermission {
+    pub permission: permission::Model,
+    pub source_role_id: Uuid,
+    pub source_role_name: String,
+    /// Role names from the role up to the source role
+    pub path: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PermissionExplanation {
+    pub allowed: bool,
+    pub rules: Vec<PermissionRule>,
+}
+
+/// Merge rules in order: grants add the action, a direct deny removes it
+pub fn merge_permission_rules(rules: &[PermissionRule]) -> HashSet<String> {
+    let mut permissions = HashSet::new();
+
+    for rule in rules.iter().filter(|rule| rule.applied) {
+        if let Some(action) = &rule.action {
+            match rule.kind {
+                PermissionRuleKind::RoleGrant | PermissionRuleKind::DirectGrant => {
+                    permissions.insert(action.clone());
+                }
+                PermissionRuleKind::DirectDeny => {
+                    permissions.remove(action);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    permissions
+}
+
+/// Whether the user's role assignment is currently in effect
+fn role_assignment_active(user: &crate::entities::user::Model, now: DateTime<Utc>) -> bool {
+    is_within_window(user.role_valid_from, user.role_valid_until, now)
+}
+
+/// Time until the next validity boundary among applied and pending rules, so a cached
+/// permission set never outlives a grant that starts or ends
+fn next_window_boundary(rules: &[PermissionRule], now: DateTime<Utc>) -> Option<std::time::Duration> {
+    rules
+        .iter()
+        .flat_map(|rule| [rule.valid_from, rule.valid_until])
+        .flatten()
+        .map(|boundary| boundary.with_timezone(&Utc))
+        .filter(|boundary| *boundary > now)
+        .min()
+        .and_then(|boundary| (boundary - now).to_std().ok())
+}
+
+/// Cache scope for a resource's permissions as resolved within an organization
+fn permission_scope(resource_name: &str, organization_id: Option<Uuid>) -> String {
+    match organization_id {
+        Some(organization_id) => format!("{}@{}", resource_name, organization_id),
+        None => resource_name.to_string(),
+    }
+}
+
+/// Whether a direct grant or denial applies within `organization_id`. Assignments
+/// without an organization are platform-wide.
+fn is_assignment_visible(assignment: &user_permission::Model, organization_id: Option<Uuid>) -> bool {
+    assignment.organization_id.is_none() || assignment.organization_id == organization_id
+}
 
 #[derive(Clone)]
 pub struct PermissionService {
     db: DatabaseConnection,
+    cache: PermissionCache,
 }
 
 impl PermissionService {
-    pub fn new(db: DatabaseConnection) -> Self {
-        Self { db }
+    pub fn new(db: DatabaseConnection, cache: PermissionCache) -> Self {
+        Self { db, cache }
+    }
+
+    pub fn cache(&self) -> &PermissionCache {
+        &self.cache
+    }
+
+    /// Drop a user's cached permissions on every replica. A failed NOTIFY is logged
+    /// rather than returned; other replicas fall back to the cache TTL.
+    pub async fn invalidate_user(&self, user_id: Uuid) {
+        if let Err(e) = self.cache.invalidate(&self.db, PermissionInvalidation::User(user_id)).await {
+            tracing::warn!("Failed to broadcast permission invalidation for user {}: {}", user_id, e);
+        }
+    }
+
+    /// Drop every cached permission on every replica, for role, permission and
+    /// resource changes that affect an unknown set of users
+    pub async fn invalidate_all(&self) {
+        if let Err(e) = self.cache.invalidate(&self.db, PermissionInvalidation::All).await {
+            tracing::warn!("Failed to broadcast permission invalidation: {}", e);
+        }
     }
 
-    /// Get ALL permissions for a user across ALL resources - OPTIMIZED
+    /// Get ALL permissions for a user across ALL resources
     pub async fn get_user_all_permissions(
         &self,
         user_id: Uuid,
+        organization_id: Option<Uuid>,
     ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
-        use crate::entities::{role_permission};
-        
-        let mut permissions = HashSet::new();
+        let rules = self.collect_users_permission_rules(&[user_id], organization_id, None).await?;
+        let permissions = rules.get(&user_id).map(|rules| merge_permission_rules(rules)).unwrap_or_default();
 
-        // Get user with role in single query
-        let user_with_role = User::find_by_id(user_id)
-            .find_also_related(Role)
+        Ok(permissions.into_iter().collect())
+    }
+
+    /// Get all permissions for a user (both from role and direct assignments) within
+    /// `organization_id`
+    pub async fn get_user_permissions(
+        &self,
+        user_id: Uuid,
+        organization_id: Option<Uuid>,
+        resource_name: &str,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let scope = permission_scope(resource_name, organization_id);
+        if let Some(permissions) = self.cache.get(user_id, &scope) {
+            return Ok(permissions.iter().cloned().collect());
+        }
+
+        let version = self.cache.version(user_id);
+        let rules = self.collect_permission_rules(user_id, organization_id, resource_name).await?;
+        let permissions = merge_permission_rules(&rules);
+        let max_age = next_window_boundary(&rules, Utc::now());
+        self.cache.insert(user_id, &scope, version, permissions.clone(), max_age);
+
+        Ok(permissions.into_iter().collect())
+    }
+
+    /// Every rule that could affect the user's permissions on a resource, in the order
+    /// they are merged: role grants first, then direct grants and denials. Only shared
+    /// roles, platform-wide assignments and those of `organization_id` apply. Rules that
+    /// don't apply (other organization, inactive permission, outside validity window)
+    /// are kept with `applied: false` so callers can explain them.
+    pub async fn collect_permission_rules(
+        &self,
+        user_id: Uuid,
+        organization_id: Option<Uuid>,
+        resource_name: &str,
+    ) -> Result<Vec<PermissionRule>, Box<dyn std::error::Error>> {
+        let resource_id = match self.active_resource_id(resource_name).await? {
+            Ok(resource_id) => resource_id,
+            Err(rule) => return Ok(vec![rule]),
+        };
+
+        let mut rules = self
+            .collect_users_permission_rules(&[user_id], organization_id, Some(resource_id))
+            .await?;
+        Ok(rules.remove(&user_id).unwrap_or_default())
+    }
+
+    /// The resource's id, or the rule explaining why nothing on it can be granted
+    async fn active_resource_id(&self, resource_name: &str) -> Result<Result<Uuid, PermissionRule>, DbErr> {
+        let target_resource = Resource::find()
+            .filter(resource::Column::Name.eq(resource_name))
             .one(&self.db)
             .await?;
 
-        if let Some((_, role_opt)) = user_with_role {
-            // Get ALL role permissions if user has a role (across all resources)
-            if let Some(role) = role_opt {
-                let role_permissions = RolePermission::find()
-                    .filter(role_permission::Column::RoleId.eq(role.id))
-                    .find_also_related(Permission)
-                    .all(&self.db)
-                    .await?;
-
-                for (_, permission_opt) in role_permissions {
-                    if let Some(permission) = permission_opt {
-                        if permission.is_active {
-                            permissions.insert(permission.action);
-                        }
-                    }
+        Ok(match target_resource {
+            Some(resource) if resource.is_active => Ok(resource.id),
+            Some(_) => Err(PermissionRule::resource(PermissionRuleKind::ResourceInactive, "Resource is inactive")),
+            None => Err(PermissionRule::resource(PermissionRuleKind::ResourceNotFound, "Resource does not exist")),
+        })
+    }
+
+    /// `collect_permission_rules` for several users at once, limited to one resource or
+    /// across all of them with `resource_id: None`. Every user gets an entry.
+    async fn collect_users_permission_rules(
+        &self,
+        user_ids: &[Uuid],
+        organization_id: Option<Uuid>,
+        resource_id: Option<Uuid>,
+    ) -> Result<HashMap<Uuid, Vec<PermissionRule>>, DbErr> {
+        use crate::entities::{role_permission, user};
+
+        let now = Utc::now();
+        let mut rules: HashMap<Uuid, Vec<PermissionRule>> =
+            user_ids.iter().map(|user_id| (*user_id, Vec::new())).collect();
+
+        // Roles in effect, each with the roles it inherits from
+        let users_with_roles = User::find()
+            .filter(user::Column::Id.is_in(user_ids.iter().copied()))
+            .find_also_related(Role)
+            .all(&self.db)
+            .await?;
+
+        let role_graph = RoleGraph::load(&self.db).await?;
+        let lineages: Vec<_> = users_with_roles
+            .into_iter()
+            .filter_map(|(user, role)| role.map(|role| (role_graph.lineage(role.id), user, role)))
+            .collect();
+
+        let role_ids: HashSet<Uuid> = lineages
+            .iter()
+            .flat_map(|(lineage, _, _)| lineage.iter().map(|ancestor| ancestor.role.id))
+            .collect();
+        let mut permissions_by_role: HashMap<Uuid, Vec<permission::Model>> = HashMap::new();
+        if !role_ids.is_empty() {
+            for (role_permission, permission_opt) in RolePermission::find()
+                .filter(role_permission::Column::RoleId.is_in(role_ids))
+                .find_also_related(Permission)
+                .all(&self.db)
+                .await?
+            {
+                if let Some(permission) = permission_opt
+                    .filter(|permission| resource_id.is_none_or(|resource_id| permission.resource_id == resource_id))
+                {
+                    permissions_by_role.entry(role_permission.role_id).or_default().push(permission);
                 }
             }
         }
 
-        // Get direct user permissions (can override role permissions) across all resources
+        for (lineage, user, role) in lineages {
+            let role_in_scope = is_role_visible(&role, organization_id);
+            let role_active = role_assignment_active(&user, now);
+            let user_rules = rules.entry(user.id).or_default();
+
+            // Nearest role first, so explanations list the assigned role before ancestors
+            for ancestor in &lineage {
+                for permission in permissions_by_role.get(&ancestor.role.id).into_iter().flatten() {
+                    let source = if ancestor.is_inherited() {
+                        format!("role '{}' (inherited via {})", ancestor.role.name, ancestor.path.join(" → "))
+                    } else {
+                        format!("role '{}'", ancestor.role.name)
+                    };
+
+                    let (applied, detail) = if !role_in_scope {
+                        (false, format!("Role '{}' belongs to another organization", role.name))
+                    } else if !role_active {
+                        (false, format!("Role '{}' assignment is outside its validity window", role.name))
+                    } else if !permission.is_active {
+                        (false, "Permission is inactive".to_string())
+                    } else {
+                        (true, format!("Granted by {}", source))
+                    };
+
+                    user_rules.push(PermissionRule {
+                        kind: PermissionRuleKind::RoleGrant,
+                        action: Some(permission.action.clone()),
+                        permission_id: Some(permission.id),
+                        role_id: Some(ancestor.role.id),
+                        role_name: Some(ancestor.role.name.clone()),
+                        valid_from: user.role_valid_from,
+                        valid_until: user.role_valid_until,
+                        applied,
+                        detail,
+                    });
+                }
+            }
+        }
+
+        // Direct grants and denials, merged after (and so overriding) role grants
         let user_permissions = UserPermission::find()
-            .filter(user_permission::Column::UserId.eq(user_id))
+            .filter(user_permission::Column::UserId.is_in(user_ids.iter().copied()))
             .find_also_related(Permission)
             .all(&self.db)
             .await?;
 
         for (user_perm, permission_opt) in user_permissions {
-            if let Some(permission) = permission_opt {
-                if permission.is_active {
-                    if user_perm.is_granted {
-                        permissions.insert(permission.action);
-                    } else {
-                        // Deny permission explicitly removes it
-                        permissions.remove(&permission.action);
-                    }
-                }
+            let Some(permission) = permission_opt else {
+                continue;
+            };
+            if resource_id.is_some_and(|resource_id| permission.resource_id != resource_id) {
+                continue;
             }
+
+            let kind = if user_perm.is_granted {
+                PermissionRuleKind::DirectGrant
+            } else {
+                PermissionRuleKind::DirectDeny
+            };
+
+            // Grants and denials outside their validity window are ignored
+            let (applied, detail) = if !is_assignment_visible(&user_perm, organization_id) {
+                (false, "Direct assignment belongs to another organization".to_string())
+            } else if !is_within_window(user_perm.valid_from, user_perm.valid_until, now) {
+                (false, "Direct assignment is outside its validity window".to_string())
+            } else if !permission.is_active {
+                (false, "Permission is inactive".to_string())
+            } else if user_perm.is_granted {
+                (true, "Granted directly to the user".to_string())
+            } else {
+                (true, "Denied directly for the user, overriding any role grant".to_string())
+            };
+
+            rules.entry(user_perm.user_id).or_default().push(PermissionRule {
+                kind,
+                action: Some(permission.action),
+                permission_id: Some(permission.id),
+                role_id: None,
+                role_name: None,
+                valid_from: user_perm.valid_from,
+                valid_until: user_perm.valid_until,
+                applied,
+                detail,
+            });
         }
 
-        Ok(permissions.into_iter().collect())
+        Ok(rules)
     }
 
-    /// Get all permissions for a user (both from role and direct assignments) - OPTIMIZED
-    pub async fn get_user_permissions(
+    /// Explain why a user does or doesn't hold `resource_name:action`, using the same
+    /// rules and merge as `get_user_permissions`
+    pub async fn explain_permission(
         &self,
         user_id: Uuid,
+        organization_id: Option<Uuid>,
         resource_name: &str,
-    ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        action: &str,
+    ) -> Result<PermissionExplanation, Box<dyn std::error::Error>> {
+        let rules = self.collect_permission_rules(user_id, organization_id, resource_name).await?;
+        let allowed = merge_permission_rules(&rules).contains(action);
+
+        let mut relevant: Vec<PermissionRule> = rules
+            .into_iter()
+            .filter(|rule| rule.action.is_none() || rule.action.as_deref() == Some(action))
+            .collect();
+
+        if relevant.is_empty() {
+            let permission_exists = Permission::find()
+                .inner_join(Resource)
+                .filter(resource::Column::Name.eq(resource_name))
+                .filter(crate::entities::permission::Column::Action.eq(action))
+                .one(&self.db)
+                .await?
+                .is_some();
+
+            relevant.push(if permission_exists {
+                PermissionRule::resource(PermissionRuleKind::NoGrant, "Neither the user's role nor a direct grant includes this permission")
+            } else {
+                PermissionRule::resource(PermissionRuleKind::PermissionNotFound, "Permission does not exist for this resource")
+            });
+        }
+
+        Ok(PermissionExplanation {
+            allowed,
+            rules: relevant,
+        })
+    }
+
+    /// Permissions a role holds once its ancestors are included. A permission reachable
+    /// through several roles is attributed to the nearest one.
+    pub async fn get_effective_role_permissions(
+        &self,
+        role_id: Uuid,
+    ) -> Result<Vec<EffectiveRolePermission>, Box<dyn std::error::Error>> {
         use crate::entities::{role_permission};
-        
-        let mut permissions = HashSet::new();
 
-        // Get target resource first
-        let target_resource = Resource::find()
-            .filter(resource::Column::Name.eq(resource_name))
-            .filter(resource::Column::IsActive.eq(true))
-            .one(&self.db)
+        let lineage = RoleGraph::load(&self.db).await?.lineage(role_id);
+        let role_permissions = RolePermission::find()
+            .filter(role_permission::Column::RoleId.is_in(lineage.iter().map(|ancestor| ancestor.role.id)))
+            .find_also_related(Permission)
+            .all(&self.db)
             .await?;
 
-        let resource_id = match target_resource {
-            Some(resource) => resource.id,
-            None => return Ok(Vec::new()), // Resource doesn't exist
-        };
+        let mut permissions_by_role: HashMap<Uuid, Vec<permission::Model>> = HashMap::new();
+        for (role_permission, permission_opt) in role_permissions {
+            if let Some(permission) = permission_opt.filter(|permission| permission.is_active) {
+                permissions_by_role.entry(role_permission.role_id).or_default().push(permission);
+            }
+        }
 
-        // Get user with role in single query
-        let user_with_role = User::find_by_id(user_id)
-            .find_also_related(Role)
-            .one(&self.db)
+        let mut seen = HashSet::new();
+        let mut effective = Vec::new();
+        for ancestor in lineage {
+            for permission in permissions_by_role.remove(&ancestor.role.id).unwrap_or_default() {
+                if seen.insert(permission.id) {
+                    effective.push(EffectiveRolePermission {
+                        permission,
+                        source_role_id: ancestor.role.id,
+                        source_role_name: ancestor.role.name.clone(),
+                        path: ancestor.path.clone(),
+                    });
+                }
+            }
+        }
+
+        Ok(effective)
+    }
+
+    /// Whether a role grants system administration, directly or through its ancestors
+    pub async fn role_grants_system_admin<C: ConnectionTrait>(
+        conn: &C,
+        role_id: Uuid,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        use crate::entities::role_permission;
+
+        let lineage = RoleGraph::load(conn).await?.lineage_ids(role_id);
+        let count = RolePermission::find()
+            .filter(role_permission::Column::RoleId.is_in(lineage))
+            .join(sea_orm::JoinType::InnerJoin, role_permission::Relation::Permission.def())
+            .filter(permission::Column::Action.eq(AppPermission::SystemAdmin.action()))
+            .filter(permission::Column::IsActive.eq(true))
+            .count(conn)
             .await?;
 
-        if let Some((_, role_opt)) = user_with_role {
-            // Get role permissions if user has a role
-            if let Some(role) = role_opt {
-                let role_permissions = RolePermission::find()
-                    .filter(role_permission::Column::RoleId.eq(role.id))
-                    .find_also_related(Permission)
-                    .all(&self.db)
-                    .await?;
-
-                for (_, permission_opt) in role_permissions {
-                    if let Some(permission) = permission_opt {
-                        if permission.resource_id == resource_id && permission.is_active {
-                            permissions.insert(permission.action);
-                        }
-                    }
-                }
+        Ok(count > 0)
+    }
+
+    /// Refuse unless `assigner_id` may hand out `role`. System admins may assign any role;
+    /// everyone else only roles below their own level in `organization_id` that don't grant
+    /// system administration.
+    pub async fn ensure_can_assign_role(
+        &self,
+        assigner_id: Uuid,
+        organization_id: Option<Uuid>,
+        role: &role::Model,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if self.user_is_system_admin(assigner_id, organization_id).await? {
+            return Ok(());
+        }
+        if Self::role_grants_system_admin(&self.db, role.id).await? {
+            return Err("Only system administrators can assign roles that grant system administration".into());
+        }
+        if role.level >= self.get_user_role_level(assigner_id, organization_id).await? {
+            return Err("Cannot assign a role at or above your own level".into());
+        }
+        Ok(())
+    }
+
+    /// Refuse unless `editor_id` may create or change `role` so it ends up at `level`.
+    /// System admins may edit any role; everyone else only roles below their own level in
+    /// `organization_id`, and never the role they hold.
+    pub async fn ensure_can_edit_role(
+        &self,
+        editor_id: Uuid,
+        organization_id: Option<Uuid>,
+        role: Option<&role::Model>,
+        level: i32,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if self.user_is_system_admin(editor_id, organization_id).await? {
+            return Ok(());
+        }
+        if let Some(role) = role {
+            let holds_role = User::find_by_id(editor_id)
+                .one(&self.db)
+                .await?
+                .is_some_and(|editor| editor.role_id == Some(role.id));
+            if holds_role {
+                return Err("Cannot edit your own role".into());
             }
         }
 
-        // Get direct user permissions (can override role permissions)
-        let user_permissions = UserPermission::find()
-            .filter(user_permission::Column::UserId.eq(user_id))
-            .find_also_related(Permission)
-            .all(&self.db)
+        let editor_level = self.get_user_role_level(editor_id, organization_id).await?;
+        if level >= editor_level || role.is_some_and(|role| role.level >= editor_level) {
+            return Err("Cannot edit a role at or above your own level".into());
+        }
+        Ok(())
+    }
+
+    /// Replace a role's parents, rejecting unknown or inactive parents, cycles and parents
+    /// above `editor_level`. Organization roles can't inherit system administration.
+    pub async fn set_role_parents<C: ConnectionTrait>(
+        conn: &C,
+        role_id: Uuid,
+        parent_role_ids: &[Uuid],
+        editor_level: i32,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        use crate::entities::role_parent;
+        use sea_orm::{ActiveModelTrait, Set};
+
+        let parent_role_ids: Vec<Uuid> = parent_role_ids
+            .iter()
+            .copied()
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+
+        if parent_role_ids.contains(&role_id) {
+            return Err("A role cannot be its own parent".into());
+        }
+
+        let role = Role::find_by_id(role_id).one(conn).await?.ok_or("Role not found")?;
+
+        let graph = RoleGraph::load(conn).await?;
+        for parent_id in &parent_role_ids {
+            let parent = Role::find_by_id(*parent_id)
+                .one(conn)
+                .await?
+                .ok_or_else(|| format!("Parent role {} not found", parent_id))?;
+            if !parent.is_active {
+                return Err(format!("Parent role '{}' is inactive", parent.name).into());
+            }
+            // Shared roles only inherit from shared roles; organization roles from their
+            // own organization's roles or shared ones
+            if parent.organization_id.is_some() && parent.organization_id != role.organization_id {
+                return Err(format!("Parent role {} not found", parent_id).into());
+            }
+            if parent.level > editor_level {
+                return Err(format!("Parent role '{}' is above your own level", parent.name).into());
+            }
+            // Same rule as assigning permissions to organization roles directly
+            if role.organization_id.is_some() && Self::role_grants_system_admin(conn, parent.id).await? {
+                return Err(format!("Parent role '{}' grants system administration", parent.name).into());
+            }
+        }
+        if graph.would_create_cycle(role_id, &parent_role_ids) {
+            return Err("Parent roles would create a cycle in the role hierarchy".into());
+        }
+
+        RoleParent::delete_many()
+            .filter(role_parent::Column::RoleId.eq(role_id))
+            .exec(conn)
             .await?;
 
-        for (user_perm, permission_opt) in user_permissions {
-            if let Some(permission) = permission_opt {
-                if permission.resource_id == resource_id && permission.is_active {
-                    if user_perm.is_granted {
-                        permissions.insert(permission.action);
-                    } else {
-                        // Deny permission explicitly removes it
-                        permissions.remove(&permission.action);
-                    }
-                }
+        for parent_role_id in parent_role_ids {
+            role_parent::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                role_id: Set(role_id),
+                parent_role_id: Set(parent_role_id),
+                created_at: Set(Utc::now().into()),
             }
+            .insert(conn)
+            .await?;
         }
 
-        Ok(permissions.into_iter().collect())
+        Ok(())
     }
 
-    /// Check if user has a specific permission
+    /// Check if user has a specific permission within `organization_id`
     pub async fn user_has_permission(
         &self,
         user_id: Uuid,
+        organization_id: Option<Uuid>,
         resource_name: &str,
         action: &str,
     ) -> Result<bool, Box<dyn std::error::Error>> {
-        let permissions = self.get_user_permissions(user_id, resource_name).await?;
+        let permissions = self.get_user_permissions(user_id, organization_id, resource_name).await?;
         Ok(permissions.contains(&action.to_string()))
     }
 
+    /// Check if user has a permission from the typed catalog
+    pub async fn user_has(
+        &self,
+        user_id: Uuid,
+        organization_id: Option<Uuid>,
+        permission: AppPermission,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        self.user_has_permission(user_id, organization_id, permission.resource().name(), permission.action())
+            .await
+    }
+
     /// Check if user has admin permissions
     pub async fn user_is_admin(
         &self,
         user_id: Uuid,
-        resource_name: &str,
+        organization_id: Option<Uuid>,
     ) -> Result<bool, Box<dyn std::error::Error>> {
-        self.user_has_permission(user_id, resource_name, "admin").await
+        self.user_has(user_id, organization_id, AppPermission::Admin).await
     }
 
     /// Check if user has system admin permissions (super admin)
     pub async fn user_is_system_admin(
         &self,
         user_id: Uuid,
-        resource_name: &str,
+        organization_id: Option<Uuid>,
     ) -> Result<bool, Box<dyn std::error::Error>> {
-        self.user_has_permission(user_id, resource_name, "system_admin").await
+        self.user_has(user_id, organization_id, AppPermission::SystemAdmin).await
     }
 
-    /// Get user's role level (higher = more permissions) - OPTIMIZED
+    /// Get user's role level (higher = more permissions) within `organization_id`. A role
+    /// of another organization counts as no role.
     pub async fn get_user_role_level(
         &self,
         user_id: Uuid,
+        organization_id: Option<Uuid>,
     ) -> Result<i32, Box<dyn std::error::Error>> {
+        let now = Utc::now();
         let role_level = User::find_by_id(user_id)
             .find_also_related(Role)
             .one(&self.db)
             .await?
-            .and_then(|(_, role_opt)| role_opt)
+            .and_then(|(user, role_opt)| {
+                role_opt.filter(|role| is_role_visible(role, organization_id) && role_assignment_active(&user, now))
+            })
             .map(|role| role.level)
             .unwrap_or(0);
         
         Ok(role_level)
     }
 
-    /// Check if user can manage another user (based on role hierarchy)
+    /// Check if user can manage another user (based on role hierarchy) within
+    /// `organization_id`, or any organization they share without one
     pub async fn user_can_manage_user(
         &self,
         manager_id: Uuid,
         target_id: Uuid,
+        organization_id: Option<Uuid>,
     ) -> Result<bool, Box<dyn std::error::Error>> {
-        let manager_level = self.get_user_role_level(manager_id).await?;
-        let target_level = self.get_user_role_level(target_id).await?;
+        // Users in other organizations are out of reach whatever the role level
+        let reachable = match organization_id {
+            Some(organization_id) => self.user_in_organization(target_id, organization_id).await?,
+            None => self.users_share_organization(manager_id, target_id).await?,
+        };
+        if !reachable {
+            return Ok(false);
+        }
+
+        let manager_level = self.get_user_role_level(manager_id, organization_id).await?;
+        let target_level = self.get_user_role_level(target_id, organization_id).await?;
         
         // Manager must have higher level than target
         Ok(manager_level > target_level)
     }
 
-    /// Grant permission directly to user
+    /// Organizations the user belongs to, cached under the `organizations` scope
+    pub async fn user_organization_ids(&self, user_id: Uuid) -> Result<HashSet<Uuid>, Box<dyn std::error::Error>> {
+        let organization_ids = match self.cache.get(user_id, ORGANIZATIONS_SCOPE) {
+            Some(organization_ids) => (*organization_ids).clone(),
+            None => {
+                let version = self.cache.version(user_id);
+                let organization_ids: HashSet<String> = OrganizationMember::find()
+                    .filter(organization_member::Column::UserId.eq(user_id))
+                    .all(&self.db)
+                    .await?
+                    .into_iter()
+                    .map(|member| member.organization_id.to_string())
+                    .collect();
+                self.cache.insert(user_id, ORGANIZATIONS_SCOPE, version, organization_ids.clone(), None);
+                organization_ids
+            }
+        };
+
+        Ok(organization_ids.iter().filter_map(|id| Uuid::parse_str(id).ok()).collect())
+    }
+
+    /// Check if user belongs to the organization
+    pub async fn user_in_organization(
+        &self,
+        user_id: Uuid,
+        organization_id: Uuid,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        Ok(self.user_organization_ids(user_id).await?.contains(&organization_id))
+    }
+
+    /// Check if two users have at least one organization in common
+    pub async fn users_share_organization(
+        &self,
+        user_id: Uuid,
+        other_user_id: Uuid,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        let organization_ids = self.user_organization_ids(user_id).await?;
+        let other_organization_ids = self.user_organization_ids(other_user_id).await?;
+        Ok(!organization_ids.is_disjoint(&other_organization_ids))
+    }
+
+    /// Grant permission directly to user within `organization_id` (platform-wide without
+    /// one), optionally only for a validity window
     pub async fn grant_user_permission(
         &self,
         user_id: Uuid,
         permission_id: Uuid,
+        organization_id: Option<Uuid>,
+        valid_from: Option<DateTime<Utc>>,
+        valid_until: Option<DateTime<Utc>>,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        use sea_orm::{ActiveModelTrait, Set};
-        use chrono::Utc;
-
-        // Check if permission already exists
-        if let Some(existing) = UserPermission::find()
-            .filter(user_permission::Column::UserId.eq(user_id))
-            .filter(user_permission::Column::PermissionId.eq(permission_id))
-            .one(&self.db)
-            .await?
-        {
-            // Update existing permission
-            let mut active_model: user_permission::ActiveModel = existing.into();
-            active_model.is_granted = Set(true);
-            active_model.updated_at = Set(Utc::now().into());
-            active_model.update(&self.db).await?;
-        } else {
-            // Create new permission
-            let new_permission = user_permission::ActiveModel {
-                id: Set(Uuid::new_v4()),
-                user_id: Set(user_id),
-                permission_id: Set(permission_id),
-                is_granted: Set(true),
-                created_at: Set(Utc::now().into()),
-                updated_at: Set(Utc::now().into()),
-            };
-            new_permission.insert(&self.db).await?;
-        }
-
+        Self::set_user_permission(&self.db, user_id, permission_id, organization_id, true, valid_from, valid_until)
+            .await?;
+        self.invalidate_user(user_id).await;
         Ok(())
     }
 
-    /// Revoke permission from user
+    /// Revoke permission from user within `organization_id`. The denial is permanent until
+    /// granted again.
     pub async fn revoke_user_permission(
         &self,
         user_id: Uuid,
         permission_id: Uuid,
+        organization_id: Option<Uuid>,
     ) -> Result<(), Box<dyn std::error::Error>> {
+        Self::set_user_permission(&self.db, user_id, permission_id, organization_id, false, None, None).await?;
+        self.invalidate_user(user_id).await;
+        Ok(())
+    }
+
+    /// Upsert the user's direct grant or denial for a permission within `organization_id`,
+    /// replacing any previous window
+    pub async fn set_user_permission<C: ConnectionTrait>(
+        conn: &C,
+        user_id: Uuid,
+        permission_id: Uuid,
+        organization_id: Option<Uuid>,
+        is_granted: bool,
+        valid_from: Option<DateTime<Utc>>,
+        valid_until: Option<DateTime<Utc>>,
+    ) -> Result<user_permission::Model, DbErr> {
         use sea_orm::{ActiveModelTrait, Set};
-        use chrono::Utc;
+
+        if let (Some(from), Some(until)) = (valid_from, valid_until)
+            && until <= from
+        {
+            return Err(DbErr::Custom("validUntil must be after validFrom".to_string()));
+        }
 
         // Check if permission already exists
         if let Some(existing) = UserPermission::find()
             .filter(user_permission::Column::UserId.eq(user_id))
             .filter(user_permission::Column::PermissionId.eq(permission_id))
-            .one(&self.db)
+            .filter(match organization_id {
+                Some(organization_id) => user_permission::Column::OrganizationId.eq(organization_id),
+                None => user_permission::Column::OrganizationId.is_null(),
+            })
+            .one(conn)
             .await?
         {
-            // Update existing permission to deny
             let mut active_model: user_permission::ActiveModel = existing.into();
-            active_model.is_granted = Set(false);
+            active_model.is_granted = Set(is_granted);
+            active_model.valid_from = Set(valid_from.map(Into::into));
+            active_model.valid_until = Set(valid_until.map(Into::into));
             active_model.updated_at = Set(Utc::now().into());
-            active_model.update(&self.db).await?;
+            active_model.update(conn).await
         } else {
-            // Create new denial permission
             let new_permission = user_permission::ActiveModel {
                 id: Set(Uuid::new_v4()),
                 user_id: Set(user_id),
                 permission_id: Set(permission_id),
-                is_granted: Set(false),
+                is_granted: Set(is_granted),
+                valid_from: Set(valid_from.map(Into::into)),
+                valid_until: Set(valid_until.map(Into::into)),
                 created_at: Set(Utc::now().into()),
                 updated_at: Set(Utc::now().into()),
+                organization_id: Set(organization_id),
             };
-            new_permission.insert(&self.db).await?;
+            new_permission.insert(conn).await
         }
-
-        Ok(())
     }
 
-    /// Batch load permissions for multiple users - OPTIMIZED for GraphQL resolvers
+    /// Batch load permissions for multiple users, for GraphQL resolvers. Shares the
+    /// rules, merge and cache with `get_user_permissions`.
     pub async fn get_users_permissions_batch(
         &self,
         user_ids: &[Uuid],
+        organization_id: Option<Uuid>,
         resource_name: &str,
     ) -> Result<HashMap<Uuid, Vec<String>>, Box<dyn std::error::Error>> {
-        use crate::entities::{role_permission, user};
-        
-        let mut result: HashMap<Uuid, HashSet<String>> = HashMap::new();
-        
-        // Initialize result map
-        for &user_id in user_ids {
-            result.insert(user_id, HashSet::new());
-        }
-
-        // Get target resource
-        let target_resource = Resource::find()
-            .filter(resource::Column::Name.eq(resource_name))
-            .filter(resource::Column::IsActive.eq(true))
-            .one(&self.db)
-            .await?;
-
-        let resource_id = match target_resource {
-            Some(resource) => resource.id,
-            None => return Ok(HashMap::new()), // Resource doesn't exist
-        };
-
-        // Get all users with their roles
-        let users_with_roles = User::find()
-            .filter(user::Column::Id.is_in(user_ids.iter().cloned()))
-            .find_also_related(Role)
-            .all(&self.db)
-            .await?;
-
-        // Track users with roles for role permission lookup
-        let mut users_with_roles_map: HashMap<Uuid, Uuid> = HashMap::new();
-
-        for (user, role_opt) in &users_with_roles {
-            if let Some(role) = role_opt {
-                users_with_roles_map.insert(user.id, role.id);
-            }
-        }
-
-        // Batch load role permissions for all roles at once
-        if !users_with_roles_map.is_empty() {
-            let role_ids: Vec<Uuid> = users_with_roles_map.values().cloned().collect();
-            
-            let role_permissions = RolePermission::find()
-                .filter(role_permission::Column::RoleId.is_in(role_ids))
-                .find_also_related(Permission)
-                .all(&self.db)
-                .await?;
-
-            // Group role permissions by role_id
-            let mut role_perms_map: HashMap<Uuid, Vec<String>> = HashMap::new();
-            for (role_perm, permission_opt) in role_permissions {
-                if let Some(permission) = permission_opt {
-                    if permission.resource_id == resource_id && permission.is_active {
-                        role_perms_map
-                            .entry(role_perm.role_id)
-                            .or_insert_with(Vec::new)
-                            .push(permission.action);
-                    }
+        // Serve cached users directly and only load the rest
+        let scope = permission_scope(resource_name, organization_id);
+        let mut result: HashMap<Uuid, Vec<String>> = HashMap::new();
+        let missing: Vec<Uuid> = user_ids
+            .iter()
+            .copied()
+            .filter(|user_id| match self.cache.get(*user_id, &scope) {
+                Some(permissions) => {
+                    result.insert(*user_id, permissions.iter().cloned().collect());
+                    false
                 }
-            }
+                None => true,
+            })
+            .collect();
 
-            // Apply role permissions to users
-            for (user_id, role_id) in &users_with_roles_map {
-                if let Some(role_permissions) = role_perms_map.get(role_id) {
-                    let user_permissions = result.entry(*user_id).or_insert_with(HashSet::new);
-                    for perm in role_permissions {
-                        user_permissions.insert(perm.clone());
-                    }
-                }
-            }
+        if missing.is_empty() {
+            return Ok(result);
         }
 
-        // Get direct user permissions for all users
-        let user_permissions = UserPermission::find()
-            .filter(user_permission::Column::UserId.is_in(user_ids.iter().cloned()))
-            .find_also_related(Permission)
-            .all(&self.db)
-            .await?;
+        let resource_id = match self.active_resource_id(resource_name).await? {
+            Ok(resource_id) => resource_id,
+            Err(_) => {
+                result.extend(missing.into_iter().map(|user_id| (user_id, Vec::new())));
+                return Ok(result);
+            }
+        };
 
-        for (user_perm, permission_opt) in user_permissions {
-            if let Some(permission) = permission_opt {
-                if permission.resource_id == resource_id && permission.is_active {
-                    let user_permissions = result.entry(user_perm.user_id).or_insert_with(HashSet::new);
-                    if user_perm.is_granted {
-                        user_permissions.insert(permission.action);
-                    } else {
-                        // Deny permission explicitly removes it
-                        user_permissions.remove(&permission.action);
-                    }
-                }
+        let versions: HashMap<Uuid, CacheVersion> =
+            missing.iter().map(|user_id| (*user_id, self.cache.version(*user_id))).collect();
+        let now = Utc::now();
+        for (user_id, rules) in self
+            .collect_users_permission_rules(&missing, organization_id, Some(resource_id))
+            .await?
+        {
+            let permissions = merge_permission_rules(&rules);
+            if let Some(version) = versions.get(&user_id) {
+                self.cache
+                    .insert(user_id, &scope, *version, permissions.clone(), next_window_boundary(&rules, now));
             }
+            result.insert(user_id, permissions.into_iter().collect());
         }
 
-        // Convert HashSets to Vecs
-        let final_result = result
-            .into_iter()
-            .map(|(user_id, perms)| (user_id, perms.into_iter().collect()))
-            .collect();
-
-        Ok(final_result)
+        Ok(result)
     }
 
     /// Batch check permissions for multiple users
     pub async fn users_have_permission_batch(
         &self,
         user_ids: &[Uuid],
+        organization_id: Option<Uuid>,
         resource_name: &str,
         action: &str,
     ) -> Result<HashMap<Uuid, bool>, Box<dyn std::error::Error>> {
-        let permissions_map = self.get_users_permissions_batch(user_ids, resource_name).await?;
+        let permissions_map = self.get_users_permissions_batch(user_ids, organization_id, resource_name).await?;
         
         let result = permissions_map
             .into_iter()
@@ -400,97 +868,17 @@ impl PermissionService {
         Ok(result)
     }
 
-    /// Batch load ALL permissions for multiple users across ALL resources - OPTIMIZED for GraphQL resolvers
+    /// Batch load ALL permissions for multiple users across ALL resources, for GraphQL resolvers
     pub async fn get_users_all_permissions_batch(
         &self,
         user_ids: &[Uuid],
+        organization_id: Option<Uuid>,
     ) -> Result<HashMap<Uuid, Vec<String>>, Box<dyn std::error::Error>> {
-        use crate::entities::{role_permission, user};
-        
-        let mut result: HashMap<Uuid, HashSet<String>> = HashMap::new();
-        
-        // Initialize result map
-        for &user_id in user_ids {
-            result.insert(user_id, HashSet::new());
-        }
-
-        // Get all users with their roles
-        let users_with_roles = User::find()
-            .filter(user::Column::Id.is_in(user_ids.iter().cloned()))
-            .find_also_related(Role)
-            .all(&self.db)
-            .await?;
-
-        // Track users with roles for role permission lookup
-        let mut users_with_roles_map: HashMap<Uuid, Uuid> = HashMap::new();
-
-        for (user, role_opt) in &users_with_roles {
-            if let Some(role) = role_opt {
-                users_with_roles_map.insert(user.id, role.id);
-            }
-        }
-
-        // Batch load role permissions for all roles at once (across ALL resources)
-        if !users_with_roles_map.is_empty() {
-            let role_ids: Vec<Uuid> = users_with_roles_map.values().cloned().collect();
-            
-            let role_permissions = RolePermission::find()
-                .filter(role_permission::Column::RoleId.is_in(role_ids))
-                .find_also_related(Permission)
-                .all(&self.db)
-                .await?;
-
-            // Group role permissions by role_id
-            let mut role_perms_map: HashMap<Uuid, Vec<String>> = HashMap::new();
-            for (role_perm, permission_opt) in role_permissions {
-                if let Some(permission) = permission_opt {
-                    if permission.is_active {
-                        role_perms_map
-                            .entry(role_perm.role_id)
-                            .or_insert_with(Vec::new)
-                            .push(permission.action);
-                    }
-                }
-            }
-
-            // Apply role permissions to users
-            for (user_id, role_id) in &users_with_roles_map {
-                if let Some(role_permissions) = role_perms_map.get(role_id) {
-                    let user_permissions = result.entry(*user_id).or_insert_with(HashSet::new);
-                    for perm in role_permissions {
-                        user_permissions.insert(perm.clone());
-                    }
-                }
-            }
-        }
-
-        // Get direct user permissions for all users (across ALL resources)
-        let user_permissions = UserPermission::find()
-            .filter(user_permission::Column::UserId.is_in(user_ids.iter().cloned()))
-            .find_also_related(Permission)
-            .all(&self.db)
-            .await?;
-
-        for (user_perm, permission_opt) in user_permissions {
-            if let Some(permission) = permission_opt {
-                if permission.is_active {
-                    let user_permissions = result.entry(user_perm.user_id).or_insert_with(HashSet::new);
-                    if user_perm.is_granted {
-                        user_permissions.insert(permission.action);
-                    } else {
-                        // Deny permission explicitly removes it
-                        user_permissions.remove(&permission.action);
-                    }
-                }
-            }
-        }
+        let rules = self.collect_users_permission_rules(user_ids, organization_id, None).await?;
 
-        // Convert HashSets to Vecs
-        let final_result = result
+        Ok(rules
             .into_iter()
-            .map(|(user_id, perms)| (user_id, perms.into_iter().collect()))
-            .collect();
-
-        Ok(final_result)
+            .map(|(user_id, rules)| (user_id, merge_permission_rules(&rules).into_iter().collect()))
+            .collect())
     }
 }
\ No newline at end of file
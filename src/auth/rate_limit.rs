@@ -0,0 +1,400 @@
+use std::collections::{HashMap, VecDeque};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{header::RETRY_AFTER, HeaderMap, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Duration, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbBackend, DbErr, EntityTrait, PaginatorTrait,
+    QueryFilter, QueryOrder, Set, Statement, TransactionTrait,
+};
+use uuid::Uuid;
+
+use crate::entities::{prelude::*, rate_limit_event};
+
+/// Client IP address resolved by `rate_limit_middleware`
+#[derive(Debug, Clone)]
+pub struct ClientIp(pub String);
+
+/// Sliding-window limit: at most `max_requests` within `window_seconds`
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimitRule {
+    pub max_requests: u32,
+    pub window_seconds: i64,
+}
+
+impl RateLimitRule {
+    pub fn new(max_requests: u32, window_seconds: i64) -> Self {
+        Self { max_requests, window_seconds }
+    }
+}
+
+/// Per-IP and per-account limits for a single operation
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimitPolicy {
+    pub per_ip: RateLimitRule,
+    pub per_account: RateLimitRule,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitScope {
+    Http,
+    Login,
+    PasswordReset,
+    MagicLink,
+    AcceptInvitation,
+}
+
+impl RateLimitScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RateLimitScope::Http => "http",
+            RateLimitScope::Login => "login",
+            RateLimitScope::PasswordReset => "password_reset",
+            RateLimitScope::MagicLink => "magic_link",
+            RateLimitScope::AcceptInvitation => "accept_invitation",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RateLimitConfig {
+    /// Coarse per-IP limit applied to every HTTP request by the axum layer
+    pub http_per_ip: RateLimitRule,
+    pub login: RateLimitPolicy,
+    pub password_reset: RateLimitPolicy,
+    pub accept_invitation: RateLimitPolicy,
+    /// Reverse proxies in front of the server that append to `X-Forwarded-For`.
+    /// With none, the socket peer address is the client.
+    pub trusted_proxies: usize,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            http_per_ip: RateLimitRule::new(300, 60),
+            login: RateLimitPolicy {
+                per_ip: RateLimitRule::new(20, 300),
+                per_account: RateLimitRule::new(10, 900),
+            },
+            password_reset: RateLimitPolicy {
+                per_ip: RateLimitRule::new(10, 3600),
+                per_account: RateLimitRule::new(3, 3600),
+            },
+            accept_invitation: RateLimitPolicy {
+                per_ip: RateLimitRule::new(10, 3600),
+                per_account: RateLimitRule::new(5, 3600),
+            },
+            trusted_proxies: 0,
+        }
+    }
+}
+
+impl RateLimitConfig {
+    pub fn policy(&self, scope: RateLimitScope) -> RateLimitPolicy {
+        match scope {
+            RateLimitScope::Http => RateLimitPolicy {
+                per_ip: self.http_per_ip,
+                per_account: self.http_per_ip,
+            },
+            RateLimitScope::Login => self.login,
+            // Magic links go to the same mailbox as reset links, so share the budget
+            RateLimitScope::PasswordReset | RateLimitScope::MagicLink => self.password_reset,
+            RateLimitScope::AcceptInvitation => self.accept_invitation,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimitDecision {
+    pub allowed: bool,
+    pub retry_after_seconds: i64,
+}
+
+impl RateLimitDecision {
+    fn allowed() -> Self {
+        Self { allowed: true, retry_after_seconds: 0 }
+    }
+
+    fn denied(retry_after_seconds: i64) -> Self {
+        Self { allowed: false, retry_after_seconds: retry_after_seconds.max(1) }
+    }
+}
+
+/// Backing store for the sliding-window log
+#[derive(Clone)]
+pub enum RateLimitStore {
+    /// Single-process store, lost on restart
+    InMemory(Arc<Mutex<HashMap<String, VecDeque<DateTime<Utc>>>>>),
+    /// Shared across replicas via the `rate_limit_event` table
+    Postgres(DatabaseConnection),
+}
+
+#[derive(Clone)]
+pub struct RateLimiter {
+    store: RateLimitStore,
+    config: RateLimitConfig,
+}
+
+impl RateLimiter {
+    pub fn in_memory(config: RateLimitConfig) -> Self {
+        Self {
+            store: RateLimitStore::InMemory(Arc::new(Mutex::new(HashMap::new()))),
+            config,
+        }
+    }
+
+    pub fn postgres(db: DatabaseConnection, config: RateLimitConfig) -> Self {
+        Self {
+            store: RateLimitStore::Postgres(db),
+            config,
+        }
+    }
+
+    /// Record a hit for `key` and decide whether it is within `rule`
+    pub async fn hit(&self, key: &str, rule: RateLimitRule) -> Result<RateLimitDecision, DbErr> {
+        let now = Utc::now();
+        let window_start = now - Duration::seconds(rule.window_seconds);
+
+        match &self.store {
+            RateLimitStore::InMemory(entries) => {
+                let mut entries = entries.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                let hits = entries.entry(key.to_string()).or_default();
+
+                while hits.front().is_some_and(|hit| *hit <= window_start) {
+                    hits.pop_front();
+                }
+
+                if hits.len() as u32 >= rule.max_requests {
+                    let oldest = hits.front().copied().unwrap_or(now);
+                    let retry_after = (oldest + Duration::seconds(rule.window_seconds) - now).num_seconds();
+                    return Ok(RateLimitDecision::denied(retry_after));
+                }
+
+                hits.push_back(now);
+                Ok(RateLimitDecision::allowed())
+            }
+            RateLimitStore::Postgres(db) => {
+                // Serialize hits on the same key so concurrent requests can't all pass
+                // the count before any of them is recorded
+                let tx = db.begin().await?;
+                tx.execute(Statement::from_sql_and_values(
+                    DbBackend::Postgres,
+                    "SELECT pg_advisory_xact_lock(hashtextextended($1, 0))",
+                    [key.into()],
+                ))
+                .await?;
+
+                // Drop hits that fell out of the window for this key
+                RateLimitEvent::delete_many()
+                    .filter(rate_limit_event::Column::Key.eq(key))
+                    .filter(rate_limit_event::Column::CreatedAt.lte(window_start))
+                    .exec(&tx)
+                    .await?;
+
+                let count = RateLimitEvent::find()
+                    .filter(rate_limit_event::Column::Key.eq(key))
+                    .count(&tx)
+                    .await?;
+
+                if count as u32 >= rule.max_requests {
+                    let oldest = RateLimitEvent::find()
+                        .filter(rate_limit_event::Column::Key.eq(key))
+                        .order_by_asc(rate_limit_event::Column::CreatedAt)
+                        .one(&tx)
+                        .await?
+                        .map(|event| event.created_at.with_timezone(&Utc))
+                        .unwrap_or(now);
+                    tx.commit().await?;
+                    let retry_after = (oldest + Duration::seconds(rule.window_seconds) - now).num_seconds();
+                    return Ok(RateLimitDecision::denied(retry_after));
+                }
+
+                let event = rate_limit_event::ActiveModel {
+                    id: Set(Uuid::new_v4()),
+                    key: Set(key.to_string()),
+                    created_at: Set(now.into()),
+                };
+                event.insert(&tx).await?;
+                tx.commit().await?;
+
+                Ok(RateLimitDecision::allowed())
+            }
+        }
+    }
+
+    /// Check the per-IP and (optionally) per-account limits for an operation
+    pub async fn check(
+        &self,
+        scope: RateLimitScope,
+        client_ip: Option<&str>,
+        account: Option<&str>,
+    ) -> Result<RateLimitDecision, DbErr> {
+        let policy = self.config.policy(scope);
+
+        if let Some(ip) = client_ip {
+            let decision = self
+                .hit(&format!("{}:ip:{}", scope.as_str(), ip), policy.per_ip)
+                .await?;
+            if !decision.allowed {
+                return Ok(decision);
+            }
+        }
+
+        if let Some(account) = account {
+            let decision = self
+                .hit(
+                    &format!("{}:account:{}", scope.as_str(), account.trim().to_lowercase()),
+                    policy.per_account,
+                )
+                .await?;
+            if !decision.allowed {
+                return Ok(decision);
+            }
+        }
+
+        Ok(RateLimitDecision::allowed())
+    }
+
+    /// Remove events older than the longest configured window (Postgres store only)
+    pub async fn purge_expired(&self) -> Result<u64, DbErr> {
+        match &self.store {
+            RateLimitStore::InMemory(entries) => {
+                let mut entries = entries.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                let cutoff = Utc::now() - Duration::seconds(self.longest_window_seconds());
+                entries.retain(|_, hits| hits.back().is_some_and(|hit| *hit > cutoff));
+                Ok(0)
+            }
+            RateLimitStore::Postgres(db) => {
+                let cutoff = Utc::now() - Duration::seconds(self.longest_window_seconds());
+                let result = RateLimitEvent::delete_many()
+                    .filter(rate_limit_event::Column::CreatedAt.lte(cutoff))
+                    .exec(db)
+                    .await?;
+                Ok(result.rows_affected)
+            }
+        }
+    }
+
+    fn longest_window_seconds(&self) -> i64 {
+        [
+            self.config.http_per_ip,
+            self.config.login.per_ip,
+            self.config.login.per_account,
+            self.config.password_reset.per_ip,
+            self.config.password_reset.per_account,
+            self.config.accept_invitation.per_ip,
+            self.config.accept_invitation.per_account,
+        ]
+        .iter()
+        .map(|rule| rule.window_seconds)
+        .max()
+        .unwrap_or(3600)
+    }
+}
+
+/// Resolve the client IP. Behind `trusted_proxies` proxies the client is the entry the
+/// outermost of them appended to `X-Forwarded-For`; anything left of it is whatever the
+/// client sent and can't be trusted. Without proxies, or when the header doesn't carry
+/// enough hops, the socket peer address is used.
+pub fn resolve_client_ip(headers: &HeaderMap, peer: Option<SocketAddr>, trusted_proxies: usize) -> Option<String> {
+    let forwarded_client = (trusted_proxies > 0)
+        .then(|| {
+            let hops: Vec<&str> = headers
+                .get_all("x-forwarded-for")
+                .iter()
+                .filter_map(|value| value.to_str().ok())
+                .flat_map(|value| value.split(','))
+                .map(str::trim)
+                .collect();
+            hops.len()
+                .checked_sub(trusted_proxies)
+                .and_then(|index| hops[index].parse::<IpAddr>().ok())
+        })
+        .flatten();
+
+    forwarded_client
+        .or_else(|| peer.map(|addr| addr.ip()))
+        .map(|ip| ip.to_string())
+}
+
+/// Axum layer applying the coarse per-IP limit and exposing `ClientIp` to handlers
+pub async fn rate_limit_middleware(
+    State(rate_limiter): State<RateLimiter>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let peer = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| *addr);
+    let client_ip = resolve_client_ip(request.headers(), peer, rate_limiter.config.trusted_proxies);
+
+    if let Some(ip) = &client_ip {
+        match rate_limiter.check(RateLimitScope::Http, Some(ip), None).await {
+            Ok(decision) if !decision.allowed => {
+                tracing::warn!("🚦 Rate limit exceeded for {}", ip);
+                return (
+                    StatusCode::TOO_MANY_REQUESTS,
+                    [(RETRY_AFTER, decision.retry_after_seconds.to_string())],
+                    "Too many requests",
+                )
+                    .into_response();
+            }
+            Ok(_) => {}
+            Err(e) => {
+                // Fail open: a broken store must not take the API down
+                tracing::error!("Rate limit check failed: {}", e);
+            }
+        }
+
+        request.extensions_mut().insert(ClientIp(ip.clone()));
+    }
+
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn forwarded(values: &[&str]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for value in values {
+            headers.append("x-forwarded-for", HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    fn peer() -> Option<SocketAddr> {
+        Some("10.0.0.2:41000".parse().unwrap())
+    }
+
+    #[test]
+    fn without_trusted_proxies_the_peer_is_the_client() {
+        let headers = forwarded(&["203.0.113.7"]);
+        assert_eq!(resolve_client_ip(&headers, peer(), 0).as_deref(), Some("10.0.0.2"));
+    }
+
+    #[test]
+    fn spoofed_leading_hops_are_ignored() {
+        let headers = forwarded(&["198.51.100.1, 203.0.113.7"]);
+        assert_eq!(resolve_client_ip(&headers, peer(), 1).as_deref(), Some("203.0.113.7"));
+
+        let headers = forwarded(&["198.51.100.1", "203.0.113.7, 10.0.0.1"]);
+        assert_eq!(resolve_client_ip(&headers, peer(), 2).as_deref(), Some("203.0.113.7"));
+    }
+
+    #[test]
+    fn missing_or_malformed_hops_fall_back_to_the_peer() {
+        assert_eq!(resolve_client_ip(&HeaderMap::new(), peer(), 1).as_deref(), Some("10.0.0.2"));
+        assert_eq!(resolve_client_ip(&forwarded(&["203.0.113.7"]), peer(), 2).as_deref(), Some("10.0.0.2"));
+        assert_eq!(resolve_client_ip(&forwarded(&["not-an-ip"]), peer(), 1).as_deref(), Some("10.0.0.2"));
+    }
+}
@@ -0,0 +1,104 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use sea_orm::{ConnectionTrait, DbErr, EntityTrait};
+use uuid::Uuid;
+
+use crate::entities::{prelude::*, role};
+
+/// A role reached while walking up the hierarchy from a starting role
+#[derive(Debug, Clone)]
+pub struct RoleAncestor {
+    pub role: role::Model,
+    /// Role names from the starting role up to this one, both included
+    pub path: Vec<String>,
+}
+
+impl RoleAncestor {
+    pub fn is_inherited(&self) -> bool {
+        self.path.len() > 1
+    }
+}
+
+/// Snapshot of every role and its declared parents
+pub struct RoleGraph {
+    roles: HashMap<Uuid, role::Model>,
+    parents: HashMap<Uuid, Vec<Uuid>>,
+}
+
+impl RoleGraph {
+    pub async fn load<C: ConnectionTrait>(conn: &C) -> Result<Self, DbErr> {
+        let roles = Role::find()
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|role| (role.id, role))
+            .collect();
+
+        let mut parents: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+        for edge in RoleParent::find().all(conn).await? {
+            parents.entry(edge.role_id).or_default().push(edge.parent_role_id);
+        }
+
+        Ok(Self { roles, parents })
+    }
+
+    /// Roles declared as direct parents of `role_id`
+    pub fn parents(&self, role_id: Uuid) -> Vec<&role::Model> {
+        self.parents
+            .get(&role_id)
+            .into_iter()
+            .flatten()
+            .filter_map(|parent_id| self.roles.get(parent_id))
+            .collect()
+    }
+
+    /// The role followed by its ancestors, nearest first. Inactive (deleted) roles
+    /// pass nothing on, so their branch of the hierarchy is skipped.
+    pub fn lineage(&self, role_id: Uuid) -> Vec<RoleAncestor> {
+        let Some(start) = self.roles.get(&role_id) else {
+            return Vec::new();
+        };
+
+        let mut lineage = Vec::new();
+        let mut visited = HashSet::from([role_id]);
+        let mut queue = VecDeque::from([RoleAncestor {
+            role: start.clone(),
+            path: vec![start.name.clone()],
+        }]);
+
+        while let Some(ancestor) = queue.pop_front() {
+            for parent in self.parents(ancestor.role.id) {
+                if parent.is_active && visited.insert(parent.id) {
+                    let mut path = ancestor.path.clone();
+                    path.push(parent.name.clone());
+                    queue.push_back(RoleAncestor { role: parent.clone(), path });
+                }
+            }
+            lineage.push(ancestor);
+        }
+
+        lineage
+    }
+
+    /// Ids of the role and its active ancestors
+    pub fn lineage_ids(&self, role_id: Uuid) -> Vec<Uuid> {
+        self.lineage(role_id).into_iter().map(|ancestor| ancestor.role.id).collect()
+    }
+
+    /// Whether giving `role_id` these parents would make it its own ancestor
+    pub fn would_create_cycle(&self, role_id: Uuid, parent_ids: &[Uuid]) -> bool {
+        let mut visited = HashSet::new();
+        let mut stack: Vec<Uuid> = parent_ids.to_vec();
+
+        while let Some(current) = stack.pop() {
+            if current == role_id {
+                return true;
+            }
+            if visited.insert(current) {
+                stack.extend(self.parents.get(&current).into_iter().flatten().copied());
+            }
+        }
+
+        false
+    }
+}
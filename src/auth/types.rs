@@ -7,12 +7,32 @@ pub struct Claims {
     pub email: String,
     pub exp: i64,   // expiration timestamp
     pub iat: i64,   // issued at timestamp
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub act: Option<ActorClaim>, // set when an admin is impersonating `sub`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub org: Option<Uuid>, // organization the session is working in
+}
+
+/// The identity actually acting on behalf of `sub` (RFC 8693 style `act` claim)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActorClaim {
+    pub sub: Uuid,
 }
 
 #[derive(Debug, Clone)]
 pub struct AuthenticatedUser {
     pub id: Uuid,
     pub email: String,
+    /// Admin impersonating this user, if any
+    pub actor_id: Option<Uuid>,
+    /// Organization selected with the org switcher, if any
+    pub organization_id: Option<Uuid>,
+}
+
+impl AuthenticatedUser {
+    pub fn is_impersonated(&self) -> bool {
+        self.actor_id.is_some()
+    }
 }
 
 impl From<Claims> for AuthenticatedUser {
@@ -20,6 +40,8 @@ impl From<Claims> for AuthenticatedUser {
         Self {
             id: claims.sub,
             email: claims.email,
+            actor_id: claims.act.map(|act| act.sub),
+            organization_id: claims.org,
         }
     }
-}
\ No newline at end of file
+}
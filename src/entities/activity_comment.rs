@@ -16,6 +16,14 @@ pub struct Model {
     #[sea_orm(column_type = "JsonBinary", nullable)]
     pub attachments: Option<serde_json::Value>,
     pub created_at: DateTimeWithTimeZone,
+    pub author_id: Uuid,
+    pub parent_comment_id: Option<Uuid>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub content_html: Option<String>,
+    pub edited_at: Option<DateTimeWithTimeZone>,
+    pub deleted_at: Option<DateTimeWithTimeZone>,
+    pub deleted_by: Option<Uuid>,
+    pub updated_at: DateTimeWithTimeZone,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -28,6 +36,26 @@ pub enum Relation {
         on_delete = "Cascade"
     )]
     Activity,
+    #[sea_orm(
+        belongs_to = "Entity",
+        from = "Column::ParentCommentId",
+        to = "Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    SelfRef,
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::AuthorId",
+        to = "super::user::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    User,
+    #[sea_orm(has_many = "super::activity_comment_reaction::Entity")]
+    ActivityCommentReaction,
+    #[sea_orm(has_many = "super::activity_comment_revision::Entity")]
+    ActivityCommentRevision,
 }
 
 impl Related<super::activity::Entity> for Entity {
@@ -36,4 +64,22 @@ impl Related<super::activity::Entity> for Entity {
     }
 }
 
-impl ActiveModelBehavior for ActiveModel {}
\ No newline at end of file
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl Related<super::activity_comment_reaction::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ActivityCommentReaction.def()
+    }
+}
+
+impl Related<super::activity_comment_revision::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ActivityCommentRevision.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
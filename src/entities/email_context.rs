@@ -1,7 +1,7 @@
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
 use rust_decimal::Decimal;
-use crate::graphql::types::AccountingProcess;
+use crate::graphql::types::{AccountingProcess, EmailDirection};
 
 #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel, Serialize, Deserialize)]
 #[sea_orm(table_name = "email_context")]
@@ -16,6 +16,7 @@ pub struct Model {
     pub cc_emails: Option<Vec<String>>,
     pub bcc_emails: Option<Vec<String>>,
     pub reply_to: Option<String>,
+    pub direction: EmailDirection,
     
     // Content
     pub subject: String,
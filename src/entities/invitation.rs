@@ -20,10 +20,21 @@ pub struct Model {
     pub created_at: DateTimeWithTimeZone,
     pub updated_at: DateTimeWithTimeZone,
     pub role_id: Option<Uuid>,
+    pub organization_id: Option<Uuid>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::organization::Entity",
+        from = "Column::OrganizationId",
+        to = "super::organization::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Organization,
+    #[sea_orm(has_many = "super::project_invitation::Entity")]
+    ProjectInvitation,
     #[sea_orm(
         belongs_to = "super::role::Entity",
         from = "Column::RoleId",
@@ -42,6 +53,18 @@ pub enum Relation {
     User,
 }
 
+impl Related<super::organization::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Organization.def()
+    }
+}
+
+impl Related<super::project_invitation::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProjectInvitation.def()
+    }
+}
+
 impl Related<super::role::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Role.def()
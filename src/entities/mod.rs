@@ -2,20 +2,42 @@
 
 pub mod prelude;
 
+pub mod access_request;
 pub mod activity;
 pub mod activity_comment;
+pub mod activity_comment_reaction;
+pub mod activity_comment_revision;
 pub mod context_type;
+pub mod digest_preference;
 pub mod email_attachment;
 pub mod email_context;
+pub mod email_outbox;
 pub mod invitation;
+pub mod notification;
+pub mod notification_preference;
+pub mod organization;
+pub mod organization_member;
+pub mod password_history;
 pub mod permission;
 pub mod project;
 pub mod project_context;
 pub mod project_context_category;
+pub mod project_invitation;
 pub mod project_member;
+pub mod project_role;
+pub mod project_role_permission;
+pub mod project_team;
+pub mod rate_limit_event;
 pub mod resource;
 pub mod role;
+pub mod role_parent;
 pub mod role_permission;
 pub mod task;
+pub mod task_watcher;
+pub mod team;
+pub mod team_member;
 pub mod user;
 pub mod user_permission;
+pub mod webhook_delivery;
+pub mod webhook_delivery_attempt;
+pub mod webhook_subscription;
@@ -27,6 +27,10 @@ pub enum Relation {
         on_delete = "Cascade"
     )]
     Resource,
+    #[sea_orm(has_many = "super::access_request::Entity")]
+    AccessRequest,
+    #[sea_orm(has_many = "super::project_role_permission::Entity")]
+    ProjectRolePermission,
     #[sea_orm(has_many = "super::role_permission::Entity")]
     RolePermission,
     #[sea_orm(has_many = "super::user_permission::Entity")]
@@ -39,6 +43,18 @@ impl Related<super::resource::Entity> for Entity {
     }
 }
 
+impl Related<super::access_request::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::AccessRequest.def()
+    }
+}
+
+impl Related<super::project_role_permission::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProjectRolePermission.def()
+    }
+}
+
 impl Related<super::role_permission::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::RolePermission.def()
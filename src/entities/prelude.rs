@@ -1,19 +1,41 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.14
 
+pub use super::access_request::Entity as AccessRequest;
 pub use super::activity::Entity as Activity;
 pub use super::activity_comment::Entity as ActivityComment;
+pub use super::activity_comment_reaction::Entity as ActivityCommentReaction;
+pub use super::activity_comment_revision::Entity as ActivityCommentRevision;
 pub use super::context_type::Entity as ContextType;
+pub use super::digest_preference::Entity as DigestPreference;
 pub use super::email_attachment::Entity as EmailAttachment;
 pub use super::email_context::Entity as EmailContext;
+pub use super::email_outbox::Entity as EmailOutbox;
 pub use super::invitation::Entity as Invitation;
+pub use super::notification::Entity as Notification;
+pub use super::notification_preference::Entity as NotificationPreference;
+pub use super::organization::Entity as Organization;
+pub use super::organization_member::Entity as OrganizationMember;
+pub use super::password_history::Entity as PasswordHistory;
 pub use super::permission::Entity as Permission;
 pub use super::project::Entity as Project;
 pub use super::project_context::Entity as ProjectContext;
 pub use super::project_context_category::Entity as ProjectContextCategory;
+pub use super::project_invitation::Entity as ProjectInvitation;
 pub use super::project_member::Entity as ProjectMember;
+pub use super::project_role::Entity as ProjectRole;
+pub use super::project_role_permission::Entity as ProjectRolePermission;
+pub use super::project_team::Entity as ProjectTeam;
+pub use super::rate_limit_event::Entity as RateLimitEvent;
 pub use super::resource::Entity as Resource;
 pub use super::role::Entity as Role;
+pub use super::role_parent::Entity as RoleParent;
 pub use super::role_permission::Entity as RolePermission;
 pub use super::task::Entity as Task;
+pub use super::task_watcher::Entity as TaskWatcher;
+pub use super::team::Entity as Team;
+pub use super::team_member::Entity as TeamMember;
 pub use super::user::Entity as User;
 pub use super::user_permission::Entity as UserPermission;
+pub use super::webhook_delivery::Entity as WebhookDelivery;
+pub use super::webhook_delivery_attempt::Entity as WebhookDeliveryAttempt;
+pub use super::webhook_subscription::Entity as WebhookSubscription;
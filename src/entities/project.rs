@@ -16,12 +16,27 @@ pub struct Model {
     pub is_active: bool,
     pub created_at: DateTimeWithTimeZone,
     pub updated_at: DateTimeWithTimeZone,
+    pub organization_id: Uuid,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::organization::Entity",
+        from = "Column::OrganizationId",
+        to = "super::organization::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Organization,
+    #[sea_orm(has_many = "super::project_invitation::Entity")]
+    ProjectInvitation,
     #[sea_orm(has_many = "super::project_member::Entity")]
     ProjectMember,
+    #[sea_orm(has_many = "super::project_role::Entity")]
+    ProjectRole,
+    #[sea_orm(has_many = "super::project_team::Entity")]
+    ProjectTeam,
     #[sea_orm(has_many = "super::task::Entity")]
     Task,
     #[sea_orm(
@@ -34,12 +49,36 @@ pub enum Relation {
     User,
 }
 
+impl Related<super::organization::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Organization.def()
+    }
+}
+
+impl Related<super::project_invitation::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProjectInvitation.def()
+    }
+}
+
 impl Related<super::project_member::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::ProjectMember.def()
     }
 }
 
+impl Related<super::project_role::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProjectRole.def()
+    }
+}
+
+impl Related<super::project_team::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProjectTeam.def()
+    }
+}
+
 impl Related<super::task::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Task.def()
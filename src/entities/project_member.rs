@@ -13,6 +13,7 @@ pub struct Model {
     pub user_id: Uuid,
     pub role: String,
     pub joined_at: DateTimeWithTimeZone,
+    pub project_role_id: Option<Uuid>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -25,6 +26,14 @@ pub enum Relation {
         on_delete = "Cascade"
     )]
     Project,
+    #[sea_orm(
+        belongs_to = "super::project_role::Entity",
+        from = "Column::ProjectRoleId",
+        to = "super::project_role::Column::Id",
+        on_update = "NoAction",
+        on_delete = "SetNull"
+    )]
+    ProjectRole,
     #[sea_orm(
         belongs_to = "super::user::Entity",
         from = "Column::UserId",
@@ -41,6 +50,12 @@ impl Related<super::project::Entity> for Entity {
     }
 }
 
+impl Related<super::project_role::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ProjectRole.def()
+    }
+}
+
 impl Related<super::user::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::User.def()
@@ -16,24 +16,48 @@ pub struct Model {
     pub is_active: bool,
     pub created_at: DateTimeWithTimeZone,
     pub updated_at: DateTimeWithTimeZone,
+    pub magic_link_enabled: bool,
+    pub organization_id: Option<Uuid>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(has_many = "super::access_request::Entity")]
+    AccessRequest,
     #[sea_orm(has_many = "super::invitation::Entity")]
     Invitation,
+    #[sea_orm(
+        belongs_to = "super::organization::Entity",
+        from = "Column::OrganizationId",
+        to = "super::organization::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Organization,
     #[sea_orm(has_many = "super::role_permission::Entity")]
     RolePermission,
     #[sea_orm(has_many = "super::user::Entity")]
     User,
 }
 
+impl Related<super::access_request::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::AccessRequest.def()
+    }
+}
+
 impl Related<super::invitation::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Invitation.def()
     }
 }
 
+impl Related<super::organization::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Organization.def()
+    }
+}
+
 impl Related<super::role_permission::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::RolePermission.def()
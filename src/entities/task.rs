@@ -26,6 +26,7 @@ pub struct Model {
     pub next_due_date: Option<DateTimeWithTimeZone>,
     pub created_at: DateTimeWithTimeZone,
     pub updated_at: DateTimeWithTimeZone,
+    pub assignee_team_id: Option<Uuid>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -70,6 +71,14 @@ pub enum Relation {
         on_delete = "SetNull"
     )]
     ProjectContext,
+    #[sea_orm(
+        belongs_to = "super::team::Entity",
+        from = "Column::AssigneeTeamId",
+        to = "super::team::Column::Id",
+        on_update = "NoAction",
+        on_delete = "SetNull"
+    )]
+    Team,
 }
 
 impl Related<super::project::Entity> for Entity {
@@ -84,4 +93,10 @@ impl Related<super::project_context::Entity> for Entity {
     }
 }
 
+impl Related<super::team::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Team.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -25,12 +25,36 @@ pub struct Model {
     pub refresh_token_expires_at: Option<DateTimeWithTimeZone>,
     pub invitation_token: Option<String>,
     pub role_id: Option<Uuid>,
+    pub role_valid_from: Option<DateTimeWithTimeZone>,
+    pub role_valid_until: Option<DateTimeWithTimeZone>,
+    pub failed_login_attempts: i32,
+    pub last_failed_login_at: Option<DateTimeWithTimeZone>,
+    pub locked_until: Option<DateTimeWithTimeZone>,
+    pub status: crate::graphql::types::UserStatus,
+    pub status_changed_at: Option<DateTimeWithTimeZone>,
+    pub magic_link_token: Option<String>,
+    pub magic_link_expires_at: Option<DateTimeWithTimeZone>,
+    pub password_changed_at: DateTimeWithTimeZone,
+    pub current_organization_id: Option<Uuid>,
+    pub locale: Option<String>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
     #[sea_orm(has_many = "super::invitation::Entity")]
     Invitation,
+    #[sea_orm(
+        belongs_to = "super::organization::Entity",
+        from = "Column::CurrentOrganizationId",
+        to = "super::organization::Column::Id",
+        on_update = "NoAction",
+        on_delete = "SetNull"
+    )]
+    Organization,
+    #[sea_orm(has_many = "super::organization_member::Entity")]
+    OrganizationMember,
+    #[sea_orm(has_many = "super::password_history::Entity")]
+    PasswordHistory,
     #[sea_orm(has_many = "super::project::Entity")]
     Project,
     #[sea_orm(has_many = "super::project_member::Entity")]
@@ -43,6 +67,8 @@ pub enum Relation {
         on_delete = "SetNull"
     )]
     Role,
+    #[sea_orm(has_many = "super::team_member::Entity")]
+    TeamMember,
     #[sea_orm(has_many = "super::user_permission::Entity")]
     UserPermission,
 }
@@ -53,6 +79,24 @@ impl Related<super::invitation::Entity> for Entity {
     }
 }
 
+impl Related<super::organization::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Organization.def()
+    }
+}
+
+impl Related<super::organization_member::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::OrganizationMember.def()
+    }
+}
+
+impl Related<super::password_history::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::PasswordHistory.def()
+    }
+}
+
 impl Related<super::project::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Project.def()
@@ -71,6 +115,12 @@ impl Related<super::role::Entity> for Entity {
     }
 }
 
+impl Related<super::team_member::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::TeamMember.def()
+    }
+}
+
 impl Related<super::user_permission::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::UserPermission.def()
@@ -12,12 +12,23 @@ pub struct Model {
     pub user_id: Uuid,
     pub permission_id: Uuid,
     pub is_granted: bool,
+    pub valid_from: Option<DateTimeWithTimeZone>,
+    pub valid_until: Option<DateTimeWithTimeZone>,
     pub created_at: DateTimeWithTimeZone,
     pub updated_at: DateTimeWithTimeZone,
+    pub organization_id: Option<Uuid>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::organization::Entity",
+        from = "Column::OrganizationId",
+        to = "super::organization::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Organization,
     #[sea_orm(
         belongs_to = "super::permission::Entity",
         from = "Column::PermissionId",
@@ -36,6 +47,12 @@ pub enum Relation {
     User,
 }
 
+impl Related<super::organization::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Organization.def()
+    }
+}
+
 impl Related<super::permission::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Permission.def()
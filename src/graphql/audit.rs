@@ -0,0 +1,68 @@
+use std::sync::Arc;
+
+use async_graphql::{
+    extensions::{Extension, ExtensionContext, ExtensionFactory, NextResolve, ResolveInfo},
+    ServerResult, Value,
+};
+
+use crate::auth::AuthenticatedUser;
+use crate::services::{activity::EntityType, ActivityService};
+
+/// Records every top-level mutation executed with an impersonation token,
+/// keeping both the impersonated user and the admin behind the request.
+pub struct ImpersonationAudit;
+
+impl ExtensionFactory for ImpersonationAudit {
+    fn create(&self) -> Arc<dyn Extension> {
+        Arc::new(ImpersonationAuditExtension)
+    }
+}
+
+struct ImpersonationAuditExtension;
+
+#[async_graphql::async_trait::async_trait]
+impl Extension for ImpersonationAuditExtension {
+    async fn resolve(
+        &self,
+        ctx: &ExtensionContext<'_>,
+        info: ResolveInfo<'_>,
+        next: NextResolve<'_>,
+    ) -> ServerResult<Option<Value>> {
+        let is_root_mutation = info.parent_type == "MutationRoot" && info.path_node.parent.is_none();
+        let impersonation = ctx
+            .data_opt::<AuthenticatedUser>()
+            .and_then(|user| user.actor_id.map(|actor_id| (user.id, actor_id)));
+        let mutation_name = info.name.to_string();
+
+        let result = next.run(ctx, info).await;
+
+        if is_root_mutation
+            && let Some((user_id, actor_id)) = impersonation
+            && let Some(activity_service) = ctx.data_opt::<ActivityService>()
+        {
+            let logged = activity_service
+                .log_activity(
+                    EntityType::User,
+                    user_id,
+                    actor_id,
+                    "impersonated_mutation",
+                    Some(format!("{} executed while impersonating", mutation_name)),
+                    Some(serde_json::json!({
+                        "mutation": mutation_name,
+                        "impersonated_user_id": user_id,
+                        "actor_id": actor_id,
+                        "succeeded": result.is_ok()
+                    })),
+                    None,
+                )
+                .await
+                .map_err(|e| e.to_string());
+
+            if let Err(e) = logged {
+                tracing::error!("Failed to record impersonated mutation {}: {}", mutation_name, e);
+            }
+        }
+
+        result
+    }
+}
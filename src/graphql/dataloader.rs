@@ -1,10 +1,21 @@
 use async_graphql::dataloader::{DataLoader, Loader};
-use sea_orm::DatabaseConnection;
 use std::collections::HashMap;
 use std::sync::Arc;
 use uuid::Uuid;
 
-use crate::auth::PermissionService;
+use crate::auth::{AppPermission, AppResource, PermissionService};
+
+/// A user and the organization their permissions are resolved in
+pub type PermissionKey = (Uuid, Option<Uuid>);
+
+/// Users to load, grouped by the organization their permissions are resolved in
+fn group_by_organization(keys: &[PermissionKey]) -> HashMap<Option<Uuid>, Vec<Uuid>> {
+    let mut groups: HashMap<Option<Uuid>, Vec<Uuid>> = HashMap::new();
+    for (user_id, organization_id) in keys {
+        groups.entry(*organization_id).or_default().push(*user_id);
+    }
+    groups
+}
 
 /// DataLoader for batching permission requests
 #[derive(Clone)]
@@ -14,43 +25,51 @@ pub struct PermissionLoader {
 }
 
 impl PermissionLoader {
-    pub fn new(db: DatabaseConnection, resource_name: String) -> Self {
+    pub fn new(permission_service: PermissionService, resource_name: String) -> Self {
         Self {
-            permission_service: PermissionService::new(db),
+            permission_service,
             resource_name: Some(resource_name),
         }
     }
 
-    pub fn new_all_resources(db: DatabaseConnection) -> Self {
+    pub fn new_all_resources(permission_service: PermissionService) -> Self {
         Self {
-            permission_service: PermissionService::new(db),
+            permission_service,
             resource_name: None,
         }
     }
 }
 
-impl Loader<Uuid> for PermissionLoader {
+impl Loader<PermissionKey> for PermissionLoader {
     type Value = Vec<String>;
     type Error = String;
 
-    /// Batch load permissions for multiple users
-    async fn load(&self, keys: &[Uuid]) -> Result<HashMap<Uuid, Self::Value>, Self::Error> {
-        let permissions_map = match &self.resource_name {
-            Some(resource_name) => {
-                self.permission_service
-                    .get_users_permissions_batch(keys, resource_name)
-                    .await
-                    .map_err(|e| format!("Failed to load permissions: {}", e))?
-            }
-            None => {
-                self.permission_service
-                    .get_users_all_permissions_batch(keys)
-                    .await
-                    .map_err(|e| format!("Failed to load all permissions: {}", e))?
-            }
-        };
-
-        Ok(permissions_map)
+    /// Batch load permissions for multiple users, one batch per organization
+    async fn load(&self, keys: &[PermissionKey]) -> Result<HashMap<PermissionKey, Self::Value>, Self::Error> {
+        let mut result = HashMap::new();
+        for (organization_id, user_ids) in group_by_organization(keys) {
+            let permissions_map = match &self.resource_name {
+                Some(resource_name) => {
+                    self.permission_service
+                        .get_users_permissions_batch(&user_ids, organization_id, resource_name)
+                        .await
+                        .map_err(|e| format!("Failed to load permissions: {}", e))?
+                }
+                None => {
+                    self.permission_service
+                        .get_users_all_permissions_batch(&user_ids, organization_id)
+                        .await
+                        .map_err(|e| format!("Failed to load all permissions: {}", e))?
+                }
+            };
+            result.extend(
+                permissions_map
+                    .into_iter()
+                    .map(|(user_id, permissions)| ((user_id, organization_id), permissions)),
+            );
+        }
+
+        Ok(result)
     }
 }
 
@@ -63,26 +82,30 @@ pub struct PermissionCheckLoader {
 }
 
 impl PermissionCheckLoader {
-    pub fn new(db: DatabaseConnection, resource_name: String, action: String) -> Self {
+    pub fn new(permission_service: PermissionService, permission: AppPermission) -> Self {
         Self {
-            permission_service: PermissionService::new(db),
-            resource_name,
-            action,
+            permission_service,
+            resource_name: permission.resource().name().to_string(),
+            action: permission.action().to_string(),
         }
     }
 }
 
-impl Loader<Uuid> for PermissionCheckLoader {
+impl Loader<PermissionKey> for PermissionCheckLoader {
     type Value = bool;
     type Error = String;
 
-    /// Batch check specific permission for multiple users
-    async fn load(&self, keys: &[Uuid]) -> Result<HashMap<Uuid, Self::Value>, Self::Error> {
-        let result = self
-            .permission_service
-            .users_have_permission_batch(keys, &self.resource_name, &self.action)
-            .await
-            .map_err(|e| format!("Failed to check permissions: {}", e))?;
+    /// Batch check specific permission for multiple users, one batch per organization
+    async fn load(&self, keys: &[PermissionKey]) -> Result<HashMap<PermissionKey, Self::Value>, Self::Error> {
+        let mut result = HashMap::new();
+        for (organization_id, user_ids) in group_by_organization(keys) {
+            let checks = self
+                .permission_service
+                .users_have_permission_batch(&user_ids, organization_id, &self.resource_name, &self.action)
+                .await
+                .map_err(|e| format!("Failed to check permissions: {}", e))?;
+            result.extend(checks.into_iter().map(|(user_id, allowed)| ((user_id, organization_id), allowed)));
+        }
 
         Ok(result)
     }
@@ -100,84 +123,86 @@ pub struct DataLoaderContext {
 }
 
 impl DataLoaderContext {
-    /// Create new DataLoader context with optimized batch sizes
-    pub fn new(db: DatabaseConnection) -> Self {
+    /// Create new DataLoader context with optimized batch sizes. Loaders share the
+    /// service's process-wide permission cache.
+    pub fn new(permission_service: PermissionService) -> Self {
         Self {
             permission_loader: Arc::new(DataLoader::new(
-                PermissionLoader::new(db.clone(), "freshapi".to_string()),
+                PermissionLoader::new(permission_service.clone(), AppResource::Freshapi.name().to_string()),
                 tokio::spawn,
             )
             .max_batch_size(100)), // Batch up to 100 permission requests
             
             all_permissions_loader: Arc::new(DataLoader::new(
-                PermissionLoader::new_all_resources(db.clone()),
+                PermissionLoader::new_all_resources(permission_service.clone()),
                 tokio::spawn,
             )
             .max_batch_size(100)), // Batch up to 100 all-permission requests
             
             invite_users_loader: Arc::new(DataLoader::new(
-                PermissionCheckLoader::new(db.clone(), "freshapi".to_string(), "invite_users".to_string()),
+                PermissionCheckLoader::new(permission_service.clone(), AppPermission::InviteUsers),
                 tokio::spawn,
             )
             .max_batch_size(100)),
             
             user_management_loader: Arc::new(DataLoader::new(
-                PermissionCheckLoader::new(db.clone(), "freshapi".to_string(), "user_management".to_string()),
+                PermissionCheckLoader::new(permission_service.clone(), AppPermission::UserManagement),
                 tokio::spawn,
             )
             .max_batch_size(100)),
             
             admin_loader: Arc::new(DataLoader::new(
-                PermissionCheckLoader::new(db.clone(), "freshapi".to_string(), "admin".to_string()),
+                PermissionCheckLoader::new(permission_service.clone(), AppPermission::Admin),
                 tokio::spawn,
             )
             .max_batch_size(100)),
             
             system_admin_loader: Arc::new(DataLoader::new(
-                PermissionCheckLoader::new(db, "freshapi".to_string(), "system_admin".to_string()),
+                PermissionCheckLoader::new(permission_service, AppPermission::SystemAdmin),
                 tokio::spawn,
             )
             .max_batch_size(100)),
         }
     }
 
-    /// Load permissions for a single user (with caching) - freshapi only
-    pub async fn load_user_permissions(&self, user_id: Uuid) -> Result<Vec<String>, String> {
+    /// Load permissions for a single user within an organization (with caching) - freshapi only
+    pub async fn load_user_permissions(&self, user_id: Uuid, organization_id: Option<Uuid>) -> Result<Vec<String>, String> {
         self.permission_loader
-            .load_one(user_id)
+            .load_one((user_id, organization_id))
             .await?
             .ok_or_else(|| "User not found".to_string())
     }
 
-    /// Load ALL permissions for a single user across ALL resources (with caching)
-    pub async fn load_user_all_permissions(&self, user_id: Uuid) -> Result<Vec<String>, String> {
+    /// Load ALL permissions for a single user across ALL resources within an organization (with caching)
+    pub async fn load_user_all_permissions(&self, user_id: Uuid, organization_id: Option<Uuid>) -> Result<Vec<String>, String> {
         self.all_permissions_loader
-            .load_one(user_id)
+            .load_one((user_id, organization_id))
             .await?
             .ok_or_else(|| "User not found".to_string())
     }
 
     /// Check if user can invite users (with caching)
-    pub async fn can_invite_users(&self, user_id: Uuid) -> Result<bool, String> {
-        Ok(self.invite_users_loader.load_one(user_id).await?.unwrap_or(false))
+    pub async fn can_invite_users(&self, user_id: Uuid, organization_id: Option<Uuid>) -> Result<bool, String> {
+        Ok(self.invite_users_loader.load_one((user_id, organization_id)).await?.unwrap_or(false))
     }
 
     /// Check if user can manage users (with caching)
-    pub async fn can_manage_users(&self, user_id: Uuid) -> Result<bool, String> {
-        Ok(self.user_management_loader.load_one(user_id).await?.unwrap_or(false))
+    pub async fn can_manage_users(&self, user_id: Uuid, organization_id: Option<Uuid>) -> Result<bool, String> {
+        Ok(self.user_management_loader.load_one((user_id, organization_id)).await?.unwrap_or(false))
     }
 
     /// Check if user is admin (with caching)
-    pub async fn is_admin(&self, user_id: Uuid) -> Result<bool, String> {
-        Ok(self.admin_loader.load_one(user_id).await?.unwrap_or(false))
+    pub async fn is_admin(&self, user_id: Uuid, organization_id: Option<Uuid>) -> Result<bool, String> {
+        Ok(self.admin_loader.load_one((user_id, organization_id)).await?.unwrap_or(false))
     }
 
     /// Check if user is system admin (with caching)
-    pub async fn is_system_admin(&self, user_id: Uuid) -> Result<bool, String> {
-        Ok(self.system_admin_loader.load_one(user_id).await?.unwrap_or(false))
+    pub async fn is_system_admin(&self, user_id: Uuid, organization_id: Option<Uuid>) -> Result<bool, String> {
+        Ok(self.system_admin_loader.load_one((user_id, organization_id)).await?.unwrap_or(false))
     }
 
-    /// Clear all caches (useful for testing or when permissions change)
+    /// Clear the per-request loader caches. Permission changes invalidate the shared
+    /// permission cache through `PermissionService` instead.
     pub fn clear_all(&self) {
         self.permission_loader.clear();
         self.all_permissions_loader.clear();
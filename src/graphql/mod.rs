@@ -3,9 +3,11 @@ pub mod mutation;
 pub mod query;
 pub mod schema;
 pub mod dataloader;
+pub mod audit;
 
 pub use types::*;
 pub use mutation::*;
 pub use query::*;
 pub use schema::*;
-pub use dataloader::*;
\ No newline at end of file
+pub use dataloader::*;
+pub use audit::*;
\ No newline at end of file
@@ -1,11 +1,12 @@
 use async_graphql::*;
-use sea_orm::{EntityTrait, ActiveModelTrait, Set, ColumnTrait, QueryFilter, PaginatorTrait};
+use sea_orm::{EntityTrait, ActiveModelTrait, Set, ColumnTrait, Condition, QueryFilter, PaginatorTrait, TransactionTrait};
 use chrono::Utc;
+use std::io::Read;
 use uuid::Uuid;
 
-use crate::auth::{require_user_management, AuthenticatedUser};
-use crate::graphql::types::{AcceptInvitationInput, AdminResetUserPasswordInput, AuthPayload, ChangePasswordInput, Invitation, InviteUserInput, InviteUserWithRoleInput, LoginInput, MessageResponse, RefreshTokenInput, RegisterInput, RequestPasswordResetInput, ResetPasswordInput, User, AssignRoleInput, Project, Task, CreateProjectInput, UpdateProjectInput, AddProjectMemberInput, UpdateMemberRoleInput, RemoveProjectMemberInput, CreateTaskInput, UpdateTaskInput, AssignTaskInput, Role, Permission, Resource, CreateRoleInput, UpdateRoleInput, CreatePermissionInput, UpdatePermissionInput, CreateResourceInput, UpdateResourceInput, AssignPermissionToRoleInput, RemovePermissionFromRoleInput, GrantUserPermissionInput, RevokeUserPermissionInput, AddCommentInput, Activity, GraphQLEntityType, CompleteTaskWithRecurrenceResponse};
-use crate::services::{EmailService, InvitationService, UserService, ProjectService, TaskService, ProjectRole, ActivityService};
+use crate::auth::{require_entity_access, require_organization, require_rate_limit, require_same_organization, require_team_management, require_user_management, AppPermission, AuthenticatedUser, PermissionGuard, PermissionService, RateLimitScope};
+use crate::graphql::types::{AcceptInvitationInput, AdminResetUserPasswordInput, AuthPayload, ChangePasswordInput, Invitation, InviteUserInput, InviteUserWithRoleInput, LoginInput, MessageResponse, RefreshTokenInput, RegisterInput, RequestPasswordResetInput, ResetPasswordInput, User, AssignRoleInput, Project, Task, CreateProjectInput, UpdateProjectInput, AddProjectMemberInput, UpdateMemberRoleInput, RemoveProjectMemberInput, CreateTaskInput, UpdateTaskInput, AssignTaskInput, Role, Permission, Resource, CreateRoleInput, UpdateRoleInput, CreatePermissionInput, UpdatePermissionInput, CreateResourceInput, UpdateResourceInput, AssignPermissionToRoleInput, RemovePermissionFromRoleInput, GrantUserPermissionInput, RevokeUserPermissionInput, AddCommentInput, EditCommentInput, CommentReactionInput, Comment, CompleteTaskWithRecurrenceResponse, UpdateUserStatusInput, OffboardUserInput, OffboardUserResult, UserStatus, ImpersonateUserInput, ImpersonationPayload, RequestMagicLinkInput, MagicLinkLoginInput, ProjectRoleDefinition, CreateProjectRoleInput, UpdateProjectRoleInput, AccessRequest, RequestAccessInput, ReviewAccessRequestInput, Organization, CreateOrganizationInput, OrganizationMemberInput, UpdateOrganizationBrandingInput, SwitchOrganizationInput, Team, CreateTeamInput, UpdateTeamInput, TeamMemberInput, AddProjectTeamInput, UpdateProjectTeamRoleInput, RemoveProjectTeamInput, AssignTaskToTeamInput, ProjectInvitation, InviteToProjectInput, BulkInviteUsersInput, BulkInviteResult, BulkInviteRowStatus, EmailOutboxEntry, NotificationPreference, NotificationPreferenceInput, DigestPreferences, UpdateDigestPreferencesInput, WebhookSubscription, WebhookSubscriptionWithSecret, CreateWebhookSubscriptionInput, UpdateWebhookSubscriptionInput, WebhookDelivery};
+use crate::services::{email_style, is_role_visible, parse_bulk_invite_csv, visible_roles, BulkInviteRow, EmailOutboxService, DigestService, EmailService, InvitationService, NotificationService, ProjectInvitationGrant, OrganizationService, TeamService, UserService, WebhookService, ProjectService, TaskService, AccessRequestService, CommentService, PasswordPolicyError};
 use crate::services::activity::EntityType;
 // Task enums imported when needed
 
@@ -15,14 +16,14 @@ pub struct MutationRoot;
 impl MutationRoot {
     async fn invite_user(&self, ctx: &Context<'_>, input: InviteUserInput) -> Result<Invitation> {
         use crate::auth::require_permission;
-        require_permission(ctx, "freshapi", "invite_users").await?;
+        require_permission(ctx, AppPermission::InviteUsers).await?;
         
         let invitation_service = ctx.data::<InvitationService>()?;
         let auth_user = ctx.data::<crate::auth::AuthenticatedUser>()?;
         let frontend_url = ctx.data::<String>()?;
 
         let invitation = invitation_service
-            .create_invitation(auth_user.id, &input.email, frontend_url)
+            .create_invitation(auth_user.id, auth_user.organization_id, &input.email, frontend_url)
             .await
             .map_err(|e| Error::new(format!("Failed to create invitation: {}", e)))?;
 
@@ -30,21 +31,88 @@ impl MutationRoot {
     }
 
     async fn invite_user_with_role(&self, ctx: &Context<'_>, input: InviteUserWithRoleInput) -> Result<Invitation> {
-        require_user_management(ctx, "freshapi").await?;
+        require_user_management(ctx).await?;
         
         let invitation_service = ctx.data::<InvitationService>()?;
         let auth_user = ctx.data::<crate::auth::AuthenticatedUser>()?;
         let frontend_url = ctx.data::<String>()?;
 
+        let projects: Vec<ProjectInvitationGrant> = input
+            .projects
+            .unwrap_or_default()
+            .into_iter()
+            .map(|grant| ProjectInvitationGrant { project_id: grant.project_id, role: grant.role })
+            .collect();
+
         let invitation = invitation_service
-            .create_invitation_with_role(auth_user.id, &input.email, input.role_id, frontend_url)
+            .create_invitation_with_role(auth_user.id, auth_user.organization_id, &input.email, input.role_id, &projects, frontend_url)
             .await
             .map_err(|e| Error::new(format!("Failed to create invitation: {}", e)))?;
 
         Ok(invitation.into())
     }
 
+    /// Invite many people from a CSV file or a list of rows, with a result per row
+    async fn bulk_invite_users(&self, ctx: &Context<'_>, input: BulkInviteUsersInput) -> Result<BulkInviteResult> {
+        require_user_management(ctx).await?;
+
+        let invitation_service = ctx.data::<InvitationService>()?;
+        let auth_user = ctx.data::<crate::auth::AuthenticatedUser>()?;
+        let frontend_url = ctx.data::<String>()?;
+        let dry_run = input.dry_run.unwrap_or(false);
+
+        let (rows, mut outcomes) = match (input.csv, input.rows) {
+            (Some(upload), None) => {
+                let mut content = String::new();
+                upload
+                    .value(ctx)?
+                    .into_read()
+                    .read_to_string(&mut content)
+                    .map_err(|e| Error::new(format!("Failed to read CSV: {}", e)))?;
+                parse_bulk_invite_csv(&content).map_err(|e| Error::new(format!("Failed to read CSV: {}", e)))?
+            }
+            (None, Some(rows)) => {
+                let rows = rows
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, row)| BulkInviteRow {
+                        row: index + 1,
+                        email: row.email,
+                        role: row.role,
+                        projects: row
+                            .projects
+                            .unwrap_or_default()
+                            .into_iter()
+                            .map(|grant| (grant.project_id.to_string(), grant.role))
+                            .collect(),
+                    })
+                    .collect();
+                (rows, Vec::new())
+            }
+            _ => return Err(Error::new("Provide either a CSV file or a list of rows")),
+        };
+
+        let created = invitation_service
+            .bulk_invite(auth_user.id, auth_user.organization_id, rows, dry_run, frontend_url)
+            .await
+            .map_err(|e| Error::new(format!("Failed to create invitations: {}", e)))?;
+        outcomes.extend(created);
+        outcomes.sort_by_key(|outcome| outcome.row);
+
+        let count = |status: BulkInviteRowStatus| outcomes.iter().filter(|o| o.status == status).count() as i32;
+        Ok(BulkInviteResult {
+            dry_run,
+            total: outcomes.len() as i32,
+            valid: count(BulkInviteRowStatus::Valid),
+            invited: count(BulkInviteRowStatus::Invited),
+            failed: count(BulkInviteRowStatus::Failed),
+            rows: outcomes.into_iter().map(Into::into).collect(),
+        })
+    }
+
     async fn accept_invitation(&self, ctx: &Context<'_>, input: AcceptInvitationInput) -> Result<AuthPayload> {
+        require_rate_limit(ctx, RateLimitScope::AcceptInvitation, Some(&input.invitation_token)).await?;
+
         let user_service = ctx.data::<UserService>()?;
         let invitation_service = ctx.data::<InvitationService>()?;
 
@@ -63,13 +131,9 @@ impl MutationRoot {
                 &input.invitation_token,
             )
             .await
-            .map_err(|e| Error::new(format!("Registration failed: {}", e)))?;
+            .map_err(|e| password_error("Registration failed", e))?;
 
-        Ok(AuthPayload {
-            user: user.into(),
-            access_token,
-            refresh_token,
-        })
+        Ok(AuthPayload::new(user, access_token, refresh_token))
     }
 
     async fn register(&self, _ctx: &Context<'_>, _input: RegisterInput) -> Result<User> {
@@ -79,18 +143,16 @@ impl MutationRoot {
     }
 
     async fn login(&self, ctx: &Context<'_>, input: LoginInput) -> Result<AuthPayload> {
+        require_rate_limit(ctx, RateLimitScope::Login, Some(&input.email)).await?;
+
         let user_service = ctx.data::<UserService>()?;
 
         let (user, access_token, refresh_token) = user_service
             .authenticate_user(&input.email, &input.password)
             .await
-            .map_err(|e| Error::new(format!("Authentication failed: {}", e)))?;
+            .map_err(|e| password_error("Authentication failed", e))?;
 
-        Ok(AuthPayload {
-            user: user.into(),
-            access_token,
-            refresh_token,
-        })
+        Ok(AuthPayload::new(user, access_token, refresh_token))
     }
 
     async fn refresh_token(&self, ctx: &Context<'_>, input: RefreshTokenInput) -> Result<AuthPayload> {
@@ -101,11 +163,7 @@ impl MutationRoot {
             .await
             .map_err(|e| Error::new(format!("Token refresh failed: {}", e)))?;
 
-        Ok(AuthPayload {
-            user: user.into(),
-            access_token,
-            refresh_token,
-        })
+        Ok(AuthPayload::new(user, access_token, refresh_token))
     }
 
     async fn logout(&self, ctx: &Context<'_>) -> Result<MessageResponse> {
@@ -137,37 +195,59 @@ impl MutationRoot {
     }
 
     async fn request_password_reset(&self, ctx: &Context<'_>, input: RequestPasswordResetInput) -> Result<MessageResponse> {
+        require_rate_limit(ctx, RateLimitScope::PasswordReset, Some(&input.email)).await?;
+
         let user_service = ctx.data::<UserService>()?;
-        let email_service = ctx.data::<EmailService>()?;
+        let frontend_url = ctx.data::<String>()?;
 
-        let user = user_service
-            .request_password_reset(&input.email)
+        // The reset email is queued along with the token
+        user_service
+            .request_password_reset(&input.email, frontend_url)
             .await
             .map_err(|e| Error::new(format!("Password reset request failed: {}", e)))?;
 
-        // Send password reset email
-        if let Some(reset_token) = &user.password_reset_token {
-            let frontend_url = ctx.data::<String>()?;
-            if let Err(e) = email_service
-                .send_password_reset_email(&user.email, reset_token, frontend_url)
-                .await
-            {
-                eprintln!("Failed to send password reset email: {}", e);
-            }
-        }
-
         Ok(MessageResponse {
             message: "Password reset instructions have been sent to your email".to_string(),
         })
     }
 
+    async fn request_magic_link(&self, ctx: &Context<'_>, input: RequestMagicLinkInput) -> Result<MessageResponse> {
+        require_rate_limit(ctx, RateLimitScope::MagicLink, Some(&input.email)).await?;
+
+        let user_service = ctx.data::<UserService>()?;
+        let frontend_url = ctx.data::<String>()?;
+
+        user_service
+            .request_magic_link(&input.email, frontend_url)
+            .await
+            .map_err(|e| Error::new(format!("Magic link request failed: {}", e)))?;
+
+        // Same answer whether or not a link was sent, to avoid leaking which accounts exist
+        Ok(MessageResponse {
+            message: "If magic-link sign-in is enabled for this account, a sign-in link has been sent to your email".to_string(),
+        })
+    }
+
+    async fn login_with_magic_link(&self, ctx: &Context<'_>, input: MagicLinkLoginInput) -> Result<AuthPayload> {
+        require_rate_limit(ctx, RateLimitScope::Login, None).await?;
+
+        let user_service = ctx.data::<UserService>()?;
+
+        let (user, access_token, refresh_token) = user_service
+            .authenticate_with_magic_link(&input.token)
+            .await
+            .map_err(|e| Error::new(format!("Authentication failed: {}", e)))?;
+
+        Ok(AuthPayload::new(user, access_token, refresh_token))
+    }
+
     async fn reset_password(&self, ctx: &Context<'_>, input: ResetPasswordInput) -> Result<MessageResponse> {
         let user_service = ctx.data::<UserService>()?;
 
         user_service
             .reset_password(&input.token, &input.new_password)
             .await
-            .map_err(|e| Error::new(format!("Password reset failed: {}", e)))?;
+            .map_err(|e| password_error("Password reset failed", e))?;
 
         Ok(MessageResponse {
             message: "Password has been reset successfully".to_string(),
@@ -176,7 +256,8 @@ impl MutationRoot {
 
     // Admin-only mutations
     async fn assign_role(&self, ctx: &Context<'_>, input: AssignRoleInput) -> Result<User> {
-        require_user_management(ctx, "freshapi").await?;
+        let admin = require_user_management(ctx).await?;
+        require_same_organization(ctx, input.user_id).await?;
         
         let user_service = ctx.data::<UserService>()?;
         
@@ -187,28 +268,58 @@ impl MutationRoot {
             .map_err(|e| Error::new(format!("Database error: {}", e)))?
             .ok_or_else(|| Error::new("User not found"))?;
 
-        // Verify role exists
-        let _role = crate::entities::role::Entity::find_by_id(input.role_id)
+        // Verify role exists and is available in the current organization
+        let role = crate::entities::role::Entity::find_by_id(input.role_id)
             .one(user_service.get_db())
             .await
             .map_err(|e| Error::new(format!("Database error: {}", e)))?
+            .filter(|role| is_role_visible(role, admin.organization_id))
             .ok_or_else(|| Error::new("Role not found"))?;
 
-        // Update user role
+        // An organization's role only goes to that organization's members
+        if let Some(organization_id) = role.organization_id {
+            let is_member = ctx
+                .data::<PermissionService>()?
+                .user_in_organization(input.user_id, organization_id)
+                .await
+                .map_err(|e| Error::new(format!("Organization check failed: {}", e)))?;
+            if !is_member {
+                return Err(Error::new("User is not a member of the role's organization"));
+            }
+        }
+
+        // Shared roles are visible to every organization, so the level and system admin
+        // checks are what keep platform roles out of tenant admins' hands
+        ctx.data::<PermissionService>()?
+            .ensure_can_assign_role(admin.id, admin.organization_id, &role)
+            .await
+            .map_err(|e| Error::new(format!("Failed to assign role: {}", e)))?;
+
+        if let (Some(from), Some(until)) = (input.valid_from, input.valid_until)
+            && until <= from
+        {
+            return Err(Error::new("validUntil must be after validFrom"));
+        }
+
+        // Update user role, optionally bounded to a validity window
         let mut user_active: crate::entities::user::ActiveModel = user.into();
         user_active.role_id = Set(Some(input.role_id));
+        user_active.role_valid_from = Set(input.valid_from.map(Into::into));
+        user_active.role_valid_until = Set(input.valid_until.map(Into::into));
         user_active.updated_at = Set(Utc::now().into());
 
         let updated_user = user_active
             .update(user_service.get_db())
             .await
             .map_err(|e| Error::new(format!("Failed to assign role: {}", e)))?;
+        ctx.data::<PermissionService>()?.invalidate_user(updated_user.id).await;
 
         Ok(updated_user.into())
     }
 
     async fn remove_user_role(&self, ctx: &Context<'_>, user_id: uuid::Uuid) -> Result<User> {
-        require_user_management(ctx, "freshapi").await?;
+        require_user_management(ctx).await?;
+        require_same_organization(ctx, user_id).await?;
         
         let user_service = ctx.data::<UserService>()?;
         
@@ -222,24 +333,104 @@ impl MutationRoot {
         // Remove role
         let mut user_active: crate::entities::user::ActiveModel = user.into();
         user_active.role_id = Set(None);
+        user_active.role_valid_from = Set(None);
+        user_active.role_valid_until = Set(None);
         user_active.updated_at = Set(Utc::now().into());
 
         let updated_user = user_active
             .update(user_service.get_db())
             .await
             .map_err(|e| Error::new(format!("Failed to remove role: {}", e)))?;
+        ctx.data::<PermissionService>()?.invalidate_user(updated_user.id).await;
 
         Ok(updated_user.into())
     }
 
+    async fn unlock_user(&self, ctx: &Context<'_>, user_id: uuid::Uuid) -> Result<User> {
+        let admin = require_user_management(ctx).await?;
+        require_same_organization(ctx, user_id).await?;
+
+        let user_service = ctx.data::<UserService>()?;
+
+        let updated_user = user_service
+            .unlock_user(user_id, admin.id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to unlock user: {}", e)))?;
+
+        Ok(updated_user.into())
+    }
+
+    async fn impersonate_user(&self, ctx: &Context<'_>, input: ImpersonateUserInput) -> Result<ImpersonationPayload> {
+        use crate::auth::{forbid_impersonation, require_system_admin, require_user_can_manage};
+        forbid_impersonation(ctx, "Impersonation")?;
+        require_system_admin(ctx).await?;
+        let admin = require_user_can_manage(ctx, input.user_id).await?;
+
+        let user_service = ctx.data::<UserService>()?;
+
+        let (user, access_token, expires_at) = user_service
+            .impersonate_user(input.user_id, admin.id, input.reason)
+            .await
+            .map_err(|e| Error::new(format!("Failed to impersonate user: {}", e)))?;
+
+        Ok(ImpersonationPayload {
+            user: user.into(),
+            access_token,
+            expires_at,
+            actor_id: admin.id,
+        })
+    }
+
+    async fn update_user_status(&self, ctx: &Context<'_>, input: UpdateUserStatusInput) -> Result<User> {
+        use crate::auth::require_user_can_manage;
+        require_user_management(ctx).await?;
+        let admin = require_user_can_manage(ctx, input.user_id).await?;
+
+        if input.status == UserStatus::Deactivated {
+            return Err(Error::new("Use offboardUser to deactivate an account"));
+        }
+
+        let user_service = ctx.data::<UserService>()?;
+
+        let updated_user = user_service
+            .update_user_status(input.user_id, input.status, admin.id, input.reason)
+            .await
+            .map_err(|e| Error::new(format!("Failed to update user status: {}", e)))?;
+
+        Ok(updated_user.into())
+    }
+
+    async fn offboard_user(&self, ctx: &Context<'_>, input: OffboardUserInput) -> Result<OffboardUserResult> {
+        use crate::auth::require_user_can_manage;
+        require_user_management(ctx).await?;
+        let admin = require_user_can_manage(ctx, input.user_id).await?;
+
+        let user_service = ctx.data::<UserService>()?;
+
+        let summary = user_service
+            .offboard_user(input.user_id, input.reassign_to_user_id, admin.id, input.reason)
+            .await
+            .map_err(|e| Error::new(format!("Failed to offboard user: {}", e)))?;
+
+        // Memberships were removed and projects may have changed hands
+        let permission_service = ctx.data::<PermissionService>()?;
+        permission_service.invalidate_user(input.user_id).await;
+        if let Some(successor_id) = input.reassign_to_user_id {
+            permission_service.invalidate_user(successor_id).await;
+        }
+
+        Ok(summary.into())
+    }
+
     async fn change_password(&self, ctx: &Context<'_>, input: ChangePasswordInput) -> Result<MessageResponse> {
+        use crate::auth::forbid_impersonation;
+        let auth_user = forbid_impersonation(ctx, "Changing passwords")?;
         let user_service = ctx.data::<UserService>()?;
-        let auth_user = ctx.data::<crate::auth::AuthenticatedUser>()?;
 
         user_service
             .change_password(auth_user.id, &input.current_password, &input.new_password)
             .await
-            .map_err(|e| Error::new(format!("Password change failed: {}", e)))?;
+            .map_err(|e| password_error("Password change failed", e))?;
 
         Ok(MessageResponse {
             message: "Password changed successfully. You will need to login again.".to_string(),
@@ -247,10 +438,14 @@ impl MutationRoot {
     }
 
     async fn admin_reset_user_password(&self, ctx: &Context<'_>, input: AdminResetUserPasswordInput) -> Result<MessageResponse> {
-        require_user_management(ctx, "freshapi").await?;
+        use crate::auth::forbid_impersonation;
+        forbid_impersonation(ctx, "Resetting passwords")?;
+        require_user_management(ctx).await?;
+        require_same_organization(ctx, input.user_id).await?;
         
         let user_service = ctx.data::<UserService>()?;
         let email_service = ctx.data::<EmailService>()?;
+        let email_outbox_service = ctx.data::<EmailOutboxService>()?;
         let auth_user = ctx.data::<crate::auth::AuthenticatedUser>()?;
         let frontend_url = ctx.data::<String>()?;
 
@@ -281,12 +476,16 @@ impl MutationRoot {
 
         // Send password reset email
         if let Some(reset_token) = &updated_user.password_reset_token {
-            if let Err(e) = email_service
-                .send_admin_password_reset_email(&target_user.email, reset_token, frontend_url, &admin_name)
+            let style = email_style(user_service.get_db(), auth_user.organization_id, target_user.locale.as_deref())
                 .await
-            {
-                eprintln!("Failed to send admin password reset email: {}", e);
-            }
+                .map_err(|e| Error::new(format!("Failed to load email branding: {}", e)))?;
+            let message = email_service
+                .admin_password_reset_email(&target_user.email, reset_token, frontend_url, &admin_name, &style)
+                .map_err(|e| Error::new(format!("Failed to render password reset email: {}", e)))?;
+            email_outbox_service
+                .enqueue(message)
+                .await
+                .map_err(|e| Error::new(format!("Failed to queue password reset email: {}", e)))?;
         }
 
         Ok(MessageResponse {
@@ -294,26 +493,366 @@ impl MutationRoot {
         })
     }
 
+    // Organization mutations
+    /// Change the organization the session works in; returns tokens scoped to it
+    async fn switch_organization(&self, ctx: &Context<'_>, input: SwitchOrganizationInput) -> Result<AuthPayload> {
+        use crate::auth::forbid_impersonation;
+        let auth_user = forbid_impersonation(ctx, "Switching organizations")?;
+
+        let user_service = ctx.data::<UserService>()?;
+
+        let (user, access_token, refresh_token) = user_service
+            .switch_organization(auth_user.id, input.organization_id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to switch organization: {}", e)))?;
+
+        Ok(AuthPayload::new(user, access_token, refresh_token))
+    }
+
+    async fn create_organization(&self, ctx: &Context<'_>, input: CreateOrganizationInput) -> Result<Organization> {
+        use crate::auth::require_system_admin;
+        let admin = require_system_admin(ctx).await?;
+
+        let organization_service = ctx.data::<OrganizationService>()?;
+
+        let organization = organization_service
+            .create_organization(admin.id, &input.name, &input.slug)
+            .await
+            .map_err(|e| Error::new(format!("Failed to create organization: {}", e)))?;
+
+        Ok(organization.into())
+    }
+
+    /// Set how emails sent on behalf of the current organization look
+    async fn update_organization_branding(
+        &self,
+        ctx: &Context<'_>,
+        input: UpdateOrganizationBrandingInput,
+    ) -> Result<Organization> {
+        use crate::auth::require_admin;
+        require_admin(ctx).await?;
+        let organization_id = require_organization(ctx)?;
+
+        let organization_service = ctx.data::<OrganizationService>()?;
+        let email_service = ctx.data::<EmailService>()?;
+
+        let organization = organization_service
+            .update_branding(organization_id, input, &email_service.locales())
+            .await
+            .map_err(|e| Error::new(format!("Failed to update organization branding: {}", e)))?;
+
+        Ok(organization.into())
+    }
+
+    /// Set the language of your own emails; `null` uses your organization's default
+    async fn update_my_locale(&self, ctx: &Context<'_>, locale: Option<String>) -> Result<User> {
+        use crate::auth::forbid_impersonation;
+        let auth_user = forbid_impersonation(ctx, "Changing the email language")?;
+
+        let user_service = ctx.data::<UserService>()?;
+
+        let user = user_service
+            .set_locale(auth_user.id, locale)
+            .await
+            .map_err(|e| Error::new(format!("Failed to update locale: {}", e)))?;
+
+        Ok(user.into())
+    }
+
+    /// Mark the given notifications, or all of them when `ids` is omitted, as read.
+    /// Returns how many were unread.
+    async fn mark_notifications_read(&self, ctx: &Context<'_>, ids: Option<Vec<Uuid>>) -> Result<i32> {
+        use crate::auth::forbid_impersonation;
+        let auth_user = forbid_impersonation(ctx, "Marking notifications as read")?;
+
+        let notification_service = ctx.data::<NotificationService>()?;
+
+        let count = notification_service
+            .mark_read(auth_user.id, ids)
+            .await
+            .map_err(|e| Error::new(format!("Failed to mark notifications as read: {}", e)))?;
+
+        Ok(count as i32)
+    }
+
+    /// Turn notification types on or off for yourself; types not listed keep their setting
+    async fn update_notification_preferences(
+        &self,
+        ctx: &Context<'_>,
+        input: Vec<NotificationPreferenceInput>,
+    ) -> Result<Vec<NotificationPreference>> {
+        use crate::auth::forbid_impersonation;
+        let auth_user = forbid_impersonation(ctx, "Changing notification preferences")?;
+
+        let notification_service = ctx.data::<NotificationService>()?;
+
+        notification_service
+            .update_preferences(
+                auth_user.id,
+                input.into_iter().map(|preference| (preference.notification_type, preference.enabled)).collect(),
+            )
+            .await
+            .map_err(|e| Error::new(format!("Failed to update notification preferences: {}", e)))
+    }
+
+    /// Change when and how often your email digest is sent
+    async fn update_my_digest_preferences(
+        &self,
+        ctx: &Context<'_>,
+        input: UpdateDigestPreferencesInput,
+    ) -> Result<DigestPreferences> {
+        use crate::auth::forbid_impersonation;
+        let auth_user = forbid_impersonation(ctx, "Changing digest preferences")?;
+
+        let digest_service = ctx.data::<DigestService>()?;
+
+        digest_service
+            .update_preferences(auth_user.id, input)
+            .await
+            .map_err(|e| Error::new(format!("Failed to update digest preferences: {}", e)))
+    }
+
+    /// Queue an outbox email for delivery again with a fresh set of attempts
+    async fn resend_outbox_email(&self, ctx: &Context<'_>, id: Uuid) -> Result<EmailOutboxEntry> {
+        use crate::auth::require_system_admin;
+        require_system_admin(ctx).await?;
+
+        let email_outbox_service = ctx.data::<EmailOutboxService>()?;
+
+        let email = email_outbox_service
+            .resend(id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to resend email: {}", e)))?;
+
+        Ok(email.into())
+    }
+
+    /// Subscribe an endpoint to events of the current organization, or of one of its
+    /// projects. The signing secret is only returned here and on rotation.
+    async fn create_webhook_subscription(
+        &self,
+        ctx: &Context<'_>,
+        input: CreateWebhookSubscriptionInput,
+    ) -> Result<WebhookSubscriptionWithSecret> {
+        use crate::auth::require_admin;
+        let auth_user = require_admin(ctx).await?;
+        let organization_id = require_organization(ctx)?;
+
+        let webhook_service = ctx.data::<WebhookService>()?;
+
+        let subscription = webhook_service
+            .create_subscription(organization_id, auth_user.id, input)
+            .await
+            .map_err(|e| Error::new(format!("Failed to create webhook subscription: {}", e)))?;
+
+        Ok(WebhookSubscriptionWithSecret {
+            secret: subscription.secret.clone(),
+            subscription: subscription.into(),
+        })
+    }
+
+    async fn update_webhook_subscription(
+        &self,
+        ctx: &Context<'_>,
+        input: UpdateWebhookSubscriptionInput,
+    ) -> Result<WebhookSubscription> {
+        use crate::auth::require_admin;
+        require_admin(ctx).await?;
+        let organization_id = require_organization(ctx)?;
+
+        let webhook_service = ctx.data::<WebhookService>()?;
+
+        let subscription = webhook_service
+            .update_subscription(organization_id, input)
+            .await
+            .map_err(|e| Error::new(format!("Failed to update webhook subscription: {}", e)))?;
+
+        Ok(subscription.into())
+    }
+
+    /// Replace a subscription's signing secret; the old one stops working right away
+    async fn rotate_webhook_secret(&self, ctx: &Context<'_>, subscription_id: Uuid) -> Result<WebhookSubscriptionWithSecret> {
+        use crate::auth::require_admin;
+        require_admin(ctx).await?;
+        let organization_id = require_organization(ctx)?;
+
+        let webhook_service = ctx.data::<WebhookService>()?;
+
+        let subscription = webhook_service
+            .rotate_secret(organization_id, subscription_id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to rotate webhook secret: {}", e)))?;
+
+        Ok(WebhookSubscriptionWithSecret {
+            secret: subscription.secret.clone(),
+            subscription: subscription.into(),
+        })
+    }
+
+    async fn delete_webhook_subscription(&self, ctx: &Context<'_>, subscription_id: Uuid) -> Result<MessageResponse> {
+        use crate::auth::require_admin;
+        require_admin(ctx).await?;
+        let organization_id = require_organization(ctx)?;
+
+        let webhook_service = ctx.data::<WebhookService>()?;
+
+        webhook_service
+            .delete_subscription(organization_id, subscription_id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to delete webhook subscription: {}", e)))?;
+
+        Ok(MessageResponse {
+            message: "Webhook subscription deleted successfully".to_string(),
+        })
+    }
+
+    /// Send a delivery's event again as a new delivery
+    async fn replay_webhook_delivery(&self, ctx: &Context<'_>, delivery_id: Uuid) -> Result<WebhookDelivery> {
+        use crate::auth::require_admin;
+        require_admin(ctx).await?;
+        let organization_id = require_organization(ctx)?;
+
+        let webhook_service = ctx.data::<WebhookService>()?;
+
+        let delivery = webhook_service
+            .replay_delivery(organization_id, delivery_id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to replay webhook delivery: {}", e)))?;
+
+        Ok(delivery.into())
+    }
+
+    /// Add an existing user to an organization. New people join through invitations.
+    async fn add_organization_member(&self, ctx: &Context<'_>, input: OrganizationMemberInput) -> Result<MessageResponse> {
+        use crate::auth::require_system_admin;
+        require_system_admin(ctx).await?;
+
+        let organization_service = ctx.data::<OrganizationService>()?;
+
+        organization_service
+            .add_member(input.organization_id, input.user_id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to add organization member: {}", e)))?;
+
+        Ok(MessageResponse {
+            message: "User added to organization successfully".to_string(),
+        })
+    }
+
+    /// Remove a user from an organization and its projects. User managers may do this
+    /// within their current organization to users below their own level; anywhere else
+    /// needs a system admin.
+    async fn remove_organization_member(&self, ctx: &Context<'_>, input: OrganizationMemberInput) -> Result<MessageResponse> {
+        let auth_user = crate::auth::require_auth(ctx)?;
+        if auth_user.organization_id == Some(input.organization_id) {
+            require_user_management(ctx).await?;
+            crate::auth::require_user_can_manage(ctx, input.user_id).await?;
+        } else {
+            crate::auth::require_system_admin(ctx).await?;
+        }
+
+        let organization_service = ctx.data::<OrganizationService>()?;
+
+        organization_service
+            .remove_member(input.organization_id, input.user_id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to remove organization member: {}", e)))?;
+
+        Ok(MessageResponse {
+            message: "User removed from organization successfully".to_string(),
+        })
+    }
+
+    // Team mutations
+    async fn create_team(&self, ctx: &Context<'_>, input: CreateTeamInput) -> Result<Team> {
+        let auth_user = require_user_management(ctx).await?;
+        let organization_id = require_organization(ctx)?;
+        let team_service = ctx.data::<TeamService>()?;
+
+        let team = team_service
+            .create_team(organization_id, auth_user.id, &input.name, input.description)
+            .await
+            .map_err(|e| Error::new(format!("Failed to create team: {}", e)))?;
+
+        Ok(team.into())
+    }
+
+    async fn update_team(&self, ctx: &Context<'_>, input: UpdateTeamInput) -> Result<Team> {
+        let team_service = ctx.data::<TeamService>()?;
+        let team = load_team(team_service, input.team_id).await?;
+        require_team_management(ctx, team.organization_id).await?;
+
+        let team = team_service
+            .update_team(input.team_id, input.name, input.description)
+            .await
+            .map_err(|e| Error::new(format!("Failed to update team: {}", e)))?;
+
+        Ok(team.into())
+    }
+
+    async fn delete_team(&self, ctx: &Context<'_>, team_id: Uuid) -> Result<MessageResponse> {
+        let team_service = ctx.data::<TeamService>()?;
+        let team = load_team(team_service, team_id).await?;
+        require_team_management(ctx, team.organization_id).await?;
+
+        team_service
+            .delete_team(team_id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to delete team: {}", e)))?;
+
+        Ok(MessageResponse {
+            message: "Team deleted successfully".to_string(),
+        })
+    }
+
+    /// Add a user to a team; they join every project the team is on
+    async fn add_team_member(&self, ctx: &Context<'_>, input: TeamMemberInput) -> Result<MessageResponse> {
+        let team_service = ctx.data::<TeamService>()?;
+        let team = load_team(team_service, input.team_id).await?;
+        require_team_management(ctx, team.organization_id).await?;
+
+        team_service
+            .add_team_member(input.team_id, input.user_id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to add team member: {}", e)))?;
+
+        Ok(MessageResponse {
+            message: "Team member added successfully".to_string(),
+        })
+    }
+
+    async fn remove_team_member(&self, ctx: &Context<'_>, input: TeamMemberInput) -> Result<MessageResponse> {
+        let team_service = ctx.data::<TeamService>()?;
+        let team = load_team(team_service, input.team_id).await?;
+        require_team_management(ctx, team.organization_id).await?;
+
+        team_service
+            .remove_team_member(input.team_id, input.user_id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to remove team member: {}", e)))?;
+
+        Ok(MessageResponse {
+            message: "Team member removed successfully".to_string(),
+        })
+    }
+
     // Project mutations
+    #[graphql(guard = "PermissionGuard::new(AppPermission::ProjectCreate)")]
     async fn create_project(&self, ctx: &Context<'_>, input: CreateProjectInput) -> Result<Project> {
-        use crate::auth::require_permission;
-        require_permission(ctx, "task_system", "project_create").await?;
-        
         let project_service = ctx.data::<ProjectService>()?;
         let authenticated_user = ctx.data::<crate::auth::AuthenticatedUser>()?;
+        let organization_id = require_organization(ctx)?;
         
         let project = project_service
-            .create_project(authenticated_user.id, &input.name, input.description)
+            .create_project(authenticated_user.id, organization_id, &input.name, input.description)
             .await
             .map_err(|e| Error::new(format!("Failed to create project: {}", e)))?;
             
         Ok(project.into())
     }
 
+    #[graphql(guard = "PermissionGuard::new(AppPermission::TaskWrite)")]
     async fn update_project(&self, ctx: &Context<'_>, input: UpdateProjectInput) -> Result<Project> {
-        use crate::auth::require_permission;
-        require_permission(ctx, "task_system", "task_write").await?;
-        
         let project_service = ctx.data::<ProjectService>()?;
         let authenticated_user = ctx.data::<crate::auth::AuthenticatedUser>()?;
         
@@ -325,10 +864,8 @@ impl MutationRoot {
         Ok(project.into())
     }
 
+    #[graphql(guard = "PermissionGuard::new(AppPermission::ProjectAdmin)")]
     async fn delete_project(&self, ctx: &Context<'_>, project_id: uuid::Uuid) -> Result<MessageResponse> {
-        use crate::auth::require_permission;
-        require_permission(ctx, "task_system", "project_admin").await?;
-        
         let project_service = ctx.data::<ProjectService>()?;
         let authenticated_user = ctx.data::<crate::auth::AuthenticatedUser>()?;
         
@@ -342,68 +879,234 @@ impl MutationRoot {
         })
     }
 
-    async fn add_project_member(&self, ctx: &Context<'_>, input: AddProjectMemberInput) -> Result<MessageResponse> {
-        use crate::auth::require_permission;
-        require_permission(ctx, "task_system", "project_invite").await?;
-        
+    #[graphql(guard = "PermissionGuard::new(AppPermission::ProjectInvite)")]
+    async fn add_project_member(&self, ctx: &Context<'_>, input: AddProjectMemberInput) -> Result<MessageResponse> {
+        let project_service = ctx.data::<ProjectService>()?;
+        let authenticated_user = ctx.data::<crate::auth::AuthenticatedUser>()?;
+        
+        project_service
+            .add_project_member(input.project_id, authenticated_user.id, input.user_id, &input.role)
+            .await
+            .map_err(|e| Error::new(format!("Failed to add project member: {}", e)))?;
+            
+        Ok(MessageResponse {
+            message: "Project member added successfully".to_string(),
+        })
+    }
+
+    #[graphql(guard = "PermissionGuard::new(AppPermission::ProjectAdmin)")]
+    async fn update_member_role(&self, ctx: &Context<'_>, input: UpdateMemberRoleInput) -> Result<MessageResponse> {
+        let project_service = ctx.data::<ProjectService>()?;
+        let authenticated_user = ctx.data::<crate::auth::AuthenticatedUser>()?;
+        
+        project_service
+            .update_member_role(input.project_id, authenticated_user.id, input.user_id, &input.role)
+            .await
+            .map_err(|e| Error::new(format!("Failed to update member role: {}", e)))?;
+            
+        Ok(MessageResponse {
+            message: "Member role updated successfully".to_string(),
+        })
+    }
+
+    #[graphql(guard = "PermissionGuard::new(AppPermission::ProjectAdmin)")]
+    async fn remove_project_member(&self, ctx: &Context<'_>, input: RemoveProjectMemberInput) -> Result<MessageResponse> {
+        let project_service = ctx.data::<ProjectService>()?;
+        let authenticated_user = ctx.data::<crate::auth::AuthenticatedUser>()?;
+        
+        project_service
+            .remove_project_member(input.project_id, authenticated_user.id, input.user_id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to remove project member: {}", e)))?;
+            
+        Ok(MessageResponse {
+            message: "Project member removed successfully".to_string(),
+        })
+    }
+
+    /// Invite an existing user to a project; they join once they accept
+    #[graphql(guard = "PermissionGuard::new(AppPermission::ProjectInvite)")]
+    async fn invite_to_project(&self, ctx: &Context<'_>, input: InviteToProjectInput) -> Result<ProjectInvitation> {
+        let invitation_service = ctx.data::<InvitationService>()?;
+        let authenticated_user = ctx.data::<crate::auth::AuthenticatedUser>()?;
+        let frontend_url = ctx.data::<String>()?;
+
+        let invitation = invitation_service
+            .invite_user_to_project(input.project_id, authenticated_user.id, &input.email, &input.role, frontend_url)
+            .await
+            .map_err(|e| Error::new(format!("Failed to invite to project: {}", e)))?;
+
+        Ok(invitation.into())
+    }
+
+    #[graphql(guard = "PermissionGuard::new(AppPermission::ProjectInvite)")]
+    async fn resend_project_invitation(&self, ctx: &Context<'_>, invitation_id: Uuid) -> Result<ProjectInvitation> {
+        let invitation_service = ctx.data::<InvitationService>()?;
+        let authenticated_user = ctx.data::<crate::auth::AuthenticatedUser>()?;
+        let frontend_url = ctx.data::<String>()?;
+
+        let invitation = invitation_service
+            .resend_project_invitation(invitation_id, authenticated_user.id, frontend_url)
+            .await
+            .map_err(|e| Error::new(format!("Failed to resend invitation: {}", e)))?;
+
+        Ok(invitation.into())
+    }
+
+    #[graphql(guard = "PermissionGuard::new(AppPermission::ProjectInvite)")]
+    async fn revoke_project_invitation(&self, ctx: &Context<'_>, invitation_id: Uuid) -> Result<ProjectInvitation> {
+        let invitation_service = ctx.data::<InvitationService>()?;
+        let authenticated_user = ctx.data::<crate::auth::AuthenticatedUser>()?;
+
+        let invitation = invitation_service
+            .revoke_project_invitation(invitation_id, authenticated_user.id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to revoke invitation: {}", e)))?;
+
+        Ok(invitation.into())
+    }
+
+    async fn accept_project_invitation(&self, ctx: &Context<'_>, invitation_id: Uuid) -> Result<ProjectInvitation> {
+        let authenticated_user = crate::auth::forbid_impersonation(ctx, "Accepting project invitations")?;
+        let invitation_service = ctx.data::<InvitationService>()?;
+
+        let invitation = invitation_service
+            .accept_project_invitation(invitation_id, authenticated_user.id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to accept invitation: {}", e)))?;
+
+        Ok(invitation.into())
+    }
+
+    async fn decline_project_invitation(&self, ctx: &Context<'_>, invitation_id: Uuid) -> Result<ProjectInvitation> {
+        let authenticated_user = crate::auth::forbid_impersonation(ctx, "Declining project invitations")?;
+        let invitation_service = ctx.data::<InvitationService>()?;
+
+        let invitation = invitation_service
+            .decline_project_invitation(invitation_id, authenticated_user.id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to decline invitation: {}", e)))?;
+
+        Ok(invitation.into())
+    }
+
+    #[graphql(guard = "PermissionGuard::new(AppPermission::ProjectInvite)")]
+    async fn add_project_team(&self, ctx: &Context<'_>, input: AddProjectTeamInput) -> Result<MessageResponse> {
+        let project_service = ctx.data::<ProjectService>()?;
+        let authenticated_user = ctx.data::<crate::auth::AuthenticatedUser>()?;
+
+        project_service
+            .add_project_team(input.project_id, authenticated_user.id, input.team_id, &input.role)
+            .await
+            .map_err(|e| Error::new(format!("Failed to add team to project: {}", e)))?;
+
+        Ok(MessageResponse {
+            message: "Team added to project successfully".to_string(),
+        })
+    }
+
+    #[graphql(guard = "PermissionGuard::new(AppPermission::ProjectAdmin)")]
+    async fn update_project_team_role(&self, ctx: &Context<'_>, input: UpdateProjectTeamRoleInput) -> Result<MessageResponse> {
+        let project_service = ctx.data::<ProjectService>()?;
+        let authenticated_user = ctx.data::<crate::auth::AuthenticatedUser>()?;
+
+        project_service
+            .update_project_team_role(input.project_id, authenticated_user.id, input.team_id, &input.role)
+            .await
+            .map_err(|e| Error::new(format!("Failed to update team role: {}", e)))?;
+
+        Ok(MessageResponse {
+            message: "Team role updated successfully".to_string(),
+        })
+    }
+
+    #[graphql(guard = "PermissionGuard::new(AppPermission::ProjectAdmin)")]
+    async fn remove_project_team(&self, ctx: &Context<'_>, input: RemoveProjectTeamInput) -> Result<MessageResponse> {
+        let project_service = ctx.data::<ProjectService>()?;
+        let authenticated_user = ctx.data::<crate::auth::AuthenticatedUser>()?;
+
+        project_service
+            .remove_project_team(input.project_id, authenticated_user.id, input.team_id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to remove team from project: {}", e)))?;
+
+        Ok(MessageResponse {
+            message: "Team removed from project successfully".to_string(),
+        })
+    }
+
+    #[graphql(guard = "PermissionGuard::new(AppPermission::ProjectAdmin)")]
+    async fn create_project_role(&self, ctx: &Context<'_>, input: CreateProjectRoleInput) -> Result<ProjectRoleDefinition> {
         let project_service = ctx.data::<ProjectService>()?;
         let authenticated_user = ctx.data::<crate::auth::AuthenticatedUser>()?;
-        
-        let role = ProjectRole::from_str(&input.role)
-            .ok_or_else(|| Error::new("Invalid project role"))?;
-        
-        project_service
-            .add_project_member(input.project_id, authenticated_user.id, input.user_id, role)
+
+        let role = project_service
+            .create_project_role(
+                input.project_id,
+                authenticated_user.id,
+                &input.name,
+                input.description,
+                input.permissions.clone(),
+            )
             .await
-            .map_err(|e| Error::new(format!("Failed to add project member: {}", e)))?;
-            
-        Ok(MessageResponse {
-            message: "Project member added successfully".to_string(),
-        })
+            .map_err(|e| Error::new(format!("Failed to create project role: {}", e)))?;
+
+        let mut permissions = input.permissions;
+        permissions.sort();
+        permissions.dedup();
+
+        Ok((role, permissions).into())
     }
 
-    async fn update_member_role(&self, ctx: &Context<'_>, input: UpdateMemberRoleInput) -> Result<MessageResponse> {
-        use crate::auth::require_permission;
-        require_permission(ctx, "task_system", "project_admin").await?;
-        
+    #[graphql(guard = "PermissionGuard::new(AppPermission::ProjectAdmin)")]
+    async fn update_project_role(&self, ctx: &Context<'_>, input: UpdateProjectRoleInput) -> Result<ProjectRoleDefinition> {
         let project_service = ctx.data::<ProjectService>()?;
         let authenticated_user = ctx.data::<crate::auth::AuthenticatedUser>()?;
-        
-        let role = ProjectRole::from_str(&input.role)
-            .ok_or_else(|| Error::new("Invalid project role"))?;
-        
-        project_service
-            .update_member_role(input.project_id, authenticated_user.id, input.user_id, role)
+
+        let role = project_service
+            .update_project_role(
+                input.role_id,
+                authenticated_user.id,
+                input.name,
+                input.description,
+                input.permissions,
+            )
             .await
-            .map_err(|e| Error::new(format!("Failed to update member role: {}", e)))?;
-            
-        Ok(MessageResponse {
-            message: "Member role updated successfully".to_string(),
-        })
+            .map_err(|e| Error::new(format!("Failed to update project role: {}", e)))?;
+
+        let project_id = role
+            .project_id
+            .ok_or_else(|| Error::new("System project roles cannot be modified"))?;
+        let roles = project_service
+            .get_project_roles(project_id, authenticated_user.id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to fetch project role: {}", e)))?;
+
+        roles
+            .into_iter()
+            .find(|(r, _)| r.id == role.id)
+            .map(Into::into)
+            .ok_or_else(|| Error::new("Project role not found"))
     }
 
-    async fn remove_project_member(&self, ctx: &Context<'_>, input: RemoveProjectMemberInput) -> Result<MessageResponse> {
-        use crate::auth::require_permission;
-        require_permission(ctx, "task_system", "project_admin").await?;
-        
+    #[graphql(guard = "PermissionGuard::new(AppPermission::ProjectAdmin)")]
+    async fn delete_project_role(&self, ctx: &Context<'_>, role_id: Uuid) -> Result<MessageResponse> {
         let project_service = ctx.data::<ProjectService>()?;
         let authenticated_user = ctx.data::<crate::auth::AuthenticatedUser>()?;
-        
+
         project_service
-            .remove_project_member(input.project_id, authenticated_user.id, input.user_id)
+            .delete_project_role(role_id, authenticated_user.id)
             .await
-            .map_err(|e| Error::new(format!("Failed to remove project member: {}", e)))?;
-            
+            .map_err(|e| Error::new(format!("Failed to delete project role: {}", e)))?;
+
         Ok(MessageResponse {
-            message: "Project member removed successfully".to_string(),
+            message: "Project role deleted successfully".to_string(),
         })
     }
 
     // Task mutations
+    #[graphql(guard = "PermissionGuard::new(AppPermission::TaskCreate)")]
     async fn create_task(&self, ctx: &Context<'_>, input: CreateTaskInput) -> Result<Task> {
-        use crate::auth::require_permission;
-        require_permission(ctx, "task_system", "task_create").await?;
-        
         let task_service = ctx.data::<TaskService>()?;
         let authenticated_user = ctx.data::<crate::auth::AuthenticatedUser>()?;
         
@@ -425,10 +1128,8 @@ impl MutationRoot {
         Ok(task.into())
     }
 
+    #[graphql(guard = "PermissionGuard::new(AppPermission::TaskWrite)")]
     async fn update_task(&self, ctx: &Context<'_>, input: UpdateTaskInput) -> Result<Task> {
-        use crate::auth::require_permission;
-        require_permission(ctx, "task_system", "task_write").await?;
-        
         let task_service = ctx.data::<TaskService>()?;
         let authenticated_user = ctx.data::<crate::auth::AuthenticatedUser>()?;
         
@@ -450,10 +1151,8 @@ impl MutationRoot {
         Ok(task.into())
     }
 
+    #[graphql(guard = "PermissionGuard::new(AppPermission::TaskAssign)")]
     async fn assign_task(&self, ctx: &Context<'_>, input: AssignTaskInput) -> Result<Task> {
-        use crate::auth::require_permission;
-        require_permission(ctx, "task_system", "task_assign").await?;
-        
         let task_service = ctx.data::<TaskService>()?;
         let authenticated_user = ctx.data::<crate::auth::AuthenticatedUser>()?;
         
@@ -465,10 +1164,63 @@ impl MutationRoot {
         Ok(task.into())
     }
 
+    /// Put a task in a team's queue for one of its members to claim
+    #[graphql(guard = "PermissionGuard::new(AppPermission::TaskAssign)")]
+    async fn assign_task_to_team(&self, ctx: &Context<'_>, input: AssignTaskToTeamInput) -> Result<Task> {
+        let task_service = ctx.data::<TaskService>()?;
+        let authenticated_user = ctx.data::<crate::auth::AuthenticatedUser>()?;
+
+        let task = task_service
+            .assign_task_to_team(input.task_id, authenticated_user.id, input.team_id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to assign task to team: {}", e)))?;
+
+        Ok(task.into())
+    }
+
+    /// Follow a task to be notified when it changes
+    async fn watch_task(&self, ctx: &Context<'_>, task_id: Uuid) -> Result<Task> {
+        use crate::auth::forbid_impersonation;
+        let auth_user = forbid_impersonation(ctx, "Watching tasks")?;
+        let task_service = ctx.data::<TaskService>()?;
+
+        let task = task_service
+            .watch_task(task_id, auth_user.id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to watch task: {}", e)))?;
+
+        Ok(task.into())
+    }
+
+    /// Stop following a task
+    async fn unwatch_task(&self, ctx: &Context<'_>, task_id: Uuid) -> Result<Task> {
+        use crate::auth::forbid_impersonation;
+        let auth_user = forbid_impersonation(ctx, "Unwatching tasks")?;
+        let task_service = ctx.data::<TaskService>()?;
+
+        let task = task_service
+            .unwatch_task(task_id, auth_user.id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to unwatch task: {}", e)))?;
+
+        Ok(task.into())
+    }
+
+    /// Take an unclaimed task from one of your teams' queues
+    async fn claim_task(&self, ctx: &Context<'_>, task_id: Uuid) -> Result<Task> {
+        let authenticated_user = crate::auth::require_auth(ctx)?;
+        let task_service = ctx.data::<TaskService>()?;
+
+        let task = task_service
+            .claim_task(task_id, authenticated_user.id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to claim task: {}", e)))?;
+
+        Ok(task.into())
+    }
+
+    #[graphql(guard = "PermissionGuard::new(AppPermission::TaskDelete)")]
     async fn delete_task(&self, ctx: &Context<'_>, task_id: uuid::Uuid) -> Result<MessageResponse> {
-        use crate::auth::require_permission;
-        require_permission(ctx, "task_system", "task_delete").await?;
-        
         let task_service = ctx.data::<TaskService>()?;
         let authenticated_user = ctx.data::<crate::auth::AuthenticatedUser>()?;
         
@@ -482,10 +1234,8 @@ impl MutationRoot {
         })
     }
 
+    #[graphql(guard = "PermissionGuard::new(AppPermission::TaskWrite)")]
     async fn complete_task_with_recurrence(&self, ctx: &Context<'_>, task_id: uuid::Uuid) -> Result<CompleteTaskWithRecurrenceResponse> {
-        use crate::auth::require_permission;
-        require_permission(ctx, "task_system", "task_write").await?;
-        
         let task_service = ctx.data::<TaskService>()?;
         let authenticated_user = ctx.data::<crate::auth::AuthenticatedUser>()?;
         
@@ -507,14 +1257,23 @@ impl MutationRoot {
 
     // Role Management
     async fn create_role(&self, ctx: &Context<'_>, input: CreateRoleInput) -> Result<Role> {
-        use crate::auth::require_admin;
-        require_admin(ctx, "freshapi").await?;
+        use crate::auth::require_role_management;
+        let organization_id = match input.shared {
+            Some(true) => None,
+            _ => Some(require_organization(ctx)?),
+        };
+        let editor = require_role_management(ctx, organization_id).await?;
+        ctx.data::<PermissionService>()?
+            .ensure_can_edit_role(editor.id, editor.organization_id, None, input.level)
+            .await
+            .map_err(|e| Error::new(format!("Failed to create role: {}", e)))?;
         
         let user_service = ctx.data::<UserService>()?;
         
-        // Check if role name already exists
+        // Check if role name already exists among the roles the organization can see
         let existing = crate::entities::role::Entity::find()
             .filter(crate::entities::role::Column::Name.eq(&input.name))
+            .filter(visible_roles(organization_id))
             .one(user_service.get_db())
             .await
             .map_err(|e| Error::new(format!("Database error: {}", e)))?;
@@ -531,10 +1290,33 @@ impl MutationRoot {
             is_active: Set(true),
             created_at: Set(Utc::now().into()),
             updated_at: Set(Utc::now().into()),
+            magic_link_enabled: Set(input.magic_link_enabled.unwrap_or(false)),
+            organization_id: Set(organization_id),
         };
         
+        let tx = user_service
+            .get_db()
+            .begin()
+            .await
+            .map_err(|e| Error::new(format!("Database error: {}", e)))?;
+
         let role = new_role
-            .insert(user_service.get_db())
+            .insert(&tx)
+            .await
+            .map_err(|e| Error::new(format!("Failed to create role: {}", e)))?;
+
+        if let Some(parent_role_ids) = &input.parent_role_ids {
+            let editor_level = ctx
+                .data::<PermissionService>()?
+                .get_user_role_level(editor.id, editor.organization_id)
+                .await
+                .map_err(|e| Error::new(format!("Failed to get role level: {}", e)))?;
+            PermissionService::set_role_parents(&tx, role.id, parent_role_ids, editor_level)
+                .await
+                .map_err(|e| Error::new(format!("Failed to set parent roles: {}", e)))?;
+        }
+
+        tx.commit()
             .await
             .map_err(|e| Error::new(format!("Failed to create role: {}", e)))?;
             
@@ -543,7 +1325,7 @@ impl MutationRoot {
 
     async fn update_role(&self, ctx: &Context<'_>, input: UpdateRoleInput) -> Result<Role> {
         use crate::auth::require_admin;
-        require_admin(ctx, "freshapi").await?;
+        require_admin(ctx).await?;
         
         let user_service = ctx.data::<UserService>()?;
         
@@ -553,6 +1335,11 @@ impl MutationRoot {
             .await
             .map_err(|e| Error::new(format!("Database error: {}", e)))?
             .ok_or_else(|| Error::new("Role not found"))?;
+        let editor = crate::auth::require_role_management(ctx, role.organization_id).await?;
+        ctx.data::<PermissionService>()?
+            .ensure_can_edit_role(editor.id, editor.organization_id, Some(&role), input.level.unwrap_or(role.level))
+            .await
+            .map_err(|e| Error::new(format!("Failed to update role: {}", e)))?;
             
         // Check if new name conflicts (if changing name)
         if let Some(ref new_name) = input.name {
@@ -560,6 +1347,7 @@ impl MutationRoot {
                 let existing = crate::entities::role::Entity::find()
                     .filter(crate::entities::role::Column::Name.eq(new_name))
                     .filter(crate::entities::role::Column::Id.ne(input.role_id))
+                    .filter(visible_roles(role.organization_id))
                     .one(user_service.get_db())
                     .await
                     .map_err(|e| Error::new(format!("Database error: {}", e)))?;
@@ -581,22 +1369,48 @@ impl MutationRoot {
         if let Some(level) = input.level {
             active_role.level = Set(level);
         }
+        if let Some(magic_link_enabled) = input.magic_link_enabled {
+            active_role.magic_link_enabled = Set(magic_link_enabled);
+        }
         if let Some(is_active) = input.is_active {
             active_role.is_active = Set(is_active);
         }
         active_role.updated_at = Set(Utc::now().into());
         
+        let tx = user_service
+            .get_db()
+            .begin()
+            .await
+            .map_err(|e| Error::new(format!("Database error: {}", e)))?;
+
         let updated_role = active_role
-            .update(user_service.get_db())
+            .update(&tx)
+            .await
+            .map_err(|e| Error::new(format!("Failed to update role: {}", e)))?;
+
+        // Rejects cycles, parents above the editor and, for organization roles, system administration
+        if let Some(parent_role_ids) = &input.parent_role_ids {
+            let editor_level = ctx
+                .data::<PermissionService>()?
+                .get_user_role_level(editor.id, editor.organization_id)
+                .await
+                .map_err(|e| Error::new(format!("Failed to get role level: {}", e)))?;
+            PermissionService::set_role_parents(&tx, updated_role.id, parent_role_ids, editor_level)
+                .await
+                .map_err(|e| Error::new(format!("Failed to set parent roles: {}", e)))?;
+        }
+
+        tx.commit()
             .await
             .map_err(|e| Error::new(format!("Failed to update role: {}", e)))?;
+        ctx.data::<PermissionService>()?.invalidate_all().await;
             
         Ok(updated_role.into())
     }
 
     async fn delete_role(&self, ctx: &Context<'_>, role_id: uuid::Uuid) -> Result<MessageResponse> {
         use crate::auth::require_admin;
-        require_admin(ctx, "freshapi").await?;
+        require_admin(ctx).await?;
         
         let user_service = ctx.data::<UserService>()?;
         
@@ -606,6 +1420,11 @@ impl MutationRoot {
             .await
             .map_err(|e| Error::new(format!("Database error: {}", e)))?
             .ok_or_else(|| Error::new("Role not found"))?;
+        let editor = crate::auth::require_role_management(ctx, role.organization_id).await?;
+        ctx.data::<PermissionService>()?
+            .ensure_can_edit_role(editor.id, editor.organization_id, Some(&role), role.level)
+            .await
+            .map_err(|e| Error::new(format!("Failed to delete role: {}", e)))?;
             
         // Check if any users have this role
         let user_count = crate::entities::user::Entity::find()
@@ -617,6 +1436,17 @@ impl MutationRoot {
         if user_count > 0 {
             return Err(Error::new(format!("Cannot delete role '{}' as it is assigned to {} user(s)", role.name, user_count)));
         }
+
+        // Check if other roles inherit from this role
+        let child_count = crate::entities::role_parent::Entity::find()
+            .filter(crate::entities::role_parent::Column::ParentRoleId.eq(role_id))
+            .count(user_service.get_db())
+            .await
+            .map_err(|e| Error::new(format!("Database error: {}", e)))?;
+
+        if child_count > 0 {
+            return Err(Error::new(format!("Cannot delete role '{}' as {} role(s) inherit from it", role.name, child_count)));
+        }
         
         // Use soft delete (set is_active to false) for safety
         let mut active_role: crate::entities::role::ActiveModel = role.into();
@@ -627,6 +1457,7 @@ impl MutationRoot {
             .update(user_service.get_db())
             .await
             .map_err(|e| Error::new(format!("Failed to delete role: {}", e)))?;
+        ctx.data::<PermissionService>()?.invalidate_all().await;
             
         Ok(MessageResponse {
             message: "Role deleted successfully".to_string(),
@@ -636,7 +1467,7 @@ impl MutationRoot {
     // Permission Management
     async fn create_permission(&self, ctx: &Context<'_>, input: CreatePermissionInput) -> Result<Permission> {
         use crate::auth::require_admin;
-        require_admin(ctx, "freshapi").await?;
+        require_admin(ctx).await?;
         
         let user_service = ctx.data::<UserService>()?;
         
@@ -679,7 +1510,7 @@ impl MutationRoot {
 
     async fn update_permission(&self, ctx: &Context<'_>, input: UpdatePermissionInput) -> Result<Permission> {
         use crate::auth::require_admin;
-        require_admin(ctx, "freshapi").await?;
+        require_admin(ctx).await?;
         
         let user_service = ctx.data::<UserService>()?;
         
@@ -739,13 +1570,14 @@ impl MutationRoot {
             .update(user_service.get_db())
             .await
             .map_err(|e| Error::new(format!("Failed to update permission: {}", e)))?;
+        ctx.data::<PermissionService>()?.invalidate_all().await;
             
         Ok(updated_permission.into())
     }
 
     async fn delete_permission(&self, ctx: &Context<'_>, permission_id: uuid::Uuid) -> Result<MessageResponse> {
         use crate::auth::require_admin;
-        require_admin(ctx, "freshapi").await?;
+        require_admin(ctx).await?;
         
         let user_service = ctx.data::<UserService>()?;
         
@@ -783,6 +1615,7 @@ impl MutationRoot {
             .update(user_service.get_db())
             .await
             .map_err(|e| Error::new(format!("Failed to delete permission: {}", e)))?;
+        ctx.data::<PermissionService>()?.invalidate_all().await;
             
         Ok(MessageResponse {
             message: "Permission deleted successfully".to_string(),
@@ -792,7 +1625,7 @@ impl MutationRoot {
     // Resource Management
     async fn create_resource(&self, ctx: &Context<'_>, input: CreateResourceInput) -> Result<Resource> {
         use crate::auth::require_admin;
-        require_admin(ctx, "freshapi").await?;
+        require_admin(ctx).await?;
         
         let user_service = ctx.data::<UserService>()?;
         
@@ -826,7 +1659,7 @@ impl MutationRoot {
 
     async fn update_resource(&self, ctx: &Context<'_>, input: UpdateResourceInput) -> Result<Resource> {
         use crate::auth::require_admin;
-        require_admin(ctx, "freshapi").await?;
+        require_admin(ctx).await?;
         
         let user_service = ctx.data::<UserService>()?;
         
@@ -870,13 +1703,14 @@ impl MutationRoot {
             .update(user_service.get_db())
             .await
             .map_err(|e| Error::new(format!("Failed to update resource: {}", e)))?;
+        ctx.data::<PermissionService>()?.invalidate_all().await;
             
         Ok(updated_resource.into())
     }
 
     async fn delete_resource(&self, ctx: &Context<'_>, resource_id: uuid::Uuid) -> Result<MessageResponse> {
         use crate::auth::require_admin;
-        require_admin(ctx, "freshapi").await?;
+        require_admin(ctx).await?;
         
         let user_service = ctx.data::<UserService>()?;
         
@@ -907,6 +1741,7 @@ impl MutationRoot {
             .update(user_service.get_db())
             .await
             .map_err(|e| Error::new(format!("Failed to delete resource: {}", e)))?;
+        ctx.data::<PermissionService>()?.invalidate_all().await;
             
         Ok(MessageResponse {
             message: "Resource deleted successfully".to_string(),
@@ -916,22 +1751,32 @@ impl MutationRoot {
     // Role-Permission Assignment
     async fn assign_permission_to_role(&self, ctx: &Context<'_>, input: AssignPermissionToRoleInput) -> Result<MessageResponse> {
         use crate::auth::require_admin;
-        require_admin(ctx, "freshapi").await?;
+        require_admin(ctx).await?;
         
         let user_service = ctx.data::<UserService>()?;
         
         // Verify role and permission exist
-        let _role = crate::entities::role::Entity::find_by_id(input.role_id)
+        let role = crate::entities::role::Entity::find_by_id(input.role_id)
             .one(user_service.get_db())
             .await
             .map_err(|e| Error::new(format!("Database error: {}", e)))?
             .ok_or_else(|| Error::new("Role not found"))?;
+        let editor = crate::auth::require_role_management(ctx, role.organization_id).await?;
+        ctx.data::<PermissionService>()?
+            .ensure_can_edit_role(editor.id, editor.organization_id, Some(&role), role.level)
+            .await
+            .map_err(|e| Error::new(format!("Failed to assign permission: {}", e)))?;
             
-        let _permission = crate::entities::permission::Entity::find_by_id(input.permission_id)
+        let permission = crate::entities::permission::Entity::find_by_id(input.permission_id)
             .one(user_service.get_db())
             .await
             .map_err(|e| Error::new(format!("Database error: {}", e)))?
             .ok_or_else(|| Error::new("Permission not found"))?;
+
+        // Platform-wide administration never comes from a single organization's role
+        if role.organization_id.is_some() && permission.action == AppPermission::SystemAdmin.action() {
+            return Err(Error::new("Organization roles cannot grant system administration"));
+        }
         
         // Check if assignment already exists
         let existing = crate::entities::role_permission::Entity::find()
@@ -958,6 +1803,7 @@ impl MutationRoot {
             .insert(user_service.get_db())
             .await
             .map_err(|e| Error::new(format!("Failed to assign permission: {}", e)))?;
+        ctx.data::<PermissionService>()?.invalidate_all().await;
             
         Ok(MessageResponse {
             message: "Permission assigned to role successfully".to_string(),
@@ -966,9 +1812,20 @@ impl MutationRoot {
 
     async fn remove_permission_from_role(&self, ctx: &Context<'_>, input: RemovePermissionFromRoleInput) -> Result<MessageResponse> {
         use crate::auth::require_admin;
-        require_admin(ctx, "freshapi").await?;
+        require_admin(ctx).await?;
         
         let user_service = ctx.data::<UserService>()?;
+
+        let role = crate::entities::role::Entity::find_by_id(input.role_id)
+            .one(user_service.get_db())
+            .await
+            .map_err(|e| Error::new(format!("Database error: {}", e)))?
+            .ok_or_else(|| Error::new("Role not found"))?;
+        let editor = crate::auth::require_role_management(ctx, role.organization_id).await?;
+        ctx.data::<PermissionService>()?
+            .ensure_can_edit_role(editor.id, editor.organization_id, Some(&role), role.level)
+            .await
+            .map_err(|e| Error::new(format!("Failed to remove permission: {}", e)))?;
         
         let deleted = crate::entities::role_permission::Entity::delete_many()
             .filter(crate::entities::role_permission::Column::RoleId.eq(input.role_id))
@@ -980,6 +1837,7 @@ impl MutationRoot {
         if deleted.rows_affected == 0 {
             return Err(Error::new("Permission was not assigned to this role"));
         }
+        ctx.data::<PermissionService>()?.invalidate_all().await;
         
         Ok(MessageResponse {
             message: "Permission removed from role successfully".to_string(),
@@ -989,7 +1847,8 @@ impl MutationRoot {
     // User Direct Permission Management
     async fn grant_user_permission(&self, ctx: &Context<'_>, input: GrantUserPermissionInput) -> Result<MessageResponse> {
         use crate::auth::require_admin;
-        require_admin(ctx, "freshapi").await?;
+        let admin = require_admin(ctx).await?;
+        require_same_organization(ctx, input.user_id).await?;
         
         let user_service = ctx.data::<UserService>()?;
         
@@ -1000,71 +1859,58 @@ impl MutationRoot {
             .map_err(|e| Error::new(format!("Database error: {}", e)))?
             .ok_or_else(|| Error::new("User not found"))?;
             
-        let _permission = crate::entities::permission::Entity::find_by_id(input.permission_id)
+        let permission = crate::entities::permission::Entity::find_by_id(input.permission_id)
             .one(user_service.get_db())
             .await
             .map_err(|e| Error::new(format!("Database error: {}", e)))?
             .ok_or_else(|| Error::new("Permission not found"))?;
-        
-        // Check if permission already granted
-        let existing = crate::entities::user_permission::Entity::find()
-            .filter(crate::entities::user_permission::Column::UserId.eq(input.user_id))
-            .filter(crate::entities::user_permission::Column::PermissionId.eq(input.permission_id))
-            .one(user_service.get_db())
-            .await
-            .map_err(|e| Error::new(format!("Database error: {}", e)))?;
-            
-        if let Some(existing_perm) = existing {
-            if existing_perm.is_granted {
-                return Ok(MessageResponse {
-                    message: "Permission already granted to user".to_string(),
-                });
-            } else {
-                // Update existing record to grant permission
-                let mut active_permission: crate::entities::user_permission::ActiveModel = existing_perm.into();
-                active_permission.is_granted = Set(true);
-                active_permission.updated_at = Set(Utc::now().into());
-                
-                active_permission
-                    .update(user_service.get_db())
-                    .await
-                    .map_err(|e| Error::new(format!("Failed to grant permission: {}", e)))?;
-            }
+
+        // Organization admins can't hand out platform-wide administration; everything else
+        // only applies in the admin's organization
+        let organization_id = if permission.action == AppPermission::SystemAdmin.action() {
+            crate::auth::require_system_admin(ctx).await?;
+            None
         } else {
-            // Create new permission grant
-            let user_permission = crate::entities::user_permission::ActiveModel {
-                id: Set(uuid::Uuid::new_v4()),
-                user_id: Set(input.user_id),
-                permission_id: Set(input.permission_id),
-                is_granted: Set(true),
-                created_at: Set(Utc::now().into()),
-                updated_at: Set(Utc::now().into()),
-            };
-            
-            user_permission
-                .insert(user_service.get_db())
-                .await
-                .map_err(|e| Error::new(format!("Failed to grant permission: {}", e)))?;
-        }
-        
-        Ok(MessageResponse {
-            message: "Permission granted to user successfully".to_string(),
-        })
+            admin.organization_id
+        };
+
+        // Grants the permission, replacing any previous grant, denial or validity window
+        let permission_service = ctx.data::<PermissionService>()?;
+        permission_service
+            .grant_user_permission(input.user_id, input.permission_id, organization_id, input.valid_from, input.valid_until)
+            .await
+            .map_err(|e| Error::new(format!("Failed to grant permission: {}", e)))?;
+
+        let message = match input.valid_until {
+            Some(valid_until) => format!("Permission granted to user until {}", valid_until.to_rfc3339()),
+            None => "Permission granted to user successfully".to_string(),
+        };
+
+        Ok(MessageResponse { message })
     }
 
     async fn revoke_user_permission(&self, ctx: &Context<'_>, input: RevokeUserPermissionInput) -> Result<MessageResponse> {
         use crate::auth::require_admin;
-        require_admin(ctx, "freshapi").await?;
+        use crate::entities::user_permission;
+        let admin = require_admin(ctx).await?;
+        require_same_organization(ctx, input.user_id).await?;
         
         let user_service = ctx.data::<UserService>()?;
         
-        // Find existing permission
-        let existing = crate::entities::user_permission::Entity::find()
-            .filter(crate::entities::user_permission::Column::UserId.eq(input.user_id))
-            .filter(crate::entities::user_permission::Column::PermissionId.eq(input.permission_id))
-            .one(user_service.get_db())
+        // Find the assignment in effect in this organization, preferring the organization's own
+        let existing = user_permission::Entity::find()
+            .filter(user_permission::Column::UserId.eq(input.user_id))
+            .filter(user_permission::Column::PermissionId.eq(input.permission_id))
+            .filter(
+                Condition::any()
+                    .add(user_permission::Column::OrganizationId.is_null())
+                    .add(user_permission::Column::OrganizationId.eq(admin.organization_id)),
+            )
+            .all(user_service.get_db())
             .await
-            .map_err(|e| Error::new(format!("Database error: {}", e)))?;
+            .map_err(|e| Error::new(format!("Database error: {}", e)))?
+            .into_iter()
+            .max_by_key(|assignment| assignment.organization_id.is_some());
             
         if let Some(existing_perm) = existing {
             if !existing_perm.is_granted {
@@ -1073,13 +1919,15 @@ impl MutationRoot {
                 });
             }
             
-            // Update to revoke permission (set is_granted to false)
-            let mut active_permission: crate::entities::user_permission::ActiveModel = existing_perm.into();
-            active_permission.is_granted = Set(false);
-            active_permission.updated_at = Set(Utc::now().into());
-            
-            active_permission
-                .update(user_service.get_db())
+            // Platform-wide grants are system administrators' to take back
+            if existing_perm.organization_id.is_none() {
+                crate::auth::require_system_admin(ctx).await?;
+            }
+
+            // Revoke permission as a permanent denial, clearing any validity window
+            let permission_service = ctx.data::<PermissionService>()?;
+            permission_service
+                .revoke_user_permission(existing_perm.user_id, existing_perm.permission_id, existing_perm.organization_id)
                 .await
                 .map_err(|e| Error::new(format!("Failed to revoke permission: {}", e)))?;
         } else {
@@ -1091,69 +1939,175 @@ impl MutationRoot {
         })
     }
 
-    // Comment system mutations
-    async fn add_comment(&self, ctx: &Context<'_>, input: AddCommentInput) -> Result<Activity> {
-        let auth_user = ctx.data::<crate::auth::AuthenticatedUser>()?;
-        
-        // Convert GraphQLEntityType to EntityType
-        let entity_type = match input.entity_type {
-            GraphQLEntityType::Task => EntityType::Task,
-            GraphQLEntityType::Project => EntityType::Project,
-            GraphQLEntityType::User => EntityType::User,
-            GraphQLEntityType::Settings => EntityType::Settings,
-        };
+    // Access request mutations
+    async fn request_access(&self, ctx: &Context<'_>, input: RequestAccessInput) -> Result<AccessRequest> {
+        let auth_user = crate::auth::require_auth(ctx)?;
+        let access_request_service = ctx.data::<AccessRequestService>()?;
 
-        // Verify user can access the entity they want to comment on
-        match entity_type {
-            EntityType::Task => {
-                let task_service = ctx.data::<TaskService>()?;
-                let can_access = task_service
-                    .can_user_access_task(input.entity_id, auth_user.id)
-                    .await
-                    .map_err(|e| Error::new(format!("Failed to check task access: {}", e)))?;
-                
-                if !can_access {
-                    return Err(Error::new("You don't have permission to comment on this task"));
-                }
-            },
-            EntityType::Project => {
-                let project_service = ctx.data::<ProjectService>()?;
-                let can_access = project_service
-                    .can_user_access_project(input.entity_id, auth_user.id)
-                    .await
-                    .map_err(|e| Error::new(format!("Failed to check project access: {}", e)))?;
-                
-                if !can_access {
-                    return Err(Error::new("You don't have permission to comment on this project"));
-                }
-            },
-            EntityType::User => {
-                // Users can comment on user profiles if they have user_management permission
-                use crate::auth::require_permission;
-                require_permission(ctx, "freshapi", "user_management").await?;
-            },
-            EntityType::Settings => {
-                // Only admins can comment on settings
-                use crate::auth::require_admin;
-                require_admin(ctx, "freshapi").await?;
-            },
+        let request = access_request_service
+            .request_access(auth_user.id, input.permission_id, input.role_id, &input.reason, input.valid_until)
+            .await
+            .map_err(|e| Error::new(format!("Failed to request access: {}", e)))?;
+
+        Ok(request.into())
+    }
+
+    async fn approve_access_request(&self, ctx: &Context<'_>, input: ReviewAccessRequestInput) -> Result<AccessRequest> {
+        use crate::auth::require_user_can_manage;
+        let reviewer = require_user_management(ctx).await?;
+
+        let access_request_service = ctx.data::<AccessRequestService>()?;
+        let permission_service = ctx.data::<PermissionService>()?;
+
+        let request = access_request_service
+            .get_access_request(input.request_id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to get access request: {}", e)))?
+            .ok_or_else(|| Error::new("Access request not found"))?;
+
+        // Same organization first, so other tenants' requests look missing
+        require_same_organization(ctx, request.user_id).await?;
+        require_user_can_manage(ctx, request.user_id).await?;
+
+        // Same rules as granting the permission or assigning the role directly
+        let mut grant_organization_id = reviewer.organization_id;
+        if let Some(permission_id) = request.permission_id {
+            let permission = crate::entities::permission::Entity::find_by_id(permission_id)
+                .one(access_request_service.get_db())
+                .await
+                .map_err(|e| Error::new(format!("Database error: {}", e)))?
+                .ok_or_else(|| Error::new("Permission not found"))?;
+            if permission.action == AppPermission::SystemAdmin.action() {
+                crate::auth::require_system_admin(ctx).await?;
+                grant_organization_id = None;
+            }
+        }
+        if let Some(role_id) = request.role_id {
+            let role = crate::entities::role::Entity::find_by_id(role_id)
+                .one(access_request_service.get_db())
+                .await
+                .map_err(|e| Error::new(format!("Database error: {}", e)))?
+                .filter(|role| is_role_visible(role, reviewer.organization_id))
+                .ok_or_else(|| Error::new("Role not found"))?;
+            permission_service
+                .ensure_can_assign_role(reviewer.id, reviewer.organization_id, &role)
+                .await
+                .map_err(|e| Error::new(format!("Failed to approve access request: {}", e)))?;
         }
 
-        let activity_service = ctx.data::<ActivityService>()?;
+        let approved = access_request_service
+            .approve_access_request(input.request_id, reviewer.id, grant_organization_id, input.note, input.valid_until)
+            .await
+            .map_err(|e| Error::new(format!("Failed to approve access request: {}", e)))?;
+
+        Ok(approved.into())
+    }
+
+    async fn deny_access_request(&self, ctx: &Context<'_>, input: ReviewAccessRequestInput) -> Result<AccessRequest> {
+        use crate::auth::require_user_can_manage;
+        let reviewer = require_user_management(ctx).await?;
+        let access_request_service = ctx.data::<AccessRequestService>()?;
+
+        let request = access_request_service
+            .get_access_request(input.request_id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to get access request: {}", e)))?
+            .ok_or_else(|| Error::new("Access request not found"))?;
+
+        require_same_organization(ctx, request.user_id).await?;
+        require_user_can_manage(ctx, request.user_id).await?;
+
+        let denied = access_request_service
+            .deny_access_request(input.request_id, reviewer.id, input.note)
+            .await
+            .map_err(|e| Error::new(format!("Failed to deny access request: {}", e)))?;
+
+        Ok(denied.into())
+    }
+
+    async fn cancel_access_request(&self, ctx: &Context<'_>, request_id: Uuid) -> Result<AccessRequest> {
+        let auth_user = crate::auth::require_auth(ctx)?;
+        let access_request_service = ctx.data::<AccessRequestService>()?;
+
+        let cancelled = access_request_service
+            .cancel_access_request(request_id, auth_user.id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to cancel access request: {}", e)))?;
+
+        Ok(cancelled.into())
+    }
+
+    // Comment system mutations
+    async fn add_comment(&self, ctx: &Context<'_>, input: AddCommentInput) -> Result<Comment> {
+        let entity_type: EntityType = input.entity_type.into();
+        let auth_user = require_entity_access(ctx, &entity_type, input.entity_id, "comment on").await?;
 
-        // Add comment through ActivityService
-        let activity = activity_service
+        let comment_service = ctx.data::<CommentService>()?;
+        let comment = comment_service
             .add_comment(
                 entity_type,
                 input.entity_id,
                 auth_user.id,
                 &input.content,
-                input.mentions,
+                input.mentions.unwrap_or_default(),
+                input.parent_comment_id,
             )
             .await
             .map_err(|e| Error::new(format!("Failed to add comment: {}", e)))?;
 
-        Ok(activity.into())
+        Ok(comment.into())
+    }
+
+    /// Edit your own comment; the previous version is kept in its history
+    async fn edit_comment(&self, ctx: &Context<'_>, input: EditCommentInput) -> Result<Comment> {
+        let auth_user = require_comment_access(ctx, input.comment_id).await?;
+        let comment_service = ctx.data::<CommentService>()?;
+
+        let comment = comment_service
+            .edit_comment(input.comment_id, auth_user.id, &input.content, input.mentions)
+            .await
+            .map_err(|e| Error::new(format!("Failed to edit comment: {}", e)))?;
+
+        Ok(comment.into())
+    }
+
+    /// Delete a comment, leaving a placeholder so its replies stay threaded
+    async fn delete_comment(&self, ctx: &Context<'_>, comment_id: Uuid) -> Result<MessageResponse> {
+        let auth_user = require_comment_access(ctx, comment_id).await?;
+        let comment_service = ctx.data::<CommentService>()?;
+
+        comment_service
+            .delete_comment(comment_id, auth_user.id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to delete comment: {}", e)))?;
+
+        Ok(MessageResponse {
+            message: "Comment deleted successfully".to_string(),
+        })
+    }
+
+    async fn add_comment_reaction(&self, ctx: &Context<'_>, input: CommentReactionInput) -> Result<Comment> {
+        let auth_user = require_comment_access(ctx, input.comment_id).await?;
+        let comment_service = ctx.data::<CommentService>()?;
+
+        let comment = comment_service
+            .add_reaction(input.comment_id, auth_user.id, &input.emoji)
+            .await
+            .map_err(|e| Error::new(format!("Failed to add reaction: {}", e)))?;
+
+        Ok(comment.into())
+    }
+
+    async fn remove_comment_reaction(&self, ctx: &Context<'_>, input: CommentReactionInput) -> Result<Comment> {
+        let auth_user = require_comment_access(ctx, input.comment_id).await?;
+        let comment_service = ctx.data::<CommentService>()?;
+
+        let comment = comment_service
+            .remove_reaction(input.comment_id, auth_user.id, &input.emoji)
+            .await
+            .map_err(|e| Error::new(format!("Failed to remove reaction: {}", e)))?;
+
+        Ok(comment.into())
     }
 
     // ============================================================================
@@ -1170,7 +2124,7 @@ impl MutationRoot {
         let authenticated_user = ctx.data::<AuthenticatedUser>()?;
 
         let category = context_service
-            .create_context_category(input, Some(authenticated_user.id))
+            .create_context_category(input, authenticated_user.id)
             .await
             .map_err(|e| Error::new(format!("Failed to create category: {}", e)))?;
 
@@ -1184,10 +2138,10 @@ impl MutationRoot {
         input: crate::graphql::types::UpdateContextCategoryInput,
     ) -> Result<crate::graphql::types::ProjectContextCategory> {
         let context_service = ctx.data::<crate::services::ContextService>()?;
-        let _authenticated_user = ctx.data::<AuthenticatedUser>()?;
+        let authenticated_user = ctx.data::<AuthenticatedUser>()?;
 
         let category = context_service
-            .update_context_category(input)
+            .update_context_category(input, authenticated_user.id)
             .await
             .map_err(|e| Error::new(format!("Failed to update category: {}", e)))?;
 
@@ -1201,10 +2155,10 @@ impl MutationRoot {
         category_id: Uuid,
     ) -> Result<crate::graphql::types::MessageResponse> {
         let context_service = ctx.data::<crate::services::ContextService>()?;
-        let _authenticated_user = ctx.data::<AuthenticatedUser>()?;
+        let authenticated_user = ctx.data::<AuthenticatedUser>()?;
 
         context_service
-            .delete_context_category(category_id)
+            .delete_context_category(category_id, authenticated_user.id)
             .await
             .map_err(|e| Error::new(format!("Failed to delete category: {}", e)))?;
 
@@ -1239,16 +2193,36 @@ impl MutationRoot {
         notes: Option<String>,
     ) -> Result<crate::graphql::types::EmailContext> {
         let email_service = ctx.data::<crate::services::EmailContextService>()?;
-        let _authenticated_user = ctx.data::<AuthenticatedUser>()?;
+        let authenticated_user = ctx.data::<AuthenticatedUser>()?;
 
         let email = email_service
-            .update_processing_status(email_id, status, notes)
+            .update_processing_status(email_id, status, notes, authenticated_user.id)
             .await
             .map_err(|e| Error::new(format!("Failed to update processing status: {}", e)))?;
 
         Ok(email.into())
     }
 
+    /// Reply to an email context; the reply is sent through the outbox and recorded as
+    /// an outbound email in the same thread
+    async fn reply_to_email_context(
+        &self,
+        ctx: &Context<'_>,
+        input: crate::graphql::types::ReplyToEmailContextInput,
+    ) -> Result<crate::graphql::types::EmailContext> {
+        use crate::auth::forbid_impersonation;
+        let authenticated_user = forbid_impersonation(ctx, "Sending email")?;
+
+        let email_service = ctx.data::<crate::services::EmailContextService>()?;
+
+        let email = email_service
+            .reply_to_email_context(input, authenticated_user.id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to reply to email: {}", e)))?;
+
+        Ok(email.into())
+    }
+
     /// Archive a project context
     async fn archive_context(
         &self,
@@ -1256,10 +2230,10 @@ impl MutationRoot {
         context_id: Uuid,
     ) -> Result<crate::graphql::types::ProjectContext> {
         let context_service = ctx.data::<crate::services::ContextService>()?;
-        let _authenticated_user = ctx.data::<AuthenticatedUser>()?;
+        let authenticated_user = ctx.data::<AuthenticatedUser>()?;
 
         let context = context_service
-            .archive_context(context_id)
+            .archive_context(context_id, authenticated_user.id)
             .await
             .map_err(|e| Error::new(format!("Failed to archive context: {}", e)))?;
 
@@ -1273,10 +2247,10 @@ impl MutationRoot {
         context_id: Uuid,
     ) -> Result<crate::graphql::types::ProjectContext> {
         let context_service = ctx.data::<crate::services::ContextService>()?;
-        let _authenticated_user = ctx.data::<AuthenticatedUser>()?;
+        let authenticated_user = ctx.data::<AuthenticatedUser>()?;
 
         let context = context_service
-            .restore_context(context_id)
+            .restore_context(context_id, authenticated_user.id)
             .await
             .map_err(|e| Error::new(format!("Failed to restore context: {}", e)))?;
 
@@ -1299,4 +2273,33 @@ impl MutationRoot {
 
         Ok(task.into())
     }
-}
\ No newline at end of file
+}
+
+/// Surface password policy violations with their rule codes; other failures keep the usual message
+fn password_error(action: &str, e: Box<dyn std::error::Error>) -> Error {
+    match e.downcast_ref::<PasswordPolicyError>() {
+        Some(policy_error) => policy_error.extend(),
+        None => Error::new(format!("{}: {}", action, e)),
+    }
+}
+
+/// Team by id, as a GraphQL error when missing
+async fn load_team(team_service: &TeamService, team_id: Uuid) -> Result<crate::entities::team::Model> {
+    team_service
+        .get_team(team_id)
+        .await
+        .map_err(|e| Error::new(format!("Failed to fetch team: {}", e)))?
+        .ok_or_else(|| Error::new("Team not found"))
+}
+
+/// Guard for acting on a comment: the caller must be able to comment on what it was made on
+async fn require_comment_access<'ctx>(ctx: &'ctx Context<'_>, comment_id: Uuid) -> Result<&'ctx AuthenticatedUser> {
+    let comment_service = ctx.data::<CommentService>()?;
+    let (entity_type, entity_id) = comment_service
+        .comment_target(comment_id)
+        .await
+        .map_err(|e| Error::new(format!("Failed to fetch comment: {}", e)))?
+        .ok_or_else(|| Error::new("Comment not found"))?;
+
+    require_entity_access(ctx, &entity_type, entity_id, "comment on").await
+}
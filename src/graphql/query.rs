@@ -1,13 +1,13 @@
 use async_graphql::*;
-use sea_orm::{EntityTrait, ColumnTrait, QueryFilter, QueryOrder};
+use sea_orm::{EntityTrait, ColumnTrait, Condition, QueryFilter, QueryOrder};
 use uuid::Uuid;
 
-use crate::auth::{AuthenticatedUser, PermissionService, require_admin};
-use crate::graphql::types::{Invitation, User, Role, RoleWithPermissions, Permission, Resource, UserWithRole, Project, Task, TaskStats};
+use crate::auth::{AppPermission, AuthenticatedUser, PermissionGuard, PermissionService, require_admin, require_entity_access, require_organization, require_same_organization};
+use crate::graphql::types::{Invitation, User, Role, RoleWithPermissions, Permission, Resource, UserWithRole, Project, ProjectInvitation, Task, TaskStats, Team};
 use crate::graphql::DataLoaderContext;
-use crate::services::{InvitationService, UserService, ProjectService, TaskService, ActivityService};
+use crate::services::{visible_roles, DigestService, NotificationService, InvitationService, UserService, ProjectService, TaskService, TeamService, ActivityService, AccessRequestService, CommentService};
 use crate::services::activity::EntityType;
-use crate::graphql::types::{TaskStatus, Activity, GraphQLEntityType};
+use crate::graphql::types::{TaskStatus, Activity, GraphQLEntityType, AccessRequest, AccessRequestStatus, PermissionExplanation, EmailOutboxEntry, EmailOutboxStatus, EmailPreview, EmailTemplateKind, NotificationConnection, NotificationPreference, DigestPreferences, WebhookSubscription, WebhookDelivery, WebhookDeliveryConnection, WebhookDeliveryStatus, CommentConnection};
 
 pub struct QueryRoot;
 
@@ -32,7 +32,7 @@ impl QueryRoot {
 
     async fn my_invitations(&self, ctx: &Context<'_>) -> Result<Vec<Invitation>> {
         use crate::auth::require_permission;
-        require_permission(ctx, "freshapi", "invite_users").await?;
+        require_permission(ctx, AppPermission::InviteUsers).await?;
         
         let invitation_service = ctx.data::<InvitationService>()?;
         let authenticated_user = ctx.data::<AuthenticatedUser>()?;
@@ -45,15 +45,45 @@ impl QueryRoot {
         Ok(invitations.into_iter().map(|inv| inv.into()).collect())
     }
 
+    /// Pending invitations to a project, including those waiting on a sign-up
+    #[graphql(guard = "PermissionGuard::new(AppPermission::ProjectRead)")]
+    async fn pending_invitations(&self, ctx: &Context<'_>, project_id: Uuid) -> Result<Vec<ProjectInvitation>> {
+        let invitation_service = ctx.data::<InvitationService>()?;
+        let authenticated_user = ctx.data::<AuthenticatedUser>()?;
+
+        let invitations = invitation_service
+            .get_pending_project_invitations(project_id, authenticated_user.id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to fetch invitations: {}", e)))?;
+
+        Ok(invitations.into_iter().map(|inv| inv.into()).collect())
+    }
+
+    /// Project invitations waiting for your answer
+    async fn my_project_invitations(&self, ctx: &Context<'_>) -> Result<Vec<ProjectInvitation>> {
+        let authenticated_user = crate::auth::require_auth(ctx)?;
+        let invitation_service = ctx.data::<InvitationService>()?;
+
+        let invitations = invitation_service
+            .get_user_project_invitations(authenticated_user.id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to fetch invitations: {}", e)))?;
+
+        Ok(invitations.into_iter().map(|inv| inv.into()).collect())
+    }
+
     // Admin-only queries - OPTIMIZED with DataLoader (automatic batching + caching)
     async fn all_users(&self, ctx: &Context<'_>) -> Result<Vec<UserWithRole>> {
-        require_admin(ctx, "freshapi").await?;
+        require_admin(ctx).await?;
+        let organization_id = require_organization(ctx)?;
         
         let user_service = ctx.data::<UserService>()?;
         let dataloader = ctx.data::<DataLoaderContext>()?;
         
-        // Get all users with their roles
+        // Get the current organization's users with their roles
         let users_with_roles = crate::entities::user::Entity::find()
+            .inner_join(crate::entities::organization_member::Entity)
+            .filter(crate::entities::organization_member::Column::OrganizationId.eq(organization_id))
             .find_also_related(crate::entities::role::Entity)
             .all(user_service.get_db())
             .await
@@ -65,7 +95,7 @@ impl QueryRoot {
         for (user, role_opt) in users_with_roles {
             // DataLoader automatically batches all these calls into single database query - GET ALL PERMISSIONS
             let permissions = dataloader
-                .load_user_all_permissions(user.id)
+                .load_user_all_permissions(user.id, Some(organization_id))
                 .await
                 .map_err(|e| Error::new(format!("Failed to get permissions: {}", e)))?;
                 
@@ -86,12 +116,13 @@ impl QueryRoot {
     }
 
     async fn all_roles(&self, ctx: &Context<'_>) -> Result<Vec<Role>> {
-        require_admin(ctx, "freshapi").await?;
+        let auth_user = require_admin(ctx).await?;
         
         let user_service = ctx.data::<UserService>()?;
         
         let roles = crate::entities::role::Entity::find()
             .filter(crate::entities::role::Column::IsActive.eq(true))
+            .filter(visible_roles(auth_user.organization_id))
             .all(user_service.get_db())
             .await
             .map_err(|e| Error::new(format!("Failed to fetch roles: {}", e)))?;
@@ -99,13 +130,232 @@ impl QueryRoot {
         Ok(roles.into_iter().map(|role| role.into()).collect())
     }
 
+    async fn access_requests(
+        &self,
+        ctx: &Context<'_>,
+        status: Option<AccessRequestStatus>,
+        limit: Option<i32>,
+        offset: Option<i32>,
+    ) -> Result<Vec<AccessRequest>> {
+        use crate::auth::require_user_management;
+        require_user_management(ctx).await?;
+        let organization_id = require_organization(ctx)?;
+
+        let access_request_service = ctx.data::<AccessRequestService>()?;
+
+        let requests = access_request_service
+            .get_access_requests(organization_id, status, limit.map(|l| l.max(0) as u64), offset.map(|o| o.max(0) as u64))
+            .await
+            .map_err(|e| Error::new(format!("Failed to get access requests: {}", e)))?;
+
+        Ok(requests.into_iter().map(|request| request.into()).collect())
+    }
+
+    /// Outbound emails, newest first, for checking on delivery
+    async fn email_outbox(
+        &self,
+        ctx: &Context<'_>,
+        status: Option<EmailOutboxStatus>,
+        limit: Option<i32>,
+        offset: Option<i32>,
+    ) -> Result<Vec<EmailOutboxEntry>> {
+        use crate::auth::require_system_admin;
+        require_system_admin(ctx).await?;
+
+        let email_outbox_service = ctx.data::<crate::services::EmailOutboxService>()?;
+
+        let emails = email_outbox_service
+            .list(status, limit.map(|l| l.max(0) as u64), offset.map(|o| o.max(0) as u64))
+            .await
+            .map_err(|e| Error::new(format!("Failed to get email outbox: {}", e)))?;
+
+        Ok(emails.into_iter().map(|email| email.into()).collect())
+    }
+
+    /// Render an email template with sample data, branded for the current organization.
+    /// Without a locale the organization's default is used.
+    async fn preview_email_template(
+        &self,
+        ctx: &Context<'_>,
+        template: EmailTemplateKind,
+        locale: Option<String>,
+    ) -> Result<EmailPreview> {
+        let auth_user = require_admin(ctx).await?;
+
+        let db = ctx.data::<sea_orm::DatabaseConnection>()?;
+        let email_service = ctx.data::<crate::services::EmailService>()?;
+
+        let style = crate::services::email_style(db, auth_user.organization_id, locale.as_deref())
+            .await
+            .map_err(|e| Error::new(format!("Failed to load email branding: {}", e)))?;
+        let message = email_service
+            .preview(template, &auth_user.email, &style)
+            .map_err(|e| Error::new(format!("Failed to render email template: {}", e)))?;
+
+        Ok(EmailPreview {
+            template,
+            locale: email_service.resolve_locale(&style.locale),
+            from_name: message.from_name,
+            subject: message.subject,
+            html: message.html_content,
+            text: message.text_content,
+        })
+    }
+
+    async fn my_access_requests(&self, ctx: &Context<'_>) -> Result<Vec<AccessRequest>> {
+        let auth_user = crate::auth::require_auth(ctx)?;
+        let access_request_service = ctx.data::<AccessRequestService>()?;
+
+        let requests = access_request_service
+            .get_user_access_requests(auth_user.id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to get access requests: {}", e)))?;
+
+        Ok(requests.into_iter().map(|request| request.into()).collect())
+    }
+
+    /// Your notifications, newest first
+    async fn my_notifications(
+        &self,
+        ctx: &Context<'_>,
+        unread_only: Option<bool>,
+        limit: Option<i32>,
+        offset: Option<i32>,
+    ) -> Result<NotificationConnection> {
+        let auth_user = crate::auth::require_auth(ctx)?;
+        let notification_service = ctx.data::<NotificationService>()?;
+
+        notification_service
+            .list_for_user(
+                auth_user.id,
+                unread_only.unwrap_or(false),
+                limit.map(|l| l.max(0) as u64),
+                offset.map(|o| o.max(0) as u64),
+            )
+            .await
+            .map_err(|e| Error::new(format!("Failed to fetch notifications: {}", e)))
+    }
+
+    /// Which notification types you receive
+    async fn my_notification_preferences(&self, ctx: &Context<'_>) -> Result<Vec<NotificationPreference>> {
+        let auth_user = crate::auth::require_auth(ctx)?;
+        let notification_service = ctx.data::<NotificationService>()?;
+
+        notification_service
+            .preferences(auth_user.id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to fetch notification preferences: {}", e)))
+    }
+
+    /// When and how often your email digest is sent
+    async fn my_digest_preferences(&self, ctx: &Context<'_>) -> Result<DigestPreferences> {
+        let auth_user = crate::auth::require_auth(ctx)?;
+        let digest_service = ctx.data::<DigestService>()?;
+
+        digest_service
+            .get_preferences(auth_user.id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to fetch digest preferences: {}", e)))
+    }
+
+    /// Webhook subscriptions of the current organization
+    async fn webhook_subscriptions(&self, ctx: &Context<'_>) -> Result<Vec<WebhookSubscription>> {
+        use crate::auth::{require_admin, require_organization};
+        require_admin(ctx).await?;
+        let organization_id = require_organization(ctx)?;
+
+        let webhook_service = ctx.data::<crate::services::WebhookService>()?;
+
+        let subscriptions = webhook_service
+            .list_subscriptions(organization_id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to fetch webhook subscriptions: {}", e)))?;
+
+        Ok(subscriptions.into_iter().map(|subscription| subscription.into()).collect())
+    }
+
+    /// Webhook deliveries of the current organization, newest first
+    async fn webhook_deliveries(
+        &self,
+        ctx: &Context<'_>,
+        subscription_id: Option<Uuid>,
+        status: Option<WebhookDeliveryStatus>,
+        limit: Option<i32>,
+        offset: Option<i32>,
+    ) -> Result<WebhookDeliveryConnection> {
+        use crate::auth::{require_admin, require_organization};
+        require_admin(ctx).await?;
+        let organization_id = require_organization(ctx)?;
+
+        let webhook_service = ctx.data::<crate::services::WebhookService>()?;
+
+        webhook_service
+            .list_deliveries(
+                organization_id,
+                subscription_id,
+                status,
+                limit.map(|l| l.max(0) as u64),
+                offset.map(|o| o.max(0) as u64),
+            )
+            .await
+            .map_err(|e| Error::new(format!("Failed to fetch webhook deliveries: {}", e)))
+    }
+
+    async fn webhook_delivery(&self, ctx: &Context<'_>, id: Uuid) -> Result<Option<WebhookDelivery>> {
+        use crate::auth::{require_admin, require_organization};
+        require_admin(ctx).await?;
+        let organization_id = require_organization(ctx)?;
+
+        let webhook_service = ctx.data::<crate::services::WebhookService>()?;
+
+        let delivery = webhook_service
+            .get_delivery(organization_id, id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to fetch webhook delivery: {}", e)))?;
+
+        Ok(delivery.map(|delivery| delivery.into()))
+    }
+
+    /// Why a user does or doesn't hold `resource:action`. Users may explain their own
+    /// permissions; explaining someone else's requires user management.
+    async fn explain_permission(
+        &self,
+        ctx: &Context<'_>,
+        user_id: Uuid,
+        resource: String,
+        action: String,
+    ) -> Result<PermissionExplanation> {
+        let auth_user = crate::auth::require_auth(ctx)?;
+        if auth_user.id != user_id {
+            use crate::auth::require_user_management;
+            require_user_management(ctx).await?;
+            require_same_organization(ctx, user_id).await?;
+        }
+
+        let permission_service = ctx.data::<PermissionService>()?;
+
+        let explanation = permission_service
+            .explain_permission(user_id, auth_user.organization_id, &resource, &action)
+            .await
+            .map_err(|e| Error::new(format!("Failed to explain permission: {}", e)))?;
+
+        Ok(PermissionExplanation {
+            user_id,
+            resource,
+            action,
+            allowed: explanation.allowed,
+            rules: explanation.rules.into_iter().map(|rule| rule.into()).collect(),
+        })
+    }
+
     async fn user_permissions(&self, ctx: &Context<'_>, user_id: uuid::Uuid) -> Result<Vec<String>> {
-        require_admin(ctx, "freshapi").await?;
+        let admin = require_admin(ctx).await?;
+        require_same_organization(ctx, user_id).await?;
         
         let permission_service = ctx.data::<PermissionService>()?;
         
         let permissions = permission_service
-            .get_user_all_permissions(user_id)
+            .get_user_all_permissions(user_id, admin.organization_id)
             .await
             .map_err(|e| Error::new(format!("Failed to get permissions: {}", e)))?;
 
@@ -113,7 +363,8 @@ impl QueryRoot {
     }
 
     async fn user_by_id(&self, ctx: &Context<'_>, user_id: uuid::Uuid) -> Result<UserWithRole> {
-        require_admin(ctx, "freshapi").await?;
+        let admin = require_admin(ctx).await?;
+        require_same_organization(ctx, user_id).await?;
         
         let user_service = ctx.data::<UserService>()?;
         let dataloader = ctx.data::<DataLoaderContext>()?;
@@ -128,7 +379,7 @@ impl QueryRoot {
 
         // Use DataLoader for caching (beneficial if called multiple times) - GET ALL PERMISSIONS
         let permissions = dataloader
-            .load_user_all_permissions(user.id)
+            .load_user_all_permissions(user.id, admin.organization_id)
             .await
             .map_err(|e| Error::new(format!("Failed to get permissions: {}", e)))?;
 
@@ -146,7 +397,8 @@ impl QueryRoot {
     }
 
     async fn users_by_role(&self, ctx: &Context<'_>, role_name: String) -> Result<Vec<UserWithRole>> {
-        require_admin(ctx, "freshapi").await?;
+        require_admin(ctx).await?;
+        let organization_id = require_organization(ctx)?;
         
         let user_service = ctx.data::<UserService>()?;
         let dataloader = ctx.data::<DataLoaderContext>()?;
@@ -155,14 +407,17 @@ impl QueryRoot {
         let role = crate::entities::role::Entity::find()
             .filter(crate::entities::role::Column::Name.eq(&role_name))
             .filter(crate::entities::role::Column::IsActive.eq(true))
+            .filter(visible_roles(Some(organization_id)))
             .one(user_service.get_db())
             .await
             .map_err(|e| Error::new(format!("Failed to find role: {}", e)))?
             .ok_or_else(|| Error::new("Role not found"))?;
 
-        // Get users with this role
+        // Get the current organization's users with this role
         let users_with_roles = crate::entities::user::Entity::find()
             .filter(crate::entities::user::Column::RoleId.eq(role.id))
+            .inner_join(crate::entities::organization_member::Entity)
+            .filter(crate::entities::organization_member::Column::OrganizationId.eq(organization_id))
             .find_also_related(crate::entities::role::Entity)
             .all(user_service.get_db())
             .await
@@ -174,7 +429,7 @@ impl QueryRoot {
         for (user, role_opt) in users_with_roles {
             // DataLoader automatically batches all these calls into single database query - GET ALL PERMISSIONS
             let permissions = dataloader
-                .load_user_all_permissions(user.id)
+                .load_user_all_permissions(user.id, Some(organization_id))
                 .await
                 .map_err(|e| Error::new(format!("Failed to get permissions: {}", e)))?;
                 
@@ -194,17 +449,56 @@ impl QueryRoot {
         Ok(result)
     }
 
+    // Team queries
+    /// Teams of the current organization
+    async fn teams(&self, ctx: &Context<'_>) -> Result<Vec<Team>> {
+        let organization_id = require_organization(ctx)?;
+        let team_service = ctx.data::<TeamService>()?;
+
+        let teams = team_service
+            .get_organization_teams(organization_id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to fetch teams: {}", e)))?;
+
+        Ok(teams.into_iter().map(|t| t.into()).collect())
+    }
+
+    async fn team(&self, ctx: &Context<'_>, team_id: Uuid) -> Result<Option<Team>> {
+        let organization_id = require_organization(ctx)?;
+        let team_service = ctx.data::<TeamService>()?;
+
+        let team = team_service
+            .get_team(team_id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to fetch team: {}", e)))?;
+
+        Ok(team.filter(|t| t.organization_id == organization_id).map(|t| t.into()))
+    }
+
+    /// Unclaimed open tasks queued for one of your teams
+    #[graphql(guard = "PermissionGuard::new(AppPermission::TaskRead)")]
+    async fn team_task_queue(&self, ctx: &Context<'_>, team_id: Uuid) -> Result<Vec<Task>> {
+        let task_service = ctx.data::<TaskService>()?;
+        let authenticated_user = ctx.data::<AuthenticatedUser>()?;
+
+        let tasks = task_service
+            .get_team_queue(team_id, authenticated_user.id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to fetch team queue: {}", e)))?;
+
+        Ok(tasks.into_iter().map(|t| t.into()).collect())
+    }
+
     // Project queries
+    #[graphql(guard = "PermissionGuard::new(AppPermission::ProjectRead)")]
     async fn my_projects(&self, ctx: &Context<'_>, limit: Option<i32>, offset: Option<i32>) -> Result<Vec<Project>> {
-        use crate::auth::require_permission;
-        require_permission(ctx, "task_system", "project_read").await?;
-        
         let project_service = ctx.data::<ProjectService>()?;
         let authenticated_user = ctx.data::<AuthenticatedUser>()?;
         
         let projects = project_service
             .get_user_projects(
                 authenticated_user.id,
+                authenticated_user.organization_id,
                 limit.map(|l| l.max(0) as u64),
                 offset.map(|o| o.max(0) as u64),
             )
@@ -214,10 +508,8 @@ impl QueryRoot {
         Ok(projects.into_iter().map(|p| p.into()).collect())
     }
 
+    #[graphql(guard = "PermissionGuard::new(AppPermission::ProjectRead)")]
     async fn project(&self, ctx: &Context<'_>, project_id: uuid::Uuid) -> Result<Option<Project>> {
-        use crate::auth::require_permission;
-        require_permission(ctx, "task_system", "project_read").await?;
-        
         let project_service = ctx.data::<ProjectService>()?;
         let authenticated_user = ctx.data::<AuthenticatedUser>()?;
         
@@ -229,11 +521,35 @@ impl QueryRoot {
         Ok(project.map(|p| p.into()))
     }
 
+    /// Permissions that can be granted through project roles
+    #[graphql(guard = "PermissionGuard::new(AppPermission::ProjectRead)")]
+    async fn project_permissions(&self, ctx: &Context<'_>) -> Result<Vec<crate::graphql::types::Permission>> {
+        let project_service = ctx.data::<ProjectService>()?;
+
+        let permissions = project_service
+            .get_project_permissions()
+            .await
+            .map_err(|e| Error::new(format!("Failed to fetch project permissions: {}", e)))?;
+
+        Ok(permissions.into_iter().map(Into::into).collect())
+    }
+
+    /// Whether the current user may perform `action` (e.g. `task:create`) on a project
+    async fn can_perform_project_action(&self, ctx: &Context<'_>, project_id: uuid::Uuid, action: String) -> Result<bool> {
+        let project_service = ctx.data::<ProjectService>()?;
+        let authenticated_user = ctx.data::<AuthenticatedUser>()?;
+
+        let action: crate::services::ProjectAction = action.parse().map_err(Error::new)?;
+
+        project_service
+            .can_user_perform(project_id, authenticated_user.id, action)
+            .await
+            .map_err(|e| Error::new(format!("Failed to check project permission: {}", e)))
+    }
+
     // Task queries
+    #[graphql(guard = "PermissionGuard::new(AppPermission::TaskRead)")]
     async fn task(&self, ctx: &Context<'_>, task_id: uuid::Uuid) -> Result<Option<Task>> {
-        use crate::auth::require_permission;
-        require_permission(ctx, "task_system", "task_read").await?;
-        
         let task_service = ctx.data::<TaskService>()?;
         let authenticated_user = ctx.data::<AuthenticatedUser>()?;
         
@@ -245,10 +561,8 @@ impl QueryRoot {
         Ok(task.map(|t| t.into()))
     }
 
+    #[graphql(guard = "PermissionGuard::new(AppPermission::TaskRead)")]
     async fn my_assigned_tasks(&self, ctx: &Context<'_>, status: Option<TaskStatus>, limit: Option<i32>, offset: Option<i32>) -> Result<Vec<Task>> {
-        use crate::auth::require_permission;
-        require_permission(ctx, "task_system", "task_read").await?;
-        
         let task_service = ctx.data::<TaskService>()?;
         let authenticated_user = ctx.data::<AuthenticatedUser>()?;
         
@@ -267,10 +581,8 @@ impl QueryRoot {
         Ok(tasks.into_iter().map(|t| t.into()).collect())
     }
 
+    #[graphql(guard = "PermissionGuard::new(AppPermission::TaskRead)")]
     async fn project_tasks(&self, ctx: &Context<'_>, project_id: uuid::Uuid, status: Option<TaskStatus>, assignee_id: Option<uuid::Uuid>, limit: Option<i32>, offset: Option<i32>) -> Result<Vec<Task>> {
-        use crate::auth::require_permission;
-        require_permission(ctx, "task_system", "task_read").await?;
-        
         let task_service = ctx.data::<TaskService>()?;
         let authenticated_user = ctx.data::<AuthenticatedUser>()?;
         
@@ -291,10 +603,8 @@ impl QueryRoot {
         Ok(tasks.into_iter().map(|t| t.into()).collect())
     }
 
+    #[graphql(guard = "PermissionGuard::new(AppPermission::TaskRead)")]
     async fn project_task_stats(&self, ctx: &Context<'_>, project_id: uuid::Uuid) -> Result<TaskStats> {
-        use crate::auth::require_permission;
-        require_permission(ctx, "task_system", "task_read").await?;
-        
         let task_service = ctx.data::<TaskService>()?;
         let authenticated_user = ctx.data::<AuthenticatedUser>()?;
         
@@ -315,12 +625,13 @@ impl QueryRoot {
 
     // RBAC CRUD Queries - Admin only
     async fn all_roles_with_permissions(&self, ctx: &Context<'_>) -> Result<Vec<RoleWithPermissions>> {
-        require_admin(ctx, "freshapi").await?;
+        let auth_user = require_admin(ctx).await?;
         
         let user_service = ctx.data::<UserService>()?;
         
         let roles = crate::entities::role::Entity::find()
             .filter(crate::entities::role::Column::IsActive.eq(true))
+            .filter(visible_roles(auth_user.organization_id))
             .order_by_asc(crate::entities::role::Column::Level)
             .all(user_service.get_db())
             .await
@@ -330,11 +641,12 @@ impl QueryRoot {
     }
 
     async fn role_by_id(&self, ctx: &Context<'_>, role_id: uuid::Uuid) -> Result<Option<RoleWithPermissions>> {
-        require_admin(ctx, "freshapi").await?;
+        let auth_user = require_admin(ctx).await?;
         
         let user_service = ctx.data::<UserService>()?;
         
         let role = crate::entities::role::Entity::find_by_id(role_id)
+            .filter(visible_roles(auth_user.organization_id))
             .one(user_service.get_db())
             .await
             .map_err(|e| Error::new(format!("Failed to fetch role: {}", e)))?;
@@ -343,7 +655,7 @@ impl QueryRoot {
     }
 
     async fn all_permissions(&self, ctx: &Context<'_>, resource_id: Option<uuid::Uuid>) -> Result<Vec<Permission>> {
-        require_admin(ctx, "freshapi").await?;
+        require_admin(ctx).await?;
         
         let user_service = ctx.data::<UserService>()?;
         
@@ -364,7 +676,7 @@ impl QueryRoot {
     }
 
     async fn permission_by_id(&self, ctx: &Context<'_>, permission_id: uuid::Uuid) -> Result<Option<Permission>> {
-        require_admin(ctx, "freshapi").await?;
+        require_admin(ctx).await?;
         
         let user_service = ctx.data::<UserService>()?;
         
@@ -377,7 +689,7 @@ impl QueryRoot {
     }
 
     async fn all_resources(&self, ctx: &Context<'_>) -> Result<Vec<Resource>> {
-        require_admin(ctx, "freshapi").await?;
+        require_admin(ctx).await?;
         
         let user_service = ctx.data::<UserService>()?;
         
@@ -392,7 +704,7 @@ impl QueryRoot {
     }
 
     async fn resource_by_id(&self, ctx: &Context<'_>, resource_id: uuid::Uuid) -> Result<Option<Resource>> {
-        require_admin(ctx, "freshapi").await?;
+        require_admin(ctx).await?;
         
         let user_service = ctx.data::<UserService>()?;
         
@@ -405,9 +717,18 @@ impl QueryRoot {
     }
 
     async fn role_permissions(&self, ctx: &Context<'_>, role_id: uuid::Uuid) -> Result<Vec<Permission>> {
-        require_admin(ctx, "freshapi").await?;
+        let auth_user = require_admin(ctx).await?;
         
         let user_service = ctx.data::<UserService>()?;
+
+        let role = crate::entities::role::Entity::find_by_id(role_id)
+            .filter(visible_roles(auth_user.organization_id))
+            .one(user_service.get_db())
+            .await
+            .map_err(|e| Error::new(format!("Failed to fetch role: {}", e)))?;
+        if role.is_none() {
+            return Err(Error::new("Role not found"));
+        }
         
         use sea_orm::{JoinType, QuerySelect, RelationTrait};
         
@@ -431,7 +752,8 @@ impl QueryRoot {
     }
 
     async fn user_direct_permissions(&self, ctx: &Context<'_>, user_id: uuid::Uuid) -> Result<Vec<Permission>> {
-        require_admin(ctx, "freshapi").await?;
+        let admin = require_admin(ctx).await?;
+        require_same_organization(ctx, user_id).await?;
         
         let user_service = ctx.data::<UserService>()?;
         
@@ -440,6 +762,12 @@ impl QueryRoot {
         let permissions = crate::entities::user_permission::Entity::find()
             .filter(crate::entities::user_permission::Column::UserId.eq(user_id))
             .filter(crate::entities::user_permission::Column::IsGranted.eq(true))
+            // Grants made in other organizations don't apply here
+            .filter(
+                Condition::any()
+                    .add(crate::entities::user_permission::Column::OrganizationId.is_null())
+                    .add(crate::entities::user_permission::Column::OrganizationId.eq(admin.organization_id)),
+            )
             .join(JoinType::InnerJoin, crate::entities::user_permission::Relation::Permission.def())
             .select_only()
             .column_as(crate::entities::permission::Column::Id, "id")
@@ -466,51 +794,8 @@ impl QueryRoot {
         limit: Option<i32>,
         offset: Option<i32>
     ) -> Result<Vec<Activity>> {
-        let auth_user = ctx.data::<AuthenticatedUser>()?;
-
-        // Convert GraphQLEntityType to EntityType for ActivityService
-        let entity_type_enum = match entity_type {
-            GraphQLEntityType::Task => EntityType::Task,
-            GraphQLEntityType::Project => EntityType::Project,
-            GraphQLEntityType::User => EntityType::User,
-            GraphQLEntityType::Settings => EntityType::Settings,
-        };
-
-        // Verify user can access the entity they want to view activities for
-        match entity_type_enum {
-            EntityType::Task => {
-                let task_service = ctx.data::<TaskService>()?;
-                let can_access = task_service
-                    .can_user_access_task(entity_id, auth_user.id)
-                    .await
-                    .map_err(|e| Error::new(format!("Failed to check task access: {}", e)))?;
-                
-                if !can_access {
-                    return Err(Error::new("You don't have permission to view activities for this task"));
-                }
-            },
-            EntityType::Project => {
-                let project_service = ctx.data::<ProjectService>()?;
-                let can_access = project_service
-                    .can_user_access_project(entity_id, auth_user.id)
-                    .await
-                    .map_err(|e| Error::new(format!("Failed to check project access: {}", e)))?;
-                
-                if !can_access {
-                    return Err(Error::new("You don't have permission to view activities for this project"));
-                }
-            },
-            EntityType::User => {
-                // Users can view activities on user profiles if they have user_management permission
-                use crate::auth::require_permission;
-                require_permission(ctx, "freshapi", "user_management").await?;
-            },
-            EntityType::Settings => {
-                // Only admins can view activities on settings
-                use crate::auth::require_admin;
-                require_admin(ctx, "freshapi").await?;
-            },
-        }
+        let entity_type_enum: EntityType = entity_type.into();
+        require_entity_access(ctx, &entity_type_enum, entity_id, "view activities for").await?;
 
         let activity_service = ctx.data::<ActivityService>()?;
 
@@ -527,6 +812,30 @@ impl QueryRoot {
         Ok(activities.into_iter().map(|a| a.into()).collect())
     }
 
+    /// Top-level comments on an entity, oldest first; replies hang off each comment
+    async fn comments(
+        &self,
+        ctx: &Context<'_>,
+        entity_type: GraphQLEntityType,
+        entity_id: Uuid,
+        limit: Option<i32>,
+        offset: Option<i32>,
+    ) -> Result<CommentConnection> {
+        let entity_type: EntityType = entity_type.into();
+        require_entity_access(ctx, &entity_type, entity_id, "view comments on").await?;
+
+        let comment_service = ctx.data::<CommentService>()?;
+        comment_service
+            .list_comments(
+                entity_type,
+                entity_id,
+                limit.map(|l| l.max(0) as u64),
+                offset.map(|o| o.max(0) as u64),
+            )
+            .await
+            .map_err(|e| Error::new(format!("Failed to fetch comments: {}", e)))
+    }
+
     // ============================================================================
     // ProjectMind Context System Queries
     // ============================================================================
@@ -551,10 +860,10 @@ impl QueryRoot {
         context_type_name: Option<String>,
     ) -> Result<Vec<crate::graphql::types::ProjectContextCategory>> {
         let context_service = ctx.data::<crate::services::ContextService>()?;
-        let _authenticated_user = ctx.data::<AuthenticatedUser>()?;
+        let authenticated_user = ctx.data::<AuthenticatedUser>()?;
         
         let categories = context_service
-            .get_project_categories(project_id, context_type_name)
+            .get_project_categories(project_id, context_type_name, authenticated_user.id)
             .await
             .map_err(|e| Error::new(format!("Failed to fetch categories: {}", e)))?;
 
@@ -571,7 +880,7 @@ impl QueryRoot {
         offset: Option<i32>,
     ) -> Result<crate::graphql::types::ContextConnection> {
         let context_service = ctx.data::<crate::services::ContextService>()?;
-        let _authenticated_user = ctx.data::<AuthenticatedUser>()?;
+        let authenticated_user = ctx.data::<AuthenticatedUser>()?;
         
         let result = context_service
             .get_project_contexts(
@@ -579,6 +888,7 @@ impl QueryRoot {
                 filters,
                 limit.map(|l| l.max(0) as u64),
                 offset.map(|o| o.max(0) as u64),
+                authenticated_user.id,
             )
             .await
             .map_err(|e| Error::new(format!("Failed to fetch project contexts: {}", e)))?;
@@ -596,7 +906,7 @@ impl QueryRoot {
         offset: Option<i32>,
     ) -> Result<crate::graphql::types::EmailContextConnection> {
         let email_service = ctx.data::<crate::services::EmailContextService>()?;
-        let _authenticated_user = ctx.data::<AuthenticatedUser>()?;
+        let authenticated_user = ctx.data::<AuthenticatedUser>()?;
         
         let result = email_service
             .get_email_contexts(
@@ -604,6 +914,7 @@ impl QueryRoot {
                 filters,
                 limit.map(|l| l.max(0) as u64),
                 offset.map(|o| o.max(0) as u64),
+                authenticated_user.id,
             )
             .await
             .map_err(|e| Error::new(format!("Failed to fetch email contexts: {}", e)))?;
@@ -614,10 +925,10 @@ impl QueryRoot {
     /// Get single email context by ID
     async fn email_context(&self, ctx: &Context<'_>, email_id: Uuid) -> Result<Option<crate::graphql::types::EmailContext>> {
         let email_service = ctx.data::<crate::services::EmailContextService>()?;
-        let _authenticated_user = ctx.data::<AuthenticatedUser>()?;
+        let authenticated_user = ctx.data::<AuthenticatedUser>()?;
         
         let email = email_service
-            .get_email_context_by_id(email_id)
+            .get_email_context_by_id(email_id, authenticated_user.id)
             .await
             .map_err(|e| Error::new(format!("Failed to fetch email context: {}", e)))?;
 
@@ -633,13 +944,14 @@ impl QueryRoot {
         limit: Option<i32>,
     ) -> Result<Vec<crate::graphql::types::EmailContext>> {
         let email_service = ctx.data::<crate::services::EmailContextService>()?;
-        let _authenticated_user = ctx.data::<AuthenticatedUser>()?;
+        let authenticated_user = ctx.data::<AuthenticatedUser>()?;
         
         let results = email_service
             .search_emails(
                 project_id,
                 &query,
                 limit.map(|l| l.max(0) as u64),
+                authenticated_user.id,
             )
             .await
             .map_err(|e| Error::new(format!("Failed to search email contexts: {}", e)))?;
@@ -655,10 +967,10 @@ impl QueryRoot {
         thread_id: String,
     ) -> Result<Vec<crate::graphql::types::EmailContext>> {
         let email_service = ctx.data::<crate::services::EmailContextService>()?;
-        let _authenticated_user = ctx.data::<AuthenticatedUser>()?;
+        let authenticated_user = ctx.data::<AuthenticatedUser>()?;
         
         let emails = email_service
-            .get_email_thread(&thread_id, project_id)
+            .get_email_thread(&thread_id, project_id, authenticated_user.id)
             .await
             .map_err(|e| Error::new(format!("Failed to fetch email thread: {}", e)))?;
 
@@ -666,10 +978,8 @@ impl QueryRoot {
     }
 
     // Context-Task relationship queries
+    #[graphql(guard = "PermissionGuard::new(AppPermission::TaskRead)")]
     async fn task_by_context(&self, ctx: &Context<'_>, context_id: Uuid) -> Result<Option<Task>> {
-        use crate::auth::require_permission;
-        require_permission(ctx, "task_system", "task_read").await?;
-        
         let db = ctx.data::<sea_orm::DatabaseConnection>()?;
         
         let task = crate::entities::task::Entity::find()
@@ -681,10 +991,8 @@ impl QueryRoot {
         Ok(task.map(|t| t.into()))
     }
 
+    #[graphql(guard = "PermissionGuard::new(AppPermission::TaskRead)")]
     async fn context_by_task(&self, ctx: &Context<'_>, task_id: Uuid) -> Result<Option<crate::graphql::types::ProjectContext>> {
-        use crate::auth::require_permission;
-        require_permission(ctx, "task_system", "task_read").await?;
-        
         let db = ctx.data::<sea_orm::DatabaseConnection>()?;
         
         // First get the task to find its context_id
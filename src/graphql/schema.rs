@@ -1,12 +1,13 @@
 use async_graphql::{EmptySubscription, Schema};
 use std::env;
 
-use crate::graphql::{MutationRoot, QueryRoot};
+use crate::graphql::{ImpersonationAudit, MutationRoot, QueryRoot};
 
 pub type ApiSchema = Schema<QueryRoot, MutationRoot, EmptySubscription>;
 
 pub fn create_schema() -> ApiSchema {
-    let mut schema_builder = Schema::build(QueryRoot, MutationRoot, EmptySubscription);
+    let mut schema_builder = Schema::build(QueryRoot, MutationRoot, EmptySubscription)
+        .extension(ImpersonationAudit);
     
     // Disable introspection in production for security
     let environment = env::var("ENVIRONMENT").unwrap_or_else(|_| "production".to_string());
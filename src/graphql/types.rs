@@ -188,6 +188,62 @@ impl AccountingProcess {
     }
 }
 
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug, DeriveActiveEnum, Serialize, Deserialize, EnumIter)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "email_direction")]
+#[graphql(name = "EmailDirection")]
+pub enum EmailDirection {
+    /// Received and ingested
+    #[graphql(name = "INBOUND")]
+    #[sea_orm(string_value = "inbound")]
+    Inbound,
+    /// Sent from the app, e.g. a reply
+    #[graphql(name = "OUTBOUND")]
+    #[sea_orm(string_value = "outbound")]
+    Outbound,
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug, DeriveActiveEnum, Serialize, Deserialize, EnumIter)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "digest_frequency")]
+#[graphql(name = "DigestFrequency")]
+pub enum DigestFrequency {
+    #[graphql(name = "OFF")]
+    #[sea_orm(string_value = "off")]
+    Off,
+    #[graphql(name = "DAILY")]
+    #[sea_orm(string_value = "daily")]
+    Daily,
+    /// Sent on Mondays
+    #[graphql(name = "WEEKLY")]
+    #[sea_orm(string_value = "weekly")]
+    Weekly,
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug, DeriveActiveEnum, Serialize, Deserialize, EnumIter)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "notification_type")]
+#[graphql(name = "NotificationType")]
+pub enum NotificationType {
+    /// A task was assigned to you
+    #[graphql(name = "TASK_ASSIGNED")]
+    #[sea_orm(string_value = "task_assigned")]
+    TaskAssigned,
+    /// Someone mentioned you in a comment
+    #[graphql(name = "MENTIONED")]
+    #[sea_orm(string_value = "mentioned")]
+    Mentioned,
+    /// A task you watch was changed or commented on
+    #[graphql(name = "WATCHED_TASK_CHANGED")]
+    #[sea_orm(string_value = "watched_task_changed")]
+    WatchedTaskChanged,
+    /// One of your tasks is due soon
+    #[graphql(name = "TASK_DUE_SOON")]
+    #[sea_orm(string_value = "task_due_soon")]
+    TaskDueSoon,
+    /// One of your tasks is past its due date
+    #[graphql(name = "TASK_OVERDUE")]
+    #[sea_orm(string_value = "task_overdue")]
+    TaskOverdue,
+}
+
 #[derive(Enum, Copy, Clone, Eq, PartialEq, Debug, Serialize, Deserialize)]
 #[graphql(name = "ProcessingStatus")]
 pub enum ProcessingStatus {
@@ -222,6 +278,141 @@ impl ProcessingStatus {
     }
 }
 
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug, DeriveActiveEnum, Serialize, Deserialize, EnumIter)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "user_status")]
+#[graphql(name = "UserStatus")]
+pub enum UserStatus {
+    #[graphql(name = "ACTIVE")]
+    #[sea_orm(string_value = "active")]
+    Active,
+    #[graphql(name = "SUSPENDED")]
+    #[sea_orm(string_value = "suspended")]
+    Suspended,
+    #[graphql(name = "DEACTIVATED")]
+    #[sea_orm(string_value = "deactivated")]
+    Deactivated,
+}
+
+impl UserStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UserStatus::Active => "active",
+            UserStatus::Suspended => "suspended",
+            UserStatus::Deactivated => "deactivated",
+        }
+    }
+}
+
+impl std::str::FromStr for UserStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "active" => Ok(UserStatus::Active),
+            "suspended" => Ok(UserStatus::Suspended),
+            "deactivated" => Ok(UserStatus::Deactivated),
+            _ => Err(format!("Unknown user status '{}'", s)),
+        }
+    }
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug, DeriveActiveEnum, Serialize, Deserialize, EnumIter)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "access_request_status")]
+#[graphql(name = "AccessRequestStatus")]
+pub enum AccessRequestStatus {
+    #[graphql(name = "PENDING")]
+    #[sea_orm(string_value = "pending")]
+    Pending,
+    #[graphql(name = "APPROVED")]
+    #[sea_orm(string_value = "approved")]
+    Approved,
+    #[graphql(name = "DENIED")]
+    #[sea_orm(string_value = "denied")]
+    Denied,
+    #[graphql(name = "CANCELLED")]
+    #[sea_orm(string_value = "cancelled")]
+    Cancelled,
+}
+
+impl AccessRequestStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AccessRequestStatus::Pending => "pending",
+            AccessRequestStatus::Approved => "approved",
+            AccessRequestStatus::Denied => "denied",
+            AccessRequestStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug, DeriveActiveEnum, Serialize, Deserialize, EnumIter)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "project_invitation_status")]
+#[graphql(name = "ProjectInvitationStatus")]
+pub enum ProjectInvitationStatus {
+    #[graphql(name = "PENDING")]
+    #[sea_orm(string_value = "pending")]
+    Pending,
+    #[graphql(name = "ACCEPTED")]
+    #[sea_orm(string_value = "accepted")]
+    Accepted,
+    #[graphql(name = "DECLINED")]
+    #[sea_orm(string_value = "declined")]
+    Declined,
+    #[graphql(name = "REVOKED")]
+    #[sea_orm(string_value = "revoked")]
+    Revoked,
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug, DeriveActiveEnum, Serialize, Deserialize, EnumIter)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "email_outbox_status")]
+#[graphql(name = "EmailOutboxStatus")]
+pub enum EmailOutboxStatus {
+    #[graphql(name = "PENDING")]
+    #[sea_orm(string_value = "pending")]
+    Pending,
+    #[graphql(name = "SENT")]
+    #[sea_orm(string_value = "sent")]
+    Sent,
+    /// Gave up after the maximum number of attempts
+    #[graphql(name = "DEAD")]
+    #[sea_orm(string_value = "dead")]
+    Dead,
+}
+
+/// Events pushed to webhook subscriptions
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+#[graphql(name = "WebhookEventType")]
+pub enum WebhookEventType {
+    #[graphql(name = "TASK_CREATED")]
+    TaskCreated,
+    #[graphql(name = "TASK_UPDATED")]
+    TaskUpdated,
+    #[graphql(name = "TASK_COMPLETED")]
+    TaskCompleted,
+    #[graphql(name = "TASK_DELETED")]
+    TaskDeleted,
+    #[graphql(name = "COMMENT_CREATED")]
+    CommentCreated,
+    #[graphql(name = "EMAIL_CONTEXT_INGESTED")]
+    EmailContextIngested,
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug, DeriveActiveEnum, Serialize, Deserialize, EnumIter)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "webhook_delivery_status")]
+#[graphql(name = "WebhookDeliveryStatus")]
+pub enum WebhookDeliveryStatus {
+    #[graphql(name = "PENDING")]
+    #[sea_orm(string_value = "pending")]
+    Pending,
+    #[graphql(name = "DELIVERED")]
+    #[sea_orm(string_value = "delivered")]
+    Delivered,
+    /// Gave up after the maximum number of attempts
+    #[graphql(name = "DEAD")]
+    #[sea_orm(string_value = "dead")]
+    Dead,
+}
+
 #[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
 #[graphql(name = "EntityType")]
 pub enum GraphQLEntityType {
@@ -235,6 +426,17 @@ pub enum GraphQLEntityType {
     Settings,
 }
 
+impl From<GraphQLEntityType> for crate::services::activity::EntityType {
+    fn from(entity_type: GraphQLEntityType) -> Self {
+        match entity_type {
+            GraphQLEntityType::Task => Self::Task,
+            GraphQLEntityType::Project => Self::Project,
+            GraphQLEntityType::User => Self::User,
+            GraphQLEntityType::Settings => Self::Settings,
+        }
+    }
+}
+
 // Activity system types
 #[derive(SimpleObject)]
 #[graphql(complex)]
@@ -276,7 +478,30 @@ impl Activity {
             .one(user_service.get_db())
             .await
             .map_err(|e| Error::new(format!("Failed to fetch actor: {}", e)))?;
-        Ok(user.map(|u| u.into()))
+
+        // Scope the actor to the project the activity belongs to
+        let project_id = match crate::services::activity::EntityType::from_str(&self.entity_type) {
+            Some(crate::services::activity::EntityType::Project) => Some(self.entity_id),
+            Some(crate::services::activity::EntityType::Task) => {
+                use sea_orm::QuerySelect;
+                crate::entities::task::Entity::find_by_id(self.entity_id)
+                    .select_only()
+                    .column(crate::entities::task::Column::ProjectId)
+                    .into_tuple::<Uuid>()
+                    .one(user_service.get_db())
+                    .await
+                    .map_err(|e| Error::new(format!("Failed to fetch actor: {}", e)))?
+            }
+            _ => None,
+        };
+
+        Ok(user.map(|u| {
+            let user: User = u.into();
+            match project_id {
+                Some(project_id) => user.in_project(project_id),
+                None => user,
+            }
+        }))
     }
 
     async fn metadata_json(&self, _ctx: &Context<'_>) -> Result<Option<String>> {
@@ -286,26 +511,473 @@ impl Activity {
     async fn changes_json(&self, _ctx: &Context<'_>) -> Result<Option<String>> {
         Ok(self.changes.as_ref().map(|c| serde_json::to_string(c).unwrap_or_default()))
     }
+
+    /// The comment, for `commented` activities
+    async fn comment(&self, ctx: &Context<'_>) -> Result<Option<Comment>> {
+        if self.action_type != "commented" {
+            return Ok(None);
+        }
+        let comment_service = ctx.data::<crate::services::CommentService>()?;
+        let comment = comment_service
+            .get_activity_comment(self.id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to fetch comment: {}", e)))?;
+        Ok(comment.map(|c| c.into()))
+    }
+}
+
+/// A comment, or a reply to one. Deleted comments keep their place in the thread with
+/// their content hidden.
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct Comment {
+    pub id: Uuid,
+    /// The `commented` activity the comment was logged as
+    pub activity_id: Uuid,
+    pub author_id: Uuid,
+    pub parent_comment_id: Option<Uuid>,
+    /// Markdown source; null once deleted
+    pub content: Option<String>,
+    /// Content rendered to sanitized HTML; null once deleted
+    pub content_html: Option<String>,
+    /// Mentioned users, all members of the comment's project
+    pub mentions: Vec<Uuid>,
+    pub edited_at: Option<DateTime<Utc>>,
+    pub deleted_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<crate::entities::activity_comment::Model> for Comment {
+    fn from(comment: crate::entities::activity_comment::Model) -> Self {
+        let deleted = comment.deleted_at.is_some();
+        // Comments from before rendering was stored are rendered on the way out
+        let content_html = comment
+            .content_html
+            .unwrap_or_else(|| crate::services::render_comment_markdown(&comment.content));
+        Self {
+            id: comment.id,
+            activity_id: comment.activity_id,
+            author_id: comment.author_id,
+            parent_comment_id: comment.parent_comment_id,
+            content: (!deleted).then_some(comment.content),
+            content_html: (!deleted).then_some(content_html),
+            mentions: comment
+                .mentions
+                .and_then(|mentions| serde_json::from_value(mentions).ok())
+                .unwrap_or_default(),
+            edited_at: comment.edited_at.map(|dt| dt.into()),
+            deleted_at: comment.deleted_at.map(|dt| dt.into()),
+            created_at: comment.created_at.into(),
+            updated_at: comment.updated_at.into(),
+        }
+    }
+}
+
+#[ComplexObject]
+impl Comment {
+    async fn author(&self, ctx: &Context<'_>) -> Result<Option<User>> {
+        let comment_service = ctx.data::<crate::services::CommentService>()?;
+        let user_service = ctx.data::<crate::services::UserService>()?;
+        let user = crate::entities::user::Entity::find_by_id(self.author_id)
+            .one(user_service.get_db())
+            .await
+            .map_err(|e| Error::new(format!("Failed to fetch author: {}", e)))?;
+
+        // Scope the author to the project the comment belongs to
+        let project_id = comment_service
+            .comment_project_id(self.activity_id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to fetch author: {}", e)))?;
+
+        Ok(user.map(|u| {
+            let user: User = u.into();
+            match project_id {
+                Some(project_id) => user.in_project(project_id),
+                None => user,
+            }
+        }))
+    }
+
+    /// Direct replies, oldest first
+    async fn replies(&self, ctx: &Context<'_>) -> Result<Vec<Comment>> {
+        let comment_service = ctx.data::<crate::services::CommentService>()?;
+        let replies = comment_service
+            .get_replies(self.id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to fetch replies: {}", e)))?;
+        Ok(replies.into_iter().map(|r| r.into()).collect())
+    }
+
+    async fn reactions(&self, ctx: &Context<'_>) -> Result<Vec<CommentReaction>> {
+        let user = crate::auth::guards::require_auth(ctx)?;
+        let comment_service = ctx.data::<crate::services::CommentService>()?;
+        comment_service
+            .get_reactions(self.id, user.id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to fetch reactions: {}", e)))
+    }
+
+    /// Earlier versions, oldest first; empty once deleted
+    async fn history(&self, ctx: &Context<'_>) -> Result<Vec<CommentRevision>> {
+        if self.deleted_at.is_some() {
+            return Ok(Vec::new());
+        }
+        let comment_service = ctx.data::<crate::services::CommentService>()?;
+        let revisions = comment_service
+            .get_revisions(self.id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to fetch comment history: {}", e)))?;
+        Ok(revisions.into_iter().map(|r| r.into()).collect())
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct CommentConnection {
+    pub edges: Vec<Comment>,
+    pub total_count: u32,
+}
+
+/// Everyone who reacted to a comment with one emoji
+#[derive(SimpleObject)]
+pub struct CommentReaction {
+    pub emoji: String,
+    pub count: u32,
+    pub user_ids: Vec<Uuid>,
+    pub reacted_by_me: bool,
+}
+
+/// A comment as it read before an edit
+#[derive(SimpleObject)]
+pub struct CommentRevision {
+    pub id: Uuid,
+    pub content: String,
+    pub content_html: Option<String>,
+    pub edited_by: Option<Uuid>,
+    /// When this version was replaced
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<crate::entities::activity_comment_revision::Model> for CommentRevision {
+    fn from(revision: crate::entities::activity_comment_revision::Model) -> Self {
+        Self {
+            id: revision.id,
+            content: revision.content,
+            content_html: revision.content_html,
+            edited_by: revision.edited_by,
+            created_at: revision.created_at.into(),
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct Notification {
+    pub id: Uuid,
+    pub notification_type: NotificationType,
+    pub title: String,
+    pub body: Option<String>,
+    pub actor_id: Option<Uuid>,
+    pub activity_id: Option<Uuid>,
+    pub project_id: Option<Uuid>,
+    pub task_id: Option<Uuid>,
+    pub is_read: bool,
+    pub read_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<crate::entities::notification::Model> for Notification {
+    fn from(notification: crate::entities::notification::Model) -> Self {
+        Self {
+            id: notification.id,
+            notification_type: notification.notification_type,
+            title: notification.title,
+            body: notification.body,
+            actor_id: notification.actor_id,
+            activity_id: notification.activity_id,
+            project_id: notification.project_id,
+            task_id: notification.task_id,
+            is_read: notification.is_read,
+            read_at: notification.read_at.map(|dt| dt.into()),
+            created_at: notification.created_at.into(),
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct NotificationConnection {
+    pub edges: Vec<Notification>,
+    pub total_count: u32,
+    /// Unread notifications overall, regardless of the page or filter
+    pub unread_count: u32,
+}
+
+#[derive(SimpleObject)]
+pub struct NotificationPreference {
+    pub notification_type: NotificationType,
+    pub enabled: bool,
+}
+
+#[derive(InputObject)]
+pub struct NotificationPreferenceInput {
+    pub notification_type: NotificationType,
+    pub enabled: bool,
+}
+
+/// When and how often your email digest is sent
+#[derive(SimpleObject)]
+pub struct DigestPreferences {
+    pub frequency: DigestFrequency,
+    /// IANA time zone, e.g. `Europe/Berlin`
+    pub timezone: String,
+    /// Local hour (0-23) the digest goes out
+    pub send_hour: i32,
+    /// Local hours (0-23) no digest is sent in, from start up to but excluding end
+    pub quiet_hours_start: Option<i32>,
+    pub quiet_hours_end: Option<i32>,
+    pub last_sent_at: Option<DateTime<Utc>>,
+}
+
+impl From<crate::entities::digest_preference::Model> for DigestPreferences {
+    fn from(preference: crate::entities::digest_preference::Model) -> Self {
+        Self {
+            frequency: preference.frequency,
+            timezone: preference.timezone,
+            send_hour: preference.send_hour,
+            quiet_hours_start: preference.quiet_hours_start,
+            quiet_hours_end: preference.quiet_hours_end,
+            last_sent_at: preference.last_sent_at.map(|dt| dt.into()),
+        }
+    }
+}
+
+/// `null` clears quiet hours; omitted fields keep their value
+#[derive(InputObject)]
+pub struct UpdateDigestPreferencesInput {
+    pub frequency: Option<DigestFrequency>,
+    pub timezone: Option<String>,
+    pub send_hour: Option<i32>,
+    pub quiet_hours_start: Option<Option<i32>>,
+    pub quiet_hours_end: Option<Option<i32>>,
+}
+
+/// Endpoint receiving events for an organization, or for one project
+#[derive(SimpleObject)]
+pub struct WebhookSubscription {
+    pub id: Uuid,
+    pub organization_id: Uuid,
+    /// `null` for organization-wide subscriptions
+    pub project_id: Option<Uuid>,
+    pub name: String,
+    pub url: String,
+    /// Empty means every event type
+    pub event_types: Vec<WebhookEventType>,
+    pub is_active: bool,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<crate::entities::webhook_subscription::Model> for WebhookSubscription {
+    fn from(subscription: crate::entities::webhook_subscription::Model) -> Self {
+        Self {
+            id: subscription.id,
+            organization_id: subscription.organization_id,
+            project_id: subscription.project_id,
+            name: subscription.name,
+            url: subscription.url,
+            event_types: subscription
+                .event_types
+                .iter()
+                .filter_map(|event_type| event_type.parse().ok())
+                .collect(),
+            is_active: subscription.is_active,
+            created_by: subscription.created_by,
+            created_at: subscription.created_at.into(),
+            updated_at: subscription.updated_at.into(),
+        }
+    }
+}
+
+/// A subscription with its signing secret, which is only shown when it is created or
+/// rotated
+#[derive(SimpleObject)]
+pub struct WebhookSubscriptionWithSecret {
+    pub subscription: WebhookSubscription,
+    pub secret: String,
+}
+
+#[derive(InputObject)]
+pub struct CreateWebhookSubscriptionInput {
+    pub name: String,
+    pub url: String,
+    /// Limit the subscription to one project of the organization
+    pub project_id: Option<Uuid>,
+    /// Omit or leave empty to receive every event type
+    pub event_types: Option<Vec<WebhookEventType>>,
+}
+
+/// Omitted fields keep their value
+#[derive(InputObject)]
+pub struct UpdateWebhookSubscriptionInput {
+    pub subscription_id: Uuid,
+    pub name: Option<String>,
+    pub url: Option<String>,
+    pub event_types: Option<Vec<WebhookEventType>>,
+    pub is_active: Option<bool>,
+}
+
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub subscription_id: Uuid,
+    /// Shared by every delivery of the same event, including replays
+    pub event_id: Uuid,
+    /// Event name as sent in the payload, e.g. `task.created`
+    pub event_type: String,
+    pub payload: serde_json::Value,
+    pub status: WebhookDeliveryStatus,
+    pub attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+    /// HTTP status of the latest attempt
+    pub response_status: Option<i32>,
+    pub last_error: Option<String>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    /// Delivery this one was replayed from
+    pub replay_of: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<crate::entities::webhook_delivery::Model> for WebhookDelivery {
+    fn from(delivery: crate::entities::webhook_delivery::Model) -> Self {
+        Self {
+            id: delivery.id,
+            subscription_id: delivery.subscription_id,
+            event_id: delivery.event_id,
+            event_type: delivery.event_type,
+            payload: delivery.payload,
+            status: delivery.status,
+            attempts: delivery.attempts,
+            next_attempt_at: delivery.next_attempt_at.into(),
+            response_status: delivery.response_status,
+            last_error: delivery.last_error,
+            delivered_at: delivery.delivered_at.map(|dt| dt.into()),
+            replay_of: delivery.replay_of,
+            created_at: delivery.created_at.into(),
+            updated_at: delivery.updated_at.into(),
+        }
+    }
+}
+
+#[ComplexObject]
+impl WebhookDelivery {
+    /// Request and response of every attempt, oldest first
+    async fn log(&self, ctx: &Context<'_>) -> Result<Vec<WebhookDeliveryAttempt>> {
+        let webhook_service = ctx.data::<crate::services::WebhookService>()?;
+
+        let attempts = webhook_service
+            .delivery_attempts(self.id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to fetch delivery log: {}", e)))?;
+
+        Ok(attempts.into_iter().map(|attempt| attempt.into()).collect())
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct WebhookDeliveryConnection {
+    pub edges: Vec<WebhookDelivery>,
+    pub total_count: u32,
+}
+
+/// One HTTP request made for a delivery and what came back
+#[derive(SimpleObject)]
+pub struct WebhookDeliveryAttempt {
+    pub id: Uuid,
+    pub attempt: i32,
+    pub request_url: String,
+    pub request_headers: serde_json::Value,
+    pub request_body: String,
+    pub response_status: Option<i32>,
+    /// Truncated to the first few kilobytes
+    pub response_body: Option<String>,
+    /// Connection or timeout error when no response came back
+    pub error: Option<String>,
+    pub latency_ms: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<crate::entities::webhook_delivery_attempt::Model> for WebhookDeliveryAttempt {
+    fn from(attempt: crate::entities::webhook_delivery_attempt::Model) -> Self {
+        Self {
+            id: attempt.id,
+            attempt: attempt.attempt,
+            request_url: attempt.request_url,
+            request_headers: attempt.request_headers,
+            request_body: attempt.request_body,
+            response_status: attempt.response_status,
+            response_body: attempt.response_body,
+            error: attempt.error,
+            latency_ms: attempt.latency_ms,
+            created_at: attempt.created_at.into(),
+        }
+    }
 }
 
 #[derive(InputObject)]
 pub struct AddCommentInput {
     pub entity_type: GraphQLEntityType,
     pub entity_id: Uuid,
+    /// Markdown
     pub content: String,
+    /// Users to mention besides those written as `@email`; must be project members
     pub mentions: Option<Vec<Uuid>>,
+    /// Reply to this comment, on the same entity
+    pub parent_comment_id: Option<Uuid>,
+}
+
+#[derive(InputObject)]
+pub struct EditCommentInput {
+    pub comment_id: Uuid,
+    pub content: String,
+    /// Replaces the mentioned users; omit to keep them
+    pub mentions: Option<Vec<Uuid>>,
+}
+
+#[derive(InputObject)]
+pub struct CommentReactionInput {
+    pub comment_id: Uuid,
+    pub emoji: String,
 }
 
 #[derive(SimpleObject)]
 #[graphql(complex)]
 pub struct User {
     pub id: Uuid,
+    #[graphql(skip)]
     pub email: String,
+    #[graphql(skip)]
     pub first_name: Option<String>,
+    #[graphql(skip)]
     pub last_name: Option<String>,
+    /// Project this user was reached through, for field-level policy
+    #[graphql(skip)]
+    pub project_scope: Option<Uuid>,
+    /// Set when handing users their own record before the request is authenticated
+    #[graphql(skip)]
+    pub viewer_is_owner: bool,
     pub is_email_verified: bool,
     #[graphql(skip)]
     pub role_id: Option<Uuid>,
+    #[graphql(skip)]
+    pub current_organization_id: Option<Uuid>,
+    /// When a time-bound role assignment ends
+    pub role_valid_until: Option<DateTime<Utc>>,
+    pub status: UserStatus,
+    pub locked_until: Option<DateTime<Utc>>,
+    /// Language for emails; `null` uses the organization's default
+    pub locale: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -317,16 +989,70 @@ impl From<crate::entities::user::Model> for User {
             email: user.email,
             first_name: user.first_name,
             last_name: user.last_name,
+            project_scope: None,
+            viewer_is_owner: false,
             is_email_verified: user.is_email_verified,
             role_id: user.role_id,
+            current_organization_id: user.current_organization_id,
+            role_valid_until: user.role_valid_until.map(|valid_until| valid_until.into()),
+            status: user.status,
+            locked_until: user.locked_until.map(|locked_until| locked_until.into()),
+            locale: user.locale,
             created_at: user.created_at.into(),
             updated_at: user.updated_at.into(),
         }
     }
 }
 
+impl User {
+    /// Mark the user as reached through a project, so project permissions decide field access
+    pub fn in_project(mut self, project_id: Uuid) -> Self {
+        self.project_scope = Some(project_id);
+        self
+    }
+
+    fn field_scope(&self) -> crate::auth::FieldScope {
+        crate::auth::FieldScope {
+            owner_id: Some(self.id),
+            project_id: self.project_scope,
+            viewer_is_owner: self.viewer_is_owner,
+        }
+    }
+
+    async fn visible_name(&self, ctx: &Context<'_>, name: &Option<String>) -> Result<Option<String>> {
+        use crate::auth::{mask_name, FieldAccess, FieldPolicy, ProtectedField};
+
+        Ok(match FieldPolicy::access(ctx, ProtectedField::UserName, self.field_scope()).await? {
+            FieldAccess::Full => name.clone(),
+            FieldAccess::Masked => name.as_deref().map(mask_name),
+            FieldAccess::Hidden => None,
+        })
+    }
+}
+
 #[ComplexObject]
 impl User {
+    /// Masked unless the caller is this user, manages users, or may view member
+    /// contacts in the project the user was reached through
+    async fn email(&self, ctx: &Context<'_>) -> Result<String> {
+        use crate::auth::{mask_email, FieldAccess, FieldPolicy, ProtectedField};
+
+        Ok(match FieldPolicy::access(ctx, ProtectedField::UserEmail, self.field_scope()).await? {
+            FieldAccess::Full => self.email.clone(),
+            FieldAccess::Masked | FieldAccess::Hidden => mask_email(&self.email),
+        })
+    }
+
+    /// Reduced to an initial under the same rules as `email`
+    async fn first_name(&self, ctx: &Context<'_>) -> Result<Option<String>> {
+        self.visible_name(ctx, &self.first_name).await
+    }
+
+    /// Reduced to an initial under the same rules as `email`
+    async fn last_name(&self, ctx: &Context<'_>) -> Result<Option<String>> {
+        self.visible_name(ctx, &self.last_name).await
+    }
+
     async fn role(&self, ctx: &Context<'_>) -> Result<Option<Role>> {
         if let Some(role_id) = self.role_id {
             let user_service = ctx.data::<crate::services::UserService>()?;
@@ -342,14 +1068,183 @@ impl User {
         }
     }
 
+    /// Permissions within the viewer's current organization; in authentication payloads,
+    /// the organization the new tokens are scoped to
     async fn permissions(&self, ctx: &Context<'_>) -> Result<Vec<String>> {
         let dataloader = ctx.data::<crate::graphql::DataLoaderContext>()?;
+        let organization_id = if self.viewer_is_owner {
+            self.current_organization_id
+        } else {
+            ctx.data_opt::<crate::auth::AuthenticatedUser>()
+                .and_then(|viewer| viewer.organization_id)
+        };
         
         dataloader
-            .load_user_permissions(self.id)
+            .load_user_permissions(self.id, organization_id)
             .await
             .map_err(|e| Error::new(format!("Failed to fetch permissions: {}", e)))
     }
+
+    /// Organizations the user belongs to, for the org switcher. Only visible to the
+    /// user themselves.
+    async fn organizations(&self, ctx: &Context<'_>) -> Result<Vec<Organization>> {
+        if ctx.data_opt::<crate::auth::AuthenticatedUser>().map(|user| user.id) != Some(self.id) {
+            return Ok(Vec::new());
+        }
+
+        let organization_service = ctx.data::<crate::services::OrganizationService>()?;
+        let organizations = organization_service
+            .get_user_organizations(self.id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to fetch organizations: {}", e)))?;
+
+        Ok(organizations.into_iter().map(|organization| organization.into()).collect())
+    }
+
+    /// Organization selected with `switchOrganization`. Only visible to the user themselves.
+    async fn current_organization(&self, ctx: &Context<'_>) -> Result<Option<Organization>> {
+        let Some(organization_id) = self.current_organization_id else {
+            return Ok(None);
+        };
+        if ctx.data_opt::<crate::auth::AuthenticatedUser>().map(|user| user.id) != Some(self.id) {
+            return Ok(None);
+        }
+
+        let organization_service = ctx.data::<crate::services::OrganizationService>()?;
+        let organization = organization_service
+            .get_organization(organization_id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to fetch organization: {}", e)))?;
+
+        Ok(organization.map(|organization| organization.into()))
+    }
+}
+
+/// Tenant owning users, roles, projects and context categories
+#[derive(SimpleObject)]
+pub struct Organization {
+    pub id: Uuid,
+    pub name: String,
+    pub slug: String,
+    pub is_active: bool,
+    pub brand_logo_url: Option<String>,
+    pub brand_primary_color: Option<String>,
+    pub brand_accent_color: Option<String>,
+    /// Display name in the From header of emails sent on the organization's behalf
+    pub email_sender_name: Option<String>,
+    /// Language for emails to members without one of their own
+    pub default_locale: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<crate::entities::organization::Model> for Organization {
+    fn from(organization: crate::entities::organization::Model) -> Self {
+        Self {
+            id: organization.id,
+            name: organization.name,
+            slug: organization.slug,
+            is_active: organization.is_active,
+            brand_logo_url: organization.brand_logo_url,
+            brand_primary_color: organization.brand_primary_color,
+            brand_accent_color: organization.brand_accent_color,
+            email_sender_name: organization.email_sender_name,
+            default_locale: organization.default_locale,
+            created_at: organization.created_at.into(),
+        }
+    }
+}
+
+#[derive(InputObject)]
+pub struct CreateOrganizationInput {
+    pub name: String,
+    /// Lowercase letters, digits and hyphens
+    pub slug: String,
+}
+
+/// Email branding of the current organization. Omitted fields stay unchanged, `null`
+/// clears them.
+#[derive(InputObject)]
+pub struct UpdateOrganizationBrandingInput {
+    /// http(s) URL of an image shown in the email header
+    pub brand_logo_url: Option<Option<String>>,
+    /// `#RRGGBB`
+    pub brand_primary_color: Option<Option<String>>,
+    /// `#RRGGBB`
+    pub brand_accent_color: Option<Option<String>>,
+    pub email_sender_name: Option<Option<String>>,
+    pub default_locale: Option<String>,
+}
+
+#[derive(InputObject)]
+pub struct OrganizationMemberInput {
+    pub organization_id: Uuid,
+    pub user_id: Uuid,
+}
+
+#[derive(InputObject)]
+pub struct SwitchOrganizationInput {
+    /// `null` leaves the session without a current organization
+    pub organization_id: Option<Uuid>,
+}
+
+/// Group of an organization's users that can join projects and own a task queue
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct Team {
+    pub id: Uuid,
+    pub organization_id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<crate::entities::team::Model> for Team {
+    fn from(team: crate::entities::team::Model) -> Self {
+        Self {
+            id: team.id,
+            organization_id: team.organization_id,
+            name: team.name,
+            description: team.description,
+            created_by: team.created_by,
+            created_at: team.created_at.into(),
+            updated_at: team.updated_at.into(),
+        }
+    }
+}
+
+#[ComplexObject]
+impl Team {
+    async fn members(&self, ctx: &Context<'_>) -> Result<Vec<User>> {
+        let team_service = ctx.data::<crate::services::TeamService>()?;
+
+        let members = team_service
+            .get_team_members(self.id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to fetch team members: {}", e)))?;
+
+        Ok(members.into_iter().map(User::from).collect())
+    }
+}
+
+#[derive(InputObject)]
+pub struct CreateTeamInput {
+    pub name: String,
+    pub description: Option<String>,
+}
+
+#[derive(InputObject)]
+pub struct UpdateTeamInput {
+    pub team_id: Uuid,
+    pub name: Option<String>,
+    pub description: Option<Option<String>>,
+}
+
+#[derive(InputObject)]
+pub struct TeamMemberInput {
+    pub team_id: Uuid,
+    pub user_id: Uuid,
 }
 
 #[derive(InputObject)]
@@ -373,6 +1268,18 @@ pub struct AuthPayload {
     pub refresh_token: String,
 }
 
+impl AuthPayload {
+    /// Tokens for `user`, who sees their own record in full even though the request
+    /// that signed them in carries no authenticated user
+    pub fn new(user: crate::entities::user::Model, access_token: String, refresh_token: String) -> Self {
+        Self {
+            user: User { viewer_is_owner: true, ..user.into() },
+            access_token,
+            refresh_token,
+        }
+    }
+}
+
 #[derive(InputObject)]
 pub struct RefreshTokenInput {
     pub refresh_token: String,
@@ -416,6 +1323,74 @@ impl From<crate::entities::invitation::Model> for Invitation {
     }
 }
 
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+#[graphql(name = "EmailTemplate")]
+pub enum EmailTemplateKind {
+    #[graphql(name = "EMAIL_VERIFICATION")]
+    EmailVerification,
+    #[graphql(name = "PASSWORD_RESET")]
+    PasswordReset,
+    #[graphql(name = "MAGIC_LINK")]
+    MagicLink,
+    #[graphql(name = "INVITATION")]
+    Invitation,
+    #[graphql(name = "ADMIN_PASSWORD_RESET")]
+    AdminPasswordReset,
+    #[graphql(name = "PROJECT_INVITATION")]
+    ProjectInvitation,
+    #[graphql(name = "DAILY_DIGEST")]
+    DailyDigest,
+    #[graphql(name = "WEEKLY_DIGEST")]
+    WeeklyDigest,
+}
+
+/// An email template rendered with sample data
+#[derive(SimpleObject)]
+pub struct EmailPreview {
+    pub template: EmailTemplateKind,
+    /// Locale the email was rendered in, after falling back from the requested one
+    pub locale: String,
+    pub from_name: Option<String>,
+    pub subject: String,
+    pub html: String,
+    pub text: String,
+}
+
+/// Outbound email in the outbox. Bodies stay out of the API since they carry sign-in
+/// and reset tokens.
+#[derive(SimpleObject)]
+pub struct EmailOutboxEntry {
+    pub id: Uuid,
+    pub kind: String,
+    pub to_email: String,
+    pub subject: String,
+    pub status: EmailOutboxStatus,
+    pub attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_error: Option<String>,
+    pub sent_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<crate::entities::email_outbox::Model> for EmailOutboxEntry {
+    fn from(email: crate::entities::email_outbox::Model) -> Self {
+        Self {
+            id: email.id,
+            kind: email.kind,
+            to_email: email.to_email,
+            subject: email.subject,
+            status: email.status,
+            attempts: email.attempts,
+            next_attempt_at: email.next_attempt_at.into(),
+            last_error: email.last_error,
+            sent_at: email.sent_at.map(|dt| dt.into()),
+            created_at: email.created_at.into(),
+            updated_at: email.updated_at.into(),
+        }
+    }
+}
+
 #[derive(InputObject)]
 pub struct InviteUserInput {
     pub email: String,
@@ -459,6 +1434,9 @@ pub struct Role {
     pub description: Option<String>,
     pub level: i32,
     pub is_active: bool,
+    pub magic_link_enabled: bool,
+    /// Owning organization; `None` for roles shared by every organization
+    pub organization_id: Option<Uuid>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -471,6 +1449,8 @@ impl From<crate::entities::role::Model> for Role {
             description: role.description,
             level: role.level,
             is_active: role.is_active,
+            magic_link_enabled: role.magic_link_enabled,
+            organization_id: role.organization_id,
             created_at: role.created_at.into(),
             updated_at: role.updated_at.into(),
         }
@@ -560,6 +1540,8 @@ pub struct RoleWithPermissions {
     pub description: Option<String>,
     pub level: i32,
     pub is_active: bool,
+    pub magic_link_enabled: bool,
+    pub organization_id: Option<Uuid>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -572,6 +1554,8 @@ impl From<crate::entities::role::Model> for RoleWithPermissions {
             description: role.description,
             level: role.level,
             is_active: role.is_active,
+            magic_link_enabled: role.magic_link_enabled,
+            organization_id: role.organization_id,
             created_at: role.created_at.into(),
             updated_at: role.updated_at.into(),
         }
@@ -604,6 +1588,29 @@ impl RoleWithPermissions {
         Ok(permissions.into_iter().map(|p| p.into()).collect())
     }
 
+    /// Roles this role inherits permissions from
+    async fn parent_roles(&self, ctx: &Context<'_>) -> Result<Vec<Role>> {
+        let user_service = ctx.data::<crate::services::UserService>()?;
+
+        let graph = crate::auth::RoleGraph::load(user_service.get_db())
+            .await
+            .map_err(|e| Error::new(format!("Failed to fetch parent roles: {}", e)))?;
+
+        Ok(graph.parents(self.id).into_iter().cloned().map(|role| role.into()).collect())
+    }
+
+    /// Own and inherited permissions, each with the role it comes from
+    async fn effective_permissions(&self, ctx: &Context<'_>) -> Result<Vec<EffectiveRolePermission>> {
+        let permission_service = ctx.data::<crate::auth::PermissionService>()?;
+
+        let permissions = permission_service
+            .get_effective_role_permissions(self.id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to fetch effective permissions: {}", e)))?;
+
+        Ok(permissions.into_iter().map(|permission| permission.into()).collect())
+    }
+
     async fn user_count(&self, ctx: &Context<'_>) -> Result<u32> {
         let user_service = ctx.data::<crate::services::UserService>()?;
         
@@ -617,29 +1624,216 @@ impl RoleWithPermissions {
     }
 }
 
-#[derive(SimpleObject)]
-pub struct UserWithRole {
-    pub id: Uuid,
-    pub email: String,
-    pub first_name: Option<String>,
-    pub last_name: Option<String>,
-    pub is_email_verified: bool,
-    pub role: Option<Role>,
-    pub permissions: Vec<String>,
-    pub created_at: DateTime<Utc>,
-    pub updated_at: DateTime<Utc>,
+#[derive(SimpleObject)]
+pub struct EffectiveRolePermission {
+    pub permission: Permission,
+    pub source_role_id: Uuid,
+    pub source_role_name: String,
+    /// False when the role holds the permission itself
+    pub inherited: bool,
+    /// Role names from this role up to the source role
+    pub path: Vec<String>,
+}
+
+impl From<crate::auth::EffectiveRolePermission> for EffectiveRolePermission {
+    fn from(effective: crate::auth::EffectiveRolePermission) -> Self {
+        Self {
+            permission: effective.permission.into(),
+            source_role_id: effective.source_role_id,
+            source_role_name: effective.source_role_name,
+            inherited: effective.path.len() > 1,
+            path: effective.path,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct UserWithRole {
+    pub id: Uuid,
+    pub email: String,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub is_email_verified: bool,
+    pub role: Option<Role>,
+    pub permissions: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(InputObject)]
+pub struct AssignRoleInput {
+    pub user_id: Uuid,
+    pub role_id: Uuid,
+    /// The role only applies from this moment; omit to apply immediately
+    pub valid_from: Option<DateTime<Utc>>,
+    /// The role lapses at this moment; omit for a permanent assignment
+    pub valid_until: Option<DateTime<Utc>>,
+}
+
+#[derive(InputObject)]
+pub struct RequestMagicLinkInput {
+    pub email: String,
+}
+
+#[derive(InputObject)]
+pub struct MagicLinkLoginInput {
+    pub token: String,
+}
+
+#[derive(InputObject)]
+pub struct ImpersonateUserInput {
+    pub user_id: Uuid,
+    pub reason: Option<String>,
+}
+
+#[derive(SimpleObject)]
+pub struct ImpersonationPayload {
+    pub user: User,
+    pub access_token: String,
+    pub expires_at: DateTime<Utc>,
+    pub actor_id: Uuid,
+}
+
+#[derive(InputObject)]
+pub struct UpdateUserStatusInput {
+    pub user_id: Uuid,
+    pub status: UserStatus,
+    pub reason: Option<String>,
+}
+
+#[derive(InputObject)]
+pub struct OffboardUserInput {
+    pub user_id: Uuid,
+    /// Receives the user's owned projects and their open tasks in projects this user can
+    /// access; required if the user owns projects
+    pub reassign_to_user_id: Option<Uuid>,
+    pub reason: Option<String>,
+}
+
+#[derive(SimpleObject)]
+pub struct OffboardUserResult {
+    pub user: User,
+    pub reassigned_task_ids: Vec<Uuid>,
+    /// Open tasks left without an assignee
+    pub unassigned_task_ids: Vec<Uuid>,
+    pub transferred_project_ids: Vec<Uuid>,
+    pub removed_membership_count: u64,
+}
+
+impl From<crate::services::OffboardingSummary> for OffboardUserResult {
+    fn from(summary: crate::services::OffboardingSummary) -> Self {
+        Self {
+            user: summary.user.into(),
+            reassigned_task_ids: summary.reassigned_task_ids,
+            unassigned_task_ids: summary.unassigned_task_ids,
+            transferred_project_ids: summary.transferred_project_ids,
+            removed_membership_count: summary.removed_membership_count,
+        }
+    }
+}
+
+#[derive(InputObject)]
+pub struct InviteUserWithRoleInput {
+    pub email: String,
+    pub role_id: Option<Uuid>,
+    /// Projects joined on sign-up, each with a project role
+    pub projects: Option<Vec<ProjectInvitationGrantInput>>,
+}
+
+#[derive(InputObject)]
+pub struct ProjectInvitationGrantInput {
+    pub project_id: Uuid,
+    pub role: String,
 }
 
 #[derive(InputObject)]
-pub struct AssignRoleInput {
-    pub user_id: Uuid,
-    pub role_id: Uuid,
+pub struct BulkInviteUsersInput {
+    /// CSV file with an `email` column and optional `role` and `projects` columns;
+    /// `projects` holds `project:role` pairs separated by `;`
+    pub csv: Option<Upload>,
+    /// Rows given directly instead of as a CSV file
+    pub rows: Option<Vec<BulkInviteRowInput>>,
+    /// Validate every row without creating invitations
+    pub dry_run: Option<bool>,
 }
 
 #[derive(InputObject)]
-pub struct InviteUserWithRoleInput {
+pub struct BulkInviteRowInput {
     pub email: String,
-    pub role_id: Option<Uuid>,
+    /// Organization role, by name or id
+    pub role: Option<String>,
+    pub projects: Option<Vec<ProjectInvitationGrantInput>>,
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+#[graphql(name = "BulkInviteRowStatus")]
+pub enum BulkInviteRowStatus {
+    /// Passed validation in a dry run
+    #[graphql(name = "VALID")]
+    Valid,
+    #[graphql(name = "INVITED")]
+    Invited,
+    #[graphql(name = "FAILED")]
+    Failed,
+}
+
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+#[graphql(name = "BulkInviteErrorCode")]
+pub enum BulkInviteErrorCode {
+    #[graphql(name = "MALFORMED_ROW")]
+    MalformedRow,
+    #[graphql(name = "INVALID_EMAIL")]
+    InvalidEmail,
+    #[graphql(name = "DUPLICATE_EMAIL")]
+    DuplicateEmail,
+    #[graphql(name = "USER_EXISTS")]
+    UserExists,
+    #[graphql(name = "INVITATION_PENDING")]
+    InvitationPending,
+    #[graphql(name = "UNKNOWN_ROLE")]
+    UnknownRole,
+    #[graphql(name = "INVALID_ROLE_GRANT")]
+    InvalidRoleGrant,
+    #[graphql(name = "UNKNOWN_PROJECT")]
+    UnknownProject,
+    #[graphql(name = "DUPLICATE_PROJECT")]
+    DuplicateProject,
+    #[graphql(name = "INVALID_PROJECT_GRANT")]
+    InvalidProjectGrant,
+}
+
+#[derive(SimpleObject)]
+pub struct BulkInviteRowResult {
+    /// Row number, counting from 1 after any CSV header
+    pub row: i32,
+    pub email: String,
+    pub status: BulkInviteRowStatus,
+    pub error_code: Option<BulkInviteErrorCode>,
+    pub error: Option<String>,
+    pub invitation: Option<Invitation>,
+}
+
+impl From<crate::services::BulkInviteRowOutcome> for BulkInviteRowResult {
+    fn from(outcome: crate::services::BulkInviteRowOutcome) -> Self {
+        Self {
+            row: outcome.row as i32,
+            email: outcome.email,
+            status: outcome.status,
+            error_code: outcome.error_code,
+            error: outcome.error,
+            invitation: outcome.invitation.map(Into::into),
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct BulkInviteResult {
+    pub dry_run: bool,
+    pub total: i32,
+    pub valid: i32,
+    pub invited: i32,
+    pub failed: i32,
+    pub rows: Vec<BulkInviteRowResult>,
 }
 
 // Role CRUD Input Types
@@ -648,6 +1842,11 @@ pub struct CreateRoleInput {
     pub name: String,
     pub description: Option<String>,
     pub level: i32,
+    pub magic_link_enabled: Option<bool>,
+    /// Share the role with every organization instead of the current one (system admins only)
+    pub shared: Option<bool>,
+    /// Roles to inherit permissions from
+    pub parent_role_ids: Option<Vec<Uuid>>,
 }
 
 #[derive(InputObject)]
@@ -657,6 +1856,9 @@ pub struct UpdateRoleInput {
     pub description: Option<Option<String>>,
     pub level: Option<i32>,
     pub is_active: Option<bool>,
+    pub magic_link_enabled: Option<bool>,
+    /// Replaces the role's parents; an empty list removes them all
+    pub parent_role_ids: Option<Vec<Uuid>>,
 }
 
 #[derive(InputObject)]
@@ -708,6 +1910,10 @@ pub struct UpdateResourceInput {
 pub struct GrantUserPermissionInput {
     pub user_id: Uuid,
     pub permission_id: Uuid,
+    /// The grant only applies from this moment; omit to apply immediately
+    pub valid_from: Option<DateTime<Utc>>,
+    /// The grant lapses at this moment; omit for a permanent grant
+    pub valid_until: Option<DateTime<Utc>>,
 }
 
 #[derive(InputObject)]
@@ -716,6 +1922,132 @@ pub struct RevokeUserPermissionInput {
     pub permission_id: Uuid,
 }
 
+// Permission explanation
+#[derive(Enum, Copy, Clone, Eq, PartialEq, Debug)]
+#[graphql(name = "PermissionRuleKind")]
+pub enum PermissionRuleKind {
+    #[graphql(name = "ROLE_GRANT")]
+    RoleGrant,
+    #[graphql(name = "DIRECT_GRANT")]
+    DirectGrant,
+    #[graphql(name = "DIRECT_DENY")]
+    DirectDeny,
+    #[graphql(name = "RESOURCE_NOT_FOUND")]
+    ResourceNotFound,
+    #[graphql(name = "RESOURCE_INACTIVE")]
+    ResourceInactive,
+    #[graphql(name = "PERMISSION_NOT_FOUND")]
+    PermissionNotFound,
+    #[graphql(name = "NO_GRANT")]
+    NoGrant,
+}
+
+impl From<crate::auth::PermissionRuleKind> for PermissionRuleKind {
+    fn from(kind: crate::auth::PermissionRuleKind) -> Self {
+        match kind {
+            crate::auth::PermissionRuleKind::RoleGrant => PermissionRuleKind::RoleGrant,
+            crate::auth::PermissionRuleKind::DirectGrant => PermissionRuleKind::DirectGrant,
+            crate::auth::PermissionRuleKind::DirectDeny => PermissionRuleKind::DirectDeny,
+            crate::auth::PermissionRuleKind::ResourceNotFound => PermissionRuleKind::ResourceNotFound,
+            crate::auth::PermissionRuleKind::ResourceInactive => PermissionRuleKind::ResourceInactive,
+            crate::auth::PermissionRuleKind::PermissionNotFound => PermissionRuleKind::PermissionNotFound,
+            crate::auth::PermissionRuleKind::NoGrant => PermissionRuleKind::NoGrant,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct PermissionRuleExplanation {
+    pub kind: PermissionRuleKind,
+    pub permission_id: Option<Uuid>,
+    pub role_id: Option<Uuid>,
+    pub role_name: Option<String>,
+    pub valid_from: Option<DateTime<Utc>>,
+    pub valid_until: Option<DateTime<Utc>>,
+    /// Whether this rule took part in the decision
+    pub applied: bool,
+    pub detail: String,
+}
+
+impl From<crate::auth::PermissionRule> for PermissionRuleExplanation {
+    fn from(rule: crate::auth::PermissionRule) -> Self {
+        Self {
+            kind: rule.kind.into(),
+            permission_id: rule.permission_id,
+            role_id: rule.role_id,
+            role_name: rule.role_name,
+            valid_from: rule.valid_from.map(|dt| dt.into()),
+            valid_until: rule.valid_until.map(|dt| dt.into()),
+            applied: rule.applied,
+            detail: rule.detail,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct PermissionExplanation {
+    pub user_id: Uuid,
+    pub resource: String,
+    pub action: String,
+    pub allowed: bool,
+    /// Rules in the order they were merged; a direct deny overrides earlier grants
+    pub rules: Vec<PermissionRuleExplanation>,
+}
+
+// Access Requests
+#[derive(SimpleObject)]
+pub struct AccessRequest {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub permission_id: Option<Uuid>,
+    pub role_id: Option<Uuid>,
+    pub reason: String,
+    /// Requested end of access; on approval, the end actually granted
+    pub valid_until: Option<DateTime<Utc>>,
+    pub status: AccessRequestStatus,
+    pub reviewed_by: Option<Uuid>,
+    pub review_note: Option<String>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<crate::entities::access_request::Model> for AccessRequest {
+    fn from(request: crate::entities::access_request::Model) -> Self {
+        Self {
+            id: request.id,
+            user_id: request.user_id,
+            permission_id: request.permission_id,
+            role_id: request.role_id,
+            reason: request.reason,
+            valid_until: request.valid_until.map(|dt| dt.into()),
+            status: request.status,
+            reviewed_by: request.reviewed_by,
+            review_note: request.review_note,
+            reviewed_at: request.reviewed_at.map(|dt| dt.into()),
+            created_at: request.created_at.into(),
+            updated_at: request.updated_at.into(),
+        }
+    }
+}
+
+#[derive(InputObject)]
+pub struct RequestAccessInput {
+    /// Exactly one of `permission_id` or `role_id` must be set
+    pub permission_id: Option<Uuid>,
+    pub role_id: Option<Uuid>,
+    pub reason: String,
+    pub valid_until: Option<DateTime<Utc>>,
+}
+
+#[derive(InputObject)]
+pub struct ReviewAccessRequestInput {
+    pub request_id: Uuid,
+    pub note: Option<String>,
+    /// Overrides the requested end of access when approving
+    pub valid_until: Option<DateTime<Utc>>,
+}
+
 // Project Types
 #[derive(SimpleObject)]
 #[graphql(complex)]
@@ -724,6 +2056,7 @@ pub struct Project {
     pub name: String,
     pub description: Option<String>,
     pub owner_id: Uuid,
+    pub organization_id: Uuid,
     pub is_active: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
@@ -736,6 +2069,7 @@ impl From<crate::entities::project::Model> for Project {
             name: project.name,
             description: project.description,
             owner_id: project.owner_id,
+            organization_id: project.organization_id,
             is_active: project.is_active,
             created_at: project.created_at.into(),
             updated_at: project.updated_at.into(),
@@ -753,7 +2087,7 @@ impl Project {
             .await
             .map_err(|e| Error::new(format!("Failed to fetch owner: {}", e)))?;
             
-        Ok(user.map(|u| u.into()))
+        Ok(user.map(|u| User::from(u).in_project(self.id)))
     }
 
     async fn members(&self, ctx: &Context<'_>) -> Result<Vec<ProjectMember>> {
@@ -770,11 +2104,61 @@ impl Project {
             project_id: member.project_id,
             user_id: member.user_id,
             role: member.role,
+            project_role_id: member.project_role_id,
             joined_at: member.joined_at.into(),
-            user: user.into(),
+            user: User::from(user).in_project(self.id),
+        }).collect())
+    }
+
+    /// Teams on the project; their members hold the team's role
+    async fn teams(&self, ctx: &Context<'_>) -> Result<Vec<ProjectTeam>> {
+        let project_service = ctx.data::<crate::services::ProjectService>()?;
+        let authenticated_user = ctx.data::<crate::auth::AuthenticatedUser>()?;
+
+        let teams = project_service
+            .get_project_teams(self.id, authenticated_user.id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to fetch project teams: {}", e)))?;
+
+        Ok(teams.into_iter().map(|(project_team, team)| ProjectTeam {
+            id: project_team.id,
+            project_id: project_team.project_id,
+            team_id: project_team.team_id,
+            role: project_team.role,
+            project_role_id: project_team.project_role_id,
+            added_at: project_team.added_at.into(),
+            team: team.into(),
         }).collect())
     }
 
+    /// Project roles available for memberships (system roles and this project's custom roles)
+    async fn roles(&self, ctx: &Context<'_>) -> Result<Vec<ProjectRoleDefinition>> {
+        let project_service = ctx.data::<crate::services::ProjectService>()?;
+        let authenticated_user = ctx.data::<crate::auth::AuthenticatedUser>()?;
+
+        let roles = project_service
+            .get_project_roles(self.id, authenticated_user.id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to fetch project roles: {}", e)))?;
+
+        Ok(roles.into_iter().map(ProjectRoleDefinition::from).collect())
+    }
+
+    /// Actions the current user may perform on this project
+    async fn my_permissions(&self, ctx: &Context<'_>) -> Result<Vec<String>> {
+        let project_service = ctx.data::<crate::services::ProjectService>()?;
+        let authenticated_user = ctx.data::<crate::auth::AuthenticatedUser>()?;
+
+        let permissions = project_service
+            .get_user_project_permissions(self.id, authenticated_user.id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to fetch project permissions: {}", e)))?;
+
+        let mut permissions: Vec<String> = permissions.into_iter().collect();
+        permissions.sort();
+        Ok(permissions)
+    }
+
     async fn tasks(&self, ctx: &Context<'_>, status: Option<TaskStatus>, assignee_id: Option<Uuid>, limit: Option<i32>, offset: Option<i32>) -> Result<Vec<Task>> {
         let task_service = ctx.data::<crate::services::TaskService>()?;
         let authenticated_user = ctx.data::<crate::auth::AuthenticatedUser>()?;
@@ -803,10 +2187,69 @@ pub struct ProjectMember {
     pub project_id: Uuid,
     pub user_id: Uuid,
     pub role: String,
+    pub project_role_id: Option<Uuid>,
     pub joined_at: DateTime<Utc>,
     pub user: User,
 }
 
+/// A team's membership in a project, shared by everyone on the team
+#[derive(SimpleObject)]
+pub struct ProjectTeam {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub team_id: Uuid,
+    pub role: String,
+    pub project_role_id: Option<Uuid>,
+    pub added_at: DateTime<Utc>,
+    pub team: Team,
+}
+
+#[derive(SimpleObject)]
+pub struct ProjectRoleDefinition {
+    pub id: Uuid,
+    /// `None` for system roles shared by every project
+    pub project_id: Option<Uuid>,
+    pub name: String,
+    pub description: Option<String>,
+    pub is_system: bool,
+    pub permissions: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<(crate::entities::project_role::Model, Vec<String>)> for ProjectRoleDefinition {
+    fn from((role, permissions): (crate::entities::project_role::Model, Vec<String>)) -> Self {
+        Self {
+            id: role.id,
+            project_id: role.project_id,
+            name: role.name,
+            description: role.description,
+            is_system: role.is_system,
+            permissions,
+            created_at: role.created_at.into(),
+            updated_at: role.updated_at.into(),
+        }
+    }
+}
+
+#[derive(InputObject)]
+pub struct CreateProjectRoleInput {
+    pub project_id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    /// Actions from the `project` resource, e.g. `task:create`
+    pub permissions: Vec<String>,
+}
+
+#[derive(InputObject)]
+pub struct UpdateProjectRoleInput {
+    pub role_id: Uuid,
+    pub name: Option<String>,
+    pub description: Option<Option<String>>,
+    /// Replaces the role's permissions when provided
+    pub permissions: Option<Vec<String>>,
+}
+
 #[derive(InputObject)]
 pub struct CreateProjectInput {
     pub name: String,
@@ -840,6 +2283,98 @@ pub struct RemoveProjectMemberInput {
     pub user_id: Uuid,
 }
 
+/// Offer to join a project, to an existing user or attached to a sign-up invitation
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct ProjectInvitation {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub role: String,
+    pub project_role_id: Uuid,
+    pub status: ProjectInvitationStatus,
+    /// Sign-up invitation this came with, if sent to someone without an account
+    pub invitation_id: Option<Uuid>,
+    pub user_id: Option<Uuid>,
+    pub inviter_user_id: Uuid,
+    /// Filled in for the project's invitation list
+    pub email: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub responded_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<crate::entities::project_invitation::Model> for ProjectInvitation {
+    fn from(invitation: crate::entities::project_invitation::Model) -> Self {
+        Self {
+            id: invitation.id,
+            project_id: invitation.project_id,
+            role: invitation.role,
+            project_role_id: invitation.project_role_id,
+            status: invitation.status,
+            invitation_id: invitation.invitation_id,
+            user_id: invitation.user_id,
+            inviter_user_id: invitation.inviter_user_id,
+            email: None,
+            expires_at: None,
+            responded_at: invitation.responded_at.map(|dt| dt.into()),
+            created_at: invitation.created_at.into(),
+        }
+    }
+}
+
+impl From<crate::services::PendingProjectInvitation> for ProjectInvitation {
+    fn from(pending: crate::services::PendingProjectInvitation) -> Self {
+        Self {
+            email: Some(pending.email),
+            expires_at: pending.expires_at.map(|dt| dt.into()),
+            ..pending.invitation.into()
+        }
+    }
+}
+
+#[ComplexObject]
+impl ProjectInvitation {
+    /// Name of the project, visible to invitees before they join
+    async fn project_name(&self, ctx: &Context<'_>) -> Result<Option<String>> {
+        let db = ctx.data::<sea_orm::DatabaseConnection>()?;
+
+        let project = crate::entities::project::Entity::find_by_id(self.project_id)
+            .one(db)
+            .await
+            .map_err(|e| Error::new(format!("Failed to fetch project: {}", e)))?;
+
+        Ok(project.map(|p| p.name))
+    }
+}
+
+#[derive(InputObject)]
+pub struct InviteToProjectInput {
+    pub project_id: Uuid,
+    /// Email of an existing user; people without an account need a sign-up invitation
+    pub email: String,
+    pub role: String,
+}
+
+#[derive(InputObject)]
+pub struct AddProjectTeamInput {
+    pub project_id: Uuid,
+    pub team_id: Uuid,
+    pub role: String,
+}
+
+#[derive(InputObject)]
+pub struct UpdateProjectTeamRoleInput {
+    pub project_id: Uuid,
+    pub team_id: Uuid,
+    pub role: String,
+}
+
+#[derive(InputObject)]
+pub struct RemoveProjectTeamInput {
+    pub project_id: Uuid,
+    pub team_id: Uuid,
+}
+
 // Task Types
 #[derive(SimpleObject)]
 #[graphql(complex)]
@@ -849,6 +2384,8 @@ pub struct Task {
     pub description: Option<String>,
     pub project_id: Uuid,
     pub assignee_id: Option<Uuid>,
+    /// Team whose queue the task sits in; members claim it by becoming the assignee
+    pub assignee_team_id: Option<Uuid>,
     pub creator_id: Uuid,
     pub status: TaskStatus,
     pub priority: TaskPriority,
@@ -871,6 +2408,7 @@ impl From<crate::entities::task::Model> for Task {
             description: task.description,
             project_id: task.project_id,
             assignee_id: task.assignee_id,
+            assignee_team_id: task.assignee_team_id,
             creator_id: task.creator_id,
             status: task.status,
             priority: task.priority,
@@ -910,7 +2448,22 @@ impl Task {
                 .await
                 .map_err(|e| Error::new(format!("Failed to fetch assignee: {}", e)))?;
                 
-            Ok(user.map(|u| u.into()))
+            Ok(user.map(|u| User::from(u).in_project(self.project_id)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    async fn assignee_team(&self, ctx: &Context<'_>) -> Result<Option<Team>> {
+        if let Some(team_id) = self.assignee_team_id {
+            let team_service = ctx.data::<crate::services::TeamService>()?;
+
+            let team = team_service
+                .get_team(team_id)
+                .await
+                .map_err(|e| Error::new(format!("Failed to fetch assignee team: {}", e)))?;
+
+            Ok(team.map(|t| t.into()))
         } else {
             Ok(None)
         }
@@ -924,7 +2477,42 @@ impl Task {
             .await
             .map_err(|e| Error::new(format!("Failed to fetch creator: {}", e)))?;
             
-        Ok(user.map(|u| u.into()))
+        Ok(user.map(|u| User::from(u).in_project(self.project_id)))
+    }
+
+    /// Users following the task: its creator, assignees, commenters and anyone who
+    /// chose to watch it
+    async fn watchers(&self, ctx: &Context<'_>) -> Result<Vec<User>> {
+        let auth_user = ctx.data::<crate::auth::AuthenticatedUser>()?;
+        let task_service = ctx.data::<crate::services::TaskService>()?;
+
+        let can_access = task_service
+            .can_user_access_task(self.id, auth_user.id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to check task access: {}", e)))?;
+
+        if !can_access {
+            return Err(Error::new("You don't have permission to view watchers of this task"));
+        }
+
+        let watchers = task_service
+            .get_task_watchers(self.id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to fetch watchers: {}", e)))?;
+
+        Ok(watchers.into_iter().map(|u| User::from(u).in_project(self.project_id)).collect())
+    }
+
+    /// Whether you are watching the task
+    async fn is_watching(&self, ctx: &Context<'_>) -> Result<bool> {
+        let auth_user = ctx.data::<crate::auth::AuthenticatedUser>()?;
+        let task_service = ctx.data::<crate::services::TaskService>()?;
+
+        let watcher_ids = crate::services::task::task_watcher_ids(task_service.get_db(), self.id)
+            .await
+            .map_err(|e| Error::new(format!("Failed to fetch watchers: {}", e)))?;
+
+        Ok(watcher_ids.contains(&auth_user.id))
     }
 
     async fn activities(&self, ctx: &Context<'_>, limit: Option<i32>, offset: Option<i32>) -> Result<Vec<Activity>> {
@@ -1071,6 +2659,13 @@ pub struct AssignTaskInput {
     pub assignee_id: Option<Uuid>,
 }
 
+#[derive(InputObject)]
+pub struct AssignTaskToTeamInput {
+    pub task_id: Uuid,
+    /// `null` takes the task out of its team queue
+    pub team_id: Option<Uuid>,
+}
+
 #[derive(SimpleObject)]
 pub struct TaskStats {
     pub total: u32,
@@ -1183,11 +2778,14 @@ pub struct EmailContext {
     pub from_name: Option<String>,
     pub to_emails: Vec<String>,
     pub cc_emails: Option<Vec<String>>,
+    #[graphql(skip)]
     pub bcc_emails: Option<Vec<String>>,
     pub reply_to: Option<String>,
+    pub direction: EmailDirection,
     pub subject: String,
     pub message_preview: Option<String>,
     pub full_message: String,
+    #[graphql(skip)]
     pub message_html: Option<String>,
     pub accounting_process: AccountingProcess,
     pub ai_summary: Option<String>,
@@ -1216,6 +2814,7 @@ impl From<crate::entities::email_context::Model> for EmailContext {
             cc_emails: email.cc_emails,
             bcc_emails: email.bcc_emails,
             reply_to: email.reply_to,
+            direction: email.direction,
             subject: email.subject,
             message_preview: email.message_preview,
             full_message: email.full_message,
@@ -1303,8 +2902,39 @@ impl ProjectContext {
     }
 }
 
+impl EmailContext {
+    /// Sensitive fields are scoped to the project owning the email's context
+    async fn sensitive_field_access(&self, ctx: &Context<'_>, field: crate::auth::ProtectedField) -> Result<crate::auth::FieldAccess> {
+        use sea_orm::QuerySelect;
+        let db = ctx.data::<sea_orm::DatabaseConnection>()?;
+
+        let project_id = crate::entities::project_context::Entity::find_by_id(self.id)
+            .select_only()
+            .column(crate::entities::project_context::Column::ProjectId)
+            .into_tuple::<Uuid>()
+            .one(db)
+            .await
+            .map_err(|e| Error::new(format!("Failed to fetch project context: {}", e)))?;
+
+        let scope = crate::auth::FieldScope { owner_id: None, project_id, viewer_is_owner: false };
+        crate::auth::FieldPolicy::access(ctx, field, scope).await
+    }
+}
+
 #[ComplexObject]
 impl EmailContext {
+    /// Null unless the caller holds `email:read_sensitive` in the email's project
+    async fn bcc_emails(&self, ctx: &Context<'_>) -> Result<Option<Vec<String>>> {
+        let access = self.sensitive_field_access(ctx, crate::auth::ProtectedField::EmailBccRecipients).await?;
+        Ok(self.bcc_emails.clone().filter(|_| access == crate::auth::FieldAccess::Full))
+    }
+
+    /// Null unless the caller holds `email:read_sensitive` in the email's project
+    async fn message_html(&self, ctx: &Context<'_>) -> Result<Option<String>> {
+        let access = self.sensitive_field_access(ctx, crate::auth::ProtectedField::EmailHtmlBody).await?;
+        Ok(self.message_html.clone().filter(|_| access == crate::auth::FieldAccess::Full))
+    }
+
     async fn project_context(&self, ctx: &Context<'_>) -> Result<ProjectContext> {
         let db = ctx.data::<sea_orm::DatabaseConnection>()?;
         
@@ -1418,9 +3048,24 @@ pub struct ContextFilters {
     pub tags: Option<Vec<String>>,
 }
 
+/// Reply sent from an email context, threaded under it
+#[derive(InputObject)]
+pub struct ReplyToEmailContextInput {
+    pub email_context_id: Uuid,
+    /// Plain-text body; also sent as simple HTML
+    pub body: String,
+    /// Defaults to the original's Reply-To or sender, or for an outbound email its recipient
+    pub to: Option<String>,
+    /// Defaults to the original subject with a `Re:` prefix
+    pub subject: Option<String>,
+    /// Stored attachments from emails in the same project to send along
+    pub attachment_ids: Option<Vec<Uuid>>,
+}
+
 #[derive(InputObject)]
 pub struct EmailContextFilters {
     pub accounting_process: Option<AccountingProcess>,
+    pub direction: Option<EmailDirection>,
     pub from_email: Option<String>,
     pub processing_status: Option<ProcessingStatus>,
     pub has_attachments: Option<bool>,
@@ -1448,4 +3093,76 @@ pub struct ContextCategoryConnection {
     pub total_count: u32,
 }
 
-// Comment system input types already defined above
\ No newline at end of file
+// Comment system input types already defined above
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user_model() -> crate::entities::user::Model {
+        crate::entities::user::Model {
+            id: Uuid::new_v4(),
+            email: "ada@example.com".to_string(),
+            password_hash: "not-a-real-hash".to_string(),
+            first_name: Some("Ada".to_string()),
+            last_name: Some("Lovelace".to_string()),
+            is_email_verified: true,
+            email_verification_token: None,
+            email_verification_expires_at: None,
+            password_reset_token: None,
+            password_reset_expires_at: None,
+            created_at: Utc::now().into(),
+            updated_at: Utc::now().into(),
+            refresh_token: None,
+            refresh_token_expires_at: None,
+            invitation_token: None,
+            role_id: None,
+            role_valid_from: None,
+            role_valid_until: None,
+            failed_login_attempts: 0,
+            last_failed_login_at: None,
+            locked_until: None,
+            status: UserStatus::Active,
+            status_changed_at: None,
+            magic_link_token: None,
+            magic_link_expires_at: None,
+            password_changed_at: Utc::now().into(),
+            current_organization_id: None,
+            locale: None,
+        }
+    }
+
+    struct TestQuery;
+
+    #[Object]
+    impl TestQuery {
+        /// What `login` returns: the request itself isn't authenticated
+        async fn login(&self) -> AuthPayload {
+            AuthPayload::new(user_model(), "access".to_string(), "refresh".to_string())
+        }
+
+        async fn stranger(&self) -> User {
+            user_model().into()
+        }
+    }
+
+    #[tokio::test]
+    async fn login_payload_shows_the_user_their_own_details() {
+        let schema = Schema::new(TestQuery, EmptyMutation, EmptySubscription);
+        let response = schema
+            .execute("{ login { user { email firstName lastName } } stranger { email firstName lastName } }")
+            .await;
+        assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+        let data = response.data.into_json().expect("json response");
+        assert_eq!(
+            data["login"]["user"],
+            serde_json::json!({ "email": "ada@example.com", "firstName": "Ada", "lastName": "Lovelace" })
+        );
+        // The same record reached without the owner marker stays masked
+        assert_eq!(
+            data["stranger"],
+            serde_json::json!({ "email": "a***@example.com", "firstName": "A.", "lastName": "L." })
+        );
+    }
+}
@@ -1,4 +1,7 @@
 use std::env;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
 
 use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
 use axum::{
@@ -10,7 +13,7 @@ use axum::{
     Extension, Json, Router,
 };
 use dotenvy::dotenv;
-use sea_orm::{Database, DatabaseConnection};
+use sea_orm::DatabaseConnection;
 use tokio::net::TcpListener;
 use tower_http::cors::CorsLayer;
 use tracing::{info, warn};
@@ -22,9 +25,9 @@ mod graphql;
 mod services;
 
 
-use auth::{AuthenticatedUser, JwtService, PermissionService};
+use auth::{rate_limit_middleware, verify_permission_catalog, AuthenticatedUser, ClientIp, JwtService, PermissionCache, PermissionService, RateLimitConfig, RateLimitRule, RateLimiter};
 use graphql::{create_schema, ApiSchema, DataLoaderContext};
-use services::{EmailService, InvitationService, UserService, ProjectService, TaskService, ActivityService, ContextService, EmailContextService};
+use services::{connect_database, with_organization_scope, OrganizationScope, AttachmentStorage, CaptureTransport, EmailOutboxConfig, EmailOutboxService, EmailService, EmailTemplates, EmailTransport, ResendTransport, SmtpConfig, SmtpSecurity, SmtpTransport, InvitationService, UserService, ProjectService, TaskService, ActivityService, CommentService, NotificationService, DigestService, ContextService, EmailContextService, AccessRequestService, OrganizationService, TeamService, WebhookConfig, WebhookService, LoginLockoutPolicy, PasswordPolicy, PasswordPolicyConfig};
 
 #[derive(Clone)]
 struct AppState {
@@ -35,17 +38,26 @@ struct AppState {
     dataloader_context: DataLoaderContext,
     user_service: UserService,
     email_service: EmailService,
+    email_outbox_service: EmailOutboxService,
     invitation_service: InvitationService,
     project_service: ProjectService,
     task_service: TaskService,
     activity_service: ActivityService,
+    comment_service: CommentService,
+    notification_service: NotificationService,
+    digest_service: DigestService,
     context_service: ContextService,
     email_context_service: EmailContextService,
+    access_request_service: AccessRequestService,
+    organization_service: OrganizationService,
+    team_service: TeamService,
+    webhook_service: WebhookService,
+    rate_limiter: RateLimiter,
     frontend_url: String,
 }
 
 async fn optional_auth_middleware(
-    State(jwt_service): State<JwtService>,
+    State(state): State<AppState>,
     mut request: Request,
     next: Next,
 ) -> Result<Response, StatusCode> {
@@ -57,10 +69,41 @@ async fn optional_auth_middleware(
 
     if let Some(token) = auth_header {
         // Try to authenticate
-        match jwt_service.verify_token(token) {
-            Ok(claims) => {
-                let user = AuthenticatedUser::from(claims);
-                request.extensions_mut().insert(Some(user));
+        match state.jwt_service.verify_token(token) {
+            Ok(mut claims) => {
+                // Suspended or deactivated users lose access even with an unexpired token;
+                // an impersonation token also dies with the impersonating admin's account
+                let mut is_active = match state.user_service.is_user_active(claims.sub).await {
+                    Ok(is_active) => is_active,
+                    Err(e) => {
+                        warn!("Failed to check user status: {}", e);
+                        false
+                    }
+                };
+                if let (true, Some(actor)) = (is_active, &claims.act) {
+                    is_active = state.user_service.is_user_active(actor.sub).await.unwrap_or(false);
+                }
+
+                // A token keeps its organization only while the user is still a member
+                if let (true, Some(organization_id)) = (is_active, claims.org) {
+                    let is_member = match state.permission_service.user_in_organization(claims.sub, organization_id).await {
+                        Ok(is_member) => is_member,
+                        Err(e) => {
+                            warn!("Failed to check organization membership: {}", e);
+                            false
+                        }
+                    };
+                    if !is_member {
+                        claims.org = None;
+                    }
+                }
+
+                if is_active {
+                    let user = AuthenticatedUser::from(claims);
+                    request.extensions_mut().insert(Some(user));
+                } else {
+                    request.extensions_mut().insert(None::<AuthenticatedUser>);
+                }
             }
             Err(_) => {
                 // Invalid token - continue without auth
@@ -78,13 +121,19 @@ async fn optional_auth_middleware(
 async fn graphql_handler(
     State(state): State<AppState>,
     Extension(user): Extension<Option<AuthenticatedUser>>,
+    client_ip: Option<Extension<ClientIp>>,
     req: GraphQLRequest,
 ) -> GraphQLResponse {
     let mut request = req.into_inner();
+    let scope = OrganizationScope::for_request(user.as_ref().and_then(|user| user.organization_id));
     
     if let Some(user) = user {
         request = request.data(user);
     }
+
+    if let Some(Extension(client_ip)) = client_ip {
+        request = request.data(client_ip);
+    }
     
     request = request
         .data(state.db.clone())
@@ -92,15 +141,25 @@ async fn graphql_handler(
         .data(state.dataloader_context.clone())
         .data(state.user_service.clone())
         .data(state.email_service.clone())
+        .data(state.email_outbox_service.clone())
         .data(state.invitation_service.clone())
         .data(state.project_service.clone())
         .data(state.task_service.clone())
         .data(state.activity_service.clone())
+        .data(state.comment_service.clone())
+        .data(state.notification_service.clone())
+        .data(state.digest_service.clone())
         .data(state.context_service.clone())
         .data(state.email_context_service.clone())
+        .data(state.access_request_service.clone())
+        .data(state.organization_service.clone())
+        .data(state.team_service.clone())
+        .data(state.webhook_service.clone())
+        .data(state.rate_limiter.clone())
         .data(state.frontend_url.clone());
     
-    state.schema.execute(request).await.into()
+    // Row-level security only shows the request its own organization's rows
+    with_organization_scope(scope, state.schema.execute(request)).await.into()
 }
 
 async fn graphql_playground() -> impl IntoResponse {
@@ -298,16 +357,110 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .parse::<i64>()
         .unwrap_or(24);
     let resend_api_key = env::var("RESEND_API_KEY")
-        .unwrap_or_else(|_| {
-            warn!("RESEND_API_KEY not set, email functionality will not work");
-            "dummy-key".to_string()
-        });
+        .ok()
+        .filter(|key| !key.is_empty() && key != "your-resend-api-key-here");
+    // Without an explicit choice, send through Resend when it's configured and capture otherwise
+    let email_transport_kind = env::var("EMAIL_TRANSPORT")
+        .unwrap_or_else(|_| if resend_api_key.is_some() { "resend" } else { "capture" }.to_string())
+        .to_lowercase();
     let from_email = env::var("FROM_EMAIL")
         .unwrap_or_else(|_| "noreply@freshapi.dev".to_string());
     let cors_origins = env::var("CORS_ALLOWED_ORIGINS")
         .unwrap_or_else(|_| "http://localhost:3000,http://localhost:5173".to_string());
     let frontend_url = env::var("FRONTEND_URL")
         .unwrap_or_else(|_| "http://localhost:5173".to_string());
+    let rate_limit_store = env::var("RATE_LIMIT_STORE")
+        .unwrap_or_else(|_| "memory".to_string());
+    let rate_limit_http_per_minute = env::var("RATE_LIMIT_HTTP_PER_MINUTE")
+        .unwrap_or_else(|_| "300".to_string())
+        .parse::<u32>()
+        .unwrap_or(300);
+    let rate_limit_trusted_proxies = env::var("RATE_LIMIT_TRUSTED_PROXIES")
+        .unwrap_or_else(|_| "0".to_string())
+        .parse::<usize>()
+        .unwrap_or(0);
+    let login_max_failed_attempts = env::var("LOGIN_MAX_FAILED_ATTEMPTS")
+        .unwrap_or_else(|_| "5".to_string())
+        .parse::<i32>()
+        .unwrap_or(5);
+    let login_lockout_minutes = env::var("LOGIN_LOCKOUT_MINUTES")
+        .unwrap_or_else(|_| "15".to_string())
+        .parse::<i64>()
+        .unwrap_or(15);
+    let password_min_length = env::var("PASSWORD_MIN_LENGTH")
+        .unwrap_or_else(|_| "10".to_string())
+        .parse::<usize>()
+        .unwrap_or(10);
+    let password_min_score = env::var("PASSWORD_MIN_SCORE")
+        .unwrap_or_else(|_| "2".to_string())
+        .parse::<u8>()
+        .unwrap_or(2);
+    let password_history_size = env::var("PASSWORD_HISTORY_SIZE")
+        .unwrap_or_else(|_| "5".to_string())
+        .parse::<u64>()
+        .unwrap_or(5);
+    let password_max_age_days = env::var("PASSWORD_MAX_AGE_DAYS")
+        .ok()
+        .and_then(|days| days.parse::<i64>().ok())
+        .filter(|days| *days > 0);
+    let breached_passwords_path = env::var("BREACHED_PASSWORDS_PATH").ok();
+    let email_outbox_poll_seconds = env::var("EMAIL_OUTBOX_POLL_SECONDS")
+        .unwrap_or_else(|_| "5".to_string())
+        .parse::<u64>()
+        .unwrap_or(5)
+        .max(1);
+    let email_outbox_max_attempts = env::var("EMAIL_OUTBOX_MAX_ATTEMPTS")
+        .unwrap_or_else(|_| "8".to_string())
+        .parse::<i32>()
+        .unwrap_or(8)
+        .max(1);
+    let access_expiry_sweep_seconds = env::var("ACCESS_EXPIRY_SWEEP_SECONDS")
+        .unwrap_or_else(|_| "300".to_string())
+        .parse::<u64>()
+        .unwrap_or(300)
+        .max(1);
+    let notification_sweep_seconds = env::var("NOTIFICATION_SWEEP_SECONDS")
+        .unwrap_or_else(|_| "900".to_string())
+        .parse::<u64>()
+        .unwrap_or(900)
+        .max(1);
+    let task_due_soon_hours = env::var("TASK_DUE_SOON_HOURS")
+        .unwrap_or_else(|_| "24".to_string())
+        .parse::<i64>()
+        .unwrap_or(24)
+        .max(0);
+    let digest_sweep_seconds = env::var("DIGEST_SWEEP_SECONDS")
+        .unwrap_or_else(|_| "900".to_string())
+        .parse::<u64>()
+        .unwrap_or(900)
+        .max(1);
+    let webhook_poll_seconds = env::var("WEBHOOK_POLL_SECONDS")
+        .unwrap_or_else(|_| "5".to_string())
+        .parse::<u64>()
+        .unwrap_or(5)
+        .max(1);
+    let webhook_max_attempts = env::var("WEBHOOK_MAX_ATTEMPTS")
+        .unwrap_or_else(|_| "8".to_string())
+        .parse::<i32>()
+        .unwrap_or(8)
+        .max(1);
+    let webhook_timeout_seconds = env::var("WEBHOOK_TIMEOUT_SECONDS")
+        .unwrap_or_else(|_| "10".to_string())
+        .parse::<u64>()
+        .unwrap_or(10)
+        .max(1);
+    let webhook_allow_private_networks = env::var("WEBHOOK_ALLOW_PRIVATE_NETWORKS")
+        .unwrap_or_else(|_| "false".to_string())
+        .parse::<bool>()
+        .unwrap_or(false);
+    let permission_cache_ttl_seconds = env::var("PERMISSION_CACHE_TTL_SECONDS")
+        .unwrap_or_else(|_| "60".to_string())
+        .parse::<u64>()
+        .unwrap_or(60);
+    let organization_rls_enabled = env::var("ORGANIZATION_RLS_ENABLED")
+        .unwrap_or_else(|_| "false".to_string())
+        .parse::<bool>()
+        .unwrap_or(false);
     let host = env::var("HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
     let port = env::var("PORT").unwrap_or_else(|_| "8080".to_string());
     
@@ -320,43 +473,298 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Connect to database
     info!("Connecting to database...");
-    let db = Database::connect(&database_url).await?;
+    let db = connect_database(&database_url, organization_rls_enabled).await?;
+    // Permission resolution is cached per user across organizations, so it reads through
+    // connections that aren't scoped to the request's organization
+    let system_db = if organization_rls_enabled {
+        connect_database(&database_url, false).await?
+    } else {
+        db.clone()
+    };
     info!("Database connected successfully");
 
+    // Refuse to start if the database lacks permissions the guards reference
+    verify_permission_catalog(&db).await?;
+    info!("Permission catalog verified");
 
     // Initialize services
     let jwt_service = JwtService::new(&jwt_secret, jwt_expiration_hours, 30); // 30 days for refresh tokens
-    let permission_service = PermissionService::new(db.clone());
-    let email_service = EmailService::new(&resend_api_key, from_email);
-    let user_service = UserService::new(db.clone(), jwt_service.clone());
-    let invitation_service = InvitationService::new(db.clone(), email_service.clone());
-    let project_service = ProjectService::new(db.clone());
-    let activity_service = ActivityService::new(db.clone());
+    let permission_cache = PermissionCache::new(std::time::Duration::from_secs(permission_cache_ttl_seconds));
+    let permission_service = PermissionService::new(system_db.clone(), permission_cache.clone());
+    let email_transport: Arc<dyn EmailTransport> = match email_transport_kind.as_str() {
+        "resend" => {
+            let api_key = resend_api_key.ok_or("EMAIL_TRANSPORT=resend requires RESEND_API_KEY")?;
+            Arc::new(ResendTransport::new(&api_key))
+        }
+        "smtp" => {
+            let security = env::var("SMTP_SECURITY")
+                .map(|value| SmtpSecurity::parse(&value).ok_or(format!("Unknown SMTP_SECURITY '{}'", value)))
+                .unwrap_or(Ok(SmtpSecurity::StartTls))?;
+            let smtp_config = SmtpConfig {
+                host: env::var("SMTP_HOST").map_err(|_| "EMAIL_TRANSPORT=smtp requires SMTP_HOST")?,
+                port: env::var("SMTP_PORT")
+                    .ok()
+                    .and_then(|port| port.parse::<u16>().ok())
+                    .unwrap_or(security.default_port()),
+                security,
+                credentials: env::var("SMTP_USERNAME")
+                    .ok()
+                    .map(|username| (username, env::var("SMTP_PASSWORD").unwrap_or_default())),
+            };
+            Arc::new(SmtpTransport::new(smtp_config).map_err(|e| format!("Invalid SMTP configuration: {}", e))?)
+        }
+        "capture" => {
+            if environment == "production" {
+                warn!("📧 Emails are captured, not sent; set EMAIL_TRANSPORT to resend or smtp");
+            }
+            Arc::new(CaptureTransport::new(env::var("EMAIL_CAPTURE_DIR").ok().map(PathBuf::from)))
+        }
+        other => return Err(format!("Unknown EMAIL_TRANSPORT '{}'", other).into()),
+    };
+    let email_templates = EmailTemplates::load(env::var("EMAIL_TEMPLATES_DIR").ok().map(PathBuf::from))?;
+    let attachment_storage = AttachmentStorage::new(PathBuf::from(
+        env::var("ATTACHMENT_STORAGE_DIR").unwrap_or_else(|_| "./storage".to_string()),
+    ));
+    let email_service = EmailService::new(email_transport, from_email, Arc::new(email_templates), attachment_storage);
+    info!("📧 Sending email through the {} transport", email_service.transport_name());
+    info!("📧 Email templates loaded for locales: {}", email_service.locales().join(", "));
+    let email_outbox_service = EmailOutboxService::new(
+        db.clone(),
+        email_service.clone(),
+        EmailOutboxConfig {
+            max_attempts: email_outbox_max_attempts,
+            ..EmailOutboxConfig::default()
+        },
+    );
+    let project_service = ProjectService::new(db.clone(), permission_service.clone());
+    let notification_service = NotificationService::new(db.clone(), project_service.clone());
+    let activity_service = ActivityService::new(db.clone(), notification_service.clone());
+    let lockout_policy = LoginLockoutPolicy {
+        max_failed_attempts: login_max_failed_attempts,
+        lockout_minutes: login_lockout_minutes,
+        ..LoginLockoutPolicy::default()
+    };
+    let password_policy = PasswordPolicy::new(PasswordPolicyConfig {
+        min_length: password_min_length,
+        min_strength_score: password_min_score,
+        history_size: password_history_size,
+        max_age_days: password_max_age_days,
+        breached_passwords_path,
+    });
+    let user_service = UserService::new(
+        db.clone(),
+        jwt_service.clone(),
+        email_service.clone(),
+        activity_service.clone(),
+        lockout_policy,
+        password_policy,
+    );
+    let invitation_service = InvitationService::new(
+        db.clone(),
+        email_service.clone(),
+        project_service.clone(),
+        permission_service.clone(),
+    );
     let task_service = TaskService::new(db.clone(), project_service.clone(), activity_service.clone());
-    let context_service = ContextService::new(db.clone());
-    let email_context_service = EmailContextService::new(db.clone());
+    let comment_service = CommentService::new(db.clone(), project_service.clone(), activity_service.clone());
+    let context_service = ContextService::new(db.clone(), project_service.clone());
+    let email_context_service = EmailContextService::new(
+        db.clone(),
+        project_service.clone(),
+        email_service.clone(),
+        activity_service.clone(),
+    );
+    let access_request_service = AccessRequestService::new(db.clone(), activity_service.clone(), permission_service.clone());
+    let organization_service = OrganizationService::new(db.clone(), permission_service.clone());
+    let team_service = TeamService::new(db.clone(), permission_service.clone());
+    let digest_service = DigestService::new(
+        db.clone(),
+        email_service.clone(),
+        project_service.clone(),
+        frontend_url.clone(),
+    );
+    let webhook_service = WebhookService::new(
+        db.clone(),
+        WebhookConfig {
+            max_attempts: webhook_max_attempts,
+            timeout_seconds: webhook_timeout_seconds,
+            allow_private_networks: webhook_allow_private_networks,
+            ..WebhookConfig::default()
+        },
+    );
+
+    // Row-level security backs up the organization filters in service queries
+    if organization_rls_enabled {
+        organization_service.force_row_level_security().await?;
+        info!("🏢 Organization row-level security enforced");
+    }
+
+    // Rate limiting for login, password reset and invitation flows
+    let rate_limit_config = RateLimitConfig {
+        http_per_ip: RateLimitRule::new(rate_limit_http_per_minute, 60),
+        trusted_proxies: rate_limit_trusted_proxies,
+        ..RateLimitConfig::default()
+    };
+    let rate_limiter = match rate_limit_store.as_str() {
+        "postgres" => {
+            info!("🚦 Using Postgres rate limit store");
+            RateLimiter::postgres(db.clone(), rate_limit_config)
+        }
+        _ => {
+            info!("🚦 Using in-memory rate limit store");
+            RateLimiter::in_memory(rate_limit_config)
+        }
+    };
+
+    // Keep replicas' permission caches consistent through Postgres LISTEN/NOTIFY
+    if permission_cache.is_enabled() {
+        permission_cache.spawn_listener(db.clone());
+        info!("🔐 Permission cache enabled ({}s TTL)", permission_cache_ttl_seconds);
+    }
+
+    // Periodically drop expired rate limit entries
+    let purge_rate_limiter = rate_limiter.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(600));
+        loop {
+            interval.tick().await;
+            if let Err(e) = purge_rate_limiter.purge_expired().await {
+                warn!("Failed to purge rate limit entries: {}", e);
+            }
+        }
+    });
+
+    // Periodically expire time-bound permission grants and role assignments
+    let sweep_access_request_service = access_request_service.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(access_expiry_sweep_seconds));
+        loop {
+            interval.tick().await;
+            permission_cache.purge_expired();
+            match sweep_access_request_service.sweep_expired_grants().await {
+                Ok(summary) => {
+                    if summary.expired_permission_grants > 0 || summary.expired_role_assignments > 0 {
+                        info!(
+                            "⏳ Expired {} permission grant(s) and {} role assignment(s)",
+                            summary.expired_permission_grants, summary.expired_role_assignments
+                        );
+                    }
+                }
+                Err(e) => warn!("Failed to sweep expired grants: {}", e),
+            }
+        }
+    });
+
+    // Deliver queued emails, retrying failures with backoff
+    let dispatch_email_outbox_service = email_outbox_service.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(email_outbox_poll_seconds));
+        loop {
+            interval.tick().await;
+            match dispatch_email_outbox_service.dispatch_due().await {
+                Ok(summary) => {
+                    if summary.retried > 0 || summary.dead > 0 {
+                        warn!(
+                            "📧 Sent {} email(s), {} to retry, {} dead-lettered",
+                            summary.sent, summary.retried, summary.dead
+                        );
+                    }
+                }
+                Err(e) => warn!("Failed to dispatch email outbox: {}", e),
+            }
+        }
+    });
+
+    // Notify assignees about tasks coming due or overdue
+    let sweep_notification_service = notification_service.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(notification_sweep_seconds));
+        loop {
+            interval.tick().await;
+            match sweep_notification_service
+                .notify_due_tasks(chrono::Duration::hours(task_due_soon_hours))
+                .await
+            {
+                Ok(summary) => {
+                    if summary.due_soon > 0 || summary.overdue > 0 {
+                        info!(
+                            "🔔 Notified about {} task(s) due soon and {} overdue",
+                            summary.due_soon, summary.overdue
+                        );
+                    }
+                }
+                Err(e) => warn!("Failed to sweep due tasks: {}", e),
+            }
+        }
+    });
+
+    // Email daily and weekly digests as users' local send hours come around
+    let sweep_digest_service = digest_service.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(digest_sweep_seconds));
+        loop {
+            interval.tick().await;
+            match sweep_digest_service.send_due_digests().await {
+                Ok(summary) => {
+                    if summary.sent > 0 || summary.failed > 0 {
+                        info!("📬 Queued {} digest(s), {} failed", summary.sent, summary.failed);
+                    }
+                }
+                Err(e) => warn!("Failed to send digests: {}", e),
+            }
+        }
+    });
+
+    // Deliver queued webhook events, retrying failures with backoff
+    let dispatch_webhook_service = webhook_service.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(webhook_poll_seconds));
+        loop {
+            interval.tick().await;
+            match dispatch_webhook_service.dispatch_due().await {
+                Ok(summary) => {
+                    if summary.retried > 0 || summary.dead > 0 {
+                        warn!(
+                            "🪝 Delivered {} webhook(s), {} to retry, {} dead-lettered",
+                            summary.delivered, summary.retried, summary.dead
+                        );
+                    }
+                }
+                Err(e) => warn!("Failed to dispatch webhooks: {}", e),
+            }
+        }
+    });
 
     // Create GraphQL schema
     let schema = create_schema();
 
     // Create DataLoader context for optimized permission loading
-    let dataloader_context = DataLoaderContext::new(db.clone());
+    let dataloader_context = DataLoaderContext::new(permission_service.clone());
 
     // Application state
     let app_state = AppState {
         schema,
         db,
-        jwt_service: jwt_service.clone(),
+        jwt_service,
         permission_service,
         dataloader_context,
         user_service,
         email_service,
+        email_outbox_service: email_outbox_service.clone(),
         invitation_service,
         project_service,
         task_service,
         activity_service,
+        comment_service,
+        notification_service: notification_service.clone(),
+        digest_service: digest_service.clone(),
         context_service,
         email_context_service,
+        access_request_service,
+        organization_service,
+        team_service,
+        webhook_service,
+        rate_limiter: rate_limiter.clone(),
         frontend_url,
     };
 
@@ -395,9 +803,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .route("/webhooks/email/ingest", post(ingest_email_webhook))
         .layer(cors)
         .layer(middleware::from_fn_with_state(
-            jwt_service,
+            app_state.clone(),
             optional_auth_middleware,
         ))
+        .layer(middleware::from_fn_with_state(
+            rate_limiter,
+            rate_limit_middleware,
+        ))
         .with_state(app_state);
 
     // Start server
@@ -408,7 +820,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     info!("📊 GraphQL Playground available at http://{}/playground", addr);
     info!("🏥 Health check available at http://{}/health", addr);
 
-    axum::serve(listener, app).await?;
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await?;
 
     Ok(())
 }
@@ -0,0 +1,437 @@
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect, QueryTrait,
+    Set, TransactionTrait,
+};
+use uuid::Uuid;
+
+use crate::auth::PermissionService;
+use crate::entities::{access_request, organization_member, prelude::*, user, user_permission};
+use crate::graphql::types::AccessRequestStatus;
+use crate::services::activity::{ActivityService, EntityType, NewActivity};
+
+/// What a single expiry sweep cleaned up
+#[derive(Debug, Default)]
+pub struct ExpirySweepSummary {
+    pub expired_permission_grants: usize,
+    pub expired_role_assignments: usize,
+}
+
+/// Elevated access requests and expiry of time-bound grants
+#[derive(Clone)]
+pub struct AccessRequestService {
+    db: DatabaseConnection,
+    activity_service: ActivityService,
+    permission_service: PermissionService,
+}
+
+impl AccessRequestService {
+    pub fn new(db: DatabaseConnection, activity_service: ActivityService, permission_service: PermissionService) -> Self {
+        Self { db, activity_service, permission_service }
+    }
+
+    pub fn get_db(&self) -> &DatabaseConnection {
+        &self.db
+    }
+
+    /// Ask for a permission or a role, optionally only until `valid_until`
+    pub async fn request_access(
+        &self,
+        user_id: Uuid,
+        permission_id: Option<Uuid>,
+        role_id: Option<Uuid>,
+        reason: &str,
+        valid_until: Option<DateTime<Utc>>,
+    ) -> Result<access_request::Model, Box<dyn std::error::Error>> {
+        if permission_id.is_some() == role_id.is_some() {
+            return Err("Specify exactly one of permissionId or roleId".into());
+        }
+
+        if reason.trim().is_empty() {
+            return Err("A reason is required to request access".into());
+        }
+
+        if valid_until.is_some_and(|until| until <= Utc::now()) {
+            return Err("validUntil must be in the future".into());
+        }
+
+        if let Some(permission_id) = permission_id {
+            Permission::find_by_id(permission_id)
+                .one(&self.db)
+                .await?
+                .filter(|permission| permission.is_active)
+                .ok_or("Permission not found")?;
+        }
+
+        if let Some(role_id) = role_id {
+            Role::find_by_id(role_id)
+                .one(&self.db)
+                .await?
+                .filter(|role| role.is_active)
+                .ok_or("Role not found")?;
+        }
+
+        // One pending request per requested permission or role
+        let mut pending = AccessRequest::find()
+            .filter(access_request::Column::UserId.eq(user_id))
+            .filter(access_request::Column::Status.eq(AccessRequestStatus::Pending));
+        pending = match permission_id {
+            Some(permission_id) => pending.filter(access_request::Column::PermissionId.eq(permission_id)),
+            None => pending.filter(access_request::Column::RoleId.eq(role_id)),
+        };
+        if pending.one(&self.db).await?.is_some() {
+            return Err("You already have a pending request for this access".into());
+        }
+
+        let request = access_request::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            user_id: Set(user_id),
+            permission_id: Set(permission_id),
+            role_id: Set(role_id),
+            previous_role_id: Set(None),
+            reason: Set(reason.trim().to_string()),
+            valid_until: Set(valid_until.map(Into::into)),
+            status: Set(AccessRequestStatus::Pending),
+            reviewed_by: Set(None),
+            review_note: Set(None),
+            reviewed_at: Set(None),
+            created_at: Set(Utc::now().into()),
+            updated_at: Set(Utc::now().into()),
+        }
+        .insert(&self.db)
+        .await?;
+
+        self.activity_service
+            .log_activity(
+                EntityType::User,
+                user_id,
+                user_id,
+                "access_requested",
+                Some("Elevated access requested".to_string()),
+                Some(serde_json::json!({
+                    "access_request_id": request.id,
+                    "permission_id": permission_id,
+                    "role_id": role_id,
+                    "valid_until": valid_until,
+                    "reason": request.reason,
+                })),
+                None,
+            )
+            .await?;
+
+        Ok(request)
+    }
+
+    pub async fn get_access_request(
+        &self,
+        request_id: Uuid,
+    ) -> Result<Option<access_request::Model>, Box<dyn std::error::Error>> {
+        Ok(AccessRequest::find_by_id(request_id).one(&self.db).await?)
+    }
+
+    /// Requests from the organization's members, newest first, optionally filtered by status
+    pub async fn get_access_requests(
+        &self,
+        organization_id: Uuid,
+        status: Option<AccessRequestStatus>,
+        limit: Option<u64>,
+        offset: Option<u64>,
+    ) -> Result<Vec<access_request::Model>, Box<dyn std::error::Error>> {
+        let members = OrganizationMember::find()
+            .select_only()
+            .column(organization_member::Column::UserId)
+            .filter(organization_member::Column::OrganizationId.eq(organization_id))
+            .into_query();
+        let mut query = AccessRequest::find()
+            .filter(access_request::Column::UserId.in_subquery(members))
+            .order_by_desc(access_request::Column::CreatedAt);
+
+        if let Some(status) = status {
+            query = query.filter(access_request::Column::Status.eq(status));
+        }
+
+        Ok(query
+            .limit(limit.unwrap_or(50))
+            .offset(offset.unwrap_or(0))
+            .all(&self.db)
+            .await?)
+    }
+
+    pub async fn get_user_access_requests(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<access_request::Model>, Box<dyn std::error::Error>> {
+        Ok(AccessRequest::find()
+            .filter(access_request::Column::UserId.eq(user_id))
+            .order_by_desc(access_request::Column::CreatedAt)
+            .all(&self.db)
+            .await?)
+    }
+
+    async fn find_pending(&self, request_id: Uuid) -> Result<access_request::Model, Box<dyn std::error::Error>> {
+        let request = AccessRequest::find_by_id(request_id)
+            .one(&self.db)
+            .await?
+            .ok_or("Access request not found")?;
+
+        if request.status != AccessRequestStatus::Pending {
+            return Err(format!("Access request is already {}", request.status.as_str()).into());
+        }
+
+        Ok(request)
+    }
+
+    /// Approve a pending request and grant the access in the same transaction. A requested
+    /// permission is granted within `organization_id`; `valid_until` overrides the requested
+    /// end of access.
+    pub async fn approve_access_request(
+        &self,
+        request_id: Uuid,
+        reviewer_id: Uuid,
+        organization_id: Option<Uuid>,
+        note: Option<String>,
+        valid_until: Option<DateTime<Utc>>,
+    ) -> Result<access_request::Model, Box<dyn std::error::Error>> {
+        let request = self.find_pending(request_id).await?;
+
+        if request.user_id == reviewer_id {
+            return Err("You cannot review your own access request".into());
+        }
+
+        let valid_until = valid_until.or(request.valid_until.map(|until| until.with_timezone(&Utc)));
+        if valid_until.is_some_and(|until| until <= Utc::now()) {
+            return Err("validUntil must be in the future".into());
+        }
+
+        let tx = self.db.begin().await?;
+        let mut previous_role_id = None;
+
+        if let Some(permission_id) = request.permission_id {
+            PermissionService::set_user_permission(&tx, request.user_id, permission_id, organization_id, true, None, valid_until)
+                .await?;
+        } else if let Some(role_id) = request.role_id {
+            let requester = User::find_by_id(request.user_id)
+                .one(&tx)
+                .await?
+                .ok_or("User not found")?;
+            previous_role_id = requester.role_id;
+
+            let mut requester_active: user::ActiveModel = requester.into();
+            requester_active.role_id = Set(Some(role_id));
+            requester_active.role_valid_from = Set(None);
+            requester_active.role_valid_until = Set(valid_until.map(Into::into));
+            requester_active.updated_at = Set(Utc::now().into());
+            requester_active.update(&tx).await?;
+        }
+
+        let mut request_active: access_request::ActiveModel = request.into();
+        request_active.status = Set(AccessRequestStatus::Approved);
+        request_active.valid_until = Set(valid_until.map(Into::into));
+        request_active.previous_role_id = Set(previous_role_id);
+        request_active.reviewed_by = Set(Some(reviewer_id));
+        request_active.review_note = Set(note);
+        request_active.reviewed_at = Set(Some(Utc::now().into()));
+        request_active.updated_at = Set(Utc::now().into());
+        let approved = request_active.update(&tx).await?;
+
+        self.activity_service
+            .log_activity_with(
+                &tx,
+                NewActivity {
+                    entity_type: EntityType::User,
+                    entity_id: approved.user_id,
+                    actor_id: reviewer_id,
+                    action_type: "access_request_approved".to_string(),
+                    description: Some("Elevated access request approved".to_string()),
+                    metadata: Some(serde_json::json!({
+                        "access_request_id": approved.id,
+                        "permission_id": approved.permission_id,
+                        "role_id": approved.role_id,
+                        "valid_until": valid_until,
+                        "note": approved.review_note,
+                    })),
+                    changes: approved.role_id.map(|role_id| {
+                        serde_json::json!({ "role_id": { "old": previous_role_id, "new": role_id } })
+                    }),
+                },
+            )
+            .await?;
+
+        tx.commit().await?;
+        self.permission_service.invalidate_user(approved.user_id).await;
+
+        Ok(approved)
+    }
+
+    pub async fn deny_access_request(
+        &self,
+        request_id: Uuid,
+        reviewer_id: Uuid,
+        note: Option<String>,
+    ) -> Result<access_request::Model, Box<dyn std::error::Error>> {
+        let request = self.find_pending(request_id).await?;
+
+        if request.user_id == reviewer_id {
+            return Err("You cannot review your own access request".into());
+        }
+
+        let mut request_active: access_request::ActiveModel = request.into();
+        request_active.status = Set(AccessRequestStatus::Denied);
+        request_active.reviewed_by = Set(Some(reviewer_id));
+        request_active.review_note = Set(note);
+        request_active.reviewed_at = Set(Some(Utc::now().into()));
+        request_active.updated_at = Set(Utc::now().into());
+        let denied = request_active.update(&self.db).await?;
+
+        self.activity_service
+            .log_activity(
+                EntityType::User,
+                denied.user_id,
+                reviewer_id,
+                "access_request_denied",
+                Some("Elevated access request denied".to_string()),
+                Some(serde_json::json!({
+                    "access_request_id": denied.id,
+                    "permission_id": denied.permission_id,
+                    "role_id": denied.role_id,
+                    "note": denied.review_note,
+                })),
+                None,
+            )
+            .await?;
+
+        Ok(denied)
+    }
+
+    /// Withdraw one of the user's own pending requests
+    pub async fn cancel_access_request(
+        &self,
+        request_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<access_request::Model, Box<dyn std::error::Error>> {
+        let request = self.find_pending(request_id).await?;
+
+        if request.user_id != user_id {
+            return Err("Access request not found".into());
+        }
+
+        let mut request_active: access_request::ActiveModel = request.into();
+        request_active.status = Set(AccessRequestStatus::Cancelled);
+        request_active.updated_at = Set(Utc::now().into());
+
+        Ok(request_active.update(&self.db).await?)
+    }
+
+    /// Remove grants and role assignments whose validity window has ended, logging each
+    /// expiry against the affected user. Reads already ignore expired grants; this keeps
+    /// the tables and the audit trail in step. Expired temporary roles fall back to the
+    /// role held before the access request that granted them.
+    pub async fn sweep_expired_grants(&self) -> Result<ExpirySweepSummary, Box<dyn std::error::Error>> {
+        let now = Utc::now();
+        let mut summary = ExpirySweepSummary::default();
+
+        let expired_grants = UserPermission::find()
+            .filter(user_permission::Column::ValidUntil.lte(now))
+            .find_also_related(Permission)
+            .all(&self.db)
+            .await?;
+
+        for (grant, permission) in expired_grants {
+            let tx = self.db.begin().await?;
+
+            UserPermission::delete_by_id(grant.id).exec(&tx).await?;
+
+            let kind = if grant.is_granted { "grant" } else { "denial" };
+            let action = permission.map(|permission| permission.action);
+            self.activity_service
+                .log_activity_with(
+                    &tx,
+                    NewActivity {
+                        entity_type: EntityType::User,
+                        entity_id: grant.user_id,
+                        actor_id: grant.user_id,
+                        action_type: format!("permission_{}_expired", kind),
+                        description: Some(format!(
+                            "Temporary permission {} expired: {}",
+                            kind,
+                            action.as_deref().unwrap_or("unknown")
+                        )),
+                        metadata: Some(serde_json::json!({
+                            "permission_id": grant.permission_id,
+                            "action": action,
+                            "valid_from": grant.valid_from,
+                            "valid_until": grant.valid_until,
+                            "automated": true,
+                        })),
+                        changes: None,
+                    },
+                )
+                .await?;
+
+            tx.commit().await?;
+            self.permission_service.invalidate_user(grant.user_id).await;
+            summary.expired_permission_grants += 1;
+        }
+
+        let expired_roles = User::find()
+            .filter(user::Column::RoleId.is_not_null())
+            .filter(user::Column::RoleValidUntil.lte(now))
+            .all(&self.db)
+            .await?;
+
+        for expired in expired_roles {
+            let tx = self.db.begin().await?;
+
+            // The approved request that set this exact window, if the role came from one
+            let previous_role_id = AccessRequest::find()
+                .filter(access_request::Column::UserId.eq(expired.id))
+                .filter(access_request::Column::RoleId.eq(expired.role_id))
+                .filter(access_request::Column::Status.eq(AccessRequestStatus::Approved))
+                .filter(access_request::Column::ValidUntil.eq(expired.role_valid_until))
+                .order_by_desc(access_request::Column::ReviewedAt)
+                .one(&tx)
+                .await?
+                .and_then(|request| request.previous_role_id);
+
+            let restored_role_id = match previous_role_id {
+                Some(role_id) => Role::find_by_id(role_id).one(&tx).await?.map(|role| role.id),
+                None => None,
+            };
+
+            let old_role_id = expired.role_id;
+            let role_valid_until = expired.role_valid_until;
+            let mut user_active: user::ActiveModel = expired.into();
+            user_active.role_id = Set(restored_role_id);
+            user_active.role_valid_from = Set(None);
+            user_active.role_valid_until = Set(None);
+            user_active.updated_at = Set(Utc::now().into());
+            let updated = user_active.update(&tx).await?;
+
+            self.activity_service
+                .log_activity_with(
+                    &tx,
+                    NewActivity {
+                        entity_type: EntityType::User,
+                        entity_id: updated.id,
+                        actor_id: updated.id,
+                        action_type: "role_assignment_expired".to_string(),
+                        description: Some("Temporary role assignment expired".to_string()),
+                        metadata: Some(serde_json::json!({
+                            "valid_until": role_valid_until,
+                            "automated": true,
+                        })),
+                        changes: Some(serde_json::json!({
+                            "role_id": { "old": old_role_id, "new": restored_role_id }
+                        })),
+                    },
+                )
+                .await?;
+
+            tx.commit().await?;
+            self.permission_service.invalidate_user(updated.id).await;
+            summary.expired_role_assignments += 1;
+        }
+
+        Ok(summary)
+    }
+}
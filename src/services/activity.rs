@@ -1,15 +1,31 @@
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect, PaginatorTrait, Set,
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect, PaginatorTrait, Set,
 };
 use uuid::Uuid;
 use chrono::Utc;
 use serde_json::Value;
 
 use crate::entities::{prelude::*, activity};
+use crate::services::NotificationService;
+use crate::services::task::watch_for_activity;
+use crate::services::webhook::queue_webhook_event_for_activity;
+
+/// An activity about to be stored
+#[derive(Debug, Clone)]
+pub struct NewActivity {
+    pub entity_type: EntityType,
+    pub entity_id: Uuid,
+    pub actor_id: Uuid,
+    pub action_type: String,
+    pub description: Option<String>,
+    pub metadata: Option<Value>,
+    pub changes: Option<Value>,
+}
 
 #[derive(Clone)]
 pub struct ActivityService {
     db: DatabaseConnection,
+    notification_service: NotificationService,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -42,8 +58,8 @@ impl EntityType {
 }
 
 impl ActivityService {
-    pub fn new(db: DatabaseConnection) -> Self {
-        Self { db }
+    pub fn new(db: DatabaseConnection, notification_service: NotificationService) -> Self {
+        Self { db, notification_service }
     }
 
     pub fn get_db(&self) -> &DatabaseConnection {
@@ -60,21 +76,67 @@ impl ActivityService {
         description: Option<String>,
         metadata: Option<Value>,
         changes: Option<Value>,
+    ) -> Result<activity::Model, Box<dyn std::error::Error>> {
+        self.log_activity_with(
+            &self.db,
+            NewActivity {
+                entity_type,
+                entity_id,
+                actor_id,
+                action_type: action_type.to_string(),
+                description,
+                metadata,
+                changes,
+            },
+        )
+        .await
+    }
+
+    /// Log an activity on a specific connection, e.g. inside a caller's transaction
+    pub async fn log_activity_with<C: ConnectionTrait>(
+        &self,
+        conn: &C,
+        new_activity: NewActivity,
+    ) -> Result<activity::Model, Box<dyn std::error::Error>> {
+        let activity = self.record_activity_with(conn, new_activity).await?;
+        self.publish_activity(conn, &activity).await?;
+        Ok(activity)
+    }
+
+    /// Store an activity without telling anyone yet. For changes that write rows
+    /// referencing the activity before `publish_activity` hands it on.
+    pub async fn record_activity_with<C: ConnectionTrait>(
+        &self,
+        conn: &C,
+        new_activity: NewActivity,
     ) -> Result<activity::Model, Box<dyn std::error::Error>> {
         let new_activity = activity::ActiveModel {
             id: Set(Uuid::new_v4()),
-            entity_type: Set(entity_type.as_str().to_string()),
-            entity_id: Set(entity_id),
-            actor_id: Set(actor_id),
-            action_type: Set(action_type.to_string()),
-            description: Set(description),
-            metadata: Set(metadata),
-            changes: Set(changes),
+            entity_type: Set(new_activity.entity_type.as_str().to_string()),
+            entity_id: Set(new_activity.entity_id),
+            actor_id: Set(new_activity.actor_id),
+            action_type: Set(new_activity.action_type),
+            description: Set(new_activity.description),
+            metadata: Set(new_activity.metadata),
+            changes: Set(new_activity.changes),
             created_at: Set(Utc::now().into()),
         };
 
-        let activity = new_activity.insert(&self.db).await?;
-        Ok(activity)
+        Ok(new_activity.insert(conn).await?)
+    }
+
+    /// Hand a recorded activity on to task watchers, notifications and webhooks
+    pub async fn publish_activity<C: ConnectionTrait>(
+        &self,
+        conn: &C,
+        activity: &activity::Model,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if EntityType::from_str(&activity.entity_type) == Some(EntityType::Task) {
+            watch_for_activity(conn, activity).await?;
+        }
+        self.notification_service.notify_for_activity(conn, activity).await?;
+        queue_webhook_event_for_activity(conn, activity).await?;
+        Ok(())
     }
 
     /// Get activities for any entity type
@@ -102,31 +164,6 @@ impl ActivityService {
         Ok(activities)
     }
 
-    /// Add a comment to any entity
-    pub async fn add_comment(
-        &self,
-        entity_type: EntityType,
-        entity_id: Uuid,
-        actor_id: Uuid,
-        content: &str,
-        mentions: Option<Vec<Uuid>>,
-    ) -> Result<activity::Model, Box<dyn std::error::Error>> {
-        let mentions_json = mentions.map(|m| serde_json::to_value(m).unwrap());
-        
-        self.log_activity(
-            entity_type,
-            entity_id,
-            actor_id,
-            "commented",
-            Some(format!("Added comment: {}", content.chars().take(100).collect::<String>())),
-            Some(serde_json::json!({
-                "comment_content": content,
-                "mentions": mentions_json
-            })),
-            None,
-        ).await
-    }
-
     /// Log task status change
     pub async fn log_task_status_change(
         &self,
@@ -258,23 +295,30 @@ impl ActivityService {
         ).await
     }
 
-    /// Log task deletion
-    pub async fn log_task_deletion(
+    /// Log task deletion. Call it inside the deleting transaction, before the task is
+    /// gone, so the deletion is logged with the task it removed.
+    pub async fn log_task_deletion<C: ConnectionTrait>(
         &self,
+        conn: &C,
         task_id: Uuid,
         actor_id: Uuid,
         task_name: &str,
+        project_id: Uuid,
     ) -> Result<activity::Model, Box<dyn std::error::Error>> {
-        self.log_activity(
-            EntityType::Task,
-            task_id,
-            actor_id,
-            "deleted",
-            Some(format!("Deleted task: {}", task_name)),
-            Some(serde_json::json!({
-                "task_name": task_name
-            })),
-            None,
+        self.log_activity_with(
+            conn,
+            NewActivity {
+                entity_type: EntityType::Task,
+                entity_id: task_id,
+                actor_id,
+                action_type: "deleted".to_string(),
+                description: Some(format!("Deleted task: {}", task_name)),
+                metadata: Some(serde_json::json!({
+                    "task_name": task_name,
+                    "project_id": project_id
+                })),
+                changes: None,
+            },
         ).await
     }
 
@@ -0,0 +1,41 @@
+use std::path::{Component, Path, PathBuf};
+
+use crate::services::EmailTransportError;
+
+/// Files referenced by `email_attachment.storage_path`, kept under one root directory
+/// (`ATTACHMENT_STORAGE_DIR`)
+#[derive(Debug, Clone)]
+pub struct AttachmentStorage {
+    root: PathBuf,
+}
+
+impl AttachmentStorage {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// Location of a stored file. Relative paths are taken from the root; absolute ones
+    /// and `..` segments must not lead outside of it.
+    pub fn resolve(&self, storage_path: &str) -> Result<PathBuf, EmailTransportError> {
+        let path = Path::new(storage_path);
+        let relative = if path.is_absolute() {
+            path.strip_prefix(&self.root)
+                .map_err(|_| format!("Attachment {} is outside of the attachment storage", storage_path))?
+        } else {
+            path
+        };
+
+        if !relative.components().all(|component| matches!(component, Component::Normal(_) | Component::CurDir)) {
+            return Err(format!("Attachment {} is outside of the attachment storage", storage_path).into());
+        }
+
+        Ok(self.root.join(relative))
+    }
+
+    pub async fn read(&self, storage_path: &str) -> Result<Vec<u8>, EmailTransportError> {
+        let path = self.resolve(storage_path)?;
+        tokio::fs::read(&path)
+            .await
+            .map_err(|e| format!("Failed to read attachment {}: {}", storage_path, e).into())
+    }
+}
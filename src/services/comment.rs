@@ -0,0 +1,515 @@
+use std::collections::BTreeSet;
+
+use chrono::Utc;
+use pulldown_cmark::{html, Options, Parser};
+use sea_orm::sea_query::{Expr, Func, OnConflict};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, JoinType, PaginatorTrait, QueryFilter, QueryOrder,
+    QuerySelect, RelationTrait, Set, TransactionTrait,
+};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::entities::{
+    activity, activity_comment, activity_comment_reaction, activity_comment_revision, prelude::*, user,
+};
+use crate::graphql::types::{CommentConnection, CommentReaction};
+use crate::services::activity::{ActivityService, EntityType, NewActivity};
+use crate::services::{ProjectAction, ProjectService};
+
+/// Longest comment accepted, in characters
+const MAX_COMMENT_LENGTH: usize = 10_000;
+
+/// Longest reaction accepted, in bytes; room for joined emoji sequences
+const MAX_REACTION_BYTES: usize = 32;
+
+/// Render comment markdown to HTML that is safe to put on a page
+pub fn render_comment_markdown(content: &str) -> String {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+    options.insert(Options::ENABLE_TABLES);
+
+    let mut unsafe_html = String::new();
+    html::push_html(&mut unsafe_html, Parser::new_ext(content, options));
+    ammonia::clean(&unsafe_html)
+}
+
+/// Email addresses mentioned as `@jane@example.com` in a comment, lowercased
+fn written_mentions(content: &str) -> BTreeSet<String> {
+    content
+        .split_whitespace()
+        .filter_map(|word| word.strip_prefix('@'))
+        .map(|handle| handle.trim_end_matches(|c: char| !c.is_alphanumeric()).to_lowercase())
+        .filter(|handle| handle.contains('@'))
+        .collect()
+}
+
+/// Activity feed line for a comment
+fn describe(content: &str, is_reply: bool) -> String {
+    let excerpt: String = content.chars().take(100).collect();
+    if is_reply {
+        format!("Replied to a comment: {}", excerpt)
+    } else {
+        format!("Added comment: {}", excerpt)
+    }
+}
+
+#[derive(Clone)]
+pub struct CommentService {
+    db: DatabaseConnection,
+    project_service: ProjectService,
+    activity_service: ActivityService,
+}
+
+impl CommentService {
+    pub fn new(db: DatabaseConnection, project_service: ProjectService, activity_service: ActivityService) -> Self {
+        Self { db, project_service, activity_service }
+    }
+
+    /// Comment on an entity, or reply to one of its comments. The comment is logged as a
+    /// `commented` activity, which notifies mentioned users and watchers.
+    pub async fn add_comment(
+        &self,
+        entity_type: EntityType,
+        entity_id: Uuid,
+        author_id: Uuid,
+        content: &str,
+        mentions: Vec<Uuid>,
+        parent_comment_id: Option<Uuid>,
+    ) -> Result<activity_comment::Model, Box<dyn std::error::Error>> {
+        let content = Self::validate_content(content)?;
+
+        if let Some(parent_comment_id) = parent_comment_id {
+            let (parent, parent_activity) = self
+                .get_comment_with_activity(parent_comment_id)
+                .await?
+                .ok_or("Comment not found")?;
+            if parent_activity.entity_type != entity_type.as_str() || parent_activity.entity_id != entity_id {
+                return Err("A reply must be on the same item as the comment it answers".into());
+            }
+            if parent.deleted_at.is_some() {
+                return Err("Cannot reply to a deleted comment".into());
+            }
+        }
+
+        let project_id = self.entity_project_id(&entity_type, entity_id).await?;
+        let mentions = self.resolve_mentions(project_id, &content, mentions).await?;
+        let comment_id = Uuid::new_v4();
+
+        // The comment has to exist before the activity is published, since webhooks send it
+        let txn = self.db.begin().await?;
+        let activity = self
+            .activity_service
+            .record_activity_with(
+                &txn,
+                NewActivity {
+                    entity_type,
+                    entity_id,
+                    actor_id: author_id,
+                    action_type: "commented".to_string(),
+                    description: Some(describe(&content, parent_comment_id.is_some())),
+                    metadata: Some(json!({
+                        "comment_id": comment_id,
+                        "parent_comment_id": parent_comment_id,
+                        "mentions": mentions
+                    })),
+                    changes: None,
+                },
+            )
+            .await?;
+
+        let comment = activity_comment::ActiveModel {
+            id: Set(comment_id),
+            activity_id: Set(activity.id),
+            author_id: Set(author_id),
+            parent_comment_id: Set(parent_comment_id),
+            content_html: Set(Some(render_comment_markdown(&content))),
+            content: Set(content),
+            mentions: Set(Some(json!(mentions))),
+            attachments: Set(None),
+            edited_at: Set(None),
+            deleted_at: Set(None),
+            deleted_by: Set(None),
+            created_at: Set(Utc::now().into()),
+            updated_at: Set(Utc::now().into()),
+        }
+        .insert(&txn)
+        .await?;
+
+        self.activity_service.publish_activity(&txn, &activity).await?;
+        txn.commit().await?;
+        Ok(comment)
+    }
+
+    /// Change your own comment, keeping the previous version in its history. Without
+    /// `mentions` the comment keeps the users it already mentioned.
+    pub async fn edit_comment(
+        &self,
+        comment_id: Uuid,
+        user_id: Uuid,
+        content: &str,
+        mentions: Option<Vec<Uuid>>,
+    ) -> Result<activity_comment::Model, Box<dyn std::error::Error>> {
+        let (comment, activity) = self.get_comment_with_activity(comment_id).await?.ok_or("Comment not found")?;
+        if comment.author_id != user_id {
+            return Err("Only the author can edit a comment".into());
+        }
+        if comment.deleted_at.is_some() {
+            return Err("Cannot edit a deleted comment".into());
+        }
+
+        let content = Self::validate_content(content)?;
+        let entity_type = EntityType::from_str(&activity.entity_type).ok_or("Unknown comment target")?;
+        let project_id = self.entity_project_id(&entity_type, activity.entity_id).await?;
+        let mentions = mentions.unwrap_or_else(|| Self::mention_ids(&comment));
+        let mentions = self.resolve_mentions(project_id, &content, mentions).await?;
+
+        let txn = self.db.begin().await?;
+        activity_comment_revision::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            comment_id: Set(comment.id),
+            content: Set(comment.content.clone()),
+            content_html: Set(comment.content_html.clone()),
+            edited_by: Set(Some(user_id)),
+            created_at: Set(Utc::now().into()),
+        }
+        .insert(&txn)
+        .await?;
+
+        let is_reply = comment.parent_comment_id.is_some();
+        let mut comment_active: activity_comment::ActiveModel = comment.into();
+        comment_active.content_html = Set(Some(render_comment_markdown(&content)));
+        comment_active.content = Set(content.clone());
+        comment_active.mentions = Set(Some(json!(mentions)));
+        comment_active.edited_at = Set(Some(Utc::now().into()));
+        comment_active.updated_at = Set(Utc::now().into());
+        let comment = comment_active.update(&txn).await?;
+
+        // Keep the feed line in step with what the comment now says
+        let mut activity_active: activity::ActiveModel = activity.into();
+        activity_active.description = Set(Some(describe(&content, is_reply)));
+        activity_active.update(&txn).await?;
+
+        txn.commit().await?;
+        Ok(comment)
+    }
+
+    /// Hide a comment's content while keeping its place in the thread. Authors can delete
+    /// their own comments; project member managers can delete any on their project.
+    pub async fn delete_comment(
+        &self,
+        comment_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<activity_comment::Model, Box<dyn std::error::Error>> {
+        let (comment, activity) = self.get_comment_with_activity(comment_id).await?.ok_or("Comment not found")?;
+        if comment.deleted_at.is_some() {
+            return Err("Comment is already deleted".into());
+        }
+
+        if comment.author_id != user_id {
+            let project_id = match EntityType::from_str(&activity.entity_type) {
+                Some(entity_type) => self.entity_project_id(&entity_type, activity.entity_id).await?,
+                None => None,
+            };
+            let can_moderate = match project_id {
+                Some(project_id) => {
+                    self.project_service
+                        .can_user_perform(project_id, user_id, ProjectAction::MemberManage)
+                        .await?
+                }
+                None => false,
+            };
+            if !can_moderate {
+                return Err("Only the author or a project manager can delete this comment".into());
+            }
+        }
+
+        let txn = self.db.begin().await?;
+        let mut comment_active: activity_comment::ActiveModel = comment.into();
+        comment_active.deleted_at = Set(Some(Utc::now().into()));
+        comment_active.deleted_by = Set(Some(user_id));
+        comment_active.updated_at = Set(Utc::now().into());
+        let comment = comment_active.update(&txn).await?;
+
+        // The feed line quotes the comment, so it goes too
+        let mut activity_active: activity::ActiveModel = activity.into();
+        activity_active.description = Set(Some("Deleted a comment".to_string()));
+        activity_active.update(&txn).await?;
+
+        txn.commit().await?;
+        Ok(comment)
+    }
+
+    /// React to a comment with an emoji; reacting twice with the same one changes nothing
+    pub async fn add_reaction(
+        &self,
+        comment_id: Uuid,
+        user_id: Uuid,
+        emoji: &str,
+    ) -> Result<activity_comment::Model, Box<dyn std::error::Error>> {
+        let emoji = Self::validate_emoji(emoji)?;
+        let comment = self.get_comment(comment_id).await?.ok_or("Comment not found")?;
+        if comment.deleted_at.is_some() {
+            return Err("Cannot react to a deleted comment".into());
+        }
+
+        ActivityCommentReaction::insert(activity_comment_reaction::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            comment_id: Set(comment.id),
+            user_id: Set(user_id),
+            emoji: Set(emoji),
+            created_at: Set(Utc::now().into()),
+        })
+        .on_conflict(
+            OnConflict::columns([
+                activity_comment_reaction::Column::CommentId,
+                activity_comment_reaction::Column::UserId,
+                activity_comment_reaction::Column::Emoji,
+            ])
+            .do_nothing()
+            .to_owned(),
+        )
+        .exec_without_returning(&self.db)
+        .await?;
+
+        Ok(comment)
+    }
+
+    pub async fn remove_reaction(
+        &self,
+        comment_id: Uuid,
+        user_id: Uuid,
+        emoji: &str,
+    ) -> Result<activity_comment::Model, Box<dyn std::error::Error>> {
+        let comment = self.get_comment(comment_id).await?.ok_or("Comment not found")?;
+
+        ActivityCommentReaction::delete_many()
+            .filter(activity_comment_reaction::Column::CommentId.eq(comment.id))
+            .filter(activity_comment_reaction::Column::UserId.eq(user_id))
+            .filter(activity_comment_reaction::Column::Emoji.eq(emoji.trim()))
+            .exec(&self.db)
+            .await?;
+
+        Ok(comment)
+    }
+
+    /// Top-level comments on an entity, oldest first. Deleted ones stay so their replies
+    /// keep a parent.
+    pub async fn list_comments(
+        &self,
+        entity_type: EntityType,
+        entity_id: Uuid,
+        limit: Option<u64>,
+        offset: Option<u64>,
+    ) -> Result<CommentConnection, Box<dyn std::error::Error>> {
+        let query = ActivityComment::find()
+            .join(JoinType::InnerJoin, activity_comment::Relation::Activity.def())
+            .filter(activity::Column::EntityType.eq(entity_type.as_str()))
+            .filter(activity::Column::EntityId.eq(entity_id))
+            .filter(activity_comment::Column::ParentCommentId.is_null());
+
+        let total_count = query.clone().count(&self.db).await?;
+        let comments = query
+            .order_by_asc(activity_comment::Column::CreatedAt)
+            .limit(limit.unwrap_or(50))
+            .offset(offset.unwrap_or(0))
+            .all(&self.db)
+            .await?;
+
+        Ok(CommentConnection {
+            edges: comments.into_iter().map(|comment| comment.into()).collect(),
+            total_count: total_count as u32,
+        })
+    }
+
+    /// Direct replies to a comment, oldest first
+    pub async fn get_replies(&self, comment_id: Uuid) -> Result<Vec<activity_comment::Model>, Box<dyn std::error::Error>> {
+        let replies = ActivityComment::find()
+            .filter(activity_comment::Column::ParentCommentId.eq(comment_id))
+            .order_by_asc(activity_comment::Column::CreatedAt)
+            .all(&self.db)
+            .await?;
+        Ok(replies)
+    }
+
+    /// Reactions on a comment grouped by emoji, in the order each emoji was first used
+    pub async fn get_reactions(
+        &self,
+        comment_id: Uuid,
+        viewer_id: Uuid,
+    ) -> Result<Vec<CommentReaction>, Box<dyn std::error::Error>> {
+        let reactions = ActivityCommentReaction::find()
+            .filter(activity_comment_reaction::Column::CommentId.eq(comment_id))
+            .order_by_asc(activity_comment_reaction::Column::CreatedAt)
+            .all(&self.db)
+            .await?;
+
+        let mut grouped: Vec<CommentReaction> = Vec::new();
+        for reaction in reactions {
+            let index = match grouped.iter().position(|group| group.emoji == reaction.emoji) {
+                Some(index) => index,
+                None => {
+                    grouped.push(CommentReaction {
+                        emoji: reaction.emoji.clone(),
+                        count: 0,
+                        user_ids: Vec::new(),
+                        reacted_by_me: false,
+                    });
+                    grouped.len() - 1
+                }
+            };
+            let group = &mut grouped[index];
+            group.count += 1;
+            group.reacted_by_me |= reaction.user_id == viewer_id;
+            group.user_ids.push(reaction.user_id);
+        }
+        Ok(grouped)
+    }
+
+    /// Earlier versions of a comment, oldest first
+    pub async fn get_revisions(
+        &self,
+        comment_id: Uuid,
+    ) -> Result<Vec<activity_comment_revision::Model>, Box<dyn std::error::Error>> {
+        let revisions = ActivityCommentRevision::find()
+            .filter(activity_comment_revision::Column::CommentId.eq(comment_id))
+            .order_by_asc(activity_comment_revision::Column::CreatedAt)
+            .all(&self.db)
+            .await?;
+        Ok(revisions)
+    }
+
+    pub async fn get_comment(&self, comment_id: Uuid) -> Result<Option<activity_comment::Model>, Box<dyn std::error::Error>> {
+        Ok(ActivityComment::find_by_id(comment_id).one(&self.db).await?)
+    }
+
+    /// The comment logged as an activity, if the activity is one
+    pub async fn get_activity_comment(
+        &self,
+        activity_id: Uuid,
+    ) -> Result<Option<activity_comment::Model>, Box<dyn std::error::Error>> {
+        let comment = ActivityComment::find()
+            .filter(activity_comment::Column::ActivityId.eq(activity_id))
+            .one(&self.db)
+            .await?;
+        Ok(comment)
+    }
+
+    /// What a comment was made on, for access checks
+    pub async fn comment_target(&self, comment_id: Uuid) -> Result<Option<(EntityType, Uuid)>, Box<dyn std::error::Error>> {
+        Ok(self
+            .get_comment_with_activity(comment_id)
+            .await?
+            .and_then(|(_, activity)| Some((EntityType::from_str(&activity.entity_type)?, activity.entity_id))))
+    }
+
+    /// Project a comment belongs to, through the task or project it was made on
+    pub async fn comment_project_id(&self, activity_id: Uuid) -> Result<Option<Uuid>, Box<dyn std::error::Error>> {
+        let Some(activity) = Activity::find_by_id(activity_id).one(&self.db).await? else {
+            return Ok(None);
+        };
+        match EntityType::from_str(&activity.entity_type) {
+            Some(entity_type) => self.entity_project_id(&entity_type, activity.entity_id).await,
+            None => Ok(None),
+        }
+    }
+
+    async fn get_comment_with_activity(
+        &self,
+        comment_id: Uuid,
+    ) -> Result<Option<(activity_comment::Model, activity::Model)>, Box<dyn std::error::Error>> {
+        let comment = ActivityComment::find_by_id(comment_id)
+            .find_also_related(Activity)
+            .one(&self.db)
+            .await?;
+        Ok(comment.and_then(|(comment, activity)| Some((comment, activity?))))
+    }
+
+    async fn entity_project_id(
+        &self,
+        entity_type: &EntityType,
+        entity_id: Uuid,
+    ) -> Result<Option<Uuid>, Box<dyn std::error::Error>> {
+        match entity_type {
+            EntityType::Task => Ok(Task::find_by_id(entity_id).one(&self.db).await?.map(|task| task.project_id)),
+            EntityType::Project => Ok(Some(entity_id)),
+            EntityType::User | EntityType::Settings => Ok(None),
+        }
+    }
+
+    /// Mentioned users: those passed explicitly, which must be project members, plus
+    /// project members written as `@email` in the content. Only comments on a task or
+    /// project can mention anyone.
+    async fn resolve_mentions(
+        &self,
+        project_id: Option<Uuid>,
+        content: &str,
+        explicit: Vec<Uuid>,
+    ) -> Result<Vec<Uuid>, Box<dyn std::error::Error>> {
+        let Some(project_id) = project_id else {
+            if explicit.is_empty() {
+                return Ok(Vec::new());
+            }
+            return Err("Only comments on tasks and projects can mention users".into());
+        };
+
+        let mut mentions: Vec<Uuid> = Vec::new();
+        for user_id in explicit {
+            if mentions.contains(&user_id) {
+                continue;
+            }
+            if !self.project_service.is_project_member(project_id, user_id).await? {
+                return Err("Mentioned users must be project members".into());
+            }
+            mentions.push(user_id);
+        }
+
+        let written = written_mentions(content);
+        if !written.is_empty() {
+            let users = User::find()
+                .filter(Expr::expr(Func::lower(Expr::col(user::Column::Email))).is_in(written))
+                .all(&self.db)
+                .await?;
+            // Writing an outsider's address mentions nobody; it stays plain text
+            for user in users {
+                if !mentions.contains(&user.id) && self.project_service.is_project_member(project_id, user.id).await? {
+                    mentions.push(user.id);
+                }
+            }
+        }
+
+        Ok(mentions)
+    }
+
+    fn mention_ids(comment: &activity_comment::Model) -> Vec<Uuid> {
+        comment
+            .mentions
+            .as_ref()
+            .and_then(|mentions| serde_json::from_value(mentions.clone()).ok())
+            .unwrap_or_default()
+    }
+
+    fn validate_content(content: &str) -> Result<String, Box<dyn std::error::Error>> {
+        let content = content.trim();
+        if content.is_empty() {
+            return Err("Comment cannot be empty".into());
+        }
+        if content.chars().count() > MAX_COMMENT_LENGTH {
+            return Err(format!("Comment cannot be longer than {} characters", MAX_COMMENT_LENGTH).into());
+        }
+        Ok(content.to_string())
+    }
+
+    /// A single emoji or emoji sequence, not text
+    fn validate_emoji(emoji: &str) -> Result<String, Box<dyn std::error::Error>> {
+        let emoji = emoji.trim();
+        if emoji.is_empty()
+            || emoji.len() > MAX_REACTION_BYTES
+            || emoji.chars().any(char::is_whitespace)
+            || emoji.is_ascii()
+        {
+            return Err("Reaction must be an emoji".into());
+        }
+        Ok(emoji.to_string())
+    }
+}
@@ -11,21 +11,49 @@ use crate::graphql::types::{
     CreateContextCategoryInput, UpdateContextCategoryInput,
     ContextFilters, ContextConnection
 };
+use crate::services::{ProjectAction, ProjectService};
 
 #[derive(Clone)]
 pub struct ContextService {
     db: DatabaseConnection,
+    project_service: ProjectService,
 }
 
 impl ContextService {
-    pub fn new(db: DatabaseConnection) -> Self {
-        Self { db }
+    pub fn new(db: DatabaseConnection, project_service: ProjectService) -> Self {
+        Self { db, project_service }
     }
 
     pub fn get_db(&self) -> &DatabaseConnection {
         &self.db
     }
 
+    /// Project-level authorization, evaluated by `ProjectService::can_user_perform`
+    pub async fn authorize(&self, project_id: Uuid, user_id: Uuid, action: ProjectAction) -> Result<()> {
+        let allowed = self
+            .project_service
+            .can_user_perform(project_id, user_id, action)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to check project permissions: {}", e))?;
+
+        if !allowed {
+            return Err(anyhow::anyhow!(
+                "Insufficient permissions: '{}' is required on this project",
+                action.as_str()
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Organization owning the project; categories are stored under the same one
+    pub async fn project_organization_id(&self, project_id: Uuid) -> Result<Uuid> {
+        self.project_service
+            .project_organization_id(project_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Project not found"))
+    }
+
     // Context Type Management
     pub async fn get_context_types(&self, active_only: bool) -> Result<Vec<context_type::Model>> {
         let mut query = ContextType::find();
@@ -50,8 +78,10 @@ impl ContextService {
     pub async fn create_context_category(
         &self,
         input: CreateContextCategoryInput,
-        created_by: Option<Uuid>,
+        created_by: Uuid,
     ) -> Result<project_context_category::Model> {
+        self.authorize(input.project_id, created_by, ProjectAction::ContextCreate).await?;
+
         // Get context type by name
         let context_type = self.get_context_type_by_name(&input.context_type_name)
             .await?
@@ -73,15 +103,18 @@ impl ContextService {
             ));
         }
 
+        let organization_id = self.project_organization_id(input.project_id).await?;
+
         let category = project_context_category::ActiveModel {
             id: Set(Uuid::new_v4()),
             project_id: Set(input.project_id),
+            organization_id: Set(organization_id),
             context_type_id: Set(context_type.id),
             name: Set(input.name),
             color: Set(input.color.unwrap_or_else(|| "#6366f1".to_string())),
             description: Set(input.description),
             is_active: Set(true),
-            created_by: Set(created_by),
+            created_by: Set(Some(created_by)),
             created_at: Set(Utc::now().into()),
             updated_at: Set(Utc::now().into()),
         };
@@ -92,12 +125,15 @@ impl ContextService {
     pub async fn update_context_category(
         &self,
         input: UpdateContextCategoryInput,
+        user_id: Uuid,
     ) -> Result<project_context_category::Model> {
         let category = ProjectContextCategory::find_by_id(input.category_id)
             .one(&self.db)
             .await?
             .ok_or_else(|| anyhow::anyhow!("Category not found"))?;
 
+        self.authorize(category.project_id, user_id, ProjectAction::ContextUpdate).await?;
+
         let mut category: project_context_category::ActiveModel = category.into();
 
         if let Some(name) = input.name {
@@ -135,13 +171,15 @@ impl ContextService {
         category.update(&self.db).await.map_err(Into::into)
     }
 
-    pub async fn delete_context_category(&self, category_id: Uuid) -> Result<()> {
+    pub async fn delete_context_category(&self, category_id: Uuid, user_id: Uuid) -> Result<()> {
         // Soft delete by marking as inactive
         let category = ProjectContextCategory::find_by_id(category_id)
             .one(&self.db)
             .await?
             .ok_or_else(|| anyhow::anyhow!("Category not found"))?;
 
+        self.authorize(category.project_id, user_id, ProjectAction::ContextArchive).await?;
+
         let mut category: project_context_category::ActiveModel = category.into();
         category.is_active = Set(false);
         category.updated_at = Set(Utc::now().into());
@@ -154,7 +192,10 @@ impl ContextService {
         &self,
         project_id: Uuid,
         context_type_name: Option<String>,
+        user_id: Uuid,
     ) -> Result<Vec<project_context_category::Model>> {
+        self.authorize(project_id, user_id, ProjectAction::ProjectView).await?;
+
         let mut query = ProjectContextCategory::find()
             .filter(project_context_category::Column::ProjectId.eq(project_id))
             .filter(project_context_category::Column::IsActive.eq(true));
@@ -179,7 +220,10 @@ impl ContextService {
         filters: Option<ContextFilters>,
         limit: Option<u64>,
         offset: Option<u64>,
+        user_id: Uuid,
     ) -> Result<ContextConnection> {
+        self.authorize(project_id, user_id, ProjectAction::ProjectView).await?;
+
         let mut query = ProjectContext::find()
             .filter(project_context::Column::ProjectId.eq(project_id));
 
@@ -255,12 +299,14 @@ impl ContextService {
             .map_err(Into::into)
     }
 
-    pub async fn archive_context(&self, context_id: Uuid) -> Result<project_context::Model> {
+    pub async fn archive_context(&self, context_id: Uuid, user_id: Uuid) -> Result<project_context::Model> {
         let context = ProjectContext::find_by_id(context_id)
             .one(&self.db)
             .await?
             .ok_or_else(|| anyhow::anyhow!("Context not found"))?;
 
+        self.authorize(context.project_id, user_id, ProjectAction::ContextArchive).await?;
+
         let mut context: project_context::ActiveModel = context.into();
         context.is_archived = Set(true);
         context.updated_at = Set(Utc::now().into());
@@ -268,12 +314,14 @@ impl ContextService {
         context.update(&self.db).await.map_err(Into::into)
     }
 
-    pub async fn restore_context(&self, context_id: Uuid) -> Result<project_context::Model> {
+    pub async fn restore_context(&self, context_id: Uuid, user_id: Uuid) -> Result<project_context::Model> {
         let context = ProjectContext::find_by_id(context_id)
             .one(&self.db)
             .await?
             .ok_or_else(|| anyhow::anyhow!("Context not found"))?;
 
+        self.authorize(context.project_id, user_id, ProjectAction::ContextArchive).await?;
+
         let mut context: project_context::ActiveModel = context.into();
         context.is_archived = Set(false);
         context.updated_at = Set(Utc::now().into());
@@ -0,0 +1,535 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Timelike, Utc};
+use chrono_tz::Tz;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect, RelationTrait, Set,
+    TransactionTrait, TryIntoModel,
+};
+use sea_orm::sea_query::JoinType;
+use serde::Serialize;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::entities::{prelude::*, digest_preference, email_context, notification, project, project_context, task, user};
+use crate::graphql::types::{
+    AccountingProcess, DigestFrequency, DigestPreferences, EmailDirection, NotificationType, TaskStatus, UpdateDigestPreferencesInput,
+    UserStatus,
+};
+use crate::services::{email_style_for_user, queue_email, EmailService, ProjectService};
+
+const DEFAULT_TIMEZONE: &str = "UTC";
+const DEFAULT_SEND_HOUR: i32 = 8;
+/// Unread mentions listed in one digest
+const MAX_MENTIONS: u64 = 10;
+/// Email subjects listed per accounting process; the rest are only counted
+const MAX_EMAILS_PER_PROCESS: usize = 5;
+
+/// A task line in a digest
+#[derive(Debug, Clone, Serialize)]
+pub struct DigestTask {
+    pub name: String,
+    pub project_name: String,
+    /// Local date, `YYYY-MM-DD`
+    pub due_date: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DigestMention {
+    pub title: String,
+    pub body: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DigestEmail {
+    pub subject: String,
+    pub project_name: String,
+}
+
+/// Newly ingested emails for one accounting process
+#[derive(Debug, Clone, Serialize)]
+pub struct DigestEmailGroup {
+    /// `AccountingProcess::as_str`
+    pub process: String,
+    pub count: usize,
+    pub emails: Vec<DigestEmail>,
+}
+
+/// Everything in one user's digest, as handed to the digest templates
+#[derive(Debug, Clone, Serialize)]
+pub struct Digest {
+    /// `daily` or `weekly`
+    pub frequency: String,
+    /// The day, or first day of the week, the digest covers
+    pub date: String,
+    pub overdue: Vec<DigestTask>,
+    pub due_today: Vec<DigestTask>,
+    pub due_this_week: Vec<DigestTask>,
+    pub new_assignments: Vec<DigestTask>,
+    pub mentions: Vec<DigestMention>,
+    pub email_count: usize,
+    pub emails: Vec<DigestEmailGroup>,
+}
+
+impl Digest {
+    pub fn is_empty(&self) -> bool {
+        self.overdue.is_empty()
+            && self.due_today.is_empty()
+            && self.due_this_week.is_empty()
+            && self.new_assignments.is_empty()
+            && self.mentions.is_empty()
+            && self.emails.is_empty()
+    }
+}
+
+/// Counts from one digest run
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DigestSweepSummary {
+    pub sent: usize,
+    /// Due, but with nothing to report
+    pub empty: usize,
+    pub failed: usize,
+}
+
+/// A user's digest settings, with the defaults filled in for users without a row
+#[derive(Debug, Clone)]
+struct DigestSettings {
+    frequency: DigestFrequency,
+    timezone: Tz,
+    send_hour: u32,
+    quiet_hours: Option<(u32, u32)>,
+    last_sent_at: Option<DateTime<Utc>>,
+}
+
+impl DigestSettings {
+    fn from_preference(preference: Option<&digest_preference::Model>) -> Self {
+        let Some(preference) = preference else {
+            return Self {
+                frequency: DigestFrequency::Daily,
+                timezone: Tz::UTC,
+                send_hour: DEFAULT_SEND_HOUR as u32,
+                quiet_hours: None,
+                last_sent_at: None,
+            };
+        };
+
+        Self {
+            frequency: preference.frequency,
+            timezone: preference.timezone.parse().unwrap_or(Tz::UTC),
+            send_hour: preference.send_hour.clamp(0, 23) as u32,
+            quiet_hours: preference
+                .quiet_hours_start
+                .zip(preference.quiet_hours_end)
+                .map(|(start, end)| (start.clamp(0, 23) as u32, end.clamp(0, 23) as u32)),
+            last_sent_at: preference.last_sent_at.map(|sent_at| sent_at.into()),
+        }
+    }
+
+    /// Quiet hours run from start up to, not including, end and may wrap past midnight
+    fn is_quiet(&self, hour: u32) -> bool {
+        match self.quiet_hours {
+            Some((start, end)) if start < end => hour >= start && hour < end,
+            Some((start, end)) if start > end => hour >= start || hour < end,
+            _ => false,
+        }
+    }
+}
+
+/// Local calendar boundaries a digest is built around, as UTC instants
+struct DigestPeriod {
+    /// Start of the day (daily) or week (weekly) the digest is for
+    date: NaiveDate,
+    start: DateTime<Utc>,
+    today_start: DateTime<Utc>,
+    tomorrow_start: DateTime<Utc>,
+    week_end: DateTime<Utc>,
+}
+
+impl DigestPeriod {
+    fn new(settings: &DigestSettings, now: DateTime<Utc>) -> Self {
+        let tz = settings.timezone;
+        let today = now.with_timezone(&tz).date_naive();
+        let week_start = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+        let date = match settings.frequency {
+            DigestFrequency::Weekly => week_start,
+            _ => today,
+        };
+
+        Self {
+            date,
+            start: local_midnight(tz, date),
+            today_start: local_midnight(tz, today),
+            tomorrow_start: local_midnight(tz, today + Duration::days(1)),
+            week_end: local_midnight(tz, week_start + Duration::days(7)),
+        }
+    }
+}
+
+/// Start of a local day; days starting in a DST gap begin at the first valid instant
+fn local_midnight(tz: Tz, date: NaiveDate) -> DateTime<Utc> {
+    let midnight = date.and_hms_opt(0, 0, 0).unwrap_or_default();
+    tz.from_local_datetime(&midnight)
+        .earliest()
+        .or_else(|| tz.from_local_datetime(&(midnight + Duration::hours(1))).earliest())
+        .map(|start| start.with_timezone(&Utc))
+        .unwrap_or_else(|| midnight.and_utc())
+}
+
+#[derive(Clone)]
+pub struct DigestService {
+    db: DatabaseConnection,
+    email_service: EmailService,
+    project_service: ProjectService,
+    frontend_url: String,
+}
+
+impl DigestService {
+    pub fn new(
+        db: DatabaseConnection,
+        email_service: EmailService,
+        project_service: ProjectService,
+        frontend_url: String,
+    ) -> Self {
+        Self { db, email_service, project_service, frontend_url }
+    }
+
+    pub async fn get_preferences(&self, user_id: Uuid) -> Result<DigestPreferences, Box<dyn std::error::Error>> {
+        let preference = DigestPreference::find()
+            .filter(digest_preference::Column::UserId.eq(user_id))
+            .one(&self.db)
+            .await?;
+
+        Ok(match preference {
+            Some(preference) => preference.into(),
+            None => DigestPreferences {
+                frequency: DigestFrequency::Daily,
+                timezone: DEFAULT_TIMEZONE.to_string(),
+                send_hour: DEFAULT_SEND_HOUR,
+                quiet_hours_start: None,
+                quiet_hours_end: None,
+                last_sent_at: None,
+            },
+        })
+    }
+
+    pub async fn update_preferences(
+        &self,
+        user_id: Uuid,
+        input: UpdateDigestPreferencesInput,
+    ) -> Result<DigestPreferences, Box<dyn std::error::Error>> {
+        if let Some(timezone) = &input.timezone {
+            timezone
+                .parse::<Tz>()
+                .map_err(|_| format!("Unknown time zone: {}", timezone))?;
+        }
+        for hour in [input.send_hour, input.quiet_hours_start.flatten(), input.quiet_hours_end.flatten()]
+            .into_iter()
+            .flatten()
+        {
+            if !(0..=23).contains(&hour) {
+                return Err(format!("Hours must be between 0 and 23, got {}", hour).into());
+            }
+        }
+
+        let existing = DigestPreference::find()
+            .filter(digest_preference::Column::UserId.eq(user_id))
+            .one(&self.db)
+            .await?;
+
+        let mut preference = match existing {
+            Some(preference) => preference.into(),
+            None => digest_preference::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                user_id: Set(user_id),
+                frequency: Set(DigestFrequency::Daily),
+                timezone: Set(DEFAULT_TIMEZONE.to_string()),
+                send_hour: Set(DEFAULT_SEND_HOUR),
+                quiet_hours_start: Set(None),
+                quiet_hours_end: Set(None),
+                last_sent_at: Set(None),
+                created_at: Set(Utc::now().into()),
+                updated_at: Set(Utc::now().into()),
+            },
+        };
+
+        if let Some(frequency) = input.frequency {
+            preference.frequency = Set(frequency);
+        }
+        if let Some(timezone) = input.timezone {
+            preference.timezone = Set(timezone);
+        }
+        if let Some(send_hour) = input.send_hour {
+            preference.send_hour = Set(send_hour);
+        }
+        if let Some(quiet_hours_start) = input.quiet_hours_start {
+            preference.quiet_hours_start = Set(quiet_hours_start);
+        }
+        if let Some(quiet_hours_end) = input.quiet_hours_end {
+            preference.quiet_hours_end = Set(quiet_hours_end);
+        }
+        preference.updated_at = Set(Utc::now().into());
+
+        let preference = preference.save(&self.db).await?.try_into_model()?;
+        Ok(preference.into())
+    }
+
+    /// Queue a digest for every active user whose digest is due: their local send hour
+    /// has passed outside quiet hours, and they haven't had one this day (daily) or week
+    /// (weekly). Users with nothing to report are skipped until the next period.
+    pub async fn send_due_digests(&self) -> Result<DigestSweepSummary, Box<dyn std::error::Error>> {
+        let now = Utc::now();
+        let users = User::find()
+            .filter(user::Column::Status.eq(UserStatus::Active))
+            .all(&self.db)
+            .await?;
+        let preferences: HashMap<Uuid, digest_preference::Model> = DigestPreference::find()
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .map(|preference| (preference.user_id, preference))
+            .collect();
+
+        let mut summary = DigestSweepSummary::default();
+        for user in users {
+            let preference = preferences.get(&user.id);
+            let settings = DigestSettings::from_preference(preference);
+            if settings.frequency == DigestFrequency::Off {
+                continue;
+            }
+
+            let period = DigestPeriod::new(&settings, now);
+            let local_hour = now.with_timezone(&settings.timezone).hour();
+            let already_sent = settings.last_sent_at.is_some_and(|sent_at| sent_at >= period.start);
+            if already_sent || local_hour < settings.send_hour || settings.is_quiet(local_hour) {
+                continue;
+            }
+
+            match self.send_digest(&user, preference, &settings, &period, now).await {
+                Ok(true) => summary.sent += 1,
+                Ok(false) => summary.empty += 1,
+                Err(e) => {
+                    warn!("Failed to send digest to user {}: {}", user.id, e);
+                    summary.failed += 1;
+                }
+            }
+        }
+
+        Ok(summary)
+    }
+
+    /// Build and queue one user's digest, and remember that the period is covered.
+    /// Returns whether there was anything to send.
+    async fn send_digest(
+        &self,
+        user: &user::Model,
+        preference: Option<&digest_preference::Model>,
+        settings: &DigestSettings,
+        period: &DigestPeriod,
+        now: DateTime<Utc>,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        let lookback = match settings.frequency {
+            DigestFrequency::Weekly => Duration::days(7),
+            _ => Duration::days(1),
+        };
+        let since = settings.last_sent_at.unwrap_or(now - lookback);
+        let digest = self.build_digest(user.id, settings, period, since).await?;
+
+        let tx = self.db.begin().await?;
+        if !digest.is_empty() {
+            let style = email_style_for_user(&tx, user).await?;
+            let message = self.email_service.digest_email(&user.email, &digest, &self.frontend_url, &style)?;
+            queue_email(&tx, message).await?;
+        }
+
+        match preference {
+            Some(preference) => {
+                let mut preference: digest_preference::ActiveModel = preference.clone().into();
+                preference.last_sent_at = Set(Some(now.into()));
+                preference.update(&tx).await?;
+            }
+            None => {
+                digest_preference::ActiveModel {
+                    id: Set(Uuid::new_v4()),
+                    user_id: Set(user.id),
+                    frequency: Set(DigestFrequency::Daily),
+                    timezone: Set(DEFAULT_TIMEZONE.to_string()),
+                    send_hour: Set(DEFAULT_SEND_HOUR),
+                    quiet_hours_start: Set(None),
+                    quiet_hours_end: Set(None),
+                    last_sent_at: Set(Some(now.into())),
+                    created_at: Set(now.into()),
+                    updated_at: Set(now.into()),
+                }
+                .insert(&tx)
+                .await?;
+            }
+        }
+        tx.commit().await?;
+
+        Ok(!digest.is_empty())
+    }
+
+    async fn build_digest(
+        &self,
+        user_id: Uuid,
+        settings: &DigestSettings,
+        period: &DigestPeriod,
+        since: DateTime<Utc>,
+    ) -> Result<Digest, Box<dyn std::error::Error>> {
+        let open_statuses = [TaskStatus::Todo, TaskStatus::InProgress];
+
+        // Open tasks of the user's due before the end of the week, including overdue ones
+        let due_tasks = Task::find()
+            .filter(task::Column::AssigneeId.eq(user_id))
+            .filter(task::Column::Status.is_in(open_statuses))
+            .filter(task::Column::DueDate.is_not_null())
+            .filter(task::Column::DueDate.lt(period.week_end))
+            .order_by_asc(task::Column::DueDate)
+            .all(&self.db)
+            .await?;
+
+        // Tasks assigned to the user since the last digest that are still theirs and open
+        let assigned_task_ids: Vec<Uuid> = Notification::find()
+            .select_only()
+            .column(notification::Column::TaskId)
+            .filter(notification::Column::UserId.eq(user_id))
+            .filter(notification::Column::NotificationType.eq(NotificationType::TaskAssigned))
+            .filter(notification::Column::CreatedAt.gte(since))
+            .into_tuple::<Option<Uuid>>()
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .flatten()
+            .collect();
+        let new_assignments = if assigned_task_ids.is_empty() {
+            Vec::new()
+        } else {
+            Task::find()
+                .filter(task::Column::Id.is_in(assigned_task_ids))
+                .filter(task::Column::AssigneeId.eq(user_id))
+                .filter(task::Column::Status.is_in(open_statuses))
+                .order_by_asc(task::Column::CreatedAt)
+                .all(&self.db)
+                .await?
+        };
+
+        let project_ids: Vec<Uuid> = due_tasks
+            .iter()
+            .chain(new_assignments.iter())
+            .map(|task| task.project_id)
+            .collect();
+        let project_names: HashMap<Uuid, String> = Project::find()
+            .filter(project::Column::Id.is_in(project_ids))
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .map(|project| (project.id, project.name))
+            .collect();
+
+        let to_digest_task = |task: &task::Model| DigestTask {
+            name: task.name.clone(),
+            project_name: project_names.get(&task.project_id).cloned().unwrap_or_default(),
+            due_date: task
+                .due_date
+                .map(|due_date| due_date.with_timezone(&settings.timezone).format("%Y-%m-%d").to_string()),
+        };
+
+        let mut overdue = Vec::new();
+        let mut due_today = Vec::new();
+        let mut due_this_week = Vec::new();
+        for task in &due_tasks {
+            let Some(due_date) = task.due_date.map(|due_date| due_date.with_timezone(&Utc)) else {
+                continue;
+            };
+            if due_date < period.today_start {
+                overdue.push(to_digest_task(task));
+            } else if due_date < period.tomorrow_start {
+                due_today.push(to_digest_task(task));
+            } else {
+                due_this_week.push(to_digest_task(task));
+            }
+        }
+
+        let mentions = Notification::find()
+            .filter(notification::Column::UserId.eq(user_id))
+            .filter(notification::Column::NotificationType.eq(NotificationType::Mentioned))
+            .filter(notification::Column::IsRead.eq(false))
+            .order_by_desc(notification::Column::CreatedAt)
+            .limit(MAX_MENTIONS)
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .map(|mention| DigestMention { title: mention.title, body: mention.body })
+            .collect();
+
+        let (email_count, emails) = self.ingested_emails(user_id, since).await?;
+
+        Ok(Digest {
+            frequency: match settings.frequency {
+                DigestFrequency::Weekly => "weekly",
+                _ => "daily",
+            }
+            .to_string(),
+            date: period.date.format("%Y-%m-%d").to_string(),
+            overdue,
+            due_today,
+            due_this_week,
+            new_assignments: new_assignments.iter().map(to_digest_task).collect(),
+            mentions,
+            email_count,
+            emails,
+        })
+    }
+
+    /// Emails received into the user's projects since `since`, grouped by accounting
+    /// process, busiest first
+    async fn ingested_emails(
+        &self,
+        user_id: Uuid,
+        since: DateTime<Utc>,
+    ) -> Result<(usize, Vec<DigestEmailGroup>), Box<dyn std::error::Error>> {
+        let projects = self.project_service.get_user_projects(user_id, None, None, None).await?;
+        if projects.is_empty() {
+            return Ok((0, Vec::new()));
+        }
+        let project_names: HashMap<Uuid, String> =
+            projects.into_iter().map(|project| (project.id, project.name)).collect();
+
+        let rows: Vec<(String, AccountingProcess, Uuid)> = EmailContext::find()
+            .select_only()
+            .column(email_context::Column::Subject)
+            .column(email_context::Column::AccountingProcess)
+            .column(project_context::Column::ProjectId)
+            .join(JoinType::InnerJoin, email_context::Relation::ProjectContext.def())
+            .filter(project_context::Column::ProjectId.is_in(project_names.keys().copied()))
+            .filter(email_context::Column::Direction.eq(EmailDirection::Inbound))
+            .filter(email_context::Column::ReceivedDate.gte(since))
+            .order_by_desc(email_context::Column::ReceivedDate)
+            .into_tuple()
+            .all(&self.db)
+            .await?;
+
+        let email_count = rows.len();
+        let mut groups: Vec<DigestEmailGroup> = Vec::new();
+        for (subject, process, project_id) in rows {
+            let index = match groups.iter().position(|group| group.process == process.as_str()) {
+                Some(index) => index,
+                None => {
+                    groups.push(DigestEmailGroup { process: process.as_str().to_string(), count: 0, emails: Vec::new() });
+                    groups.len() - 1
+                }
+            };
+            let group = &mut groups[index];
+            group.count += 1;
+            if group.emails.len() < MAX_EMAILS_PER_PROCESS {
+                group.emails.push(DigestEmail {
+                    subject,
+                    project_name: project_names.get(&project_id).cloned().unwrap_or_default(),
+                });
+            }
+        }
+        groups.sort_by_key(|group| std::cmp::Reverse(group.count));
+
+        Ok((email_count, groups))
+    }
+}
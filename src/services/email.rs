@@ -1,174 +1,230 @@
-use serde_json;
+use std::sync::Arc;
+
+use lettre::message::Mailbox;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::graphql::types::EmailTemplateKind;
+use crate::services::{
+    AttachmentContent, AttachmentStorage, Digest, EmailStyle, EmailTemplates, EmailTransport, EmailTransportError,
+};
+
+/// A file from attachment storage to send along with an email
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutgoingAttachment {
+    pub filename: String,
+    pub content_type: Option<String>,
+    pub storage_path: String,
+}
+
+/// A rendered email, ready to be queued in the outbox
+#[derive(Debug, Clone, Default)]
+pub struct EmailMessage {
+    /// What the email is for, e.g. `invitation`; shown in the outbox
+    pub kind: String,
+    pub to_email: String,
+    /// Display name for the From header; the address is always the configured one
+    pub from_name: Option<String>,
+    pub subject: String,
+    pub html_content: String,
+    pub text_content: String,
+    /// Set when the email is kept elsewhere too, e.g. as an outbound email context, so
+    /// replies to it can be threaded
+    pub message_id: Option<String>,
+    pub in_reply_to: Option<String>,
+    /// Message-IDs of the earlier emails in the thread, oldest first
+    pub references: Vec<String>,
+    pub attachments: Vec<OutgoingAttachment>,
+}
 
+#[derive(Clone)]
 pub struct EmailService {
-    api_key: String,
+    transport: Arc<dyn EmailTransport>,
     from_email: String,
-    client: reqwest::Client,
+    templates: Arc<EmailTemplates>,
+    attachment_storage: AttachmentStorage,
 }
 
 impl EmailService {
-    pub fn new(api_key: &str, from_email: String) -> Self {
-        Self {
-            api_key: api_key.to_string(),
-            from_email,
-            client: reqwest::Client::new(),
-        }
+    pub fn new(
+        transport: Arc<dyn EmailTransport>,
+        from_email: String,
+        templates: Arc<EmailTemplates>,
+        attachment_storage: AttachmentStorage,
+    ) -> Self {
+        Self { transport, from_email, templates, attachment_storage }
     }
 
-    pub async fn send_verification_email(
+    pub fn transport_name(&self) -> &'static str {
+        self.transport.name()
+    }
+
+    /// Address emails are sent from, without any display name
+    pub fn sender_address(&self) -> String {
+        self.from_email
+            .parse::<Mailbox>()
+            .map(|mailbox| mailbox.email.to_string())
+            .unwrap_or_else(|_| self.from_email.clone())
+    }
+
+    /// A new Message-ID on the sending domain
+    pub fn new_message_id(&self) -> String {
+        let sender_address = self.sender_address();
+        let domain = sender_address.rsplit_once('@').map(|(_, domain)| domain).unwrap_or("localhost");
+        format!("<{}@{}>", Uuid::new_v4(), domain)
+    }
+
+    /// Locales emails can be rendered in
+    pub fn locales(&self) -> Vec<String> {
+        self.templates.locales()
+    }
+
+    /// The locale an email asked for in `locale` is actually rendered in
+    pub fn resolve_locale(&self, locale: &str) -> String {
+        self.templates.resolve_locale(locale)
+    }
+
+    /// Render any template with its sample data, for previews
+    pub fn preview(&self, kind: EmailTemplateKind, to_email: &str, style: &EmailStyle) -> Result<EmailMessage, minijinja::Error> {
+        self.templates.render(kind, to_email, style, kind.sample_vars())
+    }
+
+    pub fn verification_email(
         &self,
         to_email: &str,
         verification_token: &str,
         base_url: &str,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+        style: &EmailStyle,
+    ) -> Result<EmailMessage, minijinja::Error> {
         let verification_url = format!("{}/verify-email?token={}", base_url, verification_token);
-        
-        let html_content = format!(
-            r#"
-            <h2>Verify Your Email Address</h2>
-            <p>Please click the link below to verify your email address:</p>
-            <p><a href="{}">Verify Email</a></p>
-            <p>If you didn't request this verification, please ignore this email.</p>
-            "#,
-            verification_url
-        );
-
-        self.send_email(
+        self.templates.render(
+            EmailTemplateKind::EmailVerification,
             to_email,
-            "Verify your email address",
-            &html_content,
-        ).await
+            style,
+            serde_json::json!({ "url": verification_url }),
+        )
     }
 
-    pub async fn send_password_reset_email(
+    pub fn password_reset_email(
         &self,
         to_email: &str,
         reset_token: &str,
         base_url: &str,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+        style: &EmailStyle,
+    ) -> Result<EmailMessage, minijinja::Error> {
         let reset_url = format!("{}/reset-password?token={}", base_url, reset_token);
-        
-        let html_content = format!(
-            r#"
-            <h2>Reset Your Password</h2>
-            <p>You requested to reset your password. Click the link below to proceed:</p>
-            <p><a href="{}">Reset Password</a></p>
-            <p>This link will expire in 1 hour.</p>
-            <p>If you didn't request this reset, please ignore this email.</p>
-            "#,
-            reset_url
-        );
-
-        self.send_email(
+        self.templates.render(
+            EmailTemplateKind::PasswordReset,
+            to_email,
+            style,
+            serde_json::json!({ "url": reset_url }),
+        )
+    }
+
+    pub fn magic_link_email(
+        &self,
+        to_email: &str,
+        magic_link_token: &str,
+        base_url: &str,
+        expires_in_minutes: i64,
+        style: &EmailStyle,
+    ) -> Result<EmailMessage, minijinja::Error> {
+        let login_url = format!("{}/magic-link?token={}", base_url, magic_link_token);
+        self.templates.render(
+            EmailTemplateKind::MagicLink,
             to_email,
-            "Reset your password",
-            &html_content,
-        ).await
+            style,
+            serde_json::json!({ "url": login_url, "expires_in_minutes": expires_in_minutes }),
+        )
     }
 
-    pub async fn send_invitation_email(
+    pub fn invitation_email(
         &self,
         to_email: &str,
         invitation_token: &str,
         base_url: &str,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+        style: &EmailStyle,
+    ) -> Result<EmailMessage, minijinja::Error> {
         let invitation_url = format!("{}/accept-invitation?token={}", base_url, invitation_token);
-        
-        let html_content = format!(
-            r#"
-            <h2>You're Invited to Join FreshAPI!</h2>
-            <p>You've been invited to create an account on FreshAPI.</p>
-            <p>Click the link below to accept your invitation and set up your account:</p>
-            <p><a href="{}">Accept Invitation</a></p>
-            <p>This invitation will expire in 7 days.</p>
-            "#,
-            invitation_url
-        );
-
-        self.send_email(
+        self.templates.render(
+            EmailTemplateKind::Invitation,
             to_email,
-            "You're invited to join FreshAPI",
-            &html_content,
-        ).await
+            style,
+            serde_json::json!({ "url": invitation_url }),
+        )
     }
 
-    pub async fn send_admin_password_reset_email(
+    pub fn admin_password_reset_email(
         &self,
         to_email: &str,
         reset_token: &str,
         base_url: &str,
         admin_name: &str,
-    ) -> Result<(), Box<dyn std::error::Error>> {
+        style: &EmailStyle,
+    ) -> Result<EmailMessage, minijinja::Error> {
         let reset_url = format!("{}/reset-password?token={}", base_url, reset_token);
-        
-        let html_content = format!(
-            r#"
-            <h2>Password Reset Requested</h2>
-            <p>An administrator ({}) has initiated a password reset for your account.</p>
-            <p>Click the link below to set a new password:</p>
-            <p><a href="{}">Reset Password</a></p>
-            <p>This link will expire in 24 hours.</p>
-            <p>If you have any concerns about this reset, please contact your administrator.</p>
-            "#,
-            admin_name,
-            reset_url
-        );
-
-        self.send_email(
+        self.templates.render(
+            EmailTemplateKind::AdminPasswordReset,
             to_email,
-            "Password Reset - Admin Request",
-            &html_content,
-        ).await
+            style,
+            serde_json::json!({ "url": reset_url, "admin_name": admin_name }),
+        )
     }
 
-    async fn send_email(
+    pub fn project_invitation_email(
         &self,
         to_email: &str,
-        subject: &str,
-        html_content: &str,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        // Check if we have a real API key or just the placeholder
-        if self.api_key == "your-resend-api-key-here" || self.api_key == "dummy-key" {
-            // Fallback to console logging for development
-            println!(
-                "📧 [DEVELOPMENT] Email would be sent via Resend:\nTo: {}\nFrom: {}\nSubject: {}\nContent: {}",
-                to_email, self.from_email, subject, html_content
-            );
-            return Ok(());
-        }
-
-        let payload = serde_json::json!({
-            "from": self.from_email,
-            "to": [to_email],
-            "subject": subject,
-            "html": html_content
-        });
-
-        let response = self.client
-            .post("https://api.resend.com/emails")
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .header("Content-Type", "application/json")
-            .json(&payload)
-            .send()
-            .await?;
-
-        if response.status().is_success() {
-            println!("✅ Email sent successfully to {}", to_email);
-        } else {
-            let error_text = response.text().await?;
-            return Err(format!("Failed to send email via Resend: {}", error_text).into());
-        }
+        project_name: &str,
+        inviter_name: &str,
+        base_url: &str,
+        style: &EmailStyle,
+    ) -> Result<EmailMessage, minijinja::Error> {
+        let invitations_url = format!("{}/project-invitations", base_url);
+        self.templates.render(
+            EmailTemplateKind::ProjectInvitation,
+            to_email,
+            style,
+            serde_json::json!({
+                "url": invitations_url,
+                "inviter_name": inviter_name,
+                "project_name": project_name,
+            }),
+        )
+    }
 
-        Ok(())
+    pub fn digest_email(
+        &self,
+        to_email: &str,
+        digest: &Digest,
+        base_url: &str,
+        style: &EmailStyle,
+    ) -> Result<EmailMessage, minijinja::Error> {
+        let kind = match digest.frequency.as_str() {
+            "weekly" => EmailTemplateKind::WeeklyDigest,
+            _ => EmailTemplateKind::DailyDigest,
+        };
+        let mut vars = serde_json::to_value(digest)
+            .map_err(|e| minijinja::Error::new(minijinja::ErrorKind::BadSerialization, e.to_string()))?;
+        vars["url"] = serde_json::Value::String(base_url.to_string());
+        self.templates.render(kind, to_email, style, vars)
     }
-}
 
-impl Clone for EmailService {
-    fn clone(&self) -> Self {
-        Self {
-            api_key: self.api_key.clone(),
-            from_email: self.from_email.clone(),
-            client: self.client.clone(),
+    /// Deliver a message through the configured transport. Callers queue messages in the
+    /// outbox instead of calling this directly, so delivery failures don't fail the
+    /// triggering request.
+    pub async fn deliver(&self, message: &EmailMessage) -> Result<(), EmailTransportError> {
+        let mut attachments = Vec::with_capacity(message.attachments.len());
+        for attachment in &message.attachments {
+            attachments.push(AttachmentContent {
+                filename: attachment.filename.clone(),
+                content_type: attachment
+                    .content_type
+                    .clone()
+                    .unwrap_or_else(|| "application/octet-stream".to_string()),
+                content: self.attachment_storage.read(&attachment.storage_path).await?,
+            });
         }
+
+        self.transport.send(&self.from_email, message, &attachments).await
     }
-}
\ No newline at end of file
+}
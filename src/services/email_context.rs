@@ -4,25 +4,38 @@ use anyhow::Result;
 use chrono::Utc;
 
 use crate::entities::{
-    project_context, email_context, project_context_category,
+    project_context, email_context, email_attachment, project_context_category, user,
     prelude::*
 };
 use crate::graphql::types::{
-    EmailIngestInput, EmailContextFilters, EmailContextConnection,
-    AccountingProcess, ProcessingStatus
+    EmailIngestInput, EmailContextFilters, EmailContextConnection, ReplyToEmailContextInput,
+    AccountingProcess, EmailDirection, ProcessingStatus
 };
-use crate::services::ContextService;
+use crate::services::activity::{ActivityService, EntityType, NewActivity};
+use crate::services::{
+    queue_email, queue_email_ingested_event, ContextService, EmailMessage, EmailService, OutgoingAttachment, ProjectAction, ProjectService,
+};
+
+/// Attachments allowed on a single reply
+const MAX_REPLY_ATTACHMENTS: usize = 10;
 
 #[derive(Clone)]
 pub struct EmailContextService {
     db: DatabaseConnection,
     context_service: ContextService,
+    email_service: EmailService,
+    activity_service: ActivityService,
 }
 
 impl EmailContextService {
-    pub fn new(db: DatabaseConnection) -> Self {
-        let context_service = ContextService::new(db.clone());
-        Self { db, context_service }
+    pub fn new(
+        db: DatabaseConnection,
+        project_service: ProjectService,
+        email_service: EmailService,
+        activity_service: ActivityService,
+    ) -> Self {
+        let context_service = ContextService::new(db.clone(), project_service);
+        Self { db, context_service, email_service, activity_service }
     }
 
     pub fn get_db(&self) -> &DatabaseConnection {
@@ -53,9 +66,11 @@ impl EmailContextService {
                 Some(category) => Some(category.id),
                 None => {
                     // Auto-create category
+                    let organization_id = self.context_service.project_organization_id(input.project_id).await?;
                     let new_category = project_context_category::ActiveModel {
                         id: Set(Uuid::new_v4()),
                         project_id: Set(input.project_id),
+                        organization_id: Set(organization_id),
                         context_type_id: Set(email_context_type.id),
                         name: Set(category_name.clone()),
                         color: Set("#10b981".to_string()), // Green for auto-created email categories
@@ -132,6 +147,7 @@ impl EmailContextService {
             cc_emails: Set(input.cc_emails),
             bcc_emails: Set(input.bcc_emails),
             reply_to: Set(None), // Could be extracted from headers
+            direction: Set(EmailDirection::Inbound),
             subject: Set(input.subject),
             message_preview: Set(input.message_preview.or_else(|| {
                 // Auto-generate preview from full message
@@ -160,6 +176,10 @@ impl EmailContextService {
 
         let created_email = email_context.insert(&txn).await?;
 
+        // 7. Let webhook subscribers know
+        let organization_id = self.context_service.project_organization_id(input.project_id).await?;
+        queue_email_ingested_event(&txn, organization_id, input.project_id, &created_email).await?;
+
         txn.commit().await?;
         Ok(created_email)
     }
@@ -171,7 +191,10 @@ impl EmailContextService {
         filters: Option<EmailContextFilters>,
         limit: Option<u64>,
         offset: Option<u64>,
+        user_id: Uuid,
     ) -> Result<EmailContextConnection> {
+        self.context_service.authorize(project_id, user_id, ProjectAction::ProjectView).await?;
+
         let mut query = EmailContext::find()
             .join(JoinType::InnerJoin, email_context::Relation::ProjectContext.def())
             .filter(project_context::Column::ProjectId.eq(project_id))
@@ -183,6 +206,10 @@ impl EmailContextService {
                 query = query.filter(email_context::Column::AccountingProcess.eq(accounting_process));
             }
 
+            if let Some(direction) = filters.direction {
+                query = query.filter(email_context::Column::Direction.eq(direction));
+            }
+
             if let Some(from_email) = filters.from_email {
                 query = query.filter(email_context::Column::FromEmail.contains(&from_email));
             }
@@ -238,11 +265,19 @@ impl EmailContextService {
     }
 
     // Get email by ID
-    pub async fn get_email_context_by_id(&self, email_id: Uuid) -> Result<Option<email_context::Model>> {
-        EmailContext::find_by_id(email_id)
+    pub async fn get_email_context_by_id(&self, email_id: Uuid, user_id: Uuid) -> Result<Option<email_context::Model>> {
+        let email = EmailContext::find_by_id(email_id)
+            .find_also_related(ProjectContext)
             .one(&self.db)
-            .await
-            .map_err(Into::into)
+            .await?;
+
+        match email {
+            Some((email, Some(context))) => {
+                self.context_service.authorize(context.project_id, user_id, ProjectAction::ProjectView).await?;
+                Ok(Some(email))
+            }
+            _ => Ok(None),
+        }
     }
 
     // Update processing status
@@ -251,11 +286,16 @@ impl EmailContextService {
         email_id: Uuid,
         status: ProcessingStatus,
         notes: Option<String>,
+        user_id: Uuid,
     ) -> Result<email_context::Model> {
-        let email = EmailContext::find_by_id(email_id)
+        let (email, context) = EmailContext::find_by_id(email_id)
+            .find_also_related(ProjectContext)
             .one(&self.db)
             .await?
             .ok_or_else(|| anyhow::anyhow!("Email context not found"))?;
+        let context = context.ok_or_else(|| anyhow::anyhow!("Email context not found"))?;
+
+        self.context_service.authorize(context.project_id, user_id, ProjectAction::EmailReview).await?;
 
         let mut email: email_context::ActiveModel = email.into();
         email.processing_status = Set(status.as_str().to_string());
@@ -266,13 +306,244 @@ impl EmailContextService {
         email.update(&self.db).await.map_err(Into::into)
     }
 
+    /// Reply to an email context. The reply goes out through the email outbox with
+    /// `In-Reply-To` and `References` set, and is recorded as an outbound email context
+    /// in the same thread.
+    pub async fn reply_to_email_context(
+        &self,
+        input: ReplyToEmailContextInput,
+        user_id: Uuid,
+    ) -> Result<email_context::Model> {
+        let (original, context) = EmailContext::find_by_id(input.email_context_id)
+            .find_also_related(ProjectContext)
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Email context not found"))?;
+        let context = context.ok_or_else(|| anyhow::anyhow!("Email context not found"))?;
+
+        self.context_service.authorize(context.project_id, user_id, ProjectAction::EmailSend).await?;
+
+        let body = input.body.trim().to_string();
+        if body.is_empty() {
+            return Err(anyhow::anyhow!("Reply body is required"));
+        }
+
+        let to_email = match input.to.map(|to| to.trim().to_string()) {
+            Some(to) => to,
+            None => match original.direction {
+                EmailDirection::Inbound => original.reply_to.clone().unwrap_or_else(|| original.from_email.clone()),
+                EmailDirection::Outbound => original
+                    .to_emails
+                    .first()
+                    .cloned()
+                    .ok_or_else(|| anyhow::anyhow!("Email has no recipient to reply to"))?,
+            },
+        };
+        if to_email.parse::<lettre::Address>().is_err() {
+            return Err(anyhow::anyhow!("Invalid recipient address: {}", to_email));
+        }
+
+        let subject = match input.subject.map(|subject| subject.trim().to_string()).filter(|subject| !subject.is_empty()) {
+            Some(subject) => subject,
+            None if original.subject.to_lowercase().starts_with("re:") => original.subject.clone(),
+            None => format!("Re: {}", original.subject),
+        };
+
+        let attachment_ids = input.attachment_ids.unwrap_or_default();
+        if attachment_ids.len() > MAX_REPLY_ATTACHMENTS {
+            return Err(anyhow::anyhow!("A reply can carry at most {} attachments", MAX_REPLY_ATTACHMENTS));
+        }
+        let attachments = if attachment_ids.is_empty() {
+            Vec::new()
+        } else {
+            // Only files that came with emails of the same project can be sent on
+            EmailAttachment::find()
+                .join(JoinType::InnerJoin, email_attachment::Relation::EmailContext.def())
+                .join(JoinType::InnerJoin, email_context::Relation::ProjectContext.def())
+                .filter(email_attachment::Column::Id.is_in(attachment_ids.clone()))
+                .filter(project_context::Column::ProjectId.eq(context.project_id))
+                .all(&self.db)
+                .await?
+        };
+        if let Some(missing) = attachment_ids
+            .iter()
+            .find(|id| !attachments.iter().any(|attachment| attachment.id == **id))
+        {
+            return Err(anyhow::anyhow!("Attachment {} not found in this project", missing));
+        }
+
+        let sender = User::find_by_id(user_id)
+            .one(&self.db)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("User not found"))?;
+        let sender_name = Self::display_name(&sender);
+
+        let txn = self.db.begin().await?;
+
+        // Thread on the original's thread, starting one from its Message-ID if it has none
+        let thread_id = original
+            .thread_id
+            .clone()
+            .or_else(|| original.message_id.clone())
+            .unwrap_or_else(|| original.id.to_string());
+        let mut references: Vec<String> = EmailContext::find()
+            .join(JoinType::InnerJoin, email_context::Relation::ProjectContext.def())
+            .filter(project_context::Column::ProjectId.eq(context.project_id))
+            .filter(email_context::Column::ThreadId.eq(thread_id.as_str()))
+            .order_by_asc(email_context::Column::MessageDate)
+            .order_by_asc(email_context::Column::ReceivedDate)
+            .all(&txn)
+            .await?
+            .into_iter()
+            .filter_map(|email| email.message_id)
+            .filter(|message_id| Some(message_id) != original.message_id.as_ref())
+            .collect();
+        references.extend(original.message_id.clone());
+
+        if original.thread_id.is_none() {
+            let mut original_active: email_context::ActiveModel = original.clone().into();
+            original_active.thread_id = Set(Some(thread_id.clone()));
+            original_active.update(&txn).await?;
+        }
+
+        let message_id = self.email_service.new_message_id();
+        let html = Self::text_to_html(&body);
+
+        let reply_id = Uuid::new_v4();
+        project_context::ActiveModel {
+            id: Set(reply_id),
+            project_id: Set(context.project_id),
+            context_type_id: Set(context.context_type_id),
+            category_id: Set(context.category_id),
+            title: Set(format!("{} (to {})", subject, to_email)),
+            description: Set(None),
+            tags: Set(context.tags.clone()),
+            metadata: Set(Some(serde_json::json!({
+                "source": "reply",
+                "in_reply_to_context_id": original.id,
+                "accounting_process": original.accounting_process.as_str(),
+                "has_attachments": !attachments.is_empty(),
+            }))),
+            is_archived: Set(false),
+            created_by: Set(Some(user_id)),
+            created_at: Set(Utc::now().into()),
+            updated_at: Set(Utc::now().into()),
+        }
+        .insert(&txn)
+        .await?;
+
+        let reply = email_context::ActiveModel {
+            id: Set(reply_id), // Same ID as project context
+            from_email: Set(self.email_service.sender_address()),
+            from_name: Set(Some(sender_name.clone())),
+            to_emails: Set(vec![to_email.clone()]),
+            cc_emails: Set(None),
+            bcc_emails: Set(None),
+            reply_to: Set(None),
+            direction: Set(EmailDirection::Outbound),
+            subject: Set(subject.clone()),
+            message_preview: Set(Some(if body.chars().count() > 200 {
+                format!("{}...", body.chars().take(197).collect::<String>())
+            } else {
+                body.clone()
+            })),
+            full_message: Set(body.clone()),
+            message_html: Set(Some(html.clone())),
+            accounting_process: Set(original.accounting_process),
+            ai_summary: Set(None),
+            confidence_score: Set(None),
+            extracted_entities: Set(None),
+            message_id: Set(Some(message_id.clone())),
+            thread_id: Set(Some(thread_id)),
+            in_reply_to: Set(original.message_id.clone()),
+            message_date: Set(Some(Utc::now().into())),
+            received_date: Set(Utc::now().into()),
+            has_attachments: Set(!attachments.is_empty()),
+            attachment_count: Set(attachments.len() as i32),
+            processing_status: Set(ProcessingStatus::Completed.as_str().to_string()),
+            processing_notes: Set(None),
+        }
+        .insert(&txn)
+        .await?;
+
+        // The reply lists the files it sent; they stay where they are in storage
+        for attachment in &attachments {
+            email_attachment::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                email_context_id: Set(reply.id),
+                filename: Set(attachment.filename.clone()),
+                original_filename: Set(attachment.original_filename.clone()),
+                file_size: Set(attachment.file_size),
+                content_type: Set(attachment.content_type.clone()),
+                file_hash: Set(attachment.file_hash.clone()),
+                storage_path: Set(attachment.storage_path.clone()),
+                extracted_text: Set(attachment.extracted_text.clone()),
+                is_processed: Set(attachment.is_processed),
+                created_at: Set(Utc::now().into()),
+            }
+            .insert(&txn)
+            .await?;
+        }
+
+        queue_email(
+            &txn,
+            EmailMessage {
+                kind: "email_reply".to_string(),
+                to_email: to_email.clone(),
+                from_name: Some(sender_name),
+                subject: subject.clone(),
+                html_content: html,
+                text_content: body,
+                message_id: Some(message_id),
+                in_reply_to: original.message_id.clone(),
+                references,
+                attachments: attachments
+                    .iter()
+                    .map(|attachment| OutgoingAttachment {
+                        filename: attachment.original_filename.clone(),
+                        content_type: attachment.content_type.clone(),
+                        storage_path: attachment.storage_path.clone(),
+                    })
+                    .collect(),
+            },
+        )
+        .await?;
+
+        self.activity_service
+            .log_activity_with(
+                &txn,
+                NewActivity {
+                    entity_type: EntityType::Project,
+                    entity_id: context.project_id,
+                    actor_id: user_id,
+                    action_type: "email_replied".to_string(),
+                    description: Some(format!("Replied to \"{}\" (to {})", original.subject, to_email)),
+                    metadata: Some(serde_json::json!({
+                        "email_context_id": original.id,
+                        "reply_email_context_id": reply.id,
+                        "to_email": to_email,
+                        "attachment_count": attachments.len(),
+                    })),
+                    changes: None,
+                },
+            )
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to log reply: {}", e))?;
+
+        txn.commit().await?;
+        Ok(reply)
+    }
+
     // Search emails with full-text search
     pub async fn search_emails(
         &self,
         project_id: Uuid,
         search_query: &str,
         limit: Option<u64>,
+        user_id: Uuid,
     ) -> Result<Vec<email_context::Model>> {
+        self.context_service.authorize(project_id, user_id, ProjectAction::ProjectView).await?;
+
         if search_query.trim().is_empty() {
             return Ok(Vec::new());
         }
@@ -309,6 +580,34 @@ impl EmailContextService {
         Ok(stats.into_iter().map(|(count, process)| (process, count as u32)).collect())
     }
 
+    fn display_name(user: &user::Model) -> String {
+        match (&user.first_name, &user.last_name) {
+            (None, None) => user.email.clone(),
+            (first_name, last_name) => format!(
+                "{} {}",
+                first_name.as_deref().unwrap_or_default(),
+                last_name.as_deref().unwrap_or_default()
+            )
+            .trim()
+            .to_string(),
+        }
+    }
+
+    // Plain-text reply as simple HTML: escaped, with paragraphs and line breaks kept
+    fn text_to_html(text: &str) -> String {
+        let escaped = text
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;");
+
+        escaped
+            .split("\n\n")
+            .map(|paragraph| format!("<p>{}</p>", paragraph.trim().replace('\n', "<br>")))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
     // Helper function to extract tags from email content
     fn extract_tags_from_email(&self, input: &EmailIngestInput) -> Option<Vec<String>> {
         let mut tags = Vec::new();
@@ -361,7 +660,9 @@ impl EmailContextService {
     }
 
     // Thread management
-    pub async fn get_email_thread(&self, thread_id: &str, project_id: Uuid) -> Result<Vec<email_context::Model>> {
+    pub async fn get_email_thread(&self, thread_id: &str, project_id: Uuid, user_id: Uuid) -> Result<Vec<email_context::Model>> {
+        self.context_service.authorize(project_id, user_id, ProjectAction::ProjectView).await?;
+
         EmailContext::find()
             .join(JoinType::InnerJoin, email_context::Relation::ProjectContext.def())
             .filter(project_context::Column::ProjectId.eq(project_id))
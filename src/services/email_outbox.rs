@@ -0,0 +1,216 @@
+use chrono::{Duration, Utc};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder,
+    QuerySelect, Set,
+};
+use uuid::Uuid;
+
+use crate::entities::{email_outbox, prelude::*};
+use crate::graphql::types::EmailOutboxStatus;
+use crate::services::{EmailMessage, EmailService};
+use crate::services::queue::{claim_due_ids, retry_backoff};
+
+/// Queue an email for delivery. Pass the transaction of the change that triggers the
+/// email so both are committed, or rolled back, together.
+pub async fn queue_email<C: ConnectionTrait>(conn: &C, message: EmailMessage) -> Result<email_outbox::Model, DbErr> {
+    let attachments = if message.attachments.is_empty() {
+        None
+    } else {
+        Some(serde_json::to_value(&message.attachments).map_err(|e| DbErr::Custom(e.to_string()))?)
+    };
+
+    email_outbox::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        kind: Set(message.kind),
+        to_email: Set(message.to_email),
+        subject: Set(message.subject),
+        html_body: Set(message.html_content),
+        text_body: Set(Some(message.text_content)),
+        from_name: Set(message.from_name),
+        message_id: Set(message.message_id),
+        in_reply_to: Set(message.in_reply_to),
+        thread_references: Set(Some(message.references.join(" ")).filter(|references| !references.is_empty())),
+        attachments: Set(attachments),
+        status: Set(EmailOutboxStatus::Pending),
+        attempts: Set(0),
+        next_attempt_at: Set(Utc::now().into()),
+        last_error: Set(None),
+        sent_at: Set(None),
+        created_at: Set(Utc::now().into()),
+        updated_at: Set(Utc::now().into()),
+    }
+    .insert(conn)
+    .await
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct EmailOutboxConfig {
+    /// Attempts before an email is dead-lettered
+    pub max_attempts: i32,
+    /// Delay before the first retry; doubles with each further attempt
+    pub base_backoff_seconds: i64,
+    pub max_backoff_seconds: i64,
+    /// Emails claimed per dispatch run
+    pub batch_size: u64,
+}
+
+impl Default for EmailOutboxConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 8,
+            base_backoff_seconds: 30,
+            max_backoff_seconds: 6 * 60 * 60,
+            batch_size: 50,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct EmailDispatchSummary {
+    pub sent: usize,
+    pub retried: usize,
+    pub dead: usize,
+}
+
+#[derive(Clone)]
+pub struct EmailOutboxService {
+    db: DatabaseConnection,
+    email_service: EmailService,
+    config: EmailOutboxConfig,
+}
+
+impl EmailOutboxService {
+    pub fn new(db: DatabaseConnection, email_service: EmailService, config: EmailOutboxConfig) -> Self {
+        Self { db, email_service, config }
+    }
+
+    /// Queue an email outside of any other change
+    pub async fn enqueue(&self, message: EmailMessage) -> Result<email_outbox::Model, Box<dyn std::error::Error>> {
+        Ok(queue_email(&self.db, message).await?)
+    }
+
+    /// Deliver the emails that are due. Safe to run from several replicas at once:
+    /// each email is claimed by exactly one of them.
+    pub async fn dispatch_due(&self) -> Result<EmailDispatchSummary, Box<dyn std::error::Error>> {
+        let mut summary = EmailDispatchSummary::default();
+
+        for email in self.claim_due().await? {
+            let result = match Self::message(&email) {
+                Ok(message) => self.email_service.deliver(&message).await.map_err(|e| e.to_string()),
+                Err(e) => Err(format!("Invalid outbox entry: {}", e)),
+            };
+
+            let attempts = email.attempts;
+            let mut email_active: email_outbox::ActiveModel = email.into();
+            match result {
+                Ok(()) => {
+                    email_active.status = Set(EmailOutboxStatus::Sent);
+                    email_active.sent_at = Set(Some(Utc::now().into()));
+                    email_active.last_error = Set(None);
+                    summary.sent += 1;
+                }
+                Err(error) if attempts >= self.config.max_attempts => {
+                    email_active.status = Set(EmailOutboxStatus::Dead);
+                    email_active.last_error = Set(Some(error));
+                    summary.dead += 1;
+                }
+                Err(error) => {
+                    email_active.next_attempt_at = Set((Utc::now() + self.backoff(attempts)).into());
+                    email_active.last_error = Set(Some(error));
+                    summary.retried += 1;
+                }
+            }
+            email_active.updated_at = Set(Utc::now().into());
+            email_active.update(&self.db).await?;
+        }
+
+        Ok(summary)
+    }
+
+    /// Outbox entries, newest first
+    pub async fn list(
+        &self,
+        status: Option<EmailOutboxStatus>,
+        limit: Option<u64>,
+        offset: Option<u64>,
+    ) -> Result<Vec<email_outbox::Model>, Box<dyn std::error::Error>> {
+        let mut query = EmailOutbox::find();
+        if let Some(status) = status {
+            query = query.filter(email_outbox::Column::Status.eq(status));
+        }
+
+        let emails = query
+            .order_by_desc(email_outbox::Column::CreatedAt)
+            .limit(limit.unwrap_or(50))
+            .offset(offset.unwrap_or(0))
+            .all(&self.db)
+            .await?;
+        Ok(emails)
+    }
+
+    pub async fn get(&self, id: Uuid) -> Result<Option<email_outbox::Model>, Box<dyn std::error::Error>> {
+        Ok(EmailOutbox::find_by_id(id).one(&self.db).await?)
+    }
+
+    /// Put an email back in the queue with a fresh set of attempts, e.g. after fixing
+    /// whatever made it dead-letter, or to send a delivered one again
+    pub async fn resend(&self, id: Uuid) -> Result<email_outbox::Model, Box<dyn std::error::Error>> {
+        let email = self.get(id).await?.ok_or("Email not found")?;
+        if email.status == EmailOutboxStatus::Pending && email.attempts == 0 {
+            return Err("Email is already queued".into());
+        }
+
+        let mut email_active: email_outbox::ActiveModel = email.into();
+        email_active.status = Set(EmailOutboxStatus::Pending);
+        email_active.attempts = Set(0);
+        email_active.next_attempt_at = Set(Utc::now().into());
+        email_active.last_error = Set(None);
+        email_active.sent_at = Set(None);
+        email_active.updated_at = Set(Utc::now().into());
+        Ok(email_active.update(&self.db).await?)
+    }
+
+    /// Rebuild the message an outbox entry was queued from
+    fn message(email: &email_outbox::Model) -> Result<EmailMessage, serde_json::Error> {
+        let attachments = match &email.attachments {
+            Some(attachments) => serde_json::from_value(attachments.clone())?,
+            None => Vec::new(),
+        };
+
+        Ok(EmailMessage {
+            kind: email.kind.clone(),
+            to_email: email.to_email.clone(),
+            from_name: email.from_name.clone(),
+            subject: email.subject.clone(),
+            html_content: email.html_body.clone(),
+            text_content: email.text_body.clone().unwrap_or_default(),
+            message_id: email.message_id.clone(),
+            in_reply_to: email.in_reply_to.clone(),
+            references: email
+                .thread_references
+                .as_deref()
+                .map(|references| references.split_whitespace().map(str::to_string).collect())
+                .unwrap_or_default(),
+            attachments,
+        })
+    }
+
+    /// Claim a batch of due emails, counting the attempt and leasing them so other
+    /// dispatchers skip them
+    async fn claim_due(&self) -> Result<Vec<email_outbox::Model>, DbErr> {
+        let ids = claim_due_ids::<EmailOutbox, _>(&self.db, self.config.batch_size).await?;
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        EmailOutbox::find()
+            .filter(email_outbox::Column::Id.is_in(ids))
+            .order_by_asc(email_outbox::Column::NextAttemptAt)
+            .all(&self.db)
+            .await
+    }
+
+    fn backoff(&self, attempts: i32) -> Duration {
+        retry_backoff(attempts, self.config.base_backoff_seconds, self.config.max_backoff_seconds)
+    }
+}
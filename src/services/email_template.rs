@@ -0,0 +1,366 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use minijinja::value::{Kwargs, Value};
+use minijinja::{Environment, Error, ErrorKind, State};
+use sea_orm::{ConnectionTrait, DbErr, EntityTrait};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::entities::{prelude::*, user};
+use crate::graphql::types::EmailTemplateKind;
+use crate::services::EmailMessage;
+
+/// Locale used when neither the recipient nor their organization picked one
+pub const DEFAULT_EMAIL_LOCALE: &str = "en";
+
+const PRODUCT_NAME: &str = "FreshAPI";
+const DEFAULT_PRIMARY_COLOR: &str = "#1f2937";
+const DEFAULT_ACCENT_COLOR: &str = "#2563eb";
+
+/// Built-in templates; files in `EMAIL_TEMPLATES_DIR` with the same name take precedence
+const EMBEDDED_TEMPLATES: &[(&str, &str)] = &[
+    ("layout.html", include_str!("../../templates/email/layout.html")),
+    ("layout.txt", include_str!("../../templates/email/layout.txt")),
+    ("macros.html", include_str!("../../templates/email/macros.html")),
+    ("email_verification.html", include_str!("../../templates/email/email_verification.html")),
+    ("email_verification.txt", include_str!("../../templates/email/email_verification.txt")),
+    ("password_reset.html", include_str!("../../templates/email/password_reset.html")),
+    ("password_reset.txt", include_str!("../../templates/email/password_reset.txt")),
+    ("magic_link.html", include_str!("../../templates/email/magic_link.html")),
+    ("magic_link.txt", include_str!("../../templates/email/magic_link.txt")),
+    ("invitation.html", include_str!("../../templates/email/invitation.html")),
+    ("invitation.txt", include_str!("../../templates/email/invitation.txt")),
+    ("admin_password_reset.html", include_str!("../../templates/email/admin_password_reset.html")),
+    ("admin_password_reset.txt", include_str!("../../templates/email/admin_password_reset.txt")),
+    ("project_invitation.html", include_str!("../../templates/email/project_invitation.html")),
+    ("project_invitation.txt", include_str!("../../templates/email/project_invitation.txt")),
+    ("digest.html", include_str!("../../templates/email/digest.html")),
+    ("digest.txt", include_str!("../../templates/email/digest.txt")),
+    ("daily_digest.html", include_str!("../../templates/email/daily_digest.html")),
+    ("daily_digest.txt", include_str!("../../templates/email/daily_digest.txt")),
+    ("weekly_digest.html", include_str!("../../templates/email/weekly_digest.html")),
+    ("weekly_digest.txt", include_str!("../../templates/email/weekly_digest.txt")),
+];
+
+/// Built-in message catalogs; `locales/<locale>.json` in `EMAIL_TEMPLATES_DIR` adds or
+/// overrides strings
+const EMBEDDED_LOCALES: &[(&str, &str)] = &[
+    ("en", include_str!("../../templates/email/locales/en.json")),
+    ("de", include_str!("../../templates/email/locales/de.json")),
+];
+
+type Catalogs = HashMap<String, HashMap<String, String>>;
+
+impl EmailTemplateKind {
+    pub const ALL: [EmailTemplateKind; 8] = [
+        EmailTemplateKind::EmailVerification,
+        EmailTemplateKind::PasswordReset,
+        EmailTemplateKind::MagicLink,
+        EmailTemplateKind::Invitation,
+        EmailTemplateKind::AdminPasswordReset,
+        EmailTemplateKind::ProjectInvitation,
+        EmailTemplateKind::DailyDigest,
+        EmailTemplateKind::WeeklyDigest,
+    ];
+
+    /// Template file stem, catalog key prefix and outbox kind
+    pub fn name(&self) -> &'static str {
+        match self {
+            EmailTemplateKind::EmailVerification => "email_verification",
+            EmailTemplateKind::PasswordReset => "password_reset",
+            EmailTemplateKind::MagicLink => "magic_link",
+            EmailTemplateKind::Invitation => "invitation",
+            EmailTemplateKind::AdminPasswordReset => "admin_password_reset",
+            EmailTemplateKind::ProjectInvitation => "project_invitation",
+            EmailTemplateKind::DailyDigest => "daily_digest",
+            EmailTemplateKind::WeeklyDigest => "weekly_digest",
+        }
+    }
+
+    /// Made-up variables for previews and the startup check
+    pub fn sample_vars(&self) -> serde_json::Value {
+        let url = "https://app.example.com/link?token=sample-token";
+        match self {
+            EmailTemplateKind::MagicLink => serde_json::json!({ "url": url, "expires_in_minutes": 15 }),
+            EmailTemplateKind::AdminPasswordReset => serde_json::json!({ "url": url, "admin_name": "Alex Admin" }),
+            EmailTemplateKind::ProjectInvitation => serde_json::json!({
+                "url": url,
+                "inviter_name": "Alex Admin",
+                "project_name": "Year-end close",
+            }),
+            EmailTemplateKind::DailyDigest | EmailTemplateKind::WeeklyDigest => {
+                let task = |name: &str, due_date: &str| {
+                    serde_json::json!({ "name": name, "project_name": "Year-end close", "due_date": due_date })
+                };
+                serde_json::json!({
+                    "url": "https://app.example.com",
+                    "frequency": if *self == EmailTemplateKind::DailyDigest { "daily" } else { "weekly" },
+                    "date": "2026-01-05",
+                    "overdue": [task("Reconcile December bank statements", "2026-01-02")],
+                    "due_today": [task("Approve supplier invoices", "2026-01-05")],
+                    "due_this_week": [task("Send payroll report", "2026-01-08")],
+                    "new_assignments": [task("Review VAT return", "")],
+                    "mentions": [{ "title": "You were mentioned on Review VAT return", "body": "Added comment: Can you check the totals?" }],
+                    "email_count": 4,
+                    "emails": [
+                        {
+                            "process": "AP",
+                            "count": 3,
+                            "emails": [
+                                { "subject": "Invoice 2026-001", "project_name": "Year-end close" },
+                                { "subject": "Invoice 2026-002", "project_name": "Year-end close" },
+                            ],
+                        },
+                        {
+                            "process": "Tax",
+                            "count": 1,
+                            "emails": [{ "subject": "VAT assessment", "project_name": "Year-end close" }],
+                        },
+                    ],
+                })
+            }
+            _ => serde_json::json!({ "url": url }),
+        }
+    }
+}
+
+/// Look and feel of an email, from the organization it is sent on behalf of
+#[derive(Debug, Clone, Serialize)]
+pub struct EmailBranding {
+    pub product: String,
+    /// Organization name, or the product name without one
+    pub display_name: String,
+    pub organization_name: Option<String>,
+    pub logo_url: Option<String>,
+    pub primary_color: String,
+    pub accent_color: String,
+    /// Display name in the From header
+    pub sender_name: Option<String>,
+}
+
+impl Default for EmailBranding {
+    fn default() -> Self {
+        Self {
+            product: PRODUCT_NAME.to_string(),
+            display_name: PRODUCT_NAME.to_string(),
+            organization_name: None,
+            logo_url: None,
+            primary_color: DEFAULT_PRIMARY_COLOR.to_string(),
+            accent_color: DEFAULT_ACCENT_COLOR.to_string(),
+            sender_name: None,
+        }
+    }
+}
+
+/// Language and branding to render an email with
+#[derive(Debug, Clone)]
+pub struct EmailStyle {
+    pub locale: String,
+    pub branding: EmailBranding,
+}
+
+impl Default for EmailStyle {
+    fn default() -> Self {
+        Self {
+            locale: DEFAULT_EMAIL_LOCALE.to_string(),
+            branding: EmailBranding::default(),
+        }
+    }
+}
+
+/// Style for an email sent on behalf of an organization. The recipient's locale wins over
+/// the organization's default.
+pub async fn email_style<C: ConnectionTrait>(
+    conn: &C,
+    organization_id: Option<Uuid>,
+    locale: Option<&str>,
+) -> Result<EmailStyle, DbErr> {
+    let organization = match organization_id {
+        Some(organization_id) => Organization::find_by_id(organization_id).one(conn).await?,
+        None => None,
+    };
+
+    let Some(organization) = organization else {
+        return Ok(EmailStyle {
+            locale: locale.unwrap_or(DEFAULT_EMAIL_LOCALE).to_string(),
+            ..EmailStyle::default()
+        });
+    };
+
+    Ok(EmailStyle {
+        locale: locale.unwrap_or(&organization.default_locale).to_string(),
+        branding: EmailBranding {
+            display_name: organization.name.clone(),
+            organization_name: Some(organization.name),
+            logo_url: organization.brand_logo_url,
+            primary_color: organization.brand_primary_color.unwrap_or_else(|| DEFAULT_PRIMARY_COLOR.to_string()),
+            accent_color: organization.brand_accent_color.unwrap_or_else(|| DEFAULT_ACCENT_COLOR.to_string()),
+            sender_name: organization.email_sender_name,
+            ..EmailBranding::default()
+        },
+    })
+}
+
+/// Style for an email to an existing user, branded for their current organization
+pub async fn email_style_for_user<C: ConnectionTrait>(conn: &C, user: &user::Model) -> Result<EmailStyle, DbErr> {
+    email_style(conn, user.current_organization_id, user.locale.as_deref()).await
+}
+
+/// Transactional email templates: an HTML and a plain-text part per email, sharing a
+/// layout, with strings from per-locale message catalogs
+pub struct EmailTemplates {
+    env: Environment<'static>,
+    catalogs: Arc<Catalogs>,
+}
+
+impl EmailTemplates {
+    /// Load the templates, preferring files in `dir` over the built-in ones, and check
+    /// that every template renders in every locale
+    pub fn load(dir: Option<PathBuf>) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut catalogs = Catalogs::new();
+        for (locale, source) in EMBEDDED_LOCALES {
+            catalogs.insert(locale.to_string(), serde_json::from_str(source)?);
+        }
+        if let Some(locales_dir) = dir.as_ref().map(|dir| dir.join("locales")).filter(|dir| dir.is_dir()) {
+            for entry in std::fs::read_dir(locales_dir)? {
+                let path = entry?.path();
+                let Some(locale) = path
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .filter(|_| path.extension().is_some_and(|ext| ext == "json"))
+                else {
+                    continue;
+                };
+                let strings: HashMap<String, String> = serde_json::from_str(&std::fs::read_to_string(&path)?)
+                    .map_err(|e| format!("Invalid email catalog {}: {}", path.display(), e))?;
+                catalogs.entry(locale.to_string()).or_default().extend(strings);
+            }
+        }
+        let catalogs = Arc::new(catalogs);
+
+        let mut env = Environment::new();
+        env.set_loader(move |name| {
+            if let Some(dir) = &dir {
+                let path = dir.join(name);
+                if path.is_file() {
+                    return std::fs::read_to_string(&path).map(Some).map_err(|e| {
+                        Error::new(ErrorKind::InvalidOperation, format!("Failed to read {}", path.display())).with_source(e)
+                    });
+                }
+            }
+            Ok(EMBEDDED_TEMPLATES
+                .iter()
+                .find(|(template, _)| *template == name)
+                .map(|(_, source)| source.to_string()))
+        });
+
+        let function_catalogs = catalogs.clone();
+        env.add_function("t", move |state: &State, key: &str, kwargs: Kwargs| -> Result<String, Error> {
+            let locale = state
+                .lookup("locale")
+                .and_then(|locale| locale.as_str().map(str::to_string))
+                .unwrap_or_else(|| DEFAULT_EMAIL_LOCALE.to_string());
+            let mut args = Vec::new();
+            for name in kwargs.args() {
+                let value: Value = kwargs.get(name)?;
+                args.push((name.to_string(), if value.is_none() || value.is_undefined() { String::new() } else { value.to_string() }));
+            }
+            Ok(translate(&function_catalogs, &locale, key, &args))
+        });
+
+        let templates = Self { env, catalogs };
+        for locale in templates.locales() {
+            let style = EmailStyle { locale: locale.clone(), ..EmailStyle::default() };
+            for kind in EmailTemplateKind::ALL {
+                templates
+                    .render(kind, "check@example.com", &style, kind.sample_vars())
+                    .map_err(|e| format!("Email template {} ({}) is invalid: {:#}", kind.name(), locale, e))?;
+            }
+        }
+
+        Ok(templates)
+    }
+
+    /// Locales with a message catalog
+    pub fn locales(&self) -> Vec<String> {
+        let mut locales: Vec<String> = self.catalogs.keys().cloned().collect();
+        locales.sort();
+        locales
+    }
+
+    /// The closest supported locale: `de-AT` falls back to `de`, unknown ones to the default
+    pub fn resolve_locale(&self, locale: &str) -> String {
+        let locale = locale.trim().replace('_', "-").to_lowercase();
+        if self.catalogs.contains_key(&locale) {
+            return locale;
+        }
+        match locale.split('-').next() {
+            Some(language) if self.catalogs.contains_key(language) => language.to_string(),
+            _ => DEFAULT_EMAIL_LOCALE.to_string(),
+        }
+    }
+
+    pub fn render(
+        &self,
+        kind: EmailTemplateKind,
+        to_email: &str,
+        style: &EmailStyle,
+        vars: serde_json::Value,
+    ) -> Result<EmailMessage, Error> {
+        let locale = self.resolve_locale(&style.locale);
+
+        // Subjects come straight from the catalog, filled in from the template variables
+        let mut subject_args = vec![("product".to_string(), style.branding.product.clone())];
+        if let Some(vars) = vars.as_object() {
+            for (name, value) in vars {
+                match value {
+                    serde_json::Value::String(value) => subject_args.push((name.clone(), value.clone())),
+                    serde_json::Value::Number(value) => subject_args.push((name.clone(), value.to_string())),
+                    _ => {}
+                }
+            }
+        }
+        let subject = translate(&self.catalogs, &locale, &format!("{}.subject", kind.name()), &subject_args);
+
+        let mut context = match vars {
+            serde_json::Value::Object(vars) => vars,
+            _ => serde_json::Map::new(),
+        };
+        context.insert("locale".to_string(), serde_json::Value::String(locale));
+        context.insert("subject".to_string(), serde_json::Value::String(subject.clone()));
+        context.insert(
+            "brand".to_string(),
+            serde_json::to_value(&style.branding).map_err(|e| Error::new(ErrorKind::BadSerialization, e.to_string()))?,
+        );
+        let context = Value::from_serialize(&context);
+
+        let html_content = self.env.get_template(&format!("{}.html", kind.name()))?.render(&context)?;
+        let text_content = self.env.get_template(&format!("{}.txt", kind.name()))?.render(&context)?;
+
+        Ok(EmailMessage {
+            kind: kind.name().to_string(),
+            to_email: to_email.to_string(),
+            from_name: style.branding.sender_name.clone(),
+            subject,
+            html_content,
+            text_content: text_content.trim().to_string(),
+            ..EmailMessage::default()
+        })
+    }
+}
+
+/// Look up a catalog string, falling back to the default locale and then the key itself,
+/// and fill in its `{name}` placeholders
+fn translate(catalogs: &Catalogs, locale: &str, key: &str, args: &[(String, String)]) -> String {
+    let template = catalogs
+        .get(locale)
+        .and_then(|strings| strings.get(key))
+        .or_else(|| catalogs.get(DEFAULT_EMAIL_LOCALE).and_then(|strings| strings.get(key)))
+        .map(String::as_str)
+        .unwrap_or(key);
+
+    args.iter()
+        .fold(template.to_string(), |text, (name, value)| text.replace(&format!("{{{}}}", name), value))
+}
@@ -0,0 +1,354 @@
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use base64::Engine;
+use lettre::message::header::ContentType;
+use lettre::message::{Attachment, Mailbox, MultiPart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::services::EmailMessage;
+
+pub type EmailTransportError = Box<dyn std::error::Error + Send + Sync>;
+
+/// An attachment read from storage, ready to be sent
+#[derive(Debug, Clone)]
+pub struct AttachmentContent {
+    pub filename: String,
+    pub content_type: String,
+    pub content: Vec<u8>,
+}
+
+/// Delivers rendered emails. Chosen at startup with `EMAIL_TRANSPORT`.
+#[async_trait]
+pub trait EmailTransport: Send + Sync {
+    /// Name shown in logs
+    fn name(&self) -> &'static str;
+
+    async fn send(
+        &self,
+        from_email: &str,
+        message: &EmailMessage,
+        attachments: &[AttachmentContent],
+    ) -> Result<(), EmailTransportError>;
+}
+
+/// The From header: the configured address under the message's display name, if any
+fn sender(from_email: &str, message: &EmailMessage) -> Result<Mailbox, EmailTransportError> {
+    let mut mailbox = from_email.parse::<Mailbox>()?;
+    if let Some(name) = &message.from_name {
+        mailbox.name = Some(name.clone());
+    }
+    Ok(mailbox)
+}
+
+/// Sends through the Resend HTTP API
+pub struct ResendTransport {
+    api_key: String,
+    client: reqwest::Client,
+}
+
+impl ResendTransport {
+    pub fn new(api_key: &str) -> Self {
+        Self {
+            api_key: api_key.to_string(),
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl EmailTransport for ResendTransport {
+    fn name(&self) -> &'static str {
+        "resend"
+    }
+
+    async fn send(
+        &self,
+        from_email: &str,
+        message: &EmailMessage,
+        attachments: &[AttachmentContent],
+    ) -> Result<(), EmailTransportError> {
+        let mut headers = serde_json::Map::new();
+        if let Some(message_id) = &message.message_id {
+            headers.insert("Message-ID".to_string(), message_id.clone().into());
+        }
+        if let Some(in_reply_to) = &message.in_reply_to {
+            headers.insert("In-Reply-To".to_string(), in_reply_to.clone().into());
+        }
+        if !message.references.is_empty() {
+            headers.insert("References".to_string(), message.references.join(" ").into());
+        }
+
+        let attachments: Vec<serde_json::Value> = attachments
+            .iter()
+            .map(|attachment| {
+                serde_json::json!({
+                    "filename": attachment.filename,
+                    "content": base64::engine::general_purpose::STANDARD.encode(&attachment.content),
+                    "content_type": attachment.content_type
+                })
+            })
+            .collect();
+
+        let payload = serde_json::json!({
+            "from": sender(from_email, message)?.to_string(),
+            "to": [message.to_email],
+            "subject": message.subject,
+            "html": message.html_content,
+            "text": message.text_content,
+            "headers": headers,
+            "attachments": attachments
+        });
+
+        let response = self.client
+            .post("https://api.resend.com/emails")
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .header("Content-Type", "application/json")
+            .json(&payload)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            return Err(format!("Failed to send email via Resend: {}", error_text).into());
+        }
+
+        println!("✅ Email sent successfully to {}", message.to_email);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmtpSecurity {
+    /// Plain connection, e.g. to Mailpit or MailHog on localhost
+    None,
+    /// Upgrade a plain connection with STARTTLS, usually on port 587
+    StartTls,
+    /// TLS from the start, usually on port 465
+    Tls,
+}
+
+impl SmtpSecurity {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "none" | "plain" => Some(Self::None),
+            "starttls" => Some(Self::StartTls),
+            "tls" | "ssl" => Some(Self::Tls),
+            _ => None,
+        }
+    }
+
+    pub fn default_port(&self) -> u16 {
+        match self {
+            Self::None => 25,
+            Self::StartTls => 587,
+            Self::Tls => 465,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub security: SmtpSecurity,
+    /// Username and password, for servers that require authentication
+    pub credentials: Option<(String, String)>,
+}
+
+/// Sends through any SMTP server
+pub struct SmtpTransport {
+    mailer: AsyncSmtpTransport<Tokio1Executor>,
+}
+
+impl SmtpTransport {
+    pub fn new(config: SmtpConfig) -> Result<Self, EmailTransportError> {
+        let builder = match config.security {
+            SmtpSecurity::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.host),
+            SmtpSecurity::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host)?,
+            SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host)?,
+        };
+
+        let mut builder = builder.port(config.port);
+        if let Some((username, password)) = config.credentials {
+            builder = builder.credentials(Credentials::new(username, password));
+        }
+
+        Ok(Self { mailer: builder.build() })
+    }
+}
+
+#[async_trait]
+impl EmailTransport for SmtpTransport {
+    fn name(&self) -> &'static str {
+        "smtp"
+    }
+
+    async fn send(
+        &self,
+        from_email: &str,
+        message: &EmailMessage,
+        attachments: &[AttachmentContent],
+    ) -> Result<(), EmailTransportError> {
+        let mut builder = Message::builder()
+            .from(sender(from_email, message)?)
+            .to(message.to_email.parse::<Mailbox>()?)
+            .subject(message.subject.as_str())
+            .message_id(message.message_id.clone());
+        if let Some(in_reply_to) = &message.in_reply_to {
+            builder = builder.in_reply_to(in_reply_to.clone());
+        }
+        if !message.references.is_empty() {
+            builder = builder.references(message.references.join(" "));
+        }
+
+        let body = MultiPart::alternative_plain_html(message.text_content.clone(), message.html_content.clone());
+        let email = if attachments.is_empty() {
+            builder.multipart(body)?
+        } else {
+            let mut mixed = MultiPart::mixed().multipart(body);
+            for attachment in attachments {
+                let content_type = ContentType::parse(&attachment.content_type)
+                    .unwrap_or_else(|_| ContentType::parse("application/octet-stream").expect("valid content type"));
+                mixed = mixed.singlepart(
+                    Attachment::new(attachment.filename.clone()).body(attachment.content.clone(), content_type),
+                );
+            }
+            builder.multipart(mixed)?
+        };
+
+        self.mailer.send(email).await?;
+        Ok(())
+    }
+}
+
+/// An email kept by the capture transport
+#[derive(Debug, Clone, Serialize)]
+pub struct CapturedEmail {
+    pub id: Uuid,
+    pub kind: String,
+    pub from_email: String,
+    pub to_email: String,
+    pub subject: String,
+    pub html_content: String,
+    pub text_content: String,
+    pub message_id: Option<String>,
+    pub in_reply_to: Option<String>,
+    pub references: Vec<String>,
+    /// Attachment file names
+    pub attachments: Vec<String>,
+    pub captured_at: DateTime<Utc>,
+}
+
+/// Keeps emails instead of sending them, for tests and local development. Emails are
+/// held in memory and, when a directory is given, also written there as JSON files.
+#[derive(Clone)]
+pub struct CaptureTransport {
+    captured: Arc<Mutex<Vec<CapturedEmail>>>,
+    dir: Option<PathBuf>,
+}
+
+impl CaptureTransport {
+    /// Emails kept in memory; older ones are dropped beyond this
+    const MAX_CAPTURED: usize = 1000;
+
+    pub fn new(dir: Option<PathBuf>) -> Self {
+        Self {
+            captured: Arc::new(Mutex::new(Vec::new())),
+            dir,
+        }
+    }
+
+    /// Emails captured so far, oldest first
+    #[cfg(test)]
+    pub fn captured(&self) -> Vec<CapturedEmail> {
+        self.captured.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+    }
+
+    #[cfg(test)]
+    pub fn clear(&self) {
+        self.captured.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clear();
+    }
+}
+
+#[async_trait]
+impl EmailTransport for CaptureTransport {
+    fn name(&self) -> &'static str {
+        "capture"
+    }
+
+    async fn send(
+        &self,
+        from_email: &str,
+        message: &EmailMessage,
+        attachments: &[AttachmentContent],
+    ) -> Result<(), EmailTransportError> {
+        let email = CapturedEmail {
+            id: Uuid::new_v4(),
+            kind: message.kind.clone(),
+            from_email: sender(from_email, message)?.to_string(),
+            to_email: message.to_email.clone(),
+            subject: message.subject.clone(),
+            html_content: message.html_content.clone(),
+            text_content: message.text_content.clone(),
+            message_id: message.message_id.clone(),
+            in_reply_to: message.in_reply_to.clone(),
+            references: message.references.clone(),
+            attachments: attachments.iter().map(|attachment| attachment.filename.clone()).collect(),
+            captured_at: Utc::now(),
+        };
+
+        if let Some(dir) = &self.dir {
+            tokio::fs::create_dir_all(dir).await?;
+            let path = dir.join(format!("{}-{}.json", email.captured_at.format("%Y%m%dT%H%M%S%.3f"), email.id));
+            tokio::fs::write(&path, serde_json::to_vec_pretty(&email)?).await?;
+        }
+
+        println!(
+            "📧 [CAPTURED] Email not sent:\nTo: {}\nFrom: {}\nSubject: {}\n\n{}",
+            email.to_email, email.from_email, email.subject, email.text_content
+        );
+
+        let mut captured = self.captured.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if captured.len() >= Self::MAX_CAPTURED {
+            captured.remove(0);
+        }
+        captured.push(email);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn capture_transport_keeps_emails_until_cleared() {
+        let transport = CaptureTransport::new(None);
+        let message = EmailMessage {
+            kind: "invitation".to_string(),
+            to_email: "invitee@example.com".to_string(),
+            from_name: Some("Acme".to_string()),
+            subject: "You're invited".to_string(),
+            html_content: "<p>Join us</p>".to_string(),
+            text_content: "Join us".to_string(),
+            ..Default::default()
+        };
+
+        transport.send("noreply@example.com", &message, &[]).await.unwrap();
+
+        let captured = transport.captured();
+        assert_eq!(captured.len(), 1);
+        assert_eq!(captured[0].to_email, "invitee@example.com");
+        assert_eq!(captured[0].from_email, "Acme <noreply@example.com>");
+
+        transport.clear();
+        assert!(transport.captured().is_empty());
+    }
+}
@@ -1,57 +1,231 @@
 use chrono::{Duration, Utc};
-use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set};
+use std::collections::HashSet;
+
+use sea_orm::{
+    prelude::DateTimeWithTimeZone, ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr,
+    EntityTrait, QueryFilter, QueryOrder, Set, TransactionTrait,
+};
 use uuid::Uuid;
 
-use crate::entities::{prelude::*, invitation, user};
-use crate::services::EmailService;
+use crate::auth::PermissionService;
+use crate::entities::{prelude::*, invitation, project, project_invitation, project_member, project_role, role, user};
+use crate::graphql::types::{BulkInviteErrorCode, BulkInviteRowStatus, ProjectInvitationStatus};
+use crate::services::{email_style, queue_email, EmailService, ProjectAction, ProjectRole, ProjectService, unscoped};
+
+/// Upper bound on rows in one bulk invitation
+pub const BULK_INVITE_MAX_ROWS: usize = 500;
+
+/// A project the invited person joins, with the given project role, once they sign up
+#[derive(Debug, Clone)]
+pub struct ProjectInvitationGrant {
+    pub project_id: Uuid,
+    pub role: String,
+}
+
+/// A pending project invitation with the address it was sent to
+pub struct PendingProjectInvitation {
+    pub invitation: project_invitation::Model,
+    pub email: String,
+    /// Expiry of the sign-up invitation; offers to existing users don't expire
+    pub expires_at: Option<DateTimeWithTimeZone>,
+}
+
+/// One person to invite in a bulk invitation. Roles and projects are given by name or id,
+/// as they appear in a spreadsheet.
+#[derive(Debug, Clone)]
+pub struct BulkInviteRow {
+    /// 1-based row number reported back to the caller
+    pub row: usize,
+    pub email: String,
+    pub role: Option<String>,
+    /// (project name or id, project role name)
+    pub projects: Vec<(String, String)>,
+}
+
+/// Outcome for one row of a bulk invitation
+pub struct BulkInviteRowOutcome {
+    pub row: usize,
+    pub email: String,
+    pub status: BulkInviteRowStatus,
+    pub error_code: Option<BulkInviteErrorCode>,
+    pub error: Option<String>,
+    pub invitation: Option<invitation::Model>,
+}
+
+impl BulkInviteRowOutcome {
+    fn failed(row: usize, email: String, code: BulkInviteErrorCode, error: impl Into<String>) -> Self {
+        Self {
+            row,
+            email,
+            status: BulkInviteRowStatus::Failed,
+            error_code: Some(code),
+            error: Some(error.into()),
+            invitation: None,
+        }
+    }
+}
+
+/// Parse a bulk invitation CSV. The header row names the columns: `email` is required,
+/// `role` and `projects` are optional. `projects` lists `project:role` pairs separated by
+/// `;`, with the project given by name or id. Rows that can't be read come back as
+/// failed outcomes so they are reported alongside the rest.
+pub fn parse_bulk_invite_csv(
+    content: &str,
+) -> Result<(Vec<BulkInviteRow>, Vec<BulkInviteRowOutcome>), Box<dyn std::error::Error>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .flexible(true)
+        .from_reader(content.as_bytes());
+
+    let headers: Vec<String> = reader.headers()?.iter().map(|h| h.to_lowercase()).collect();
+    let column = |name: &str| headers.iter().position(|h| h == name);
+    let email_column = column("email").ok_or("CSV must have an 'email' column")?;
+    let role_column = column("role");
+    let projects_column = column("projects");
+
+    let mut rows = Vec::new();
+    let mut malformed = Vec::new();
+    for (index, record) in reader.records().enumerate() {
+        let row = index + 1;
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                malformed.push(BulkInviteRowOutcome::failed(row, String::new(), BulkInviteErrorCode::MalformedRow, e.to_string()));
+                continue;
+            }
+        };
+        if record.iter().all(|field| field.is_empty()) {
+            continue;
+        }
+
+        let email = record.get(email_column).unwrap_or_default().to_string();
+        let role = role_column
+            .and_then(|i| record.get(i))
+            .filter(|role| !role.is_empty())
+            .map(str::to_string);
+
+        let mut projects = Vec::new();
+        let mut bad_project = None;
+        for entry in projects_column
+            .and_then(|i| record.get(i))
+            .unwrap_or_default()
+            .split(';')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+        {
+            match entry.rsplit_once(':') {
+                Some((project, project_role)) if !project.trim().is_empty() && !project_role.trim().is_empty() => {
+                    projects.push((project.trim().to_string(), project_role.trim().to_string()));
+                }
+                _ => {
+                    bad_project = Some(entry.to_string());
+                    break;
+                }
+            }
+        }
+
+        match bad_project {
+            Some(entry) => malformed.push(BulkInviteRowOutcome::failed(
+                row,
+                email,
+                BulkInviteErrorCode::MalformedRow,
+                format!("Project entry '{}' must be written as project:role", entry),
+            )),
+            None => rows.push(BulkInviteRow { row, email, role, projects }),
+        }
+    }
+
+    Ok((rows, malformed))
+}
+
+/// Cheap sanity check on an address; the invitation email is the real test
+fn is_valid_email(email: &str) -> bool {
+    match email.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && domain.contains('.')
+                && !domain.starts_with('.')
+                && !domain.ends_with('.')
+                && !domain.contains('@')
+                && !email.chars().any(char::is_whitespace)
+        }
+        None => false,
+    }
+}
 
 #[derive(Clone)]
 pub struct InvitationService {
     db: DatabaseConnection,
     email_service: EmailService,
+    project_service: ProjectService,
+    permission_service: PermissionService,
 }
 
 impl InvitationService {
-    pub fn new(db: DatabaseConnection, email_service: EmailService) -> Self {
-        Self { db, email_service }
+    pub fn new(
+        db: DatabaseConnection,
+        email_service: EmailService,
+        project_service: ProjectService,
+        permission_service: PermissionService,
+    ) -> Self {
+        Self { db, email_service, project_service, permission_service }
     }
 
     pub async fn create_invitation_with_role(
         &self,
         inviter_user_id: Uuid,
+        organization_id: Option<Uuid>,
         email: &str,
         role_id: Option<Uuid>,
+        projects: &[ProjectInvitationGrant],
         base_url: &str,
     ) -> Result<invitation::Model, Box<dyn std::error::Error>> {
-        // Validate role if provided
+        // Validate role if provided; another organization's roles can't be handed out
         if let Some(role_id) = role_id {
-            let role_exists = crate::entities::role::Entity::find_by_id(role_id)
+            let role = crate::entities::role::Entity::find_by_id(role_id)
                 .one(&self.db)
                 .await?
-                .is_some();
-            
-            if !role_exists {
+                .ok_or("Invalid role specified")?;
+
+            if role.organization_id.is_some() && role.organization_id != organization_id {
                 return Err("Invalid role specified".into());
             }
+            self.permission_service.ensure_can_assign_role(inviter_user_id, organization_id, &role).await?;
+        }
+
+        let mut project_roles = Vec::with_capacity(projects.len());
+        for grant in projects {
+            if project_roles.iter().any(|(project_id, _)| *project_id == grant.project_id) {
+                return Err("Each project can only be listed once".into());
+            }
+            let role = self
+                .resolve_project_grant(inviter_user_id, organization_id, grant.project_id, &grant.role)
+                .await?;
+            project_roles.push((grant.project_id, role));
         }
 
-        self.create_invitation_internal(inviter_user_id, email, role_id, base_url).await
+        self.create_invitation_internal(inviter_user_id, organization_id, email, role_id, project_roles, base_url)
+            .await
     }
 
     pub async fn create_invitation(
         &self,
         inviter_user_id: Uuid,
+        organization_id: Option<Uuid>,
         email: &str,
         base_url: &str,
     ) -> Result<invitation::Model, Box<dyn std::error::Error>> {
-        self.create_invitation_internal(inviter_user_id, email, None, base_url).await
+        self.create_invitation_internal(inviter_user_id, organization_id, email, None, Vec::new(), base_url)
+            .await
     }
 
     async fn create_invitation_internal(
         &self,
         inviter_user_id: Uuid,
+        organization_id: Option<Uuid>,
         email: &str,
         role_id: Option<Uuid>,
+        project_roles: Vec<(Uuid, project_role::Model)>,
         base_url: &str,
     ) -> Result<invitation::Model, Box<dyn std::error::Error>> {
         // Check if user already exists
@@ -74,34 +248,251 @@ impl InvitationService {
             return Err("An active invitation already exists for this email".into());
         }
 
+        let tx = self.db.begin().await?;
+        let invitation =
+            Self::insert_invitation(&tx, inviter_user_id, organization_id, email, role_id, project_roles).await?;
+        let style = email_style(&tx, organization_id, None).await?;
+        queue_email(&tx, self.email_service.invitation_email(email, &invitation.token, base_url, &style)?).await?;
+        tx.commit().await?;
+
+        Ok(invitation)
+    }
+
+    /// Insert a sign-up invitation along with the project invitations it carries
+    async fn insert_invitation<C: ConnectionTrait>(
+        conn: &C,
+        inviter_user_id: Uuid,
+        organization_id: Option<Uuid>,
+        email: &str,
+        role_id: Option<Uuid>,
+        project_roles: Vec<(Uuid, project_role::Model)>,
+    ) -> Result<invitation::Model, DbErr> {
         // Generate invitation token
         let token = Uuid::new_v4().to_string();
         let expires_at = Utc::now() + Duration::days(7); // 7 days to accept invitation
 
         // Create invitation
-        let new_invitation = invitation::ActiveModel {
+        let invitation = invitation::ActiveModel {
             id: Set(Uuid::new_v4()),
             email: Set(email.to_string()),
             inviter_user_id: Set(inviter_user_id),
-            token: Set(token.clone()),
+            token: Set(token),
             expires_at: Set(expires_at.into()),
             is_used: Set(false),
             used_at: Set(None),
             role_id: Set(role_id),
+            organization_id: Set(organization_id),
             created_at: Set(Utc::now().into()),
             updated_at: Set(Utc::now().into()),
-        };
-
-        let invitation = new_invitation.insert(&self.db).await?;
+        }
+        .insert(conn)
+        .await?;
 
-        // Send invitation email
-        self.email_service
-            .send_invitation_email(email, &token, base_url)
+        for (project_id, role) in project_roles {
+            project_invitation::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                project_id: Set(project_id),
+                project_role_id: Set(role.id),
+                role: Set(role.name),
+                invitation_id: Set(Some(invitation.id)),
+                user_id: Set(None),
+                inviter_user_id: Set(inviter_user_id),
+                status: Set(ProjectInvitationStatus::Pending),
+                responded_at: Set(None),
+                created_at: Set(Utc::now().into()),
+                updated_at: Set(Utc::now().into()),
+            }
+            .insert(conn)
             .await?;
+        }
 
         Ok(invitation)
     }
 
+    /// Invite many people at once. Every row is validated first; unless this is a dry run,
+    /// the valid rows and their emails are then queued in a single transaction.
+    /// Invalid rows are reported and skipped.
+    pub async fn bulk_invite(
+        &self,
+        inviter_user_id: Uuid,
+        organization_id: Option<Uuid>,
+        rows: Vec<BulkInviteRow>,
+        dry_run: bool,
+        base_url: &str,
+    ) -> Result<Vec<BulkInviteRowOutcome>, Box<dyn std::error::Error>> {
+        if rows.len() > BULK_INVITE_MAX_ROWS {
+            return Err(format!("At most {} rows can be invited at once", BULK_INVITE_MAX_ROWS).into());
+        }
+
+        let mut outcomes = Vec::with_capacity(rows.len());
+        let mut valid = Vec::new();
+        let mut seen_emails = HashSet::new();
+
+        for row in rows {
+            let email = row.email.trim().to_string();
+            match self
+                .validate_bulk_row(inviter_user_id, organization_id, &row, &email, &mut seen_emails)
+                .await?
+            {
+                Ok((role_id, project_roles)) => {
+                    outcomes.push(BulkInviteRowOutcome {
+                        row: row.row,
+                        email: email.clone(),
+                        status: BulkInviteRowStatus::Valid,
+                        error_code: None,
+                        error: None,
+                        invitation: None,
+                    });
+                    valid.push((outcomes.len() - 1, email, role_id, project_roles));
+                }
+                Err((code, error)) => outcomes.push(BulkInviteRowOutcome::failed(row.row, email, code, error)),
+            }
+        }
+
+        if dry_run || valid.is_empty() {
+            return Ok(outcomes);
+        }
+
+        let tx = self.db.begin().await?;
+        let style = email_style(&tx, organization_id, None).await?;
+        let mut created = Vec::with_capacity(valid.len());
+        for (index, email, role_id, project_roles) in valid {
+            let invitation =
+                Self::insert_invitation(&tx, inviter_user_id, organization_id, &email, role_id, project_roles).await?;
+            queue_email(&tx, self.email_service.invitation_email(&email, &invitation.token, base_url, &style)?).await?;
+            created.push((index, invitation));
+        }
+        tx.commit().await?;
+
+        for (index, invitation) in created {
+            let outcome = &mut outcomes[index];
+            outcome.status = BulkInviteRowStatus::Invited;
+            outcome.invitation = Some(invitation);
+        }
+
+        Ok(outcomes)
+    }
+
+    /// Check one bulk row, resolving its role and project grants. The outer error is for
+    /// database failures; the inner one is the row's validation result.
+    async fn validate_bulk_row(
+        &self,
+        inviter_user_id: Uuid,
+        organization_id: Option<Uuid>,
+        row: &BulkInviteRow,
+        email: &str,
+        seen_emails: &mut HashSet<String>,
+    ) -> Result<
+        Result<(Option<Uuid>, Vec<(Uuid, project_role::Model)>), (BulkInviteErrorCode, String)>,
+        Box<dyn std::error::Error>,
+    > {
+        if !is_valid_email(email) {
+            return Ok(Err((BulkInviteErrorCode::InvalidEmail, "Invalid email address".to_string())));
+        }
+        if !seen_emails.insert(email.to_lowercase()) {
+            return Ok(Err((BulkInviteErrorCode::DuplicateEmail, "Email appears more than once".to_string())));
+        }
+
+        if User::find()
+            .filter(user::Column::Email.eq(email))
+            .one(&self.db)
+            .await?
+            .is_some()
+        {
+            return Ok(Err((BulkInviteErrorCode::UserExists, "User with this email already exists".to_string())));
+        }
+        if Invitation::find()
+            .filter(invitation::Column::Email.eq(email))
+            .filter(invitation::Column::IsUsed.eq(false))
+            .filter(invitation::Column::ExpiresAt.gt(Utc::now()))
+            .one(&self.db)
+            .await?
+            .is_some()
+        {
+            return Ok(Err((
+                BulkInviteErrorCode::InvitationPending,
+                "An active invitation already exists for this email".to_string(),
+            )));
+        }
+
+        let role_id = match &row.role {
+            Some(role_name) => match self.find_invitable_role(organization_id, role_name).await? {
+                Some(role) => {
+                    if let Err(e) = self.permission_service.ensure_can_assign_role(inviter_user_id, organization_id, &role).await {
+                        return Ok(Err((BulkInviteErrorCode::InvalidRoleGrant, e.to_string())));
+                    }
+                    Some(role.id)
+                }
+                None => return Ok(Err((BulkInviteErrorCode::UnknownRole, format!("Unknown role '{}'", role_name)))),
+            },
+            None => None,
+        };
+
+        let mut project_roles: Vec<(Uuid, project_role::Model)> = Vec::with_capacity(row.projects.len());
+        for (project_ref, role_name) in &row.projects {
+            let Some(project) = self.find_organization_project(organization_id, project_ref).await? else {
+                return Ok(Err((BulkInviteErrorCode::UnknownProject, format!("Unknown project '{}'", project_ref))));
+            };
+            if project_roles.iter().any(|(project_id, _)| *project_id == project.id) {
+                return Ok(Err((
+                    BulkInviteErrorCode::DuplicateProject,
+                    format!("Project '{}' is listed more than once", project.name),
+                )));
+            }
+            match self
+                .resolve_project_grant(inviter_user_id, organization_id, project.id, role_name)
+                .await
+            {
+                Ok(role) => project_roles.push((project.id, role)),
+                Err(e) => {
+                    return Ok(Err((
+                        BulkInviteErrorCode::InvalidProjectGrant,
+                        format!("Project '{}': {}", project.name, e),
+                    )));
+                }
+            }
+        }
+
+        Ok(Ok((role_id, project_roles)))
+    }
+
+    /// A role available in the organization, by id or name. Whether the inviter may hand
+    /// it out is checked separately.
+    async fn find_invitable_role(
+        &self,
+        organization_id: Option<Uuid>,
+        role_ref: &str,
+    ) -> Result<Option<role::Model>, DbErr> {
+        let query = match Uuid::parse_str(role_ref) {
+            Ok(role_id) => Role::find_by_id(role_id),
+            Err(_) => Role::find().filter(role::Column::Name.eq(role_ref)),
+        };
+        let role = query.filter(role::Column::IsActive.eq(true)).one(&self.db).await?;
+
+        Ok(role.filter(|role| role.organization_id.is_none() || role.organization_id == organization_id))
+    }
+
+    /// An active project of the organization, by id or name
+    async fn find_organization_project(
+        &self,
+        organization_id: Option<Uuid>,
+        project_ref: &str,
+    ) -> Result<Option<project::Model>, DbErr> {
+        let Some(organization_id) = organization_id else {
+            return Ok(None);
+        };
+        let query = match Uuid::parse_str(project_ref) {
+            Ok(project_id) => Project::find_by_id(project_id),
+            Err(_) => Project::find().filter(project::Column::Name.eq(project_ref)),
+        };
+
+        query
+            .filter(project::Column::OrganizationId.eq(organization_id))
+            .filter(project::Column::IsActive.eq(true))
+            .one(&self.db)
+            .await
+    }
+
     pub async fn validate_invitation_token(
         &self,
         token: &str,
@@ -148,4 +539,362 @@ impl InvitationService {
 
         Ok(invitations)
     }
-}
\ No newline at end of file
+
+    /// Invite an existing user to a project. They join once they accept.
+    pub async fn invite_user_to_project(
+        &self,
+        project_id: Uuid,
+        inviter_user_id: Uuid,
+        email: &str,
+        role_name: &str,
+        base_url: &str,
+    ) -> Result<project_invitation::Model, Box<dyn std::error::Error>> {
+        let organization_id = self.project_service.project_organization_id(project_id).await?;
+        let role = self
+            .resolve_project_grant(inviter_user_id, organization_id, project_id, role_name)
+            .await?;
+
+        let invitee = User::find()
+            .filter(user::Column::Email.eq(email))
+            .one(&self.db)
+            .await?
+            .ok_or("No user with this email; send them a sign-up invitation instead")?;
+
+        if let Some(organization_id) = organization_id
+            && !self.permission_service.user_in_organization(invitee.id, organization_id).await?
+        {
+            return Err("User is not a member of this project's organization".into());
+        }
+
+        if ProjectMember::find()
+            .filter(project_member::Column::ProjectId.eq(project_id))
+            .filter(project_member::Column::UserId.eq(invitee.id))
+            .one(&self.db)
+            .await?
+            .is_some()
+        {
+            return Err("User is already a member of this project".into());
+        }
+
+        if ProjectInvitation::find()
+            .filter(project_invitation::Column::ProjectId.eq(project_id))
+            .filter(project_invitation::Column::UserId.eq(invitee.id))
+            .filter(project_invitation::Column::Status.eq(ProjectInvitationStatus::Pending))
+            .one(&self.db)
+            .await?
+            .is_some()
+        {
+            return Err("User already has a pending invitation to this project".into());
+        }
+
+        let tx = self.db.begin().await?;
+        let project_invitation = project_invitation::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            project_id: Set(project_id),
+            project_role_id: Set(role.id),
+            role: Set(role.name),
+            invitation_id: Set(None),
+            user_id: Set(Some(invitee.id)),
+            inviter_user_id: Set(inviter_user_id),
+            status: Set(ProjectInvitationStatus::Pending),
+            responded_at: Set(None),
+            created_at: Set(Utc::now().into()),
+            updated_at: Set(Utc::now().into()),
+        }
+        .insert(&tx)
+        .await?;
+        self.queue_project_invitation(&tx, &project_invitation, &invitee, base_url).await?;
+        tx.commit().await?;
+
+        Ok(project_invitation)
+    }
+
+    /// Send a pending project invitation again. Sign-up invitations get a fresh token
+    /// and expiry, so earlier links stop working.
+    pub async fn resend_project_invitation(
+        &self,
+        project_invitation_id: Uuid,
+        actor_id: Uuid,
+        base_url: &str,
+    ) -> Result<project_invitation::Model, Box<dyn std::error::Error>> {
+        let project_invitation = self.find_pending(project_invitation_id).await?;
+        self.project_service
+            .ensure_can_perform(project_invitation.project_id, actor_id, ProjectAction::MemberInvite, "manage invitations")
+            .await?;
+
+        let tx = self.db.begin().await?;
+        match project_invitation.invitation_id {
+            Some(invitation_id) => {
+                let invitation = Invitation::find_by_id(invitation_id)
+                    .one(&tx)
+                    .await?
+                    .ok_or("Invitation not found")?;
+                if invitation.is_used {
+                    return Err("Invitation has already been used".into());
+                }
+
+                let token = Uuid::new_v4().to_string();
+                let email = invitation.email.clone();
+                let organization_id = invitation.organization_id;
+                let mut invitation_active: invitation::ActiveModel = invitation.into();
+                invitation_active.token = Set(token.clone());
+                invitation_active.expires_at = Set((Utc::now() + Duration::days(7)).into());
+                invitation_active.updated_at = Set(Utc::now().into());
+                invitation_active.update(&tx).await?;
+
+                let style = email_style(&tx, organization_id, None).await?;
+                queue_email(&tx, self.email_service.invitation_email(&email, &token, base_url, &style)?).await?;
+            }
+            None => {
+                let invitee_id = project_invitation.user_id.ok_or("Invitation has no recipient")?;
+                let invitee = User::find_by_id(invitee_id)
+                    .one(&tx)
+                    .await?
+                    .ok_or("User not found")?;
+                self.queue_project_invitation(&tx, &project_invitation, &invitee, base_url).await?;
+            }
+        }
+
+        let mut project_invitation_active: project_invitation::ActiveModel = project_invitation.into();
+        project_invitation_active.updated_at = Set(Utc::now().into());
+        let project_invitation = project_invitation_active.update(&tx).await?;
+        tx.commit().await?;
+
+        Ok(project_invitation)
+    }
+
+    /// Withdraw a pending project invitation. A sign-up invitation it came with stays
+    /// valid for joining the organization.
+    pub async fn revoke_project_invitation(
+        &self,
+        project_invitation_id: Uuid,
+        actor_id: Uuid,
+    ) -> Result<project_invitation::Model, Box<dyn std::error::Error>> {
+        let project_invitation = self.find_pending(project_invitation_id).await?;
+        self.project_service
+            .ensure_can_perform(project_invitation.project_id, actor_id, ProjectAction::MemberInvite, "manage invitations")
+            .await?;
+
+        let mut project_invitation_active: project_invitation::ActiveModel = project_invitation.into();
+        project_invitation_active.status = Set(ProjectInvitationStatus::Revoked);
+        project_invitation_active.updated_at = Set(Utc::now().into());
+        Ok(project_invitation_active.update(&self.db).await?)
+    }
+
+    /// Accept a project invitation addressed to `user_id`, joining the project
+    pub async fn accept_project_invitation(
+        &self,
+        project_invitation_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<project_invitation::Model, Box<dyn std::error::Error>> {
+        let project_invitation = self.find_pending_for_user(project_invitation_id, user_id).await?;
+
+        // The invitation may lead into another organization than the one selected;
+        // membership of that organization is checked right after
+        let project = unscoped(
+            Project::find_by_id(project_invitation.project_id)
+                .filter(project::Column::IsActive.eq(true))
+                .one(&self.db),
+        )
+        .await?
+        .ok_or("Project not found")?;
+        if !self.permission_service.user_in_organization(user_id, project.organization_id).await? {
+            return Err("You are not a member of this project's organization".into());
+        }
+
+        let tx = self.db.begin().await?;
+
+        let existing_membership = ProjectMember::find()
+            .filter(project_member::Column::ProjectId.eq(project.id))
+            .filter(project_member::Column::UserId.eq(user_id))
+            .one(&tx)
+            .await?;
+        if existing_membership.is_none() {
+            project_member::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                project_id: Set(project.id),
+                user_id: Set(user_id),
+                role: Set(project_invitation.role.clone()),
+                joined_at: Set(Utc::now().into()),
+                project_role_id: Set(Some(project_invitation.project_role_id)),
+            }
+            .insert(&tx)
+            .await?;
+        }
+
+        let mut project_invitation_active: project_invitation::ActiveModel = project_invitation.into();
+        project_invitation_active.status = Set(ProjectInvitationStatus::Accepted);
+        project_invitation_active.responded_at = Set(Some(Utc::now().into()));
+        project_invitation_active.updated_at = Set(Utc::now().into());
+        let accepted = project_invitation_active.update(&tx).await?;
+
+        tx.commit().await?;
+        self.permission_service.invalidate_user(user_id).await;
+
+        Ok(accepted)
+    }
+
+    pub async fn decline_project_invitation(
+        &self,
+        project_invitation_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<project_invitation::Model, Box<dyn std::error::Error>> {
+        let project_invitation = self.find_pending_for_user(project_invitation_id, user_id).await?;
+
+        let mut project_invitation_active: project_invitation::ActiveModel = project_invitation.into();
+        project_invitation_active.status = Set(ProjectInvitationStatus::Declined);
+        project_invitation_active.responded_at = Set(Some(Utc::now().into()));
+        project_invitation_active.updated_at = Set(Utc::now().into());
+        Ok(project_invitation_active.update(&self.db).await?)
+    }
+
+    /// Pending invitations to a project, both to existing users and attached to sign-ups
+    pub async fn get_pending_project_invitations(
+        &self,
+        project_id: Uuid,
+        actor_id: Uuid,
+    ) -> Result<Vec<PendingProjectInvitation>, Box<dyn std::error::Error>> {
+        self.project_service
+            .ensure_can_perform(project_id, actor_id, ProjectAction::MemberInvite, "view invitations")
+            .await?;
+
+        let pending = ProjectInvitation::find()
+            .filter(project_invitation::Column::ProjectId.eq(project_id))
+            .filter(project_invitation::Column::Status.eq(ProjectInvitationStatus::Pending))
+            .find_also_related(Invitation)
+            .order_by_asc(project_invitation::Column::CreatedAt)
+            .all(&self.db)
+            .await?;
+
+        let invitee_ids: Vec<Uuid> = pending.iter().filter_map(|(pi, _)| pi.user_id).collect();
+        let invitees = User::find()
+            .filter(user::Column::Id.is_in(invitee_ids))
+            .all(&self.db)
+            .await?;
+
+        let result = pending
+            .into_iter()
+            .filter_map(|(project_invitation, invitation)| {
+                let (email, expires_at) = match invitation {
+                    Some(invitation) => (invitation.email, Some(invitation.expires_at)),
+                    None => {
+                        let invitee_id = project_invitation.user_id?;
+                        let invitee = invitees.iter().find(|u| u.id == invitee_id)?;
+                        (invitee.email.clone(), None)
+                    }
+                };
+                Some(PendingProjectInvitation { invitation: project_invitation, email, expires_at })
+            })
+            .collect();
+
+        Ok(result)
+    }
+
+    /// Project invitations waiting for the user's answer
+    pub async fn get_user_project_invitations(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<project_invitation::Model>, Box<dyn std::error::Error>> {
+        let invitations = ProjectInvitation::find()
+            .filter(project_invitation::Column::UserId.eq(user_id))
+            .filter(project_invitation::Column::Status.eq(ProjectInvitationStatus::Pending))
+            .order_by_desc(project_invitation::Column::CreatedAt)
+            .all(&self.db)
+            .await?;
+
+        Ok(invitations)
+    }
+
+    /// Check the inviter may bring people into the project and resolve the role offered
+    async fn resolve_project_grant(
+        &self,
+        inviter_user_id: Uuid,
+        organization_id: Option<Uuid>,
+        project_id: Uuid,
+        role_name: &str,
+    ) -> Result<project_role::Model, Box<dyn std::error::Error>> {
+        self.project_service
+            .ensure_can_perform(project_id, inviter_user_id, ProjectAction::MemberInvite, "invite members to this project")
+            .await?;
+
+        // Invitations only lead into projects of the organization they join
+        let project_organization_id = self.project_service.project_organization_id(project_id).await?;
+        if organization_id.is_none() || project_organization_id != organization_id {
+            return Err("Project not found".into());
+        }
+
+        if role_name == ProjectRole::Owner.as_str() {
+            return Err("Projects have a single owner".into());
+        }
+        let role = self
+            .project_service
+            .find_project_role_by_name(project_id, role_name)
+            .await?
+            .ok_or("Invalid project role")?;
+        self.project_service.ensure_can_grant_role(project_id, inviter_user_id, &role).await?;
+
+        Ok(role)
+    }
+
+    async fn queue_project_invitation<C: ConnectionTrait>(
+        &self,
+        conn: &C,
+        project_invitation: &project_invitation::Model,
+        invitee: &user::Model,
+        base_url: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let project = Project::find_by_id(project_invitation.project_id)
+            .one(conn)
+            .await?
+            .ok_or("Project not found")?;
+        let inviter = User::find_by_id(project_invitation.inviter_user_id)
+            .one(conn)
+            .await?
+            .ok_or("Inviter not found")?;
+
+        let inviter_name = match (inviter.first_name, inviter.last_name) {
+            (None, None) => inviter.email,
+            (first_name, last_name) => format!(
+                "{} {}",
+                first_name.unwrap_or_default(),
+                last_name.unwrap_or_default()
+            )
+            .trim()
+            .to_string(),
+        };
+
+        // Branded for the project's organization rather than whichever one the invitee has open
+        let style = email_style(conn, Some(project.organization_id), invitee.locale.as_deref()).await?;
+        let message = self
+            .email_service
+            .project_invitation_email(&invitee.email, &project.name, &inviter_name, base_url, &style)?;
+        queue_email(conn, message).await?;
+        Ok(())
+    }
+
+    async fn find_pending(
+        &self,
+        project_invitation_id: Uuid,
+    ) -> Result<project_invitation::Model, Box<dyn std::error::Error>> {
+        let project_invitation = ProjectInvitation::find_by_id(project_invitation_id)
+            .one(&self.db)
+            .await?
+            .ok_or("Invitation not found")?;
+        if project_invitation.status != ProjectInvitationStatus::Pending {
+            return Err("Invitation is no longer pending".into());
+        }
+        Ok(project_invitation)
+    }
+
+    async fn find_pending_for_user(
+        &self,
+        project_invitation_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<project_invitation::Model, Box<dyn std::error::Error>> {
+        let project_invitation = self.find_pending(project_invitation_id).await?;
+        if project_invitation.user_id != Some(user_id) {
+            return Err("Invitation not found".into());
+        }
+        Ok(project_invitation)
+    }
+}
@@ -1,17 +1,42 @@
+pub mod access_request;
 pub mod activity;
+pub mod attachment_storage;
+pub mod comment;
 pub mod context;
+pub mod digest;
 pub mod email;
 pub mod email_context;
+pub mod email_outbox;
+pub mod email_template;
+pub mod email_transport;
 pub mod invitation;
+pub mod notification;
+pub mod organization;
+pub mod password_policy;
 pub mod project;
+pub mod queue;
 pub mod task;
+pub mod team;
 pub mod user;
+pub mod webhook;
 
+pub use access_request::*;
 pub use activity::*;
+pub use attachment_storage::*;
+pub use comment::*;
 pub use context::*;
+pub use digest::*;
 pub use email::*;
 pub use email_context::*;
+pub use email_outbox::*;
+pub use email_template::*;
+pub use email_transport::*;
 pub use invitation::*;
+pub use notification::*;
+pub use organization::*;
+pub use password_policy::*;
 pub use project::*;
 pub use task::*;
-pub use user::*;
\ No newline at end of file
+pub use team::*;
+pub use user::*;
+pub use webhook::*;
\ No newline at end of file
@@ -0,0 +1,442 @@
+use std::collections::HashSet;
+
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder,
+    QuerySelect, Set,
+};
+use uuid::Uuid;
+use chrono::{Duration, Utc};
+
+use crate::entities::{prelude::*, activity, notification, notification_preference, task, task_watcher};
+use crate::graphql::types::{NotificationConnection, NotificationPreference as NotificationPreferenceType, NotificationType, TaskStatus};
+use crate::services::activity::EntityType;
+use crate::services::ProjectService;
+use crate::services::task::{task_watcher_ids, TASK_CREATION_ACTIONS};
+
+impl NotificationType {
+    pub const ALL: [NotificationType; 5] = [
+        NotificationType::TaskAssigned,
+        NotificationType::Mentioned,
+        NotificationType::WatchedTaskChanged,
+        NotificationType::TaskDueSoon,
+        NotificationType::TaskOverdue,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationType::TaskAssigned => "task_assigned",
+            NotificationType::Mentioned => "mentioned",
+            NotificationType::WatchedTaskChanged => "watched_task_changed",
+            NotificationType::TaskDueSoon => "task_due_soon",
+            NotificationType::TaskOverdue => "task_overdue",
+        }
+    }
+}
+
+/// Counts from one due date sweep
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DueSweepSummary {
+    pub due_soon: u64,
+    pub overdue: u64,
+}
+
+/// A notification about to be stored for one recipient
+struct NewNotification {
+    user_id: Uuid,
+    notification_type: NotificationType,
+    title: String,
+    body: Option<String>,
+    actor_id: Option<Uuid>,
+    activity_id: Option<Uuid>,
+    project_id: Option<Uuid>,
+    task_id: Option<Uuid>,
+    dedupe_key: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct NotificationService {
+    db: DatabaseConnection,
+    project_service: ProjectService,
+}
+
+impl NotificationService {
+    pub fn new(db: DatabaseConnection, project_service: ProjectService) -> Self {
+        Self { db, project_service }
+    }
+
+    /// Fan a freshly logged activity out to the people it concerns. Runs on the
+    /// connection the activity was written on, so both commit or roll back together.
+    pub async fn notify_for_activity<C: ConnectionTrait>(
+        &self,
+        conn: &C,
+        activity: &activity::Model,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let (project_id, task) = match EntityType::from_str(&activity.entity_type) {
+            Some(EntityType::Task) => match Task::find_by_id(activity.entity_id).one(conn).await? {
+                Some(task) => (task.project_id, Some(task)),
+                // Deleted tasks have nothing left to link to
+                None => return Ok(()),
+            },
+            Some(EntityType::Project) => (activity.entity_id, None),
+            _ => return Ok(()),
+        };
+
+        let subject = match &task {
+            Some(task) => task.name.clone(),
+            None => Project::find_by_id(project_id)
+                .one(conn)
+                .await?
+                .map(|project| project.name)
+                .unwrap_or_else(|| "a project".to_string()),
+        };
+
+        // The actor never hears about their own activity, and nobody is told twice
+        let mut notified: HashSet<Uuid> = HashSet::from([activity.actor_id]);
+        let mut pending = Vec::new();
+
+        if activity.action_type == "assignment_changed" {
+            let assignee_id = activity
+                .changes
+                .as_ref()
+                .and_then(|changes| changes.get("new_value"))
+                .and_then(|value| value.as_str())
+                .and_then(|value| Uuid::parse_str(value).ok());
+
+            if let Some(assignee_id) = assignee_id
+                && notified.insert(assignee_id)
+            {
+                pending.push(self.for_activity(
+                    activity,
+                    assignee_id,
+                    NotificationType::TaskAssigned,
+                    format!("You were assigned to {}", subject),
+                    project_id,
+                    task.as_ref(),
+                ));
+            }
+        }
+
+        if activity.action_type == "commented" {
+            let mentions: Vec<Uuid> = activity
+                .metadata
+                .as_ref()
+                .and_then(|metadata| metadata.get("mentions"))
+                .and_then(|mentions| serde_json::from_value(mentions.clone()).ok())
+                .unwrap_or_default();
+
+            for user_id in mentions {
+                if notified.contains(&user_id) {
+                    continue;
+                }
+                // Mentioning someone doesn't leak the conversation to outsiders
+                if !self.project_service.can_user_access_project(project_id, user_id).await? {
+                    continue;
+                }
+                notified.insert(user_id);
+                pending.push(self.for_activity(
+                    activity,
+                    user_id,
+                    NotificationType::Mentioned,
+                    format!("You were mentioned on {}", subject),
+                    project_id,
+                    task.as_ref(),
+                ));
+            }
+        }
+
+        // Watchers aren't told about new tasks; an assignee hears about those through
+        // the assignment
+        if let Some(task) = &task
+            && !TASK_CREATION_ACTIONS.contains(&activity.action_type.as_str())
+        {
+            for user_id in task_watcher_ids(conn, task.id).await? {
+                if notified.contains(&user_id)
+                    || !self.project_service.can_user_access_project(project_id, user_id).await?
+                {
+                    continue;
+                }
+                notified.insert(user_id);
+                pending.push(self.for_activity(
+                    activity,
+                    user_id,
+                    NotificationType::WatchedTaskChanged,
+                    format!("{} was updated", subject),
+                    project_id,
+                    Some(task),
+                ));
+            }
+        }
+
+        for new_notification in pending {
+            if self.is_enabled(conn, new_notification.user_id, new_notification.notification_type).await? {
+                self.insert(conn, new_notification).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Notify a task's watchers when it is due within `window` or already past its due
+    /// date. Each watcher hears once per due date, so moving the due date re-arms it.
+    pub async fn notify_due_tasks(&self, window: Duration) -> Result<DueSweepSummary, Box<dyn std::error::Error>> {
+        let now = Utc::now();
+        let tasks = Task::find()
+            .filter(task::Column::DueDate.is_not_null())
+            .filter(task::Column::DueDate.lte(now + window))
+            .filter(task::Column::Status.is_in([TaskStatus::Todo, TaskStatus::InProgress]))
+            .all(&self.db)
+            .await?;
+
+        let mut summary = DueSweepSummary::default();
+        if tasks.is_empty() {
+            return Ok(summary);
+        }
+
+        let watchers = TaskWatcher::find()
+            .filter(task_watcher::Column::TaskId.is_in(tasks.iter().map(|task| task.id)))
+            .all(&self.db)
+            .await?;
+
+        let candidates: Vec<(&task::Model, Uuid, NotificationType, String)> = tasks
+            .iter()
+            .filter_map(|task| Some((task, task.due_date?)))
+            .flat_map(|(task, due_date)| {
+                let notification_type = if due_date < now {
+                    NotificationType::TaskOverdue
+                } else {
+                    NotificationType::TaskDueSoon
+                };
+                watchers.iter().filter(move |watcher| watcher.task_id == task.id).map(move |watcher| {
+                    let dedupe_key = format!(
+                        "{}:{}:{}:{}",
+                        notification_type.as_str(),
+                        task.id,
+                        due_date.timestamp(),
+                        watcher.user_id
+                    );
+                    (task, watcher.user_id, notification_type, dedupe_key)
+                })
+            })
+            .collect();
+
+        let already_sent: HashSet<String> = Notification::find()
+            .select_only()
+            .column(notification::Column::DedupeKey)
+            .filter(notification::Column::DedupeKey.is_in(candidates.iter().map(|(_, _, _, key)| key.clone())))
+            .into_tuple::<Option<String>>()
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .flatten()
+            .collect();
+
+        for (task, user_id, notification_type, dedupe_key) in candidates {
+            if already_sent.contains(&dedupe_key) || !self.is_enabled(&self.db, user_id, notification_type).await? {
+                continue;
+            }
+            if !self.project_service.can_user_access_project(task.project_id, user_id).await? {
+                continue;
+            }
+
+            let title = match notification_type {
+                NotificationType::TaskOverdue => format!("{} is overdue", task.name),
+                _ => format!("{} is due soon", task.name),
+            };
+            self.insert(
+                &self.db,
+                NewNotification {
+                    user_id,
+                    notification_type,
+                    title,
+                    body: None,
+                    actor_id: None,
+                    activity_id: None,
+                    project_id: Some(task.project_id),
+                    task_id: Some(task.id),
+                    dedupe_key: Some(dedupe_key),
+                },
+            )
+            .await?;
+
+            match notification_type {
+                NotificationType::TaskOverdue => summary.overdue += 1,
+                _ => summary.due_soon += 1,
+            }
+        }
+
+        Ok(summary)
+    }
+
+    /// A user's notifications, newest first
+    pub async fn list_for_user(
+        &self,
+        user_id: Uuid,
+        unread_only: bool,
+        limit: Option<u64>,
+        offset: Option<u64>,
+    ) -> Result<NotificationConnection, Box<dyn std::error::Error>> {
+        let mut query = Notification::find().filter(notification::Column::UserId.eq(user_id));
+        if unread_only {
+            query = query.filter(notification::Column::IsRead.eq(false));
+        }
+
+        let total_count = query.clone().count(&self.db).await?;
+        let unread_count = Notification::find()
+            .filter(notification::Column::UserId.eq(user_id))
+            .filter(notification::Column::IsRead.eq(false))
+            .count(&self.db)
+            .await?;
+
+        let notifications = query
+            .order_by_desc(notification::Column::CreatedAt)
+            .limit(limit.unwrap_or(50).min(200))
+            .offset(offset.unwrap_or(0))
+            .all(&self.db)
+            .await?;
+
+        Ok(NotificationConnection {
+            edges: notifications.into_iter().map(Into::into).collect(),
+            total_count: total_count as u32,
+            unread_count: unread_count as u32,
+        })
+    }
+
+    /// Mark some, or with `None` all, of a user's notifications as read. Returns how many
+    /// changed.
+    pub async fn mark_read(&self, user_id: Uuid, ids: Option<Vec<Uuid>>) -> Result<u64, Box<dyn std::error::Error>> {
+        let mut update = Notification::update_many()
+            .col_expr(notification::Column::IsRead, sea_orm::sea_query::Expr::value(true))
+            .col_expr(
+                notification::Column::ReadAt,
+                sea_orm::sea_query::Expr::value(chrono::DateTime::<chrono::FixedOffset>::from(Utc::now())),
+            )
+            .filter(notification::Column::UserId.eq(user_id))
+            .filter(notification::Column::IsRead.eq(false));
+
+        if let Some(ids) = ids {
+            update = update.filter(notification::Column::Id.is_in(ids));
+        }
+
+        let result = update.exec(&self.db).await?;
+        Ok(result.rows_affected)
+    }
+
+    /// Every notification type with whether the user currently receives it
+    pub async fn preferences(&self, user_id: Uuid) -> Result<Vec<NotificationPreferenceType>, Box<dyn std::error::Error>> {
+        let stored = NotificationPreference::find()
+            .filter(notification_preference::Column::UserId.eq(user_id))
+            .all(&self.db)
+            .await?;
+
+        Ok(NotificationType::ALL
+            .iter()
+            .map(|notification_type| NotificationPreferenceType {
+                notification_type: *notification_type,
+                enabled: stored
+                    .iter()
+                    .find(|preference| preference.notification_type == *notification_type)
+                    .map(|preference| preference.enabled)
+                    .unwrap_or(true),
+            })
+            .collect())
+    }
+
+    pub async fn update_preferences(
+        &self,
+        user_id: Uuid,
+        changes: Vec<(NotificationType, bool)>,
+    ) -> Result<Vec<NotificationPreferenceType>, Box<dyn std::error::Error>> {
+        for (notification_type, enabled) in changes {
+            let existing = NotificationPreference::find()
+                .filter(notification_preference::Column::UserId.eq(user_id))
+                .filter(notification_preference::Column::NotificationType.eq(notification_type))
+                .one(&self.db)
+                .await?;
+
+            match existing {
+                Some(preference) => {
+                    let mut preference: notification_preference::ActiveModel = preference.into();
+                    preference.enabled = Set(enabled);
+                    preference.updated_at = Set(Utc::now().into());
+                    preference.update(&self.db).await?;
+                }
+                None => {
+                    notification_preference::ActiveModel {
+                        id: Set(Uuid::new_v4()),
+                        user_id: Set(user_id),
+                        notification_type: Set(notification_type),
+                        enabled: Set(enabled),
+                        created_at: Set(Utc::now().into()),
+                        updated_at: Set(Utc::now().into()),
+                    }
+                    .insert(&self.db)
+                    .await?;
+                }
+            }
+        }
+
+        self.preferences(user_id).await
+    }
+
+    /// Users get every notification type unless they turned it off
+    async fn is_enabled<C: ConnectionTrait>(
+        &self,
+        conn: &C,
+        user_id: Uuid,
+        notification_type: NotificationType,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        let preference = NotificationPreference::find()
+            .filter(notification_preference::Column::UserId.eq(user_id))
+            .filter(notification_preference::Column::NotificationType.eq(notification_type))
+            .one(conn)
+            .await?;
+
+        Ok(preference.map(|preference| preference.enabled).unwrap_or(true))
+    }
+
+    fn for_activity(
+        &self,
+        activity: &activity::Model,
+        user_id: Uuid,
+        notification_type: NotificationType,
+        title: String,
+        project_id: Uuid,
+        task: Option<&task::Model>,
+    ) -> NewNotification {
+        NewNotification {
+            user_id,
+            notification_type,
+            title,
+            body: activity.description.clone(),
+            actor_id: Some(activity.actor_id),
+            activity_id: Some(activity.id),
+            project_id: Some(project_id),
+            task_id: task.map(|task| task.id),
+            dedupe_key: None,
+        }
+    }
+
+    async fn insert<C: ConnectionTrait>(
+        &self,
+        conn: &C,
+        new_notification: NewNotification,
+    ) -> Result<notification::Model, Box<dyn std::error::Error>> {
+        let notification = notification::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            user_id: Set(new_notification.user_id),
+            notification_type: Set(new_notification.notification_type),
+            title: Set(new_notification.title),
+            body: Set(new_notification.body),
+            actor_id: Set(new_notification.actor_id),
+            activity_id: Set(new_notification.activity_id),
+            project_id: Set(new_notification.project_id),
+            task_id: Set(new_notification.task_id),
+            dedupe_key: Set(new_notification.dedupe_key),
+            is_read: Set(false),
+            read_at: Set(None),
+            created_at: Set(Utc::now().into()),
+        };
+
+        Ok(notification.insert(conn).await?)
+    }
+}
@@ -0,0 +1,407 @@
+use std::future::Future;
+
+use chrono::Utc;
+use sea_orm::{
+    sea_query::Expr, ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, DbBackend, DbErr,
+    EntityTrait, QueryFilter, QueryOrder, QuerySelect, RuntimeErr, Set, SqlxPostgresConnector, TransactionTrait,
+};
+use sqlx::postgres::{PgConnection, PgPoolOptions};
+use uuid::Uuid;
+
+use crate::auth::PermissionService;
+use crate::entities::{
+    organization, organization_member, prelude::*, project, project_member, role, team, team_member, user, user_permission,
+};
+use crate::graphql::types::UpdateOrganizationBrandingInput;
+use crate::services::DEFAULT_EMAIL_LOCALE;
+
+/// Tables carrying an `organization_isolation` row-level security policy
+const ROW_LEVEL_SECURITY_TABLES: [&str; 4] = ["project", "project_context_category", "role", "webhook_subscription"];
+
+/// Which organizations' rows the policies let the current task see. The database side
+/// is the `app.organization_id` setting: an organization id, `*` for all of them, or
+/// unset, which shows none.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrganizationScope {
+    /// Rows of one organization, plus shared roles
+    Organization(Uuid),
+    /// Only shared roles; requests without a selected organization
+    NoOrganization,
+    /// Every organization; background work and deliberate cross-organization steps
+    Unrestricted,
+}
+
+impl OrganizationScope {
+    /// Scope of a request, from the organization selected for it
+    pub fn for_request(organization_id: Option<Uuid>) -> Self {
+        match organization_id {
+            Some(organization_id) => OrganizationScope::Organization(organization_id),
+            None => OrganizationScope::NoOrganization,
+        }
+    }
+
+    fn setting(&self) -> String {
+        match self {
+            OrganizationScope::Organization(organization_id) => organization_id.to_string(),
+            OrganizationScope::NoOrganization => String::new(),
+            OrganizationScope::Unrestricted => "*".to_string(),
+        }
+    }
+}
+
+tokio::task_local! {
+    static ORGANIZATION_SCOPE: OrganizationScope;
+}
+
+/// Run `future` with the database connections it acquires scoped to `scope`
+pub async fn with_organization_scope<F: Future>(scope: OrganizationScope, future: F) -> F::Output {
+    ORGANIZATION_SCOPE.scope(scope, future).await
+}
+
+/// Run `future` across organizations, for steps that check organization membership
+/// themselves, such as accepting an invitation into another organization
+pub async fn unscoped<F: Future>(future: F) -> F::Output {
+    with_organization_scope(OrganizationScope::Unrestricted, future).await
+}
+
+/// Scope of the current task. Work outside any request is the server's own and unrestricted.
+pub fn current_organization_scope() -> OrganizationScope {
+    ORGANIZATION_SCOPE.try_with(|scope| *scope).unwrap_or(OrganizationScope::Unrestricted)
+}
+
+/// Connect to Postgres. With `scoped`, each connection is set to the acquiring task's
+/// organization scope as it leaves the pool; otherwise connections see every organization.
+pub async fn connect_database(database_url: &str, scoped: bool) -> Result<DatabaseConnection, DbErr> {
+    let mut options = PgPoolOptions::new().after_connect(move |conn, _| {
+        let scope = if scoped { current_organization_scope() } else { OrganizationScope::Unrestricted };
+        Box::pin(async move { set_organization_scope(conn, scope).await })
+    });
+    if scoped {
+        options = options.before_acquire(|conn, _| {
+            let scope = current_organization_scope();
+            Box::pin(async move {
+                set_organization_scope(conn, scope).await?;
+                Ok(true)
+            })
+        });
+    }
+
+    let pool = options
+        .connect(database_url)
+        .await
+        .map_err(|e| DbErr::Conn(RuntimeErr::SqlxError(e)))?;
+    Ok(SqlxPostgresConnector::from_sqlx_postgres_pool(pool))
+}
+
+async fn set_organization_scope(conn: &mut PgConnection, scope: OrganizationScope) -> Result<(), sqlx::Error> {
+    sqlx::query("SELECT set_config('app.organization_id', $1, false)")
+        .bind(scope.setting())
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+/// Roles usable within an organization: the shared ones plus the organization's own
+pub fn visible_roles(organization_id: Option<Uuid>) -> Condition {
+    let shared = Condition::any().add(role::Column::OrganizationId.is_null());
+    match organization_id {
+        Some(organization_id) => shared.add(role::Column::OrganizationId.eq(organization_id)),
+        None => shared,
+    }
+}
+
+/// Whether a role may be seen or handed out within `organization_id`
+pub fn is_role_visible(role: &role::Model, organization_id: Option<Uuid>) -> bool {
+    role.organization_id.is_none() || role.organization_id == organization_id
+}
+
+#[derive(Clone)]
+pub struct OrganizationService {
+    db: DatabaseConnection,
+    permission_service: PermissionService,
+}
+
+impl OrganizationService {
+    pub fn new(db: DatabaseConnection, permission_service: PermissionService) -> Self {
+        Self { db, permission_service }
+    }
+
+    /// Make the row-level security policies binding for the application's own database
+    /// role too; table owners bypass them otherwise. Needs the connection to own the tables.
+    pub async fn force_row_level_security(&self) -> Result<(), DbErr> {
+        if self.db.get_database_backend() != DbBackend::Postgres {
+            return Ok(());
+        }
+
+        for table in ROW_LEVEL_SECURITY_TABLES {
+            self.db
+                .execute_unprepared(&format!("ALTER TABLE {} FORCE ROW LEVEL SECURITY", table))
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Create an organization with its creator as the first member
+    pub async fn create_organization(
+        &self,
+        creator_id: Uuid,
+        name: &str,
+        slug: &str,
+    ) -> Result<organization::Model, Box<dyn std::error::Error>> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Organization name is required".into());
+        }
+        if slug.is_empty() || !slug.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-') {
+            return Err("Organization slug may only contain lowercase letters, digits and hyphens".into());
+        }
+
+        let tx = self.db.begin().await?;
+
+        if Organization::find()
+            .filter(organization::Column::Slug.eq(slug))
+            .one(&tx)
+            .await?
+            .is_some()
+        {
+            return Err("An organization with this slug already exists".into());
+        }
+
+        let organization = organization::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            name: Set(name.to_string()),
+            slug: Set(slug.to_string()),
+            is_active: Set(true),
+            created_at: Set(Utc::now().into()),
+            updated_at: Set(Utc::now().into()),
+            brand_logo_url: Set(None),
+            brand_primary_color: Set(None),
+            brand_accent_color: Set(None),
+            email_sender_name: Set(None),
+            default_locale: Set(DEFAULT_EMAIL_LOCALE.to_string()),
+        }
+        .insert(&tx)
+        .await?;
+
+        organization_member::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            organization_id: Set(organization.id),
+            user_id: Set(creator_id),
+            created_at: Set(Utc::now().into()),
+        }
+        .insert(&tx)
+        .await?;
+
+        tx.commit().await?;
+        self.permission_service.invalidate_user(creator_id).await;
+
+        Ok(organization)
+    }
+
+    pub async fn get_organization(
+        &self,
+        organization_id: Uuid,
+    ) -> Result<Option<organization::Model>, Box<dyn std::error::Error>> {
+        Ok(Organization::find_by_id(organization_id).one(&self.db).await?)
+    }
+
+    /// Update how emails sent on the organization's behalf look. `locales` are the ones
+    /// emails can be rendered in.
+    pub async fn update_branding(
+        &self,
+        organization_id: Uuid,
+        input: UpdateOrganizationBrandingInput,
+        locales: &[String],
+    ) -> Result<organization::Model, Box<dyn std::error::Error>> {
+        let organization = Organization::find_by_id(organization_id)
+            .one(&self.db)
+            .await?
+            .ok_or("Organization not found")?;
+
+        let mut organization_active: organization::ActiveModel = organization.into();
+
+        if let Some(logo_url) = input.brand_logo_url {
+            let logo_url = logo_url.map(|url| url.trim().to_string()).filter(|url| !url.is_empty());
+            if let Some(url) = &logo_url
+                && !(url.starts_with("https://") || url.starts_with("http://"))
+            {
+                return Err("Logo URL must start with http:// or https://".into());
+            }
+            organization_active.brand_logo_url = Set(logo_url);
+        }
+        if let Some(color) = input.brand_primary_color {
+            organization_active.brand_primary_color = Set(parse_brand_color(color)?);
+        }
+        if let Some(color) = input.brand_accent_color {
+            organization_active.brand_accent_color = Set(parse_brand_color(color)?);
+        }
+        if let Some(sender_name) = input.email_sender_name {
+            let sender_name = sender_name.map(|name| name.trim().to_string()).filter(|name| !name.is_empty());
+            if sender_name.as_ref().is_some_and(|name| name.chars().any(|c| c.is_control() || c == '<' || c == '>')) {
+                return Err("Sender name may not contain control characters or angle brackets".into());
+            }
+            organization_active.email_sender_name = Set(sender_name);
+        }
+        if let Some(locale) = input.default_locale {
+            if !locales.contains(&locale) {
+                return Err(format!("Unsupported locale, expected one of: {}", locales.join(", ")).into());
+            }
+            organization_active.default_locale = Set(locale);
+        }
+
+        organization_active.updated_at = Set(Utc::now().into());
+        Ok(organization_active.update(&self.db).await?)
+    }
+
+    /// Active organizations the user belongs to
+    pub async fn get_user_organizations(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<organization::Model>, Box<dyn std::error::Error>> {
+        let organizations = Organization::find()
+            .inner_join(OrganizationMember)
+            .filter(organization_member::Column::UserId.eq(user_id))
+            .filter(organization::Column::IsActive.eq(true))
+            .order_by_asc(organization::Column::Name)
+            .all(&self.db)
+            .await?;
+
+        Ok(organizations)
+    }
+
+    pub async fn add_member(
+        &self,
+        organization_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<organization_member::Model, Box<dyn std::error::Error>> {
+        Organization::find_by_id(organization_id)
+            .filter(organization::Column::IsActive.eq(true))
+            .one(&self.db)
+            .await?
+            .ok_or("Organization not found")?;
+
+        User::find_by_id(user_id)
+            .one(&self.db)
+            .await?
+            .ok_or("User not found")?;
+
+        if self.permission_service.user_in_organization(user_id, organization_id).await? {
+            return Err("User is already a member of this organization".into());
+        }
+
+        let member = organization_member::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            organization_id: Set(organization_id),
+            user_id: Set(user_id),
+            created_at: Set(Utc::now().into()),
+        }
+        .insert(&self.db)
+        .await?;
+
+        self.permission_service.invalidate_user(user_id).await;
+        Ok(member)
+    }
+
+    /// Remove a user from an organization along with their memberships in its projects
+    /// and teams, its role and its direct grants. Projects they own have to be transferred
+    /// first.
+    pub async fn remove_member(
+        &self,
+        organization_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let tx = self.db.begin().await?;
+
+        let member = OrganizationMember::find()
+            .filter(organization_member::Column::OrganizationId.eq(organization_id))
+            .filter(organization_member::Column::UserId.eq(user_id))
+            .one(&tx)
+            .await?
+            .ok_or("User is not a member of this organization")?;
+
+        let organization_project_ids: Vec<Uuid> = Project::find()
+            .select_only()
+            .column(project::Column::Id)
+            .filter(project::Column::OrganizationId.eq(organization_id))
+            .into_tuple()
+            .all(&tx)
+            .await?;
+
+        let owns_project = Project::find()
+            .filter(project::Column::Id.is_in(organization_project_ids.clone()))
+            .filter(project::Column::OwnerId.eq(user_id))
+            .filter(project::Column::IsActive.eq(true))
+            .one(&tx)
+            .await?
+            .is_some();
+        if owns_project {
+            return Err("User still owns projects in this organization; transfer them first".into());
+        }
+
+        ProjectMember::delete_many()
+            .filter(project_member::Column::UserId.eq(user_id))
+            .filter(project_member::Column::ProjectId.is_in(organization_project_ids))
+            .exec(&tx)
+            .await?;
+
+        let organization_team_ids: Vec<Uuid> = Team::find()
+            .select_only()
+            .column(team::Column::Id)
+            .filter(team::Column::OrganizationId.eq(organization_id))
+            .into_tuple()
+            .all(&tx)
+            .await?;
+
+        TeamMember::delete_many()
+            .filter(team_member::Column::UserId.eq(user_id))
+            .filter(team_member::Column::TeamId.is_in(organization_team_ids))
+            .exec(&tx)
+            .await?;
+
+        OrganizationMember::delete_by_id(member.id).exec(&tx).await?;
+
+        // The organization's role and direct grants go with the membership
+        if let Some((removed_user, Some(role))) = User::find_by_id(user_id).find_also_related(Role).one(&tx).await?
+            && role.organization_id == Some(organization_id)
+        {
+            let mut user_active: user::ActiveModel = removed_user.into();
+            user_active.role_id = Set(None);
+            user_active.role_valid_from = Set(None);
+            user_active.role_valid_until = Set(None);
+            user_active.updated_at = Set(Utc::now().into());
+            user_active.update(&tx).await?;
+        }
+
+        UserPermission::delete_many()
+            .filter(user_permission::Column::UserId.eq(user_id))
+            .filter(user_permission::Column::OrganizationId.eq(organization_id))
+            .exec(&tx)
+            .await?;
+
+        // Sessions in the removed organization fall back to no current organization
+        User::update_many()
+            .col_expr(user::Column::CurrentOrganizationId, Expr::value(Option::<Uuid>::None))
+            .filter(user::Column::Id.eq(user_id))
+            .filter(user::Column::CurrentOrganizationId.eq(organization_id))
+            .exec(&tx)
+            .await?;
+
+        tx.commit().await?;
+        self.permission_service.invalidate_user(user_id).await;
+
+        Ok(())
+    }
+}
+
+/// Normalize a `#RRGGBB` colour, or clear it with `None`
+fn parse_brand_color(color: Option<String>) -> Result<Option<String>, Box<dyn std::error::Error>> {
+    let Some(color) = color.map(|color| color.trim().to_lowercase()).filter(|color| !color.is_empty()) else {
+        return Ok(None);
+    };
+    let valid = color.len() == 7 && color.starts_with('#') && color[1..].chars().all(|c| c.is_ascii_hexdigit());
+    if !valid {
+        return Err("Brand colors must be hex colors like #1f2937".into());
+    }
+    Ok(Some(color))
+}
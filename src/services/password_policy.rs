@@ -0,0 +1,360 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::path::PathBuf;
+
+use async_graphql::{Error, ErrorExtensions, Value};
+use sha1::{Digest, Sha1};
+use tracing::{info, warn};
+
+/// Tunable rules applied whenever a password is set
+#[derive(Debug, Clone)]
+pub struct PasswordPolicyConfig {
+    pub min_length: usize,
+    /// Minimum strength on a 0 (trivial) to 4 (very strong) scale
+    pub min_strength_score: u8,
+    /// Number of previous passwords that may not be reused
+    pub history_size: u64,
+    /// Passwords older than this must be reset before logging in
+    pub max_age_days: Option<i64>,
+    /// Directory of breached password range files: one `<PREFIX>.txt` per 5-hex-digit
+    /// SHA-1 prefix, listing the remaining 35 hex digits (`SUFFIX` or `SUFFIX:COUNT` per
+    /// line) as served by the k-anonymity range API
+    pub breached_passwords_path: Option<String>,
+}
+
+impl Default for PasswordPolicyConfig {
+    fn default() -> Self {
+        Self {
+            min_length: 10,
+            min_strength_score: 2,
+            history_size: 5,
+            max_age_days: None,
+            breached_passwords_path: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PasswordViolation {
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl PasswordViolation {
+    pub const TOO_SHORT: &'static str = "PASSWORD_TOO_SHORT";
+    pub const TOO_WEAK: &'static str = "PASSWORD_TOO_WEAK";
+    pub const REUSED: &'static str = "PASSWORD_REUSED";
+    pub const BREACHED: &'static str = "PASSWORD_BREACHED";
+    pub const EXPIRED: &'static str = "PASSWORD_EXPIRED";
+
+    fn new(code: &'static str, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+}
+
+/// Error carrying every rule a password failed, so resolvers can report them individually
+#[derive(Debug, Clone)]
+pub struct PasswordPolicyError {
+    pub violations: Vec<PasswordViolation>,
+}
+
+impl fmt::Display for PasswordPolicyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let messages: Vec<&str> = self.violations.iter().map(|v| v.message.as_str()).collect();
+        write!(f, "{}", messages.join("; "))
+    }
+}
+
+impl std::error::Error for PasswordPolicyError {}
+
+impl ErrorExtensions for PasswordPolicyError {
+    /// `code` carries the first failed rule, `violations` lists every rule with its message
+    fn extend(&self) -> Error {
+        let code = self.violations.first().map(|v| v.code).unwrap_or(PasswordViolation::TOO_WEAK);
+        let violations = serde_json::json!(self
+            .violations
+            .iter()
+            .map(|v| serde_json::json!({ "code": v.code, "message": v.message }))
+            .collect::<Vec<_>>());
+
+        Error::new(self.to_string()).extend_with(|_, e| {
+            e.set("code", code);
+            e.set("violations", Value::from_json(violations).unwrap_or(Value::Null));
+        })
+    }
+}
+
+impl PasswordPolicyError {
+    pub fn expired() -> Self {
+        Self {
+            violations: vec![PasswordViolation::new(
+                PasswordViolation::EXPIRED,
+                "Your password has expired. Please reset it to continue",
+            )],
+        }
+    }
+
+    pub fn reused(history_size: u64) -> Self {
+        Self {
+            violations: vec![PasswordViolation::new(
+                PasswordViolation::REUSED,
+                format!("Password must not match any of your last {} passwords", history_size),
+            )],
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct PasswordPolicy {
+    config: PasswordPolicyConfig,
+    /// Range file directory; files are read on demand, one prefix per check
+    breached_passwords_dir: Option<PathBuf>,
+}
+
+impl PasswordPolicy {
+    pub fn new(config: PasswordPolicyConfig) -> Self {
+        let breached_passwords_dir = config.breached_passwords_path.as_ref().and_then(|path| {
+            let dir = PathBuf::from(path);
+            if dir.is_dir() {
+                info!("Checking passwords against breached password ranges in {}", path);
+                Some(dir)
+            } else {
+                warn!("Breached password range directory {} not found; the breach check is disabled", path);
+                None
+            }
+        });
+
+        Self {
+            config,
+            breached_passwords_dir,
+        }
+    }
+
+    pub fn config(&self) -> &PasswordPolicyConfig {
+        &self.config
+    }
+
+    /// Check length, strength and the breached list. `user_inputs` (email, names)
+    /// lower the strength score when they appear in the password.
+    pub async fn validate(&self, password: &str, user_inputs: &[&str]) -> Result<(), PasswordPolicyError> {
+        let mut violations = Vec::new();
+
+        if password.chars().count() < self.config.min_length {
+            violations.push(PasswordViolation::new(
+                PasswordViolation::TOO_SHORT,
+                format!("Password must be at least {} characters long", self.config.min_length),
+            ));
+        }
+
+        if strength_score(password, user_inputs) < self.config.min_strength_score {
+            violations.push(PasswordViolation::new(
+                PasswordViolation::TOO_WEAK,
+                "Password is too easy to guess. Try a longer passphrase or mix in less predictable words",
+            ));
+        }
+
+        if self.is_breached(password).await {
+            violations.push(PasswordViolation::new(
+                PasswordViolation::BREACHED,
+                "Password appears in a known data breach. Please choose a different one",
+            ));
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(PasswordPolicyError { violations })
+        }
+    }
+
+    /// Look the password's SHA-1 up in the range file for its prefix. A missing file means
+    /// no breached password has that prefix; unreadable files are logged and skipped.
+    pub async fn is_breached(&self, password: &str) -> bool {
+        let Some(dir) = &self.breached_passwords_dir else {
+            return false;
+        };
+
+        let hash = format!("{:X}", Sha1::digest(password.as_bytes()));
+        let (prefix, suffix) = hash.split_at(5);
+
+        let contents = match tokio::fs::read_to_string(dir.join(format!("{}.txt", prefix))).await {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return false,
+            Err(e) => {
+                warn!("Failed to read breached password range {}: {}", prefix, e);
+                return false;
+            }
+        };
+
+        contents
+            .lines()
+            .map(|line| line.split(':').next().unwrap_or_default().trim())
+            .any(|candidate| candidate.eq_ignore_ascii_case(suffix))
+    }
+
+    /// Whether a password last changed at `changed_at` is past the maximum age
+    pub fn is_expired(&self, changed_at: chrono::DateTime<chrono::Utc>) -> bool {
+        match self.config.max_age_days {
+            Some(days) => chrono::Utc::now() - changed_at > chrono::Duration::days(days),
+            None => false,
+        }
+    }
+}
+
+const COMMON_PASSWORDS: &[&str] = &[
+    "password", "passw0rd", "qwerty", "letmein", "welcome", "admin", "iloveyou", "monkey",
+    "dragon", "football", "baseball", "sunshine", "princess", "freshapi", "changeme", "secret",
+];
+
+const KEYBOARD_SEQUENCES: &[&str] = &[
+    "abcdefghijklmnopqrstuvwxyz",
+    "qwertyuiop",
+    "asdfghjkl",
+    "zxcvbnm",
+    "01234567890",
+];
+
+/// zxcvbn-style strength estimate on a 0-4 scale: brute-force entropy over the
+/// character classes used, minus penalties for dictionary words, sequences,
+/// repeats and personal information.
+pub fn strength_score(password: &str, user_inputs: &[&str]) -> u8 {
+    let length = password.chars().count();
+    if length == 0 {
+        return 0;
+    }
+
+    let mut charset = 0u32;
+    if password.chars().any(|c| c.is_ascii_lowercase()) {
+        charset += 26;
+    }
+    if password.chars().any(|c| c.is_ascii_uppercase()) {
+        charset += 26;
+    }
+    if password.chars().any(|c| c.is_ascii_digit()) {
+        charset += 10;
+    }
+    if password.chars().any(|c| c.is_ascii_punctuation() || c == ' ') {
+        charset += 33;
+    }
+    if !password.is_ascii() {
+        charset += 100;
+    }
+
+    let mut entropy = length as f64 * f64::from(charset.max(1)).log2();
+    let lowered = password.to_lowercase();
+
+    // Few distinct characters means lots of repetition ("aaaaaa", "abababab")
+    let distinct = lowered.chars().collect::<HashSet<_>>().len();
+    if distinct * 2 < length {
+        entropy *= 0.5;
+    }
+
+    for word in COMMON_PASSWORDS {
+        if lowered.contains(word) {
+            entropy -= word.len() as f64 * 4.0;
+        }
+    }
+
+    for sequence in KEYBOARD_SEQUENCES {
+        let reversed: String = sequence.chars().rev().collect();
+        for run in [sequence.to_string(), reversed] {
+            let run: Vec<char> = run.chars().collect();
+            for window in run.windows(4) {
+                let fragment: String = window.iter().collect();
+                if lowered.contains(&fragment) {
+                    entropy -= 8.0;
+                }
+            }
+        }
+    }
+
+    for input in user_inputs {
+        for part in input
+            .to_lowercase()
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|part| part.len() >= 3)
+        {
+            if lowered.contains(part) {
+                entropy -= part.len() as f64 * 4.0;
+            }
+        }
+    }
+
+    match entropy {
+        e if e < 28.0 => 0,
+        e if e < 40.0 => 1,
+        e if e < 60.0 => 2,
+        e if e < 80.0 => 3,
+        _ => 4,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(breached_passwords_path: Option<String>) -> PasswordPolicy {
+        PasswordPolicy::new(PasswordPolicyConfig {
+            breached_passwords_path,
+            ..PasswordPolicyConfig::default()
+        })
+    }
+
+    fn codes(result: Result<(), PasswordPolicyError>) -> Vec<&'static str> {
+        result.err().map(|e| e.violations.iter().map(|v| v.code).collect()).unwrap_or_default()
+    }
+
+    #[test]
+    fn trivial_passwords_score_zero() {
+        assert_eq!(strength_score("", &[]), 0);
+        assert_eq!(strength_score("password", &[]), 0);
+        assert_eq!(strength_score("aaaaaaaaaa", &[]), 0);
+        assert_eq!(strength_score("qwerty1234", &[]), 0);
+    }
+
+    #[test]
+    fn long_mixed_passwords_score_high() {
+        assert_eq!(strength_score("correct horse battery staple", &[]), 4);
+        assert!(strength_score("Tz9#vQ2!mK", &[]) >= 3);
+    }
+
+    #[test]
+    fn personal_information_lowers_the_score() {
+        let password = "MargaretHamilton1936";
+        let without_inputs = strength_score(password, &[]);
+        let with_inputs = strength_score(password, &["margaret.hamilton@example.com", "Margaret", "Hamilton"]);
+        assert!(with_inputs < without_inputs);
+    }
+
+    #[tokio::test]
+    async fn validate_reports_every_failed_rule() {
+        let policy = policy(None);
+        assert_eq!(
+            codes(policy.validate("password", &[]).await),
+            vec![PasswordViolation::TOO_SHORT, PasswordViolation::TOO_WEAK]
+        );
+        assert_eq!(codes(policy.validate("password123456", &[]).await), vec![PasswordViolation::TOO_WEAK]);
+        assert!(policy.validate("correct horse battery staple", &[]).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn validate_checks_the_breached_range_files() {
+        let password = "correct horse battery staple";
+        let hash = format!("{:X}", Sha1::digest(password.as_bytes()));
+        let (prefix, suffix) = hash.split_at(5);
+
+        let dir = std::env::temp_dir().join(format!("breached-ranges-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join(format!("{}.txt", prefix)),
+            format!("0000000000000000000000000000000000A:3\n{}:42\n", suffix.to_lowercase()),
+        )
+        .unwrap();
+
+        let policy = policy(Some(dir.to_string_lossy().into_owned()));
+        assert_eq!(codes(policy.validate(password, &[]).await), vec![PasswordViolation::BREACHED]);
+        assert!(policy.validate("a different long passphrase", &[]).await.is_ok());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
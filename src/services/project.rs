@@ -1,17 +1,33 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter,
-    QueryOrder, QuerySelect, Set, TransactionTrait,
+    prelude::DateTimeWithTimeZone, ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, DbErr,
+    EntityTrait, PaginatorTrait,
+    QueryFilter, QueryOrder, QuerySelect, Set, TransactionTrait,
 };
 use uuid::Uuid;
 use chrono::Utc;
 
-use crate::entities::{prelude::*, project, project_member, user};
+use crate::entities::{
+    permission, prelude::*, project, project_member, project_role, project_role_permission, project_team, resource,
+    team, user, user_permission,
+};
+use crate::entities::prelude::ProjectRole as ProjectRoleEntity;
+use crate::auth::{is_within_window, AppResource, PermissionService};
+use crate::services::team::{team_member_ids, user_team_ids};
 
 #[derive(Clone)]
 pub struct ProjectService {
     db: DatabaseConnection,
+    permission_service: PermissionService,
+    /// Project -> owning organization. Projects never change organization, so this
+    /// is only ever filled, never invalidated.
+    project_organizations: Arc<RwLock<HashMap<Uuid, Uuid>>>,
 }
 
+/// Built-in project roles, each backed by a system row in `project_role`.
+/// Projects may also define custom roles, which have no variant here.
 #[derive(Debug, Clone)]
 pub enum ProjectRole {
     Owner,
@@ -40,35 +56,137 @@ impl ProjectRole {
         }
     }
 
-    pub fn can_manage_project(&self) -> bool {
-        matches!(self, ProjectRole::Owner | ProjectRole::Admin)
+    /// Precedence when several memberships apply, higher wins
+    fn rank(&self) -> u8 {
+        match self {
+            ProjectRole::Owner => 3,
+            ProjectRole::Admin => 2,
+            ProjectRole::Member => 1,
+            ProjectRole::Viewer => 0,
+        }
     }
+}
 
-    pub fn can_invite_users(&self) -> bool {
-        matches!(self, ProjectRole::Owner | ProjectRole::Admin)
+/// Actions evaluated per project by `ProjectService::can_user_perform`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectAction {
+    ProjectView,
+    ProjectUpdate,
+    ProjectDelete,
+    MemberInvite,
+    MemberManage,
+    MemberViewContact,
+    TaskCreate,
+    TaskUpdate,
+    TaskAssign,
+    TaskDelete,
+    ContextCreate,
+    ContextUpdate,
+    ContextArchive,
+    EmailReview,
+    EmailReadSensitive,
+    EmailSend,
+}
+
+impl ProjectAction {
+    pub const ALL: [ProjectAction; 16] = [
+        ProjectAction::ProjectView,
+        ProjectAction::ProjectUpdate,
+        ProjectAction::ProjectDelete,
+        ProjectAction::MemberInvite,
+        ProjectAction::MemberManage,
+        ProjectAction::MemberViewContact,
+        ProjectAction::TaskCreate,
+        ProjectAction::TaskUpdate,
+        ProjectAction::TaskAssign,
+        ProjectAction::TaskDelete,
+        ProjectAction::ContextCreate,
+        ProjectAction::ContextUpdate,
+        ProjectAction::ContextArchive,
+        ProjectAction::EmailReview,
+        ProjectAction::EmailReadSensitive,
+        ProjectAction::EmailSend,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProjectAction::ProjectView => "project:view",
+            ProjectAction::ProjectUpdate => "project:update",
+            ProjectAction::ProjectDelete => "project:delete",
+            ProjectAction::MemberInvite => "member:invite",
+            ProjectAction::MemberManage => "member:manage",
+            ProjectAction::MemberViewContact => "member:view_contact",
+            ProjectAction::TaskCreate => "task:create",
+            ProjectAction::TaskUpdate => "task:update",
+            ProjectAction::TaskAssign => "task:assign",
+            ProjectAction::TaskDelete => "task:delete",
+            ProjectAction::ContextCreate => "context:create",
+            ProjectAction::ContextUpdate => "context:update",
+            ProjectAction::ContextArchive => "context:archive",
+            ProjectAction::EmailReview => "email:review",
+            ProjectAction::EmailReadSensitive => "email:read_sensitive",
+            ProjectAction::EmailSend => "email:send",
+        }
     }
 
-    pub fn can_manage_tasks(&self) -> bool {
-        !matches!(self, ProjectRole::Viewer)
+    pub fn description(&self) -> &'static str {
+        match self {
+            ProjectAction::ProjectView => "View the project, its tasks and contexts",
+            ProjectAction::ProjectUpdate => "Edit project details",
+            ProjectAction::ProjectDelete => "Delete the project",
+            ProjectAction::MemberInvite => "Add members to the project",
+            ProjectAction::MemberManage => "Change member roles, remove members and manage project roles",
+            ProjectAction::MemberViewContact => "See members' email addresses and full names",
+            ProjectAction::TaskCreate => "Create tasks in the project",
+            ProjectAction::TaskUpdate => "Edit and complete any task in the project",
+            ProjectAction::TaskAssign => "Assign tasks to project members",
+            ProjectAction::TaskDelete => "Delete tasks in the project",
+            ProjectAction::ContextCreate => "Create contexts and context categories",
+            ProjectAction::ContextUpdate => "Edit contexts and context categories",
+            ProjectAction::ContextArchive => "Archive and restore contexts, delete categories",
+            ProjectAction::EmailReview => "Review email contexts and update their processing status",
+            ProjectAction::EmailReadSensitive => "Read HTML email bodies and BCC recipients",
+            ProjectAction::EmailSend => "Reply to email contexts and send email from the project",
+        }
+    }
+}
+
+impl std::str::FromStr for ProjectAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ProjectAction::ALL
+            .into_iter()
+            .find(|action| action.as_str() == s)
+            .ok_or_else(|| format!("Unknown project action '{}'", s))
     }
 }
 
 impl ProjectService {
-    pub fn new(db: DatabaseConnection) -> Self {
-        Self { db }
+    pub fn new(db: DatabaseConnection, permission_service: PermissionService) -> Self {
+        Self {
+            db,
+            permission_service,
+            project_organizations: Arc::new(RwLock::new(HashMap::new())),
+        }
     }
 
     pub fn get_db(&self) -> &DatabaseConnection {
         &self.db
     }
 
-    /// Create a new project
+    /// Create a new project in one of the owner's organizations
     pub async fn create_project(
         &self,
         owner_id: Uuid,
+        organization_id: Uuid,
         name: &str,
         description: Option<String>,
     ) -> Result<project::Model, Box<dyn std::error::Error>> {
+        if !self.permission_service.user_in_organization(owner_id, organization_id).await? {
+            return Err("You are not a member of this organization".into());
+        }
+
         let tx = self.db.begin().await?;
 
         // Check if user already has a project with this name
@@ -93,17 +211,20 @@ impl ProjectService {
             is_active: Set(true),
             created_at: Set(Utc::now().into()),
             updated_at: Set(Utc::now().into()),
+            organization_id: Set(organization_id),
         };
 
         let project = new_project.insert(&tx).await?;
 
         // Add owner as project member with owner role
+        let owner_role_id = Self::system_role_id(&tx, &ProjectRole::Owner).await?;
         let owner_member = project_member::ActiveModel {
             id: Set(Uuid::new_v4()),
             project_id: Set(project_id),
             user_id: Set(owner_id),
             role: Set(ProjectRole::Owner.as_str().to_string()),
             joined_at: Set(Utc::now().into()),
+            project_role_id: Set(owner_role_id),
         };
 
         owner_member.insert(&tx).await?;
@@ -119,10 +240,8 @@ impl ProjectService {
         project_id: Uuid,
         user_id: Uuid,
     ) -> Result<Option<project::Model>, Box<dyn std::error::Error>> {
-        // Check if user is a member of the project
-        let membership = self.get_user_project_role(project_id, user_id).await?;
-        if membership.is_none() {
-            return Ok(None); // User is not a member
+        if !self.can_user_perform(project_id, user_id, ProjectAction::ProjectView).await? {
+            return Ok(None);
         }
 
         let project = Project::find_by_id(project_id)
@@ -133,16 +252,42 @@ impl ProjectService {
         Ok(project)
     }
 
-    /// Get projects for a user (as member or owner)
+    /// Get projects for a user (as member, owner or through a team), limited to
+    /// `organization_id` when given and to the user's organizations otherwise
     pub async fn get_user_projects(
         &self,
         user_id: Uuid,
+        organization_id: Option<Uuid>,
         limit: Option<u64>,
         offset: Option<u64>,
     ) -> Result<Vec<project::Model>, Box<dyn std::error::Error>> {
-        let mut query = Project::find()
-            .inner_join(ProjectMember)
+        let organization_ids: Vec<Uuid> = match organization_id {
+            Some(organization_id) => vec![organization_id],
+            None => self.permission_service.user_organization_ids(user_id).await?.into_iter().collect(),
+        };
+
+        let mut project_ids: Vec<Uuid> = ProjectMember::find()
+            .select_only()
+            .column(project_member::Column::ProjectId)
             .filter(project_member::Column::UserId.eq(user_id))
+            .into_tuple()
+            .all(&self.db)
+            .await?;
+        let team_ids = user_team_ids(&self.db, user_id).await?;
+        if !team_ids.is_empty() {
+            let team_project_ids: Vec<Uuid> = ProjectTeam::find()
+                .select_only()
+                .column(project_team::Column::ProjectId)
+                .filter(project_team::Column::TeamId.is_in(team_ids))
+                .into_tuple()
+                .all(&self.db)
+                .await?;
+            project_ids.extend(team_project_ids);
+        }
+
+        let mut query = Project::find()
+            .filter(project::Column::Id.is_in(project_ids))
+            .filter(project::Column::OrganizationId.is_in(organization_ids))
             .filter(project::Column::IsActive.eq(true))
             .order_by_desc(project::Column::UpdatedAt);
 
@@ -154,8 +299,7 @@ impl ProjectService {
             query = query.offset(offset);
         }
 
-        let projects = query.all(&self.db).await?;
-        Ok(projects)
+        Ok(query.all(&self.db).await?)
     }
 
     /// Update project
@@ -166,12 +310,8 @@ impl ProjectService {
         name: Option<String>,
         description: Option<Option<String>>,
     ) -> Result<project::Model, Box<dyn std::error::Error>> {
-        // Check if user can manage project
-        let role = self.get_user_project_role(project_id, user_id).await?;
-        match role {
-            Some(r) if r.can_manage_project() => {},
-            _ => return Err("Insufficient permissions to update project".into()),
-        }
+        self.ensure_can_perform(project_id, user_id, ProjectAction::ProjectUpdate, "update project")
+            .await?;
 
         let project = Project::find_by_id(project_id)
             .filter(project::Column::IsActive.eq(true))
@@ -212,12 +352,8 @@ impl ProjectService {
         project_id: Uuid,
         user_id: Uuid,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        // Only project owner can delete
-        let role = self.get_user_project_role(project_id, user_id).await?;
-        match role {
-            Some(ProjectRole::Owner) => {},
-            _ => return Err("Only project owner can delete project".into()),
-        }
+        self.ensure_can_perform(project_id, user_id, ProjectAction::ProjectDelete, "delete project")
+            .await?;
 
         let project = Project::find_by_id(project_id)
             .one(&self.db)
@@ -239,14 +375,19 @@ impl ProjectService {
         project_id: Uuid,
         user_id: Uuid,
         new_member_id: Uuid,
-        role: ProjectRole,
+        role_name: &str,
     ) -> Result<project_member::Model, Box<dyn std::error::Error>> {
-        // Check if requester can invite users
-        let requester_role = self.get_user_project_role(project_id, user_id).await?;
-        match requester_role {
-            Some(r) if r.can_invite_users() => {},
-            _ => return Err("Insufficient permissions to add members".into()),
+        self.ensure_can_perform(project_id, user_id, ProjectAction::MemberInvite, "add members")
+            .await?;
+
+        if role_name == ProjectRole::Owner.as_str() {
+            return Err("Projects have a single owner".into());
         }
+        let role = self
+            .find_project_role_by_name(project_id, role_name)
+            .await?
+            .ok_or("Invalid project role")?;
+        self.ensure_can_grant_role(project_id, user_id, &role).await?;
 
         // Check if user is already a member
         if let Some(_) = ProjectMember::find()
@@ -265,21 +406,28 @@ impl ProjectService {
             .ok_or("User not found")?;
 
         // Verify project exists and is active
-        let _project = Project::find_by_id(project_id)
+        let project = Project::find_by_id(project_id)
             .filter(project::Column::IsActive.eq(true))
             .one(&self.db)
             .await?
             .ok_or("Project not found")?;
 
+        // Only people in the project's organization can join it
+        if !self.permission_service.user_in_organization(new_member_id, project.organization_id).await? {
+            return Err("User is not a member of this project's organization".into());
+        }
+
         let new_member = project_member::ActiveModel {
             id: Set(Uuid::new_v4()),
             project_id: Set(project_id),
             user_id: Set(new_member_id),
-            role: Set(role.as_str().to_string()),
+            role: Set(role.name),
             joined_at: Set(Utc::now().into()),
+            project_role_id: Set(Some(role.id)),
         };
 
         let member = new_member.insert(&self.db).await?;
+        self.permission_service.invalidate_user(new_member_id).await;
         Ok(member)
     }
 
@@ -289,14 +437,23 @@ impl ProjectService {
         project_id: Uuid,
         user_id: Uuid,
         target_user_id: Uuid,
-        new_role: ProjectRole,
+        role_name: &str,
     ) -> Result<project_member::Model, Box<dyn std::error::Error>> {
-        // Check if requester can manage project
-        let requester_role = self.get_user_project_role(project_id, user_id).await?;
-        match requester_role {
-            Some(r) if r.can_manage_project() => {},
-            _ => return Err("Insufficient permissions to update member roles".into()),
+        self.ensure_can_perform(project_id, user_id, ProjectAction::MemberManage, "update member roles")
+            .await?;
+
+        // Otherwise a member manager could pick any role for themselves
+        if target_user_id == user_id {
+            return Err("You cannot change your own project role".into());
         }
+        if role_name == ProjectRole::Owner.as_str() {
+            return Err("Projects have a single owner".into());
+        }
+        let new_role = self
+            .find_project_role_by_name(project_id, role_name)
+            .await?
+            .ok_or("Invalid project role")?;
+        self.ensure_can_grant_role(project_id, user_id, &new_role).await?;
 
         // Cannot change owner role
         let target_role = self.get_user_project_role(project_id, target_user_id).await?;
@@ -312,9 +469,11 @@ impl ProjectService {
             .ok_or("User is not a member of this project")?;
 
         let mut member_active: project_member::ActiveModel = member.into();
-        member_active.role = Set(new_role.as_str().to_string());
+        member_active.role = Set(new_role.name);
+        member_active.project_role_id = Set(Some(new_role.id));
 
         let updated_member = member_active.update(&self.db).await?;
+        self.permission_service.invalidate_user(target_user_id).await;
         Ok(updated_member)
     }
 
@@ -325,12 +484,8 @@ impl ProjectService {
         user_id: Uuid,
         target_user_id: Uuid,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        // Check if requester can manage project
-        let requester_role = self.get_user_project_role(project_id, user_id).await?;
-        match requester_role {
-            Some(r) if r.can_manage_project() => {},
-            _ => return Err("Insufficient permissions to remove members".into()),
-        }
+        self.ensure_can_perform(project_id, user_id, ProjectAction::MemberManage, "remove members")
+            .await?;
 
         // Cannot remove project owner
         let target_role = self.get_user_project_role(project_id, target_user_id).await?;
@@ -348,6 +503,7 @@ impl ProjectService {
         ProjectMember::delete_by_id(member.id)
             .exec(&self.db)
             .await?;
+        self.permission_service.invalidate_user(target_user_id).await;
 
         Ok(())
     }
@@ -358,9 +514,7 @@ impl ProjectService {
         project_id: Uuid,
         user_id: Uuid,
     ) -> Result<Vec<(project_member::Model, user::Model)>, Box<dyn std::error::Error>> {
-        // Check if user is a member
-        let role = self.get_user_project_role(project_id, user_id).await?;
-        if role.is_none() {
+        if !self.can_user_perform(project_id, user_id, ProjectAction::ProjectView).await? {
             return Err("Access denied".into());
         }
 
@@ -378,7 +532,168 @@ impl ProjectService {
         Ok(result)
     }
 
-    /// Get user's role in project
+    /// Add a team to the project; every current and future member of the team gets `role_name`
+    pub async fn add_project_team(
+        &self,
+        project_id: Uuid,
+        user_id: Uuid,
+        team_id: Uuid,
+        role_name: &str,
+    ) -> Result<project_team::Model, Box<dyn std::error::Error>> {
+        self.ensure_can_perform(project_id, user_id, ProjectAction::MemberInvite, "add members")
+            .await?;
+
+        if role_name == ProjectRole::Owner.as_str() {
+            return Err("Projects have a single owner".into());
+        }
+        let role = self
+            .find_project_role_by_name(project_id, role_name)
+            .await?
+            .ok_or("Invalid project role")?;
+        self.ensure_can_grant_role(project_id, user_id, &role).await?;
+
+        let team = Team::find_by_id(team_id)
+            .one(&self.db)
+            .await?
+            .ok_or("Team not found")?;
+
+        let project = Project::find_by_id(project_id)
+            .filter(project::Column::IsActive.eq(true))
+            .one(&self.db)
+            .await?
+            .ok_or("Project not found")?;
+
+        if team.organization_id != project.organization_id {
+            return Err("Team does not belong to this project's organization".into());
+        }
+
+        if ProjectTeam::find()
+            .filter(project_team::Column::ProjectId.eq(project_id))
+            .filter(project_team::Column::TeamId.eq(team_id))
+            .one(&self.db)
+            .await?
+            .is_some()
+        {
+            return Err("Team is already on this project".into());
+        }
+
+        let project_team = project_team::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            project_id: Set(project_id),
+            team_id: Set(team_id),
+            role: Set(role.name),
+            project_role_id: Set(Some(role.id)),
+            added_at: Set(Utc::now().into()),
+        }
+        .insert(&self.db)
+        .await?;
+
+        self.invalidate_team(team_id).await?;
+        Ok(project_team)
+    }
+
+    /// Change the role a team holds on the project
+    pub async fn update_project_team_role(
+        &self,
+        project_id: Uuid,
+        user_id: Uuid,
+        team_id: Uuid,
+        role_name: &str,
+    ) -> Result<project_team::Model, Box<dyn std::error::Error>> {
+        self.ensure_can_perform(project_id, user_id, ProjectAction::MemberManage, "update member roles")
+            .await?;
+
+        if role_name == ProjectRole::Owner.as_str() {
+            return Err("Projects have a single owner".into());
+        }
+        let new_role = self
+            .find_project_role_by_name(project_id, role_name)
+            .await?
+            .ok_or("Invalid project role")?;
+        self.ensure_can_grant_role(project_id, user_id, &new_role).await?;
+
+        let project_team = ProjectTeam::find()
+            .filter(project_team::Column::ProjectId.eq(project_id))
+            .filter(project_team::Column::TeamId.eq(team_id))
+            .one(&self.db)
+            .await?
+            .ok_or("Team is not on this project")?;
+
+        let mut project_team_active: project_team::ActiveModel = project_team.into();
+        project_team_active.role = Set(new_role.name);
+        project_team_active.project_role_id = Set(Some(new_role.id));
+
+        let updated = project_team_active.update(&self.db).await?;
+        self.invalidate_team(team_id).await?;
+        Ok(updated)
+    }
+
+    /// Take a team off the project. Members keep any direct membership they have.
+    pub async fn remove_project_team(
+        &self,
+        project_id: Uuid,
+        user_id: Uuid,
+        team_id: Uuid,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.ensure_can_perform(project_id, user_id, ProjectAction::MemberManage, "remove members")
+            .await?;
+
+        let result = ProjectTeam::delete_many()
+            .filter(project_team::Column::ProjectId.eq(project_id))
+            .filter(project_team::Column::TeamId.eq(team_id))
+            .exec(&self.db)
+            .await?;
+        if result.rows_affected == 0 {
+            return Err("Team is not on this project".into());
+        }
+
+        self.invalidate_team(team_id).await?;
+        Ok(())
+    }
+
+    /// Teams on the project, with the role each holds
+    pub async fn get_project_teams(
+        &self,
+        project_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Vec<(project_team::Model, team::Model)>, Box<dyn std::error::Error>> {
+        if !self.can_user_perform(project_id, user_id, ProjectAction::ProjectView).await? {
+            return Err("Access denied".into());
+        }
+
+        let project_teams = ProjectTeam::find()
+            .filter(project_team::Column::ProjectId.eq(project_id))
+            .find_also_related(Team)
+            .all(&self.db)
+            .await?;
+
+        let result = project_teams
+            .into_iter()
+            .filter_map(|(project_team, team_opt)| team_opt.map(|team| (project_team, team)))
+            .collect();
+
+        Ok(result)
+    }
+
+    /// Whether the team has been added to the project
+    pub async fn is_project_team(&self, project_id: Uuid, team_id: Uuid) -> Result<bool, DbErr> {
+        let count = ProjectTeam::find()
+            .filter(project_team::Column::ProjectId.eq(project_id))
+            .filter(project_team::Column::TeamId.eq(team_id))
+            .count(&self.db)
+            .await?;
+        Ok(count > 0)
+    }
+
+    async fn invalidate_team(&self, team_id: Uuid) -> Result<(), DbErr> {
+        for member_id in team_member_ids(&self.db, team_id).await? {
+            self.permission_service.invalidate_user(member_id).await;
+        }
+        Ok(())
+    }
+
+    /// Get user's built-in role in project (`None` for non-members and custom roles).
+    /// A direct membership decides; otherwise the strongest role among the user's teams.
     pub async fn get_user_project_role(
         &self,
         project_id: Uuid,
@@ -390,10 +705,31 @@ impl ProjectService {
             .one(&self.db)
             .await?;
 
-        match member {
-            Some(m) => Ok(ProjectRole::from_str(&m.role)),
-            None => Ok(None),
+        if let Some(m) = member {
+            return Ok(ProjectRole::from_str(&m.role));
         }
+
+        let team_role = self
+            .user_project_teams(project_id, user_id)
+            .await?
+            .iter()
+            .filter_map(|project_team| ProjectRole::from_str(&project_team.role))
+            .max_by_key(|role| role.rank());
+        Ok(team_role)
+    }
+
+    /// The project's grants to teams the user belongs to
+    async fn user_project_teams(&self, project_id: Uuid, user_id: Uuid) -> Result<Vec<project_team::Model>, DbErr> {
+        let team_ids = user_team_ids(&self.db, user_id).await?;
+        if team_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        ProjectTeam::find()
+            .filter(project_team::Column::ProjectId.eq(project_id))
+            .filter(project_team::Column::TeamId.is_in(team_ids))
+            .all(&self.db)
+            .await
     }
 
     /// Check if user can access project
@@ -402,7 +738,509 @@ impl ProjectService {
         project_id: Uuid,
         user_id: Uuid,
     ) -> Result<bool, Box<dyn std::error::Error>> {
-        let role = self.get_user_project_role(project_id, user_id).await?;
-        Ok(role.is_some())
+        self.can_user_perform(project_id, user_id, ProjectAction::ProjectView).await
+    }
+
+    /// Check if user holds any membership in the project, directly or through a team,
+    /// whatever the role
+    pub async fn is_project_member(
+        &self,
+        project_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        let count = ProjectMember::find()
+            .filter(project_member::Column::ProjectId.eq(project_id))
+            .filter(project_member::Column::UserId.eq(user_id))
+            .count(&self.db)
+            .await?;
+        if count > 0 {
+            return Ok(true);
+        }
+
+        Ok(!self.user_project_teams(project_id, user_id).await?.is_empty())
+    }
+
+    /// Effective project permissions for a user: the membership's project role,
+    /// plus grants on the `project` resource in the project's organization, minus
+    /// explicit user denials. The membership part is cached per user under `project:<id>`.
+    pub async fn get_user_project_permissions(
+        &self,
+        project_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<HashSet<String>, Box<dyn std::error::Error>> {
+        // Projects of organizations the user doesn't belong to are out of reach entirely,
+        // whatever their memberships or global grants say
+        let Some(organization_id) = self.project_organization_id(project_id).await? else {
+            return Ok(HashSet::new());
+        };
+        if !self.permission_service.user_in_organization(user_id, organization_id).await? {
+            return Ok(HashSet::new());
+        }
+
+        let cache = self.permission_service.cache();
+        let scope = format!("project:{}", project_id);
+
+        let mut permissions = match cache.get(user_id, &scope) {
+            Some(permissions) => (*permissions).clone(),
+            None => {
+                let version = cache.version(user_id);
+                let (permissions, max_age) = self.load_membership_permissions(project_id, organization_id, user_id).await?;
+                cache.insert(user_id, &scope, version, permissions.clone(), max_age);
+                permissions
+            }
+        };
+
+        // Roles can grant project actions across every project of the organization; direct
+        // denials are already applied to these
+        let global_permissions = self
+            .permission_service
+            .get_user_permissions(user_id, Some(organization_id), AppResource::Project.name())
+            .await?;
+        permissions.extend(global_permissions);
+
+        Ok(permissions)
+    }
+
+    /// Organization owning the project, `None` if the project doesn't exist
+    pub async fn project_organization_id(&self, project_id: Uuid) -> Result<Option<Uuid>, DbErr> {
+        {
+            let project_organizations =
+                self.project_organizations.read().unwrap_or_else(|poisoned| poisoned.into_inner());
+            if let Some(organization_id) = project_organizations.get(&project_id) {
+                return Ok(Some(*organization_id));
+            }
+        }
+
+        let organization_id: Option<Uuid> = Project::find_by_id(project_id)
+            .select_only()
+            .column(project::Column::OrganizationId)
+            .into_tuple()
+            .one(&self.db)
+            .await?;
+
+        if let Some(organization_id) = organization_id {
+            let mut project_organizations =
+                self.project_organizations.write().unwrap_or_else(|poisoned| poisoned.into_inner());
+            project_organizations.insert(project_id, organization_id);
+        }
+
+        Ok(organization_id)
+    }
+
+    /// Actions granted by the user's project roles (their own membership's and those of
+    /// their teams), minus direct denials in effect in the project's organization, and how
+    /// long that stays true given the denials' validity windows
+    async fn load_membership_permissions(
+        &self,
+        project_id: Uuid,
+        organization_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<(HashSet<String>, Option<std::time::Duration>), Box<dyn std::error::Error>> {
+        let mut permissions = HashSet::new();
+
+        let mut project_role_ids: HashSet<Uuid> = ProjectMember::find()
+            .select_only()
+            .column(project_member::Column::ProjectRoleId)
+            .filter(project_member::Column::ProjectId.eq(project_id))
+            .filter(project_member::Column::UserId.eq(user_id))
+            .into_tuple::<Option<Uuid>>()
+            .one(&self.db)
+            .await?
+            .flatten()
+            .into_iter()
+            .collect();
+        project_role_ids.extend(
+            self.user_project_teams(project_id, user_id)
+                .await?
+                .into_iter()
+                .filter_map(|project_team| project_team.project_role_id),
+        );
+
+        for project_role_id in project_role_ids {
+            permissions.extend(Self::role_actions(&self.db, project_role_id).await?);
+        }
+
+        // A direct denial wins over any role, project-scoped or global
+        let denials: Vec<(String, Option<DateTimeWithTimeZone>, Option<DateTimeWithTimeZone>)> = Permission::find()
+            .select_only()
+            .column(permission::Column::Action)
+            .column(user_permission::Column::ValidFrom)
+            .column(user_permission::Column::ValidUntil)
+            .inner_join(UserPermission)
+            .inner_join(Resource)
+            .filter(user_permission::Column::UserId.eq(user_id))
+            .filter(user_permission::Column::IsGranted.eq(false))
+            .filter(
+                Condition::any()
+                    .add(user_permission::Column::OrganizationId.is_null())
+                    .add(user_permission::Column::OrganizationId.eq(organization_id)),
+            )
+            .filter(resource::Column::Name.eq(AppResource::Project.name()))
+            .into_tuple()
+            .all(&self.db)
+            .await?;
+
+        let now = Utc::now();
+        let mut next_boundary = None;
+        for (action, valid_from, valid_until) in denials {
+            if is_within_window(valid_from, valid_until, now) {
+                permissions.remove(&action);
+            }
+            next_boundary = [valid_from, valid_until]
+                .into_iter()
+                .flatten()
+                .map(|boundary| boundary.with_timezone(&Utc))
+                .filter(|boundary| *boundary > now)
+                .chain(next_boundary)
+                .min();
+        }
+
+        let max_age = next_boundary.and_then(|boundary| (boundary - now).to_std().ok());
+        Ok((permissions, max_age))
+    }
+
+    /// Single evaluation point for "can user U do action A on project P"
+    pub async fn can_user_perform(
+        &self,
+        project_id: Uuid,
+        user_id: Uuid,
+        action: ProjectAction,
+    ) -> Result<bool, Box<dyn std::error::Error>> {
+        let permissions = self.get_user_project_permissions(project_id, user_id).await?;
+        Ok(permissions.contains(action.as_str()))
+    }
+
+    /// `can_user_perform` as a guard, failing with "Insufficient permissions to {operation}"
+    pub async fn ensure_can_perform(
+        &self,
+        project_id: Uuid,
+        user_id: Uuid,
+        action: ProjectAction,
+        operation: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if self.can_user_perform(project_id, user_id, action).await? {
+            Ok(())
+        } else {
+            Err(format!("Insufficient permissions to {}", operation).into())
+        }
+    }
+
+    /// Active permission actions granted by a project role
+    async fn role_actions<C: ConnectionTrait>(conn: &C, project_role_id: Uuid) -> Result<Vec<String>, DbErr> {
+        Permission::find()
+            .select_only()
+            .column(permission::Column::Action)
+            .inner_join(ProjectRolePermission)
+            .filter(project_role_permission::Column::ProjectRoleId.eq(project_role_id))
+            .filter(permission::Column::IsActive.eq(true))
+            .into_tuple()
+            .all(conn)
+            .await
+    }
+
+    /// Id of the system `project_role` row backing a built-in role
+    pub async fn system_role_id<C: ConnectionTrait>(conn: &C, role: &ProjectRole) -> Result<Option<Uuid>, DbErr> {
+        ProjectRoleEntity::find()
+            .select_only()
+            .column(project_role::Column::Id)
+            .filter(project_role::Column::ProjectId.is_null())
+            .filter(project_role::Column::Name.eq(role.as_str()))
+            .into_tuple()
+            .one(conn)
+            .await
+    }
+
+    /// Resolve a role name to the project's custom role or a system role
+    pub async fn find_project_role_by_name(
+        &self,
+        project_id: Uuid,
+        name: &str,
+    ) -> Result<Option<project_role::Model>, Box<dyn std::error::Error>> {
+        let role = ProjectRoleEntity::find()
+            .filter(project_role::Column::Name.eq(name))
+            .filter(
+                project_role::Column::ProjectId
+                    .eq(project_id)
+                    .or(project_role::Column::ProjectId.is_null()),
+            )
+            // Project-specific roles sort before system roles (NULLs last)
+            .order_by_asc(project_role::Column::ProjectId)
+            .one(&self.db)
+            .await?;
+        Ok(role)
+    }
+
+    /// System roles plus the project's custom roles, each with its permission actions
+    pub async fn get_project_roles(
+        &self,
+        project_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Vec<(project_role::Model, Vec<String>)>, Box<dyn std::error::Error>> {
+        if !self.can_user_perform(project_id, user_id, ProjectAction::ProjectView).await? {
+            return Err("Access denied".into());
+        }
+
+        let roles = ProjectRoleEntity::find()
+            .filter(
+                project_role::Column::ProjectId
+                    .eq(project_id)
+                    .or(project_role::Column::ProjectId.is_null()),
+            )
+            .order_by_desc(project_role::Column::IsSystem)
+            .order_by_asc(project_role::Column::Name)
+            .all(&self.db)
+            .await?;
+
+        let mut result = Vec::with_capacity(roles.len());
+        for role in roles {
+            let actions = Self::role_actions(&self.db, role.id).await?;
+            result.push((role, actions));
+        }
+
+        Ok(result)
+    }
+
+    /// Permissions available for project roles
+    pub async fn get_project_permissions(&self) -> Result<Vec<permission::Model>, Box<dyn std::error::Error>> {
+        let permissions = Permission::find()
+            .inner_join(Resource)
+            .filter(resource::Column::Name.eq(AppResource::Project.name()))
+            .filter(permission::Column::IsActive.eq(true))
+            .order_by_asc(permission::Column::Action)
+            .all(&self.db)
+            .await?;
+        Ok(permissions)
+    }
+
+    /// Create a custom role for a project from `project` resource permissions
+    pub async fn create_project_role(
+        &self,
+        project_id: Uuid,
+        user_id: Uuid,
+        name: &str,
+        description: Option<String>,
+        actions: Vec<String>,
+    ) -> Result<project_role::Model, Box<dyn std::error::Error>> {
+        self.ensure_can_perform(project_id, user_id, ProjectAction::MemberManage, "manage project roles")
+            .await?;
+
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Role name is required".into());
+        }
+        if self.find_project_role_by_name(project_id, name).await?.is_some() {
+            return Err(format!("Project role '{}' already exists", name).into());
+        }
+
+        let permission_ids = self.resolve_project_permission_ids(&actions).await?;
+        self.ensure_actions_held(project_id, user_id, &actions).await?;
+
+        let tx = self.db.begin().await?;
+
+        let role = project_role::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            project_id: Set(Some(project_id)),
+            name: Set(name.to_string()),
+            description: Set(description),
+            is_system: Set(false),
+            created_at: Set(Utc::now().into()),
+            updated_at: Set(Utc::now().into()),
+        }
+        .insert(&tx)
+        .await?;
+
+        Self::replace_role_permissions(&tx, role.id, &permission_ids).await?;
+
+        tx.commit().await?;
+
+        Ok(role)
+    }
+
+    /// Update a custom project role; `actions` replaces the full permission set
+    pub async fn update_project_role(
+        &self,
+        role_id: Uuid,
+        user_id: Uuid,
+        name: Option<String>,
+        description: Option<Option<String>>,
+        actions: Option<Vec<String>>,
+    ) -> Result<project_role::Model, Box<dyn std::error::Error>> {
+        let role = ProjectRoleEntity::find_by_id(role_id)
+            .one(&self.db)
+            .await?
+            .ok_or("Project role not found")?;
+        let project_id = role.project_id.ok_or("System project roles cannot be modified")?;
+
+        self.ensure_can_perform(project_id, user_id, ProjectAction::MemberManage, "manage project roles")
+            .await?;
+
+        let permission_ids = match &actions {
+            Some(actions) => {
+                let permission_ids = self.resolve_project_permission_ids(actions).await?;
+                self.ensure_actions_held(project_id, user_id, actions).await?;
+                Some(permission_ids)
+            }
+            None => None,
+        };
+
+        let tx = self.db.begin().await?;
+
+        let mut role_active: project_role::ActiveModel = role.into();
+        if let Some(name) = name {
+            let name = name.trim().to_string();
+            if name.is_empty() {
+                return Err("Role name is required".into());
+            }
+            let existing = ProjectRoleEntity::find()
+                .filter(project_role::Column::Name.eq(&name))
+                .filter(project_role::Column::Id.ne(role_id))
+                .filter(
+                    project_role::Column::ProjectId
+                        .eq(project_id)
+                        .or(project_role::Column::ProjectId.is_null()),
+                )
+                .one(&tx)
+                .await?;
+            if existing.is_some() {
+                return Err(format!("Project role '{}' already exists", name).into());
+            }
+
+            // Keep the denormalized role name on memberships in sync
+            ProjectMember::update_many()
+                .col_expr(project_member::Column::Role, sea_orm::sea_query::Expr::value(name.clone()))
+                .filter(project_member::Column::ProjectRoleId.eq(role_id))
+                .exec(&tx)
+                .await?;
+            ProjectTeam::update_many()
+                .col_expr(project_team::Column::Role, sea_orm::sea_query::Expr::value(name.clone()))
+                .filter(project_team::Column::ProjectRoleId.eq(role_id))
+                .exec(&tx)
+                .await?;
+
+            role_active.name = Set(name);
+        }
+        if let Some(description) = description {
+            role_active.description = Set(description);
+        }
+        role_active.updated_at = Set(Utc::now().into());
+        let updated_role = role_active.update(&tx).await?;
+
+        let permissions_changed = permission_ids.is_some();
+        if let Some(permission_ids) = permission_ids {
+            Self::replace_role_permissions(&tx, role_id, &permission_ids).await?;
+        }
+
+        tx.commit().await?;
+
+        // Every member holding the role is affected
+        if permissions_changed {
+            self.permission_service.invalidate_all().await;
+        }
+
+        Ok(updated_role)
+    }
+
+    /// Delete a custom project role that no membership uses
+    pub async fn delete_project_role(
+        &self,
+        role_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let role = ProjectRoleEntity::find_by_id(role_id)
+            .one(&self.db)
+            .await?
+            .ok_or("Project role not found")?;
+        let project_id = role.project_id.ok_or("System project roles cannot be deleted")?;
+
+        self.ensure_can_perform(project_id, user_id, ProjectAction::MemberManage, "manage project roles")
+            .await?;
+
+        let members_using_role = ProjectMember::find()
+            .filter(project_member::Column::ProjectRoleId.eq(role_id))
+            .count(&self.db)
+            .await?
+            + ProjectTeam::find()
+                .filter(project_team::Column::ProjectRoleId.eq(role_id))
+                .count(&self.db)
+                .await?;
+        if members_using_role > 0 {
+            return Err(format!(
+                "Project role is assigned to {} member(s) or team(s); reassign them first",
+                members_using_role
+            )
+            .into());
+        }
+
+        ProjectRoleEntity::delete_by_id(role_id).exec(&self.db).await?;
+
+        Ok(())
+    }
+
+    /// Refuse handing out a role with actions the user doesn't hold on the project
+    pub async fn ensure_can_grant_role(
+        &self,
+        project_id: Uuid,
+        user_id: Uuid,
+        role: &project_role::Model,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let actions = Self::role_actions(&self.db, role.id).await?;
+        self.ensure_actions_held(project_id, user_id, &actions).await
+    }
+
+    /// Custom roles and role assignments can't grant more than the user holds themselves
+    async fn ensure_actions_held(
+        &self,
+        project_id: Uuid,
+        user_id: Uuid,
+        actions: &[String],
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let held = self.get_user_project_permissions(project_id, user_id).await?;
+        match actions.iter().find(|action| !held.contains(action.as_str())) {
+            Some(action) => Err(format!("Cannot grant '{}', which you don't hold on this project", action).into()),
+            None => Ok(()),
+        }
+    }
+
+    async fn resolve_project_permission_ids(
+        &self,
+        actions: &[String],
+    ) -> Result<Vec<Uuid>, Box<dyn std::error::Error>> {
+        let available = self.get_project_permissions().await?;
+
+        actions
+            .iter()
+            .map(|action| {
+                available
+                    .iter()
+                    .find(|permission| &permission.action == action)
+                    .map(|permission| permission.id)
+                    .ok_or_else(|| format!("Unknown project permission '{}'", action).into())
+            })
+            .collect()
+    }
+
+    async fn replace_role_permissions<C: ConnectionTrait>(
+        conn: &C,
+        role_id: Uuid,
+        permission_ids: &[Uuid],
+    ) -> Result<(), DbErr> {
+        ProjectRolePermission::delete_many()
+            .filter(project_role_permission::Column::ProjectRoleId.eq(role_id))
+            .exec(conn)
+            .await?;
+
+        let unique_ids: HashSet<Uuid> = permission_ids.iter().copied().collect();
+        for permission_id in unique_ids {
+            project_role_permission::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                project_role_id: Set(role_id),
+                permission_id: Set(permission_id),
+                created_at: Set(Utc::now().into()),
+            }
+            .insert(conn)
+            .await?;
+        }
+
+        Ok(())
     }
 }
\ No newline at end of file
@@ -0,0 +1,57 @@
+use chrono::Duration;
+use sea_orm::{ConnectionTrait, DbBackend, DbErr, EntityTrait, Statement};
+use uuid::Uuid;
+
+/// How long a claimed row is reserved for the dispatcher that claimed it. If that
+/// dispatcher dies mid-send, the row becomes due again afterwards.
+const CLAIM_LEASE_SECONDS: i64 = 300;
+
+/// Claim up to `batch_size` due `pending` rows of a delivery queue table (the email
+/// outbox, webhook deliveries) for this dispatcher. Claiming counts the attempt and
+/// pushes `next_attempt_at` past the lease, so concurrent dispatchers skip the rows
+/// and an abandoned claim is retried once the lease runs out.
+pub async fn claim_due_ids<E: EntityTrait, C: ConnectionTrait>(conn: &C, batch_size: u64) -> Result<Vec<Uuid>, DbErr> {
+    let table = E::default().table_name().to_string();
+    let rows = conn
+        .query_all(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            format!(
+                "UPDATE {table} \
+                 SET attempts = attempts + 1, \
+                     next_attempt_at = now() + make_interval(secs => $2), \
+                     updated_at = now() \
+                 WHERE id IN ( \
+                     SELECT id FROM {table} \
+                     WHERE status = 'pending' AND next_attempt_at <= now() \
+                     ORDER BY next_attempt_at \
+                     LIMIT $1 \
+                     FOR UPDATE SKIP LOCKED \
+                 ) \
+                 RETURNING id"
+            ),
+            [(batch_size as i64).into(), (CLAIM_LEASE_SECONDS as f64).into()],
+        ))
+        .await?;
+
+    rows.iter().map(|row| row.try_get::<Uuid>("", "id")).collect()
+}
+
+/// Delay before retrying after `attempts` failed attempts: `base_seconds`, doubling
+/// with each further attempt, capped at `max_seconds`
+pub fn retry_backoff(attempts: i32, base_seconds: i64, max_seconds: i64) -> Duration {
+    let exponent = (attempts - 1).clamp(0, 20) as u32;
+    Duration::seconds(base_seconds.saturating_mul(1i64 << exponent).min(max_seconds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        assert_eq!(retry_backoff(0, 30, 3600), Duration::seconds(30));
+        assert_eq!(retry_backoff(1, 30, 3600), Duration::seconds(30));
+        assert_eq!(retry_backoff(3, 30, 3600), Duration::seconds(120));
+        assert_eq!(retry_backoff(10, 30, 3600), Duration::seconds(3600));
+    }
+}
@@ -1,14 +1,92 @@
 use sea_orm::{
-    ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect, Set,
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder, QuerySelect, Set, TransactionTrait,
 };
+use sea_orm::sea_query::OnConflict;
 use uuid::Uuid;
 use chrono::{DateTime, Utc, Datelike, Weekday, Duration};
 
-use crate::entities::{prelude::*, task, project};
-use crate::services::{ProjectService, ActivityService};
+use crate::entities::{prelude::*, activity, task, task_watcher, project, user};
+use crate::services::{ProjectService, ProjectAction, ActivityService};
+use crate::services::team::user_team_ids;
 // EntityType imported when needed
 use crate::graphql::types::{TaskStatus, TaskPriority, RecurrenceType};
 
+/// Task activities that bring a task into existence rather than change it
+pub const TASK_CREATION_ACTIONS: &[&str] = &["created", "recurring_instance_created", "created_from_context"];
+
+/// Make a user watch a task; watching it again changes nothing
+pub async fn watch_task_with<C: ConnectionTrait>(conn: &C, task_id: Uuid, user_id: Uuid) -> Result<(), DbErr> {
+    TaskWatcher::insert(task_watcher::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        task_id: Set(task_id),
+        user_id: Set(user_id),
+        created_at: Set(Utc::now().into()),
+    })
+    .on_conflict(
+        OnConflict::columns([task_watcher::Column::TaskId, task_watcher::Column::UserId])
+            .do_nothing()
+            .to_owned(),
+    )
+    .exec_without_returning(conn)
+    .await?;
+    Ok(())
+}
+
+/// Users watching a task
+pub async fn task_watcher_ids<C: ConnectionTrait>(conn: &C, task_id: Uuid) -> Result<Vec<Uuid>, DbErr> {
+    TaskWatcher::find()
+        .select_only()
+        .column(task_watcher::Column::UserId)
+        .filter(task_watcher::Column::TaskId.eq(task_id))
+        .order_by_asc(task_watcher::Column::CreatedAt)
+        .into_tuple()
+        .all(conn)
+        .await
+}
+
+/// Keep watchers current as a task's activity is logged: new tasks are watched by their
+/// creator and assignee (recurring instances also by whoever watched the previous one),
+/// assignees and commenters start watching when they get involved
+pub async fn watch_for_activity<C: ConnectionTrait>(conn: &C, activity: &activity::Model) -> Result<(), DbErr> {
+    let action = activity.action_type.as_str();
+    let mut watcher_ids = Vec::new();
+
+    if TASK_CREATION_ACTIONS.contains(&action) {
+        // Activity can outlive its task
+        let Some(task) = Task::find_by_id(activity.entity_id).one(conn).await? else {
+            return Ok(());
+        };
+        watcher_ids.push(task.creator_id);
+        watcher_ids.extend(task.assignee_id);
+        if let Some(parent_task_id) = task.parent_task_id {
+            watcher_ids.extend(task_watcher_ids(conn, parent_task_id).await?);
+        }
+    } else {
+        let watcher_id = match action {
+            "commented" => Some(activity.actor_id),
+            "assignment_changed" => activity
+                .changes
+                .as_ref()
+                .and_then(|changes| changes.get("new_value"))
+                .and_then(|value| value.as_str())
+                .and_then(|value| Uuid::parse_str(value).ok()),
+            _ => None,
+        };
+        let Some(watcher_id) = watcher_id else {
+            return Ok(());
+        };
+        if Task::find_by_id(activity.entity_id).one(conn).await?.is_none() {
+            return Ok(());
+        }
+        watcher_ids.push(watcher_id);
+    }
+
+    for watcher_id in watcher_ids {
+        watch_task_with(conn, activity.entity_id, watcher_id).await?;
+    }
+    Ok(())
+}
+
 #[derive(Clone)]
 pub struct TaskService {
     db: DatabaseConnection,
@@ -36,14 +114,9 @@ impl TaskService {
             .await?
             .ok_or("Task not found")?;
 
-        // Check if user is a member of the project containing the task
-        let user_role = self
-            .project_service
-            .get_user_project_role(task.project_id, user_id)
-            .await?;
-
-        // User can access task if they are a project member (any role including viewer)
-        Ok(user_role.is_some())
+        self.project_service
+            .can_user_perform(task.project_id, user_id, ProjectAction::ProjectView)
+            .await
     }
 
     /// Create a new task
@@ -60,16 +133,10 @@ impl TaskService {
         due_date: Option<DateTime<Utc>>,
     ) -> Result<task::Model, Box<dyn std::error::Error>> {
         // Check if user can create tasks in this project
-        let creator_role = self
-            .project_service
-            .get_user_project_role(project_id, creator_id)
+        self.project_service
+            .ensure_can_perform(project_id, creator_id, ProjectAction::TaskCreate, "create tasks in this project")
             .await?;
 
-        match creator_role {
-            Some(role) if role.can_manage_tasks() => {},
-            _ => return Err("Insufficient permissions to create tasks in this project".into()),
-        }
-
         // Verify project exists and is active
         let _project = Project::find_by_id(project_id)
             .filter(project::Column::IsActive.eq(true))
@@ -78,15 +145,10 @@ impl TaskService {
             .ok_or("Project not found or inactive")?;
 
         // If assignee is specified, verify they are a project member
-        if let Some(assignee_id) = assignee_id {
-            let assignee_role = self
-                .project_service
-                .get_user_project_role(project_id, assignee_id)
-                .await?;
-            
-            if assignee_role.is_none() {
-                return Err("Assignee must be a project member".into());
-            }
+        if let Some(assignee_id) = assignee_id
+            && !self.project_service.is_project_member(project_id, assignee_id).await?
+        {
+            return Err("Assignee must be a project member".into());
         }
 
         let recurrence = recurrence_type.unwrap_or(RecurrenceType::None);
@@ -116,6 +178,7 @@ impl TaskService {
             next_due_date: Set(next_due_date.map(|dt| dt.into())),
             created_at: Set(Utc::now().into()),
             updated_at: Set(Utc::now().into()),
+            assignee_team_id: Set(None),
         };
 
         let task = new_task.insert(&self.db).await?;
@@ -251,20 +314,14 @@ impl TaskService {
             .await?
             .ok_or("Task not found")?;
 
-        // Check permissions - user must be able to manage tasks in the project
+        // Check permissions - user must be able to update tasks in the project
         // OR be the task creator OR be the assignee
-        let user_role = self
-            .project_service
-            .get_user_project_role(task.project_id, user_id)
-            .await?;
-
-        let can_edit = match user_role {
-            Some(role) if role.can_manage_tasks() => true,
-            _ => {
-                // Allow task creator or assignee to edit
-                task.creator_id == user_id || task.assignee_id == Some(user_id)
-            }
-        };
+        let can_edit = task.creator_id == user_id
+            || task.assignee_id == Some(user_id)
+            || self
+                .project_service
+                .can_user_perform(task.project_id, user_id, ProjectAction::TaskUpdate)
+                .await?;
 
         if !can_edit {
             return Err("Insufficient permissions to update this task".into());
@@ -336,26 +393,15 @@ impl TaskService {
             .ok_or("Task not found")?;
 
         // Check if assigner can assign tasks in this project
-        let assigner_role = self
-            .project_service
-            .get_user_project_role(task.project_id, assigner_id)
+        self.project_service
+            .ensure_can_perform(task.project_id, assigner_id, ProjectAction::TaskAssign, "assign tasks")
             .await?;
 
-        match assigner_role {
-            Some(role) if role.can_manage_tasks() => {},
-            _ => return Err("Insufficient permissions to assign tasks".into()),
-        }
-
         // If assignee is specified, verify they are a project member
-        if let Some(assignee_id) = assignee_id {
-            let assignee_role = self
-                .project_service
-                .get_user_project_role(task.project_id, assignee_id)
-                .await?;
-            
-            if assignee_role.is_none() {
-                return Err("Assignee must be a project member".into());
-            }
+        if let Some(assignee_id) = assignee_id
+            && !self.project_service.is_project_member(task.project_id, assignee_id).await?
+        {
+            return Err("Assignee must be a project member".into());
         }
 
         // Store old assignee for activity logging
@@ -375,6 +421,163 @@ impl TaskService {
         Ok(updated_task)
     }
 
+    /// Put a task in a team's queue (or take it out with `None`). Queued tasks are
+    /// unassigned until a team member claims them.
+    pub async fn assign_task_to_team(
+        &self,
+        task_id: Uuid,
+        assigner_id: Uuid,
+        team_id: Option<Uuid>,
+    ) -> Result<task::Model, Box<dyn std::error::Error>> {
+        let task = Task::find_by_id(task_id)
+            .one(&self.db)
+            .await?
+            .ok_or("Task not found")?;
+
+        self.project_service
+            .ensure_can_perform(task.project_id, assigner_id, ProjectAction::TaskAssign, "assign tasks")
+            .await?;
+
+        if let Some(team_id) = team_id
+            && !self.project_service.is_project_team(task.project_id, team_id).await?
+        {
+            return Err("Team must be on the task's project".into());
+        }
+
+        let old_team_id = task.assignee_team_id;
+        let old_assignee_id = task.assignee_id;
+
+        let mut task_active: task::ActiveModel = task.into();
+        task_active.assignee_team_id = Set(team_id);
+        task_active.assignee_id = Set(None);
+        task_active.updated_at = Set(Utc::now().into());
+
+        let updated_task = task_active.update(&self.db).await?;
+
+        self.activity_service
+            .log_activity(
+                crate::services::activity::EntityType::Task,
+                task_id,
+                assigner_id,
+                "team_assignment_changed",
+                Some(match team_id {
+                    Some(_) => "Task queued for a team".to_string(),
+                    None => "Task removed from team queue".to_string(),
+                }),
+                None,
+                Some(serde_json::json!({
+                    "assignee_team_id": { "old": old_team_id, "new": team_id },
+                    "assignee_id": { "old": old_assignee_id, "new": null }
+                })),
+            )
+            .await?;
+
+        Ok(updated_task)
+    }
+
+    /// Follow a task you can see, to be notified about its changes
+    pub async fn watch_task(&self, task_id: Uuid, user_id: Uuid) -> Result<task::Model, Box<dyn std::error::Error>> {
+        if !self.can_user_access_task(task_id, user_id).await? {
+            return Err("Insufficient permissions to view this task".into());
+        }
+
+        watch_task_with(&self.db, task_id, user_id).await?;
+        Ok(Task::find_by_id(task_id).one(&self.db).await?.ok_or("Task not found")?)
+    }
+
+    /// Stop following a task. Creators and assignees may unwatch too; they start
+    /// watching again when they are assigned or comment.
+    pub async fn unwatch_task(&self, task_id: Uuid, user_id: Uuid) -> Result<task::Model, Box<dyn std::error::Error>> {
+        let task = Task::find_by_id(task_id).one(&self.db).await?.ok_or("Task not found")?;
+
+        TaskWatcher::delete_many()
+            .filter(task_watcher::Column::TaskId.eq(task_id))
+            .filter(task_watcher::Column::UserId.eq(user_id))
+            .exec(&self.db)
+            .await?;
+
+        Ok(task)
+    }
+
+    /// Users watching a task, in the order they started watching
+    pub async fn get_task_watchers(&self, task_id: Uuid) -> Result<Vec<user::Model>, Box<dyn std::error::Error>> {
+        let watcher_ids = task_watcher_ids(&self.db, task_id).await?;
+        let mut users = User::find()
+            .filter(user::Column::Id.is_in(watcher_ids.clone()))
+            .all(&self.db)
+            .await?;
+        users.sort_by_key(|user| watcher_ids.iter().position(|id| *id == user.id));
+        Ok(users)
+    }
+
+    /// Claim an unassigned task from the queue of one of the user's teams
+    pub async fn claim_task(
+        &self,
+        task_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<task::Model, Box<dyn std::error::Error>> {
+        let task = Task::find_by_id(task_id)
+            .one(&self.db)
+            .await?
+            .ok_or("Task not found")?;
+
+        let team_id = task.assignee_team_id.ok_or("Task is not in a team queue")?;
+        if !user_team_ids(&self.db, user_id).await?.contains(&team_id) {
+            return Err("Only members of the task's team can claim it".into());
+        }
+        if task.assignee_id.is_some() {
+            return Err("Task has already been claimed".into());
+        }
+
+        self.project_service
+            .ensure_can_perform(task.project_id, user_id, ProjectAction::ProjectView, "claim tasks")
+            .await?;
+
+        // Guard against a concurrent claim between the read above and this write
+        let result = Task::update_many()
+            .col_expr(task::Column::AssigneeId, sea_orm::sea_query::Expr::value(Some(user_id)))
+            .col_expr(task::Column::UpdatedAt, sea_orm::sea_query::Expr::current_timestamp().into())
+            .filter(task::Column::Id.eq(task_id))
+            .filter(task::Column::AssigneeId.is_null())
+            .exec(&self.db)
+            .await?;
+        if result.rows_affected == 0 {
+            return Err("Task has already been claimed".into());
+        }
+
+        self.activity_service
+            .log_task_assignment(task_id, user_id, None, Some(user_id))
+            .await?;
+
+        let claimed_task = Task::find_by_id(task_id)
+            .one(&self.db)
+            .await?
+            .ok_or("Task not found")?;
+        Ok(claimed_task)
+    }
+
+    /// Open, unclaimed tasks waiting in a team's queue
+    pub async fn get_team_queue(
+        &self,
+        team_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Vec<task::Model>, Box<dyn std::error::Error>> {
+        if !user_team_ids(&self.db, user_id).await?.contains(&team_id) {
+            return Err("Access denied".into());
+        }
+
+        let tasks = Task::find()
+            .filter(task::Column::AssigneeTeamId.eq(team_id))
+            .filter(task::Column::AssigneeId.is_null())
+            .filter(task::Column::Status.is_in([TaskStatus::Todo, TaskStatus::InProgress]))
+            .order_by_asc(task::Column::DueDate)
+            .order_by_asc(task::Column::CreatedAt)
+            .all(&self.db)
+            .await?;
+
+        Ok(tasks)
+    }
+
     /// Delete task
     pub async fn delete_task(
         &self,
@@ -386,23 +589,24 @@ impl TaskService {
             .await?
             .ok_or("Task not found")?;
 
-        // Check permissions - user must be able to manage tasks in the project
+        // Check permissions - user must be able to delete tasks in the project
         // OR be the task creator
-        let user_role = self
-            .project_service
-            .get_user_project_role(task.project_id, user_id)
-            .await?;
-
-        let can_delete = match user_role {
-            Some(role) if role.can_manage_tasks() => true,
-            _ => task.creator_id == user_id,
-        };
+        let can_delete = task.creator_id == user_id
+            || self
+                .project_service
+                .can_user_perform(task.project_id, user_id, ProjectAction::TaskDelete)
+                .await?;
 
         if !can_delete {
             return Err("Insufficient permissions to delete this task".into());
         }
 
-        Task::delete_by_id(task_id).exec(&self.db).await?;
+        let txn = self.db.begin().await?;
+        self.activity_service
+            .log_task_deletion(&txn, task_id, user_id, &task.name, task.project_id)
+            .await?;
+        Task::delete_by_id(task_id).exec(&txn).await?;
+        txn.commit().await?;
         Ok(())
     }
 
@@ -532,15 +736,12 @@ impl TaskService {
             .ok_or("Task not found")?;
 
         // Check permissions
-        let user_role = self
-            .project_service
-            .get_user_project_role(task.project_id, actor_id)
-            .await?;
-
-        let can_complete = match user_role {
-            Some(role) if role.can_manage_tasks() => true,
-            _ => task.creator_id == actor_id || task.assignee_id == Some(actor_id),
-        };
+        let can_complete = task.creator_id == actor_id
+            || task.assignee_id == Some(actor_id)
+            || self
+                .project_service
+                .can_user_perform(task.project_id, actor_id, ProjectAction::TaskUpdate)
+                .await?;
 
         if !can_complete {
             return Err("Insufficient permissions to complete this task".into());
@@ -571,7 +772,8 @@ impl TaskService {
                 name: Set(task.name.clone()),
                 description: Set(task.description.clone()),
                 project_id: Set(task.project_id),
-                assignee_id: Set(task.assignee_id),
+                // Occurrences of a queued task go back to the team's queue unclaimed
+                assignee_id: Set(if task.assignee_team_id.is_some() { None } else { task.assignee_id }),
                 creator_id: Set(task.creator_id),
                 context_id: Set(task.context_id),
                 status: Set(TaskStatus::Todo),
@@ -584,6 +786,7 @@ impl TaskService {
                 next_due_date: Set(None), // Will be calculated when this task is completed
                 created_at: Set(Utc::now().into()),
                 updated_at: Set(Utc::now().into()),
+                assignee_team_id: Set(task.assignee_team_id),
             };
 
             let created_instance = next_task.insert(&self.db).await?;
@@ -626,14 +829,12 @@ impl TaskService {
             .ok_or("Context not found")?;
 
         // Verify user can create tasks in this project using inherited project_id
-        let creator_role = self
+        if !self
             .project_service
-            .get_user_project_role(context.project_id, creator_id)
-            .await?;
-
-        match creator_role {
-            Some(role) if role.can_manage_tasks() => {},
-            _ => return Err("You don't have permission to create tasks in this project".into()),
+            .can_user_perform(context.project_id, creator_id, ProjectAction::TaskCreate)
+            .await?
+        {
+            return Err("You don't have permission to create tasks in this project".into());
         }
 
         // Check for existing task linked to this context (1:1 constraint)
@@ -647,15 +848,10 @@ impl TaskService {
         }
 
         // Validate assignee if provided
-        if let Some(assignee_id) = input.assignee_id {
-            let assignee_role = self
-                .project_service
-                .get_user_project_role(context.project_id, assignee_id)
-                .await?;
-
-            if assignee_role.is_none() {
-                return Err("Assignee is not a member of this project".into());
-            }
+        if let Some(assignee_id) = input.assignee_id
+            && !self.project_service.is_project_member(context.project_id, assignee_id).await?
+        {
+            return Err("Assignee is not a member of this project".into());
         }
 
         // Create the task with context relationship
@@ -680,6 +876,7 @@ impl TaskService {
             next_due_date: Set(None),
             created_at: Set(now.into()),
             updated_at: Set(now.into()),
+            assignee_team_id: Set(None),
         };
 
         let task = new_task.insert(&self.db).await?;
@@ -717,15 +914,11 @@ impl TaskService {
             .ok_or("Task not found")?;
 
         // Check permissions
-        let user_role = self
-            .project_service
-            .get_user_project_role(task.project_id, user_id)
-            .await?;
-
-        let can_edit = match user_role {
-            Some(role) if role.can_manage_tasks() => true,
-            _ => task.creator_id == user_id,
-        };
+        let can_edit = task.creator_id == user_id
+            || self
+                .project_service
+                .can_user_perform(task.project_id, user_id, ProjectAction::TaskUpdate)
+                .await?;
 
         if !can_edit {
             return Err("Insufficient permissions to update task context".into());
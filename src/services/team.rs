@@ -0,0 +1,212 @@
+use chrono::Utc;
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter,
+    QueryOrder, QuerySelect, Set,
+};
+use uuid::Uuid;
+
+use crate::auth::PermissionService;
+use crate::entities::{prelude::*, team, team_member, user};
+
+/// Teams the user belongs to
+pub async fn user_team_ids<C: ConnectionTrait>(conn: &C, user_id: Uuid) -> Result<Vec<Uuid>, DbErr> {
+    TeamMember::find()
+        .select_only()
+        .column(team_member::Column::TeamId)
+        .filter(team_member::Column::UserId.eq(user_id))
+        .into_tuple()
+        .all(conn)
+        .await
+}
+
+/// Members of a team, whose project access follows the team's
+pub async fn team_member_ids<C: ConnectionTrait>(conn: &C, team_id: Uuid) -> Result<Vec<Uuid>, DbErr> {
+    TeamMember::find()
+        .select_only()
+        .column(team_member::Column::UserId)
+        .filter(team_member::Column::TeamId.eq(team_id))
+        .into_tuple()
+        .all(conn)
+        .await
+}
+
+#[derive(Clone)]
+pub struct TeamService {
+    db: DatabaseConnection,
+    permission_service: PermissionService,
+}
+
+impl TeamService {
+    pub fn new(db: DatabaseConnection, permission_service: PermissionService) -> Self {
+        Self { db, permission_service }
+    }
+
+    /// Create a team within an organization
+    pub async fn create_team(
+        &self,
+        organization_id: Uuid,
+        creator_id: Uuid,
+        name: &str,
+        description: Option<String>,
+    ) -> Result<team::Model, Box<dyn std::error::Error>> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Team name is required".into());
+        }
+        self.ensure_name_available(organization_id, name, None).await?;
+
+        let team = team::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            organization_id: Set(organization_id),
+            name: Set(name.to_string()),
+            description: Set(description),
+            created_by: Set(Some(creator_id)),
+            created_at: Set(Utc::now().into()),
+            updated_at: Set(Utc::now().into()),
+        }
+        .insert(&self.db)
+        .await?;
+
+        Ok(team)
+    }
+
+    pub async fn update_team(
+        &self,
+        team_id: Uuid,
+        name: Option<String>,
+        description: Option<Option<String>>,
+    ) -> Result<team::Model, Box<dyn std::error::Error>> {
+        let team = self.get_team(team_id).await?.ok_or("Team not found")?;
+        let organization_id = team.organization_id;
+
+        let mut team_active: team::ActiveModel = team.into();
+        if let Some(name) = name {
+            let name = name.trim().to_string();
+            if name.is_empty() {
+                return Err("Team name is required".into());
+            }
+            self.ensure_name_available(organization_id, &name, Some(team_id)).await?;
+            team_active.name = Set(name);
+        }
+        if let Some(description) = description {
+            team_active.description = Set(description);
+        }
+        team_active.updated_at = Set(Utc::now().into());
+
+        Ok(team_active.update(&self.db).await?)
+    }
+
+    /// Delete a team; its members lose the project access it granted and its queued
+    /// tasks fall back to unassigned
+    pub async fn delete_team(&self, team_id: Uuid) -> Result<(), Box<dyn std::error::Error>> {
+        let member_ids = team_member_ids(&self.db, team_id).await?;
+
+        let result = Team::delete_by_id(team_id).exec(&self.db).await?;
+        if result.rows_affected == 0 {
+            return Err("Team not found".into());
+        }
+
+        for member_id in member_ids {
+            self.permission_service.invalidate_user(member_id).await;
+        }
+
+        Ok(())
+    }
+
+    pub async fn get_team(&self, team_id: Uuid) -> Result<Option<team::Model>, Box<dyn std::error::Error>> {
+        Ok(Team::find_by_id(team_id).one(&self.db).await?)
+    }
+
+    pub async fn get_organization_teams(
+        &self,
+        organization_id: Uuid,
+    ) -> Result<Vec<team::Model>, Box<dyn std::error::Error>> {
+        let teams = Team::find()
+            .filter(team::Column::OrganizationId.eq(organization_id))
+            .order_by_asc(team::Column::Name)
+            .all(&self.db)
+            .await?;
+        Ok(teams)
+    }
+
+    pub async fn get_team_members(&self, team_id: Uuid) -> Result<Vec<user::Model>, Box<dyn std::error::Error>> {
+        let members = User::find()
+            .inner_join(TeamMember)
+            .filter(team_member::Column::TeamId.eq(team_id))
+            .order_by_asc(user::Column::Email)
+            .all(&self.db)
+            .await?;
+        Ok(members)
+    }
+
+    pub async fn is_team_member(&self, team_id: Uuid, user_id: Uuid) -> Result<bool, Box<dyn std::error::Error>> {
+        let member = TeamMember::find()
+            .filter(team_member::Column::TeamId.eq(team_id))
+            .filter(team_member::Column::UserId.eq(user_id))
+            .one(&self.db)
+            .await?;
+        Ok(member.is_some())
+    }
+
+    /// Add a user to a team. They immediately get the team's role on all its projects.
+    pub async fn add_team_member(
+        &self,
+        team_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<team_member::Model, Box<dyn std::error::Error>> {
+        let team = self.get_team(team_id).await?.ok_or("Team not found")?;
+
+        if !self.permission_service.user_in_organization(user_id, team.organization_id).await? {
+            return Err("User is not a member of this team's organization".into());
+        }
+        if self.is_team_member(team_id, user_id).await? {
+            return Err("User is already a member of this team".into());
+        }
+
+        let member = team_member::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            team_id: Set(team_id),
+            user_id: Set(user_id),
+            created_at: Set(Utc::now().into()),
+        }
+        .insert(&self.db)
+        .await?;
+
+        self.permission_service.invalidate_user(user_id).await;
+        Ok(member)
+    }
+
+    /// Remove a user from a team along with the project access it gave them
+    pub async fn remove_team_member(&self, team_id: Uuid, user_id: Uuid) -> Result<(), Box<dyn std::error::Error>> {
+        let result = TeamMember::delete_many()
+            .filter(team_member::Column::TeamId.eq(team_id))
+            .filter(team_member::Column::UserId.eq(user_id))
+            .exec(&self.db)
+            .await?;
+        if result.rows_affected == 0 {
+            return Err("User is not a member of this team".into());
+        }
+
+        self.permission_service.invalidate_user(user_id).await;
+        Ok(())
+    }
+
+    async fn ensure_name_available(
+        &self,
+        organization_id: Uuid,
+        name: &str,
+        exclude_team_id: Option<Uuid>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut query = Team::find()
+            .filter(team::Column::OrganizationId.eq(organization_id))
+            .filter(team::Column::Name.eq(name));
+        if let Some(team_id) = exclude_team_id {
+            query = query.filter(team::Column::Id.ne(team_id));
+        }
+
+        if query.one(&self.db).await?.is_some() {
+            return Err(format!("Team '{}' already exists", name).into());
+        }
+        Ok(())
+    }
+}
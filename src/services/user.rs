@@ -1,20 +1,87 @@
+use std::collections::HashSet;
+
 use bcrypt::{hash, verify, DEFAULT_COST};
 use chrono::{Duration, Utc};
-use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set};
+use sea_orm::{
+    sea_query::Expr, ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, DbBackend, EntityTrait, QueryFilter, QueryOrder,
+    QuerySelect, Set, Statement, TransactionTrait,
+};
 use uuid::Uuid;
 
-use crate::entities::{prelude::*, user};
+use crate::entities::{organization, organization_member, password_history, prelude::*, user};
 use crate::auth::JwtService;
+use crate::graphql::types::{TaskStatus, UserStatus};
+use crate::services::activity::{ActivityService, EntityType, NewActivity};
+use crate::services::password_policy::{PasswordPolicy, PasswordPolicyError};
+use crate::services::email::EmailService;
+use crate::services::email_outbox::queue_email;
+use crate::services::email_template::email_style_for_user;
+use crate::services::organization::unscoped;
+
+/// Progressive delay and temporary lockout applied to failed password logins
+#[derive(Debug, Clone, Copy)]
+pub struct LoginLockoutPolicy {
+    pub max_failed_attempts: i32,
+    pub lockout_minutes: i64,
+    pub base_delay_ms: u64,
+    pub max_delay_ms: u64,
+}
+
+impl Default for LoginLockoutPolicy {
+    fn default() -> Self {
+        Self {
+            max_failed_attempts: 5,
+            lockout_minutes: 15,
+            base_delay_ms: 250,
+            max_delay_ms: 4000,
+        }
+    }
+}
+
+impl LoginLockoutPolicy {
+    /// Delay doubles with every consecutive failure, capped at `max_delay_ms`
+    pub fn delay_for(&self, failed_attempts: i32) -> std::time::Duration {
+        let exponent = (failed_attempts.max(1) - 1).min(16) as u32;
+        let delay = self.base_delay_ms.saturating_mul(1u64 << exponent);
+        std::time::Duration::from_millis(delay.min(self.max_delay_ms))
+    }
+}
+
+/// Magic links are short-lived compared to the 1 hour password reset window
+pub const MAGIC_LINK_TTL_MINUTES: i64 = 15;
+
+/// What `offboard_user` changed, for reporting back to the admin
+#[derive(Debug, Clone)]
+pub struct OffboardingSummary {
+    pub user: user::Model,
+    pub reassigned_task_ids: Vec<Uuid>,
+    /// Open tasks left unassigned, because there was no successor or the successor
+    /// has no access to the task's project
+    pub unassigned_task_ids: Vec<Uuid>,
+    pub transferred_project_ids: Vec<Uuid>,
+    pub removed_membership_count: u64,
+}
 
 #[derive(Clone)]
 pub struct UserService {
     db: DatabaseConnection,
     jwt_service: JwtService,
+    email_service: EmailService,
+    activity_service: ActivityService,
+    lockout_policy: LoginLockoutPolicy,
+    password_policy: PasswordPolicy,
 }
 
 impl UserService {
-    pub fn new(db: DatabaseConnection, jwt_service: JwtService) -> Self {
-        Self { db, jwt_service }
+    pub fn new(
+        db: DatabaseConnection,
+        jwt_service: JwtService,
+        email_service: EmailService,
+        activity_service: ActivityService,
+        lockout_policy: LoginLockoutPolicy,
+        password_policy: PasswordPolicy,
+    ) -> Self {
+        Self { db, jwt_service, email_service, activity_service, lockout_policy, password_policy }
     }
 
     pub fn get_db(&self) -> &DatabaseConnection {
@@ -28,8 +95,8 @@ impl UserService {
         last_name: Option<String>,
         invitation_token: &str,
     ) -> Result<(user::Model, String, String), Box<dyn std::error::Error>> {
-        use crate::entities::{prelude::*, invitation};
-        use sea_orm::TransactionTrait;
+        use crate::entities::{prelude::*, invitation, project_invitation, project_member};
+        use crate::graphql::types::ProjectInvitationStatus;
         
         // Use transaction to ensure atomicity - OPTIMIZED
         let tx = self.db.begin().await?;
@@ -59,6 +126,12 @@ impl UserService {
             return Err("Invitation has expired".into());
         }
 
+        // Enforce password policy before creating the account
+        let mut user_inputs = vec![invitation.email.as_str()];
+        user_inputs.extend(first_name.as_deref());
+        user_inputs.extend(last_name.as_deref());
+        self.password_policy.validate(password, &user_inputs).await?;
+
         // Hash password
         let password_hash = hash(password, DEFAULT_COST)?;
 
@@ -78,11 +151,62 @@ impl UserService {
             refresh_token_expires_at: Set(None),
             invitation_token: Set(Some(invitation_token.to_string())),
             role_id: Set(invitation.role_id), // Assign role from invitation
+            role_valid_from: Set(None),
+            role_valid_until: Set(None),
             created_at: Set(Utc::now().into()),
             updated_at: Set(Utc::now().into()),
+            failed_login_attempts: Set(0),
+            last_failed_login_at: Set(None),
+            locked_until: Set(None),
+            status: Set(UserStatus::Active),
+            status_changed_at: Set(None),
+            magic_link_token: Set(None),
+            magic_link_expires_at: Set(None),
+            password_changed_at: Set(Utc::now().into()),
+            current_organization_id: Set(invitation.organization_id),
+            locale: Set(None),
         };
 
         let user = new_user.insert(&tx).await?;
+        Self::record_password_history(&tx, user.id, &user.password_hash).await?;
+
+        // Invitations sent from within an organization make the new user a member of it
+        if let Some(organization_id) = invitation.organization_id {
+            organization_member::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                organization_id: Set(organization_id),
+                user_id: Set(user.id),
+                created_at: Set(Utc::now().into()),
+            }
+            .insert(&tx)
+            .await?;
+        }
+
+        // Project invitations sent along with the sign-up become memberships right away
+        let project_invitations = ProjectInvitation::find()
+            .filter(project_invitation::Column::InvitationId.eq(invitation.id))
+            .filter(project_invitation::Column::Status.eq(ProjectInvitationStatus::Pending))
+            .all(&tx)
+            .await?;
+        for project_invitation in project_invitations {
+            project_member::ActiveModel {
+                id: Set(Uuid::new_v4()),
+                project_id: Set(project_invitation.project_id),
+                user_id: Set(user.id),
+                role: Set(project_invitation.role.clone()),
+                joined_at: Set(Utc::now().into()),
+                project_role_id: Set(Some(project_invitation.project_role_id)),
+            }
+            .insert(&tx)
+            .await?;
+
+            let mut project_invitation_active: project_invitation::ActiveModel = project_invitation.into();
+            project_invitation_active.status = Set(ProjectInvitationStatus::Accepted);
+            project_invitation_active.user_id = Set(Some(user.id));
+            project_invitation_active.responded_at = Set(Some(Utc::now().into()));
+            project_invitation_active.updated_at = Set(Utc::now().into());
+            project_invitation_active.update(&tx).await?;
+        }
 
         // Mark invitation as used - within same transaction
         let mut invitation_active: invitation::ActiveModel = invitation.clone().into();
@@ -92,7 +216,7 @@ impl UserService {
         invitation_active.update(&tx).await?;
 
         // Generate tokens for immediate login
-        let access_token = self.jwt_service.generate_access_token(user.id, &invitation.email)?;
+        let access_token = self.jwt_service.generate_access_token(user.id, &invitation.email, user.current_organization_id)?;
         let refresh_token = self.jwt_service.generate_refresh_token();
         let refresh_expires = self.jwt_service.get_refresh_token_expiration();
 
@@ -132,20 +256,38 @@ impl UserService {
             .await?
             .ok_or("Invalid credentials")?;
 
+        // Reject while the account is temporarily locked
+        if let Some(locked_until) = user.locked_until
+            && locked_until > Utc::now()
+        {
+            return Err("Account is temporarily locked due to too many failed login attempts. Please try again later".into());
+        }
+
         // Verify password
         if !verify(password, &user.password_hash)? {
+            self.record_failed_login(user.id).await?;
             return Err("Invalid credentials".into());
         }
 
+        // Only active accounts may sign in
+        Self::ensure_active(&user)?;
+
+        // Expired passwords must be replaced through the reset flow
+        if self.password_policy.is_expired(user.password_changed_at.with_timezone(&Utc)) {
+            return Err(Box::new(PasswordPolicyError::expired()));
+        }
+
         // Generate tokens
-        let access_token = self.jwt_service.generate_access_token(user.id, &user.email)?;
+        let access_token = self.jwt_service.generate_access_token(user.id, &user.email, user.current_organization_id)?;
         let refresh_token = self.jwt_service.generate_refresh_token();
         let refresh_expires = self.jwt_service.get_refresh_token_expiration();
 
-        // Store refresh token in database
+        // Store refresh token in database and clear any failed attempts
         let mut user_active: user::ActiveModel = user.clone().into();
         user_active.refresh_token = Set(Some(refresh_token.clone()));
         user_active.refresh_token_expires_at = Set(Some(refresh_expires.into()));
+        user_active.failed_login_attempts = Set(0);
+        user_active.locked_until = Set(None);
         user_active.updated_at = Set(Utc::now().into());
 
         let updated_user = user_active.update(&self.db).await?;
@@ -153,6 +295,514 @@ impl UserService {
         Ok((updated_user, access_token, refresh_token))
     }
 
+    fn ensure_active(user: &user::Model) -> Result<(), Box<dyn std::error::Error>> {
+        match user.status {
+            UserStatus::Active => Ok(()),
+            UserStatus::Suspended => Err("Account is suspended".into()),
+            UserStatus::Deactivated => Err("Account has been deactivated".into()),
+        }
+    }
+
+    /// Whether the user exists and is allowed to use the API
+    pub async fn is_user_active(&self, user_id: Uuid) -> Result<bool, Box<dyn std::error::Error>> {
+        let status = User::find_by_id(user_id)
+            .select_only()
+            .column(user::Column::Status)
+            .into_tuple::<UserStatus>()
+            .one(&self.db)
+            .await?;
+
+        Ok(status == Some(UserStatus::Active))
+    }
+
+    /// Suspend or reactivate an account; leaving `active` revokes the refresh token
+    pub async fn update_user_status(
+        &self,
+        user_id: Uuid,
+        status: UserStatus,
+        admin_id: Uuid,
+        reason: Option<String>,
+    ) -> Result<user::Model, Box<dyn std::error::Error>> {
+        if user_id == admin_id {
+            return Err("You cannot change the status of your own account".into());
+        }
+
+        let user = User::find_by_id(user_id)
+            .one(&self.db)
+            .await?
+            .ok_or("User not found")?;
+
+        let old_status = user.status;
+        if old_status == status {
+            return Ok(user);
+        }
+
+        let mut user_active: user::ActiveModel = user.into();
+        user_active.status = Set(status);
+        user_active.status_changed_at = Set(Some(Utc::now().into()));
+        if status != UserStatus::Active {
+            user_active.refresh_token = Set(None);
+            user_active.refresh_token_expires_at = Set(None);
+        }
+        user_active.updated_at = Set(Utc::now().into());
+
+        let updated_user = user_active.update(&self.db).await?;
+
+        self.activity_service
+            .log_activity(
+                EntityType::User,
+                user_id,
+                admin_id,
+                "status_changed",
+                Some(format!("Account status changed from {} to {}", old_status.as_str(), status.as_str())),
+                Some(serde_json::json!({ "reason": reason })),
+                Some(serde_json::json!({
+                    "status": { "old": old_status.as_str(), "new": status.as_str() }
+                })),
+            )
+            .await?;
+
+        Ok(updated_user)
+    }
+
+    /// Deactivate a user and hand their open tasks and owned projects to someone else.
+    /// Everything happens in one transaction, including the activity log entries.
+    pub async fn offboard_user(
+        &self,
+        user_id: Uuid,
+        reassign_to: Option<Uuid>,
+        admin_id: Uuid,
+        reason: Option<String>,
+    ) -> Result<OffboardingSummary, Box<dyn std::error::Error>> {
+        use crate::entities::{project, project_member, project_team, task, team_member};
+        use crate::services::{user_team_ids, ProjectRole, ProjectService};
+
+        if user_id == admin_id {
+            return Err("You cannot offboard your own account".into());
+        }
+        if reassign_to == Some(user_id) {
+            return Err("Cannot reassign work to the user being offboarded".into());
+        }
+
+        let tx = self.db.begin().await?;
+
+        let user = User::find_by_id(user_id)
+            .one(&tx)
+            .await?
+            .ok_or("User not found")?;
+
+        if user.status == UserStatus::Deactivated {
+            return Err("User is already deactivated".into());
+        }
+
+        if let Some(successor_id) = reassign_to {
+            let successor = User::find_by_id(successor_id)
+                .one(&tx)
+                .await?
+                .ok_or("Reassignment target user not found")?;
+            if successor.status != UserStatus::Active {
+                return Err("Reassignment target user is not active".into());
+            }
+        }
+
+        let owned_projects = Project::find()
+            .filter(project::Column::OwnerId.eq(user_id))
+            .all(&tx)
+            .await?;
+
+        if !owned_projects.is_empty() && reassign_to.is_none() {
+            return Err("User owns projects; a reassignment target is required".into());
+        }
+
+        // Work only passes to someone inside the organizations it belongs to
+        if let Some(successor_id) = reassign_to {
+            let task_project_ids: Vec<Uuid> = Task::find()
+                .select_only()
+                .column(task::Column::ProjectId)
+                .filter(task::Column::AssigneeId.eq(user_id))
+                .filter(task::Column::Status.is_in([TaskStatus::Todo, TaskStatus::InProgress]))
+                .into_tuple()
+                .all(&tx)
+                .await?;
+            let task_organization_ids: Vec<Uuid> = Project::find()
+                .select_only()
+                .column(project::Column::OrganizationId)
+                .filter(project::Column::Id.is_in(task_project_ids))
+                .into_tuple()
+                .all(&tx)
+                .await?;
+            let successor_organization_ids: Vec<Uuid> = OrganizationMember::find()
+                .select_only()
+                .column(organization_member::Column::OrganizationId)
+                .filter(organization_member::Column::UserId.eq(successor_id))
+                .into_tuple()
+                .all(&tx)
+                .await?;
+
+            let outside_successor_organizations = owned_projects
+                .iter()
+                .map(|owned_project| owned_project.organization_id)
+                .chain(task_organization_ids)
+                .any(|organization_id| !successor_organization_ids.contains(&organization_id));
+            if outside_successor_organizations {
+                return Err("Reassignment target is not a member of every organization the user's work belongs to".into());
+            }
+        }
+
+        // 1. Deactivate and revoke all sessions
+        let old_status = user.status;
+        let mut user_active: user::ActiveModel = user.into();
+        user_active.status = Set(UserStatus::Deactivated);
+        user_active.status_changed_at = Set(Some(Utc::now().into()));
+        user_active.refresh_token = Set(None);
+        user_active.refresh_token_expires_at = Set(None);
+        user_active.updated_at = Set(Utc::now().into());
+        let deactivated_user = user_active.update(&tx).await?;
+
+        self.activity_service
+            .log_activity_with(
+                &tx,
+                NewActivity {
+                    entity_type: EntityType::User,
+                    entity_id: user_id,
+                    actor_id: admin_id,
+                    action_type: "deactivated".to_string(),
+                    description: Some("Account deactivated and sessions revoked".to_string()),
+                    metadata: Some(serde_json::json!({ "reason": reason })),
+                    changes: Some(serde_json::json!({
+                        "status": { "old": old_status.as_str(), "new": UserStatus::Deactivated.as_str() }
+                    })),
+                },
+            )
+            .await?;
+
+        // 2. Reassign open tasks in projects the successor can reach, directly, through a
+        // team or by taking the project over; the rest are unassigned rather than widening
+        // the successor's access
+        let successor_project_ids: HashSet<Uuid> = match reassign_to {
+            Some(successor_id) => {
+                let mut project_ids: HashSet<Uuid> = ProjectMember::find()
+                    .select_only()
+                    .column(project_member::Column::ProjectId)
+                    .filter(project_member::Column::UserId.eq(successor_id))
+                    .into_tuple::<Uuid>()
+                    .all(&tx)
+                    .await?
+                    .into_iter()
+                    .collect();
+                let team_ids = user_team_ids(&tx, successor_id).await?;
+                if !team_ids.is_empty() {
+                    project_ids.extend(
+                        ProjectTeam::find()
+                            .select_only()
+                            .column(project_team::Column::ProjectId)
+                            .filter(project_team::Column::TeamId.is_in(team_ids))
+                            .into_tuple::<Uuid>()
+                            .all(&tx)
+                            .await?,
+                    );
+                }
+                project_ids.extend(owned_projects.iter().map(|owned_project| owned_project.id));
+                project_ids
+            }
+            None => HashSet::new(),
+        };
+
+        let open_tasks = Task::find()
+            .filter(task::Column::AssigneeId.eq(user_id))
+            .filter(task::Column::Status.is_in([TaskStatus::Todo, TaskStatus::InProgress]))
+            .all(&tx)
+            .await?;
+
+        let mut reassigned_task_ids = Vec::with_capacity(open_tasks.len());
+        let mut unassigned_task_ids = Vec::new();
+        for open_task in open_tasks {
+            let task_id = open_task.id;
+            let new_assignee = reassign_to.filter(|_| successor_project_ids.contains(&open_task.project_id));
+            let mut task_active: task::ActiveModel = open_task.into();
+            task_active.assignee_id = Set(new_assignee);
+            task_active.updated_at = Set(Utc::now().into());
+            task_active.update(&tx).await?;
+
+            self.activity_service
+                .log_activity_with(
+                    &tx,
+                    NewActivity {
+                        entity_type: EntityType::Task,
+                        entity_id: task_id,
+                        actor_id: admin_id,
+                        action_type: "assignment_changed".to_string(),
+                        description: Some(match new_assignee {
+                            Some(_) => "Task reassigned during user offboarding".to_string(),
+                            None => "Task unassigned during user offboarding".to_string(),
+                        }),
+                        metadata: Some(serde_json::json!({ "offboarded_user_id": user_id })),
+                        changes: Some(serde_json::json!({
+                            "assignee_id": { "old": user_id, "new": new_assignee }
+                        })),
+                    },
+                )
+                .await?;
+
+            match new_assignee {
+                Some(_) => reassigned_task_ids.push(task_id),
+                None => unassigned_task_ids.push(task_id),
+            }
+        }
+
+        // 3. Transfer project ownership
+        let mut transferred_project_ids = Vec::with_capacity(owned_projects.len());
+        if let Some(successor_id) = reassign_to {
+            for owned_project in owned_projects {
+                let project_id = owned_project.id;
+                let mut project_active: project::ActiveModel = owned_project.into();
+                project_active.owner_id = Set(successor_id);
+                project_active.updated_at = Set(Utc::now().into());
+                project_active.update(&tx).await?;
+
+                // Successor becomes an owner member of the project
+                let owner_role_id = ProjectService::system_role_id(&tx, &ProjectRole::Owner).await?;
+                let existing_membership = ProjectMember::find()
+                    .filter(project_member::Column::ProjectId.eq(project_id))
+                    .filter(project_member::Column::UserId.eq(successor_id))
+                    .one(&tx)
+                    .await?;
+
+                match existing_membership {
+                    Some(membership) => {
+                        let mut membership_active: project_member::ActiveModel = membership.into();
+                        membership_active.role = Set(ProjectRole::Owner.as_str().to_string());
+                        membership_active.project_role_id = Set(owner_role_id);
+                        membership_active.update(&tx).await?;
+                    }
+                    None => {
+                        let membership = project_member::ActiveModel {
+                            id: Set(Uuid::new_v4()),
+                            project_id: Set(project_id),
+                            user_id: Set(successor_id),
+                            role: Set(ProjectRole::Owner.as_str().to_string()),
+                            joined_at: Set(Utc::now().into()),
+                            project_role_id: Set(owner_role_id),
+                        };
+                        membership.insert(&tx).await?;
+                    }
+                }
+
+                self.activity_service
+                    .log_activity_with(
+                        &tx,
+                        NewActivity {
+                            entity_type: EntityType::Project,
+                            entity_id: project_id,
+                            actor_id: admin_id,
+                            action_type: "ownership_transferred".to_string(),
+                            description: Some("Project ownership transferred during user offboarding".to_string()),
+                            metadata: Some(serde_json::json!({ "offboarded_user_id": user_id })),
+                            changes: Some(serde_json::json!({
+                                "owner_id": { "old": user_id, "new": successor_id }
+                            })),
+                        },
+                    )
+                    .await?;
+
+                transferred_project_ids.push(project_id);
+            }
+        }
+
+        // 4. Remove remaining project and team memberships
+        let removed = ProjectMember::delete_many()
+            .filter(project_member::Column::UserId.eq(user_id))
+            .exec(&tx)
+            .await?;
+        TeamMember::delete_many()
+            .filter(team_member::Column::UserId.eq(user_id))
+            .exec(&tx)
+            .await?;
+
+        self.activity_service
+            .log_activity_with(
+                &tx,
+                NewActivity {
+                    entity_type: EntityType::User,
+                    entity_id: user_id,
+                    actor_id: admin_id,
+                    action_type: "offboarded".to_string(),
+                    description: Some(format!(
+                        "Offboarding completed: {} tasks reassigned, {} unassigned, {} projects transferred, {} memberships removed",
+                        reassigned_task_ids.len(),
+                        unassigned_task_ids.len(),
+                        transferred_project_ids.len(),
+                        removed.rows_affected
+                    )),
+                    metadata: Some(serde_json::json!({
+                        "reassigned_to": reassign_to,
+                        "reassigned_task_ids": reassigned_task_ids,
+                        "unassigned_task_ids": unassigned_task_ids,
+                        "transferred_project_ids": transferred_project_ids,
+                        "removed_membership_count": removed.rows_affected
+                    })),
+                    changes: None,
+                },
+            )
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(OffboardingSummary {
+            user: deactivated_user,
+            reassigned_task_ids,
+            unassigned_task_ids,
+            transferred_project_ids,
+            removed_membership_count: removed.rows_affected,
+        })
+    }
+
+    /// Issue a short-lived token that lets an admin act as `user_id`
+    pub async fn impersonate_user(
+        &self,
+        user_id: Uuid,
+        actor_id: Uuid,
+        reason: Option<String>,
+    ) -> Result<(user::Model, String, chrono::DateTime<Utc>), Box<dyn std::error::Error>> {
+        if user_id == actor_id {
+            return Err("You cannot impersonate yourself".into());
+        }
+
+        let user = User::find_by_id(user_id)
+            .one(&self.db)
+            .await?
+            .ok_or("User not found")?;
+
+        Self::ensure_active(&user)?;
+
+        let (access_token, expires_at) = self
+            .jwt_service
+            .generate_impersonation_token(user.id, &user.email, user.current_organization_id, actor_id)?;
+
+        self.activity_service
+            .log_activity(
+                EntityType::User,
+                user_id,
+                actor_id,
+                "impersonation_started",
+                Some("Administrator started impersonating this user".to_string()),
+                Some(serde_json::json!({
+                    "impersonated_user_id": user_id,
+                    "actor_id": actor_id,
+                    "reason": reason,
+                    "expires_at": expires_at
+                })),
+                None,
+            )
+            .await?;
+
+        Ok((user, access_token, expires_at))
+    }
+
+    /// Count a failed password attempt, lock the account after too many and slow the caller down
+    async fn record_failed_login(
+        &self,
+        user_id: Uuid,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let now = Utc::now();
+        let locked_until = now + Duration::minutes(self.lockout_policy.lockout_minutes);
+
+        // Count in the database so concurrent failures can't overwrite each other's
+        // increments; reaching the limit locks the account and starts counting afresh
+        // once the lockout expires
+        let row = self
+            .db
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                r#"UPDATE "user"
+                   SET failed_login_attempts = CASE WHEN failed_login_attempts + 1 >= $2 THEN 0 ELSE failed_login_attempts + 1 END,
+                       locked_until = CASE WHEN failed_login_attempts + 1 >= $2 THEN $3 ELSE locked_until END,
+                       last_failed_login_at = $4,
+                       updated_at = $4
+                   WHERE id = $1
+                   RETURNING failed_login_attempts"#,
+                [
+                    user_id.into(),
+                    self.lockout_policy.max_failed_attempts.into(),
+                    locked_until.into(),
+                    now.into(),
+                ],
+            ))
+            .await?
+            .ok_or("User not found")?;
+        let remaining_attempts: i32 = row.try_get("", "failed_login_attempts")?;
+        let should_lock = remaining_attempts == 0;
+        let failed_attempts = if should_lock { self.lockout_policy.max_failed_attempts } else { remaining_attempts };
+
+        self.activity_service
+            .log_activity(
+                EntityType::User,
+                user_id,
+                user_id,
+                "login_failed",
+                Some(format!("Failed login attempt {} of {}", failed_attempts, self.lockout_policy.max_failed_attempts)),
+                Some(serde_json::json!({ "failed_attempts": failed_attempts })),
+                None,
+            )
+            .await?;
+
+        if should_lock {
+            self.activity_service
+                .log_activity(
+                    EntityType::User,
+                    user_id,
+                    user_id,
+                    "account_locked",
+                    Some(format!("Account locked for {} minutes after {} failed login attempts", self.lockout_policy.lockout_minutes, failed_attempts)),
+                    Some(serde_json::json!({
+                        "failed_attempts": failed_attempts,
+                        "lockout_minutes": self.lockout_policy.lockout_minutes
+                    })),
+                    None,
+                )
+                .await?;
+        }
+
+        tokio::time::sleep(self.lockout_policy.delay_for(failed_attempts)).await;
+
+        Ok(())
+    }
+
+    /// Clear a lockout before it expires (admin action)
+    pub async fn unlock_user(
+        &self,
+        user_id: Uuid,
+        admin_id: Uuid,
+    ) -> Result<user::Model, Box<dyn std::error::Error>> {
+        let user = User::find_by_id(user_id)
+            .one(&self.db)
+            .await?
+            .ok_or("User not found")?;
+
+        let was_locked = user.locked_until.is_some_and(|locked_until| locked_until > Utc::now());
+
+        let mut user_active: user::ActiveModel = user.into();
+        user_active.failed_login_attempts = Set(0);
+        user_active.locked_until = Set(None);
+        user_active.updated_at = Set(Utc::now().into());
+
+        let updated_user = user_active.update(&self.db).await?;
+
+        self.activity_service
+            .log_activity(
+                EntityType::User,
+                user_id,
+                admin_id,
+                "account_unlocked",
+                Some("Account unlocked by administrator".to_string()),
+                Some(serde_json::json!({ "was_locked": was_locked })),
+                None,
+            )
+            .await?;
+
+        Ok(updated_user)
+    }
+
     pub async fn verify_email(
         &self,
         token: &str,
@@ -201,8 +851,11 @@ impl UserService {
             return Err("Invalid refresh token".into());
         }
 
+        // Suspended or deactivated accounts can't renew their session
+        Self::ensure_active(&user)?;
+
         // Generate new tokens
-        let new_access_token = self.jwt_service.generate_access_token(user.id, &user.email)?;
+        let new_access_token = self.jwt_service.generate_access_token(user.id, &user.email, user.current_organization_id)?;
         let new_refresh_token = self.jwt_service.generate_refresh_token();
         let new_refresh_expires = self.jwt_service.get_refresh_token_expiration();
 
@@ -217,6 +870,73 @@ impl UserService {
         Ok((updated_user, new_access_token, new_refresh_token))
     }
 
+    /// Make `organization_id` the user's current organization and issue a fresh session
+    /// scoped to it. Passing `None` leaves the user without a current organization.
+    pub async fn switch_organization(
+        &self,
+        user_id: Uuid,
+        organization_id: Option<Uuid>,
+    ) -> Result<(user::Model, String, String), Box<dyn std::error::Error>> {
+        let user = User::find_by_id(user_id)
+            .one(&self.db)
+            .await?
+            .ok_or("User not found")?;
+
+        Self::ensure_active(&user)?;
+
+        if let Some(organization_id) = organization_id {
+            let membership = OrganizationMember::find()
+                .inner_join(Organization)
+                .filter(organization_member::Column::UserId.eq(user_id))
+                .filter(organization_member::Column::OrganizationId.eq(organization_id))
+                .filter(organization::Column::IsActive.eq(true))
+                .one(&self.db)
+                .await?;
+            if membership.is_none() {
+                return Err("You are not a member of this organization".into());
+            }
+        }
+
+        let access_token = self.jwt_service.generate_access_token(user.id, &user.email, organization_id)?;
+        let refresh_token = self.jwt_service.generate_refresh_token();
+        let refresh_expires = self.jwt_service.get_refresh_token_expiration();
+
+        let mut user_active: user::ActiveModel = user.into();
+        user_active.current_organization_id = Set(organization_id);
+        user_active.refresh_token = Set(Some(refresh_token.clone()));
+        user_active.refresh_token_expires_at = Set(Some(refresh_expires.into()));
+        user_active.updated_at = Set(Utc::now().into());
+
+        let updated_user = user_active.update(&self.db).await?;
+
+        Ok((updated_user, access_token, refresh_token))
+    }
+
+    /// Set the language the user's emails are written in. `None` falls back to their
+    /// organization's default; unsupported locales are rejected.
+    pub async fn set_locale(
+        &self,
+        user_id: Uuid,
+        locale: Option<String>,
+    ) -> Result<user::Model, Box<dyn std::error::Error>> {
+        let locales = self.email_service.locales();
+        if let Some(locale) = &locale
+            && !locales.contains(locale)
+        {
+            return Err(format!("Unsupported locale, expected one of: {}", locales.join(", ")).into());
+        }
+
+        let user = User::find_by_id(user_id)
+            .one(&self.db)
+            .await?
+            .ok_or("User not found")?;
+
+        let mut user_active: user::ActiveModel = user.into();
+        user_active.locale = Set(locale);
+        user_active.updated_at = Set(Utc::now().into());
+        Ok(user_active.update(&self.db).await?)
+    }
+
     pub async fn revoke_refresh_token(
         &self,
         user_id: Uuid,
@@ -235,9 +955,11 @@ impl UserService {
         Ok(())
     }
 
+    /// Issue a password reset token and queue the email carrying it
     pub async fn request_password_reset(
         &self,
         email: &str,
+        base_url: &str,
     ) -> Result<user::Model, Box<dyn std::error::Error>> {
         // Find user by email
         let user = User::find()
@@ -252,14 +974,131 @@ impl UserService {
 
         // Update user with reset token
         let mut user_active: user::ActiveModel = user.clone().into();
-        user_active.password_reset_token = Set(Some(reset_token));
+        user_active.password_reset_token = Set(Some(reset_token.clone()));
         user_active.password_reset_expires_at = Set(Some(reset_expires_at.into()));
         user_active.updated_at = Set(Utc::now().into());
 
-        let updated_user = user_active.update(&self.db).await?;
+        let tx = self.db.begin().await?;
+        let updated_user = user_active.update(&tx).await?;
+        let style = email_style_for_user(&tx, &updated_user).await?;
+        queue_email(&tx, self.email_service.password_reset_email(&updated_user.email, &reset_token, base_url, &style)?).await?;
+        tx.commit().await?;
+
         Ok(updated_user)
     }
 
+    /// Issue a magic-link token if the user's role allows passwordless login.
+    /// Returns `None` (rather than an error) when no link should be sent, so callers
+    /// can respond identically either way. The link itself goes out through the email outbox.
+    pub async fn request_magic_link(
+        &self,
+        email: &str,
+        base_url: &str,
+    ) -> Result<Option<user::Model>, Box<dyn std::error::Error>> {
+        let user = match User::find()
+            .filter(user::Column::Email.eq(email))
+            .one(&self.db)
+            .await?
+        {
+            Some(user) => user,
+            None => return Ok(None),
+        };
+
+        if user.status != UserStatus::Active {
+            return Ok(None);
+        }
+
+        // Requested before signing in, so no organization is selected to see the role through
+        let magic_link_enabled = match user.role_id {
+            Some(role_id) => unscoped(crate::entities::role::Entity::find_by_id(role_id).one(&self.db))
+                .await?
+                .map(|role| role.is_active && role.magic_link_enabled)
+                .unwrap_or(false),
+            None => false,
+        };
+
+        if !magic_link_enabled {
+            return Ok(None);
+        }
+
+        // Generate magic link token
+        let magic_link_token = Uuid::new_v4().to_string();
+        let magic_link_expires_at = Utc::now() + Duration::minutes(MAGIC_LINK_TTL_MINUTES);
+
+        let mut user_active: user::ActiveModel = user.into();
+        user_active.magic_link_token = Set(Some(magic_link_token.clone()));
+        user_active.magic_link_expires_at = Set(Some(magic_link_expires_at.into()));
+        user_active.updated_at = Set(Utc::now().into());
+
+        let tx = self.db.begin().await?;
+        let updated_user = user_active.update(&tx).await?;
+        let style = email_style_for_user(&tx, &updated_user).await?;
+        let message = self.email_service.magic_link_email(
+            &updated_user.email,
+            &magic_link_token,
+            base_url,
+            MAGIC_LINK_TTL_MINUTES,
+            &style,
+        )?;
+        queue_email(&tx, message).await?;
+        tx.commit().await?;
+
+        Ok(Some(updated_user))
+    }
+
+    /// Exchange a magic-link token for a session; the token is consumed on first use
+    pub async fn authenticate_with_magic_link(
+        &self,
+        token: &str,
+    ) -> Result<(user::Model, String, String), Box<dyn std::error::Error>> {
+        // Consume the token in the same statement that checks it, so a link can't be
+        // redeemed twice by concurrent requests
+        let now = Utc::now();
+        let user = User::update_many()
+            .col_expr(user::Column::MagicLinkToken, Expr::value(Option::<String>::None))
+            .col_expr(
+                user::Column::MagicLinkExpiresAt,
+                Expr::value(Option::<chrono::DateTime<chrono::FixedOffset>>::None),
+            )
+            .col_expr(user::Column::UpdatedAt, Expr::value(chrono::DateTime::<chrono::FixedOffset>::from(now)))
+            .filter(user::Column::MagicLinkToken.eq(token))
+            .filter(user::Column::MagicLinkExpiresAt.gt(now))
+            .exec_with_returning(&self.db)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or("Invalid or expired sign-in link")?;
+
+        Self::ensure_active(&user)?;
+
+        let access_token = self.jwt_service.generate_access_token(user.id, &user.email, user.current_organization_id)?;
+        let refresh_token = self.jwt_service.generate_refresh_token();
+        let refresh_expires = self.jwt_service.get_refresh_token_expiration();
+
+        let mut user_active: user::ActiveModel = user.into();
+        user_active.refresh_token = Set(Some(refresh_token.clone()));
+        user_active.refresh_token_expires_at = Set(Some(refresh_expires.into()));
+        // Proving control of the mailbox also clears a password lockout
+        user_active.failed_login_attempts = Set(0);
+        user_active.locked_until = Set(None);
+
+        let updated_user = user_active.update(&self.db).await?;
+
+        self.activity_service
+            .log_activity(
+                EntityType::User,
+                updated_user.id,
+                updated_user.id,
+                "magic_link_login",
+                Some("Signed in with a magic link".to_string()),
+                None,
+                None,
+            )
+            .await?;
+
+        Ok((updated_user, access_token, refresh_token))
+    }
+
     pub async fn reset_password(
         &self,
         token: &str,
@@ -281,12 +1120,15 @@ impl UserService {
             return Err("Invalid reset token".into());
         }
 
+        self.ensure_password_allowed(&user, new_password).await?;
+
         // Hash new password
         let password_hash = hash(new_password, DEFAULT_COST)?;
 
         // Update password and clear reset token
         let mut user_active: user::ActiveModel = user.clone().into();
-        user_active.password_hash = Set(password_hash);
+        user_active.password_hash = Set(password_hash.clone());
+        user_active.password_changed_at = Set(Utc::now().into());
         user_active.password_reset_token = Set(None);
         user_active.password_reset_expires_at = Set(None);
         user_active.updated_at = Set(Utc::now().into());
@@ -295,7 +1137,11 @@ impl UserService {
         user_active.refresh_token = Set(None);
         user_active.refresh_token_expires_at = Set(None);
 
-        let updated_user = user_active.update(&self.db).await?;
+        // The new password and its history entry land together or not at all
+        let tx = self.db.begin().await?;
+        let updated_user = user_active.update(&tx).await?;
+        Self::record_password_history(&tx, updated_user.id, &password_hash).await?;
+        tx.commit().await?;
         Ok(updated_user)
     }
 
@@ -324,22 +1170,83 @@ impl UserService {
             return Err("Current password is incorrect".into());
         }
 
+        self.ensure_password_allowed(&user, new_password).await?;
+
         // Hash new password
         let new_password_hash = hash(new_password, DEFAULT_COST)?;
 
         // Update password
         let mut user_active: user::ActiveModel = user.clone().into();
-        user_active.password_hash = Set(new_password_hash);
+        user_active.password_hash = Set(new_password_hash.clone());
+        user_active.password_changed_at = Set(Utc::now().into());
         user_active.updated_at = Set(Utc::now().into());
 
         // Revoke all refresh tokens for security
         user_active.refresh_token = Set(None);
         user_active.refresh_token_expires_at = Set(None);
 
-        let updated_user = user_active.update(&self.db).await?;
+        let tx = self.db.begin().await?;
+        let updated_user = user_active.update(&tx).await?;
+        Self::record_password_history(&tx, updated_user.id, &new_password_hash).await?;
+        tx.commit().await?;
         Ok(updated_user)
     }
 
+    /// Apply the password policy to a new password for an existing user,
+    /// including the reuse check against recent password history
+    async fn ensure_password_allowed(
+        &self,
+        user: &user::Model,
+        new_password: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut user_inputs = vec![user.email.as_str()];
+        user_inputs.extend(user.first_name.as_deref());
+        user_inputs.extend(user.last_name.as_deref());
+        self.password_policy.validate(new_password, &user_inputs).await?;
+
+        let history_size = self.password_policy.config().history_size;
+        if history_size == 0 {
+            return Ok(());
+        }
+
+        let recent_hashes: Vec<String> = PasswordHistory::find()
+            .select_only()
+            .column(password_history::Column::PasswordHash)
+            .filter(password_history::Column::UserId.eq(user.id))
+            .order_by_desc(password_history::Column::CreatedAt)
+            .limit(history_size)
+            .into_tuple()
+            .all(&self.db)
+            .await?;
+
+        let mut previous_hashes = recent_hashes;
+        // The current hash counts even if history predates this table
+        previous_hashes.push(user.password_hash.clone());
+
+        for previous_hash in &previous_hashes {
+            if verify(new_password, previous_hash).unwrap_or(false) {
+                return Err(Box::new(PasswordPolicyError::reused(history_size)));
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn record_password_history<C: ConnectionTrait>(
+        conn: &C,
+        user_id: Uuid,
+        password_hash: &str,
+    ) -> Result<(), sea_orm::DbErr> {
+        let entry = password_history::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            user_id: Set(user_id),
+            password_hash: Set(password_hash.to_string()),
+            created_at: Set(Utc::now().into()),
+        };
+        entry.insert(conn).await?;
+        Ok(())
+    }
+
     pub async fn admin_reset_user_password(
         &self,
         user_id: Uuid,
@@ -375,9 +1282,14 @@ impl UserService {
 
         let mut all_permissions = Vec::new();
 
-        // Get permissions through role
+        let now = Utc::now();
+
+        // Get permissions through role, unless the assignment is outside its validity window
         if let Ok(Some(user)) = User::find_by_id(user_id).one(&self.db).await {
-            if let Some(role_id) = user.role_id {
+            let role_id = user
+                .role_id
+                .filter(|_| crate::auth::is_within_window(user.role_valid_from, user.role_valid_until, now));
+            if let Some(role_id) = role_id {
                 let role_perms = role_permission::Entity::find()
                     .filter(role_permission::Column::RoleId.eq(role_id))
                     .join(JoinType::InnerJoin, role_permission::Relation::Permission.def())
@@ -393,9 +1305,19 @@ impl UserService {
                 }
             }
 
-            // Get direct user permissions
+            // Get direct user permissions that are currently in effect
             let user_perms = user_permission::Entity::find()
                 .filter(user_permission::Column::UserId.eq(user_id))
+                .filter(
+                    Condition::any()
+                        .add(user_permission::Column::ValidFrom.is_null())
+                        .add(user_permission::Column::ValidFrom.lte(now)),
+                )
+                .filter(
+                    Condition::any()
+                        .add(user_permission::Column::ValidUntil.is_null())
+                        .add(user_permission::Column::ValidUntil.gt(now)),
+                )
                 .join(JoinType::InnerJoin, user_permission::Relation::Permission.def())
                 .join(JoinType::InnerJoin, permission::Relation::Resource.def())
                 .column_as(permission::Column::Action, "action")
@@ -0,0 +1,825 @@
+use std::collections::BTreeMap;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Instant;
+
+use chrono::{Duration, Utc};
+use hmac::{Hmac, Mac};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, JoinType,
+    PaginatorTrait, QueryFilter, QueryOrder, QuerySelect, RelationTrait, Set,
+};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::entities::{
+    activity, activity_comment, email_context, prelude::*, webhook_delivery, webhook_delivery_attempt,
+    webhook_subscription,
+};
+use crate::graphql::types::{
+    CreateWebhookSubscriptionInput, UpdateWebhookSubscriptionInput, WebhookDeliveryConnection, WebhookDeliveryStatus,
+    WebhookEventType,
+};
+use crate::services::activity::EntityType;
+use crate::services::queue::{claim_due_ids, retry_backoff};
+use crate::services::task::TASK_CREATION_ACTIONS;
+
+/// Response bytes kept in the delivery log
+const MAX_LOGGED_RESPONSE_BYTES: usize = 4096;
+
+/// Task actions that change a task without creating, completing or deleting it
+const TASK_UPDATE_ACTIONS: &[&str] = &[
+    "updated",
+    "status_changed",
+    "assignment_changed",
+    "team_assignment_changed",
+    "context_updated",
+];
+
+type HmacSha256 = Hmac<Sha256>;
+
+impl WebhookEventType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEventType::TaskCreated => "task.created",
+            WebhookEventType::TaskUpdated => "task.updated",
+            WebhookEventType::TaskCompleted => "task.completed",
+            WebhookEventType::TaskDeleted => "task.deleted",
+            WebhookEventType::CommentCreated => "comment.created",
+            WebhookEventType::EmailContextIngested => "email_context.ingested",
+        }
+    }
+
+    /// The event an activity log entry raises, if any
+    fn for_activity(activity: &activity::Model) -> Option<Self> {
+        let action = activity.action_type.as_str();
+        match EntityType::from_str(&activity.entity_type)? {
+            EntityType::Task | EntityType::Project if action == "commented" => Some(WebhookEventType::CommentCreated),
+            EntityType::Task if TASK_CREATION_ACTIONS.contains(&action) => Some(WebhookEventType::TaskCreated),
+            EntityType::Task if TASK_UPDATE_ACTIONS.contains(&action) => Some(WebhookEventType::TaskUpdated),
+            EntityType::Task if action == "completed" => Some(WebhookEventType::TaskCompleted),
+            EntityType::Task if action == "deleted" => Some(WebhookEventType::TaskDeleted),
+            _ => None,
+        }
+    }
+}
+
+impl std::str::FromStr for WebhookEventType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "task.created" => Ok(WebhookEventType::TaskCreated),
+            "task.updated" => Ok(WebhookEventType::TaskUpdated),
+            "task.completed" => Ok(WebhookEventType::TaskCompleted),
+            "task.deleted" => Ok(WebhookEventType::TaskDeleted),
+            "comment.created" => Ok(WebhookEventType::CommentCreated),
+            "email_context.ingested" => Ok(WebhookEventType::EmailContextIngested),
+            _ => Err(format!("Unknown webhook event type '{}'", s)),
+        }
+    }
+}
+
+/// Something that happened in an organization, about to be sent to every subscription
+/// that wants it
+pub struct WebhookEvent {
+    pub event_type: WebhookEventType,
+    pub organization_id: Uuid,
+    pub project_id: Option<Uuid>,
+    pub data: Value,
+}
+
+/// Queue an event for every active subscription that wants it. Pass the transaction of
+/// the change that raised the event so both are committed, or rolled back, together.
+/// Returns the number of deliveries queued.
+pub async fn queue_webhook_event<C: ConnectionTrait>(conn: &C, event: WebhookEvent) -> Result<usize, DbErr> {
+    let mut scope = Condition::any().add(webhook_subscription::Column::ProjectId.is_null());
+    if let Some(project_id) = event.project_id {
+        scope = scope.add(webhook_subscription::Column::ProjectId.eq(project_id));
+    }
+
+    let event_name = event.event_type.as_str();
+    let subscriptions: Vec<webhook_subscription::Model> = WebhookSubscription::find()
+        .filter(webhook_subscription::Column::OrganizationId.eq(event.organization_id))
+        .filter(webhook_subscription::Column::IsActive.eq(true))
+        .filter(scope)
+        .all(conn)
+        .await?
+        .into_iter()
+        .filter(|subscription| {
+            subscription.event_types.is_empty() || subscription.event_types.iter().any(|t| t == event_name)
+        })
+        .collect();
+    if subscriptions.is_empty() {
+        return Ok(0);
+    }
+
+    let event_id = Uuid::new_v4();
+    let now = Utc::now();
+    let payload = json!({
+        "id": event_id,
+        "type": event_name,
+        "created_at": now,
+        "organization_id": event.organization_id,
+        "project_id": event.project_id,
+        "data": event.data,
+    });
+
+    let deliveries = subscriptions.iter().map(|subscription| webhook_delivery::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        subscription_id: Set(subscription.id),
+        event_id: Set(event_id),
+        event_type: Set(event_name.to_string()),
+        payload: Set(payload.clone()),
+        status: Set(WebhookDeliveryStatus::Pending),
+        attempts: Set(0),
+        next_attempt_at: Set(now.into()),
+        response_status: Set(None),
+        last_error: Set(None),
+        delivered_at: Set(None),
+        replay_of: Set(None),
+        created_at: Set(now.into()),
+        updated_at: Set(now.into()),
+    });
+    WebhookDelivery::insert_many(deliveries).exec_without_returning(conn).await?;
+
+    Ok(subscriptions.len())
+}
+
+/// Queue the webhook event an activity log entry raises, if any. Task events carry the
+/// task as it is after the change, so deletions must be logged before the task goes;
+/// comment events carry the comment, so it must be stored before the activity is
+/// published.
+pub async fn queue_webhook_event_for_activity<C: ConnectionTrait>(
+    conn: &C,
+    activity: &activity::Model,
+) -> Result<usize, DbErr> {
+    let Some(event_type) = WebhookEventType::for_activity(activity) else {
+        return Ok(0);
+    };
+
+    let (project_id, task) = match EntityType::from_str(&activity.entity_type) {
+        Some(EntityType::Task) => match Task::find_by_id(activity.entity_id).one(conn).await? {
+            Some(task) => (task.project_id, Some(task)),
+            None => return Ok(0),
+        },
+        _ => (activity.entity_id, None),
+    };
+    let Some(project) = Project::find_by_id(project_id).one(conn).await? else {
+        return Ok(0);
+    };
+
+    let data = if event_type == WebhookEventType::CommentCreated {
+        let Some(comment) = ActivityComment::find()
+            .filter(activity_comment::Column::ActivityId.eq(activity.id))
+            .one(conn)
+            .await?
+        else {
+            return Ok(0);
+        };
+        json!({
+            "comment": {
+                "id": comment.id,
+                "entity_type": activity.entity_type,
+                "entity_id": activity.entity_id,
+                "parent_comment_id": comment.parent_comment_id,
+                "author_id": comment.author_id,
+                "content": comment.content,
+                "content_html": comment.content_html,
+                "mentions": comment.mentions,
+                "created_at": comment.created_at,
+            },
+            "task": task,
+        })
+    } else {
+        json!({
+            "task": task,
+            "activity": {
+                "id": activity.id,
+                "action": activity.action_type,
+                "actor_id": activity.actor_id,
+                "description": activity.description,
+                "changes": activity.changes,
+                "created_at": activity.created_at,
+            },
+        })
+    };
+
+    queue_webhook_event(
+        conn,
+        WebhookEvent {
+            event_type,
+            organization_id: project.organization_id,
+            project_id: Some(project_id),
+            data,
+        },
+    )
+    .await
+}
+
+/// Queue the event for a freshly ingested email
+pub async fn queue_email_ingested_event<C: ConnectionTrait>(
+    conn: &C,
+    organization_id: Uuid,
+    project_id: Uuid,
+    email: &email_context::Model,
+) -> Result<usize, DbErr> {
+    queue_webhook_event(
+        conn,
+        WebhookEvent {
+            event_type: WebhookEventType::EmailContextIngested,
+            organization_id,
+            project_id: Some(project_id),
+            data: json!({
+                "email_context": {
+                    "id": email.id,
+                    "from_email": email.from_email,
+                    "from_name": email.from_name,
+                    "to_emails": email.to_emails,
+                    "cc_emails": email.cc_emails,
+                    "subject": email.subject,
+                    "message_preview": email.message_preview,
+                    "accounting_process": email.accounting_process.as_str(),
+                    "ai_summary": email.ai_summary,
+                    "message_id": email.message_id,
+                    "thread_id": email.thread_id,
+                    "message_date": email.message_date,
+                    "received_date": email.received_date,
+                    "has_attachments": email.has_attachments,
+                    "attachment_count": email.attachment_count,
+                },
+            }),
+        },
+    )
+    .await
+}
+
+/// Signature of a request body: hex HMAC-SHA256 over `<timestamp>.<body>`
+pub fn webhook_signature(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct WebhookConfig {
+    /// Attempts before a delivery is dead-lettered
+    pub max_attempts: i32,
+    /// Delay before the first retry; doubles with each further attempt
+    pub base_backoff_seconds: i64,
+    pub max_backoff_seconds: i64,
+    /// Deliveries claimed per dispatch run
+    pub batch_size: u64,
+    /// How long an endpoint gets to respond
+    pub timeout_seconds: u64,
+    /// Let endpoints resolve to loopback, private or link-local addresses; for local development
+    pub allow_private_networks: bool,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: 8,
+            base_backoff_seconds: 30,
+            max_backoff_seconds: 6 * 60 * 60,
+            batch_size: 50,
+            timeout_seconds: 10,
+            allow_private_networks: false,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct WebhookDispatchSummary {
+    pub delivered: usize,
+    pub retried: usize,
+    pub dead: usize,
+}
+
+/// What came back from one request
+struct AttemptOutcome {
+    response_status: Option<i32>,
+    error: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct WebhookService {
+    db: DatabaseConnection,
+    config: WebhookConfig,
+}
+
+impl WebhookService {
+    pub fn new(db: DatabaseConnection, config: WebhookConfig) -> Self {
+        Self { db, config }
+    }
+
+    /// Subscriptions of an organization, organization-wide ones first
+    pub async fn list_subscriptions(
+        &self,
+        organization_id: Uuid,
+    ) -> Result<Vec<webhook_subscription::Model>, Box<dyn std::error::Error>> {
+        let subscriptions = WebhookSubscription::find()
+            .filter(webhook_subscription::Column::OrganizationId.eq(organization_id))
+            .order_by_asc(webhook_subscription::Column::ProjectId)
+            .order_by_asc(webhook_subscription::Column::Name)
+            .all(&self.db)
+            .await?;
+        Ok(subscriptions)
+    }
+
+    /// Create a subscription with a freshly generated signing secret
+    pub async fn create_subscription(
+        &self,
+        organization_id: Uuid,
+        created_by: Uuid,
+        input: CreateWebhookSubscriptionInput,
+    ) -> Result<webhook_subscription::Model, Box<dyn std::error::Error>> {
+        let name = input.name.trim().to_string();
+        if name.is_empty() {
+            return Err("Name is required".into());
+        }
+        let url = self.validate_url(&input.url).await?;
+
+        if let Some(project_id) = input.project_id {
+            let project = Project::find_by_id(project_id).one(&self.db).await?.ok_or("Project not found")?;
+            if project.organization_id != organization_id {
+                return Err("Project not found".into());
+            }
+        }
+
+        let subscription = webhook_subscription::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            organization_id: Set(organization_id),
+            project_id: Set(input.project_id),
+            name: Set(name),
+            url: Set(url),
+            secret: Set(Self::generate_secret()),
+            event_types: Set(Self::event_names(input.event_types.unwrap_or_default())),
+            is_active: Set(true),
+            created_by: Set(Some(created_by)),
+            created_at: Set(Utc::now().into()),
+            updated_at: Set(Utc::now().into()),
+        }
+        .insert(&self.db)
+        .await?;
+        Ok(subscription)
+    }
+
+    pub async fn update_subscription(
+        &self,
+        organization_id: Uuid,
+        input: UpdateWebhookSubscriptionInput,
+    ) -> Result<webhook_subscription::Model, Box<dyn std::error::Error>> {
+        let subscription = self.get_subscription(organization_id, input.subscription_id).await?;
+
+        let mut subscription_active: webhook_subscription::ActiveModel = subscription.into();
+        if let Some(name) = input.name {
+            let name = name.trim().to_string();
+            if name.is_empty() {
+                return Err("Name is required".into());
+            }
+            subscription_active.name = Set(name);
+        }
+        if let Some(url) = input.url {
+            subscription_active.url = Set(self.validate_url(&url).await?);
+        }
+        if let Some(event_types) = input.event_types {
+            subscription_active.event_types = Set(Self::event_names(event_types));
+        }
+        if let Some(is_active) = input.is_active {
+            subscription_active.is_active = Set(is_active);
+        }
+        subscription_active.updated_at = Set(Utc::now().into());
+        Ok(subscription_active.update(&self.db).await?)
+    }
+
+    /// Replace the signing secret; the old one stops working immediately
+    pub async fn rotate_secret(
+        &self,
+        organization_id: Uuid,
+        subscription_id: Uuid,
+    ) -> Result<webhook_subscription::Model, Box<dyn std::error::Error>> {
+        let subscription = self.get_subscription(organization_id, subscription_id).await?;
+
+        let mut subscription_active: webhook_subscription::ActiveModel = subscription.into();
+        subscription_active.secret = Set(Self::generate_secret());
+        subscription_active.updated_at = Set(Utc::now().into());
+        Ok(subscription_active.update(&self.db).await?)
+    }
+
+    /// Delete a subscription along with its delivery log
+    pub async fn delete_subscription(
+        &self,
+        organization_id: Uuid,
+        subscription_id: Uuid,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let subscription = self.get_subscription(organization_id, subscription_id).await?;
+        WebhookSubscription::delete_by_id(subscription.id).exec(&self.db).await?;
+        Ok(())
+    }
+
+    /// Deliveries of an organization's subscriptions, newest first
+    pub async fn list_deliveries(
+        &self,
+        organization_id: Uuid,
+        subscription_id: Option<Uuid>,
+        status: Option<WebhookDeliveryStatus>,
+        limit: Option<u64>,
+        offset: Option<u64>,
+    ) -> Result<WebhookDeliveryConnection, Box<dyn std::error::Error>> {
+        let mut query = WebhookDelivery::find()
+            .join(JoinType::InnerJoin, webhook_delivery::Relation::WebhookSubscription.def())
+            .filter(webhook_subscription::Column::OrganizationId.eq(organization_id));
+        if let Some(subscription_id) = subscription_id {
+            query = query.filter(webhook_delivery::Column::SubscriptionId.eq(subscription_id));
+        }
+        if let Some(status) = status {
+            query = query.filter(webhook_delivery::Column::Status.eq(status));
+        }
+
+        let total_count = query.clone().count(&self.db).await?;
+        let deliveries = query
+            .order_by_desc(webhook_delivery::Column::CreatedAt)
+            .limit(limit.unwrap_or(50))
+            .offset(offset.unwrap_or(0))
+            .all(&self.db)
+            .await?;
+
+        Ok(WebhookDeliveryConnection {
+            edges: deliveries.into_iter().map(|delivery| delivery.into()).collect(),
+            total_count: total_count as u32,
+        })
+    }
+
+    pub async fn get_delivery(
+        &self,
+        organization_id: Uuid,
+        delivery_id: Uuid,
+    ) -> Result<Option<webhook_delivery::Model>, Box<dyn std::error::Error>> {
+        let delivery = WebhookDelivery::find_by_id(delivery_id)
+            .join(JoinType::InnerJoin, webhook_delivery::Relation::WebhookSubscription.def())
+            .filter(webhook_subscription::Column::OrganizationId.eq(organization_id))
+            .one(&self.db)
+            .await?;
+        Ok(delivery)
+    }
+
+    /// Logged attempts of a delivery, oldest first
+    pub async fn delivery_attempts(
+        &self,
+        delivery_id: Uuid,
+    ) -> Result<Vec<webhook_delivery_attempt::Model>, Box<dyn std::error::Error>> {
+        let attempts = WebhookDeliveryAttempt::find()
+            .filter(webhook_delivery_attempt::Column::DeliveryId.eq(delivery_id))
+            .order_by_asc(webhook_delivery_attempt::Column::Attempt)
+            .all(&self.db)
+            .await?;
+        Ok(attempts)
+    }
+
+    /// Send an event again as a new delivery with its own attempts and log. The event id
+    /// stays the same so receivers can tell it is the same event.
+    pub async fn replay_delivery(
+        &self,
+        organization_id: Uuid,
+        delivery_id: Uuid,
+    ) -> Result<webhook_delivery::Model, Box<dyn std::error::Error>> {
+        let delivery = self
+            .get_delivery(organization_id, delivery_id)
+            .await?
+            .ok_or("Webhook delivery not found")?;
+        let subscription = self.get_subscription(organization_id, delivery.subscription_id).await?;
+        if !subscription.is_active {
+            return Err("Webhook subscription is disabled".into());
+        }
+
+        let replay = webhook_delivery::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            subscription_id: Set(delivery.subscription_id),
+            event_id: Set(delivery.event_id),
+            event_type: Set(delivery.event_type),
+            payload: Set(delivery.payload),
+            status: Set(WebhookDeliveryStatus::Pending),
+            attempts: Set(0),
+            next_attempt_at: Set(Utc::now().into()),
+            response_status: Set(None),
+            last_error: Set(None),
+            delivered_at: Set(None),
+            replay_of: Set(Some(delivery.id)),
+            created_at: Set(Utc::now().into()),
+            updated_at: Set(Utc::now().into()),
+        }
+        .insert(&self.db)
+        .await?;
+        Ok(replay)
+    }
+
+    /// Send the deliveries that are due. Safe to run from several replicas at once:
+    /// each delivery is claimed by exactly one of them.
+    pub async fn dispatch_due(&self) -> Result<WebhookDispatchSummary, Box<dyn std::error::Error>> {
+        let mut summary = WebhookDispatchSummary::default();
+
+        for (delivery, subscription) in self.claim_due().await? {
+            // Deliveries of a disabled or deleted subscription are dead-lettered right away
+            let outcome = match subscription.as_ref().filter(|subscription| subscription.is_active) {
+                Some(subscription) => self.send(&delivery, subscription).await?,
+                None => AttemptOutcome {
+                    response_status: None,
+                    error: Some("Webhook subscription is disabled".to_string()),
+                },
+            };
+
+            let give_up = delivery.attempts >= self.config.max_attempts
+                || !subscription.is_some_and(|subscription| subscription.is_active);
+            let attempts = delivery.attempts;
+            let mut delivery_active: webhook_delivery::ActiveModel = delivery.into();
+            delivery_active.response_status = Set(outcome.response_status);
+            match outcome.error {
+                None => {
+                    delivery_active.status = Set(WebhookDeliveryStatus::Delivered);
+                    delivery_active.delivered_at = Set(Some(Utc::now().into()));
+                    delivery_active.last_error = Set(None);
+                    summary.delivered += 1;
+                }
+                Some(error) if give_up => {
+                    delivery_active.status = Set(WebhookDeliveryStatus::Dead);
+                    delivery_active.last_error = Set(Some(error));
+                    summary.dead += 1;
+                }
+                Some(error) => {
+                    delivery_active.next_attempt_at = Set((Utc::now() + self.backoff(attempts)).into());
+                    delivery_active.last_error = Set(Some(error));
+                    summary.retried += 1;
+                }
+            }
+            delivery_active.updated_at = Set(Utc::now().into());
+            delivery_active.update(&self.db).await?;
+        }
+
+        Ok(summary)
+    }
+
+    /// Make one signed request for a delivery and log it
+    async fn send(
+        &self,
+        delivery: &webhook_delivery::Model,
+        subscription: &webhook_subscription::Model,
+    ) -> Result<AttemptOutcome, DbErr> {
+        let body = delivery.payload.to_string();
+        let timestamp = Utc::now().timestamp();
+        let headers = BTreeMap::from([
+            ("Content-Type", "application/json".to_string()),
+            ("X-Webhook-Id", delivery.event_id.to_string()),
+            ("X-Webhook-Delivery", delivery.id.to_string()),
+            ("X-Webhook-Event", delivery.event_type.clone()),
+            ("X-Webhook-Timestamp", timestamp.to_string()),
+            (
+                "X-Webhook-Signature",
+                format!("sha256={}", webhook_signature(&subscription.secret, timestamp, &body)),
+            ),
+        ]);
+
+        let started = Instant::now();
+        // The endpoint is checked again here: its DNS may have changed since it was saved
+        let (response_status, response_body, request_error) = match self.endpoint_client(&subscription.url).await {
+            Ok(client) => {
+                let mut request = client.post(&subscription.url).body(body.clone());
+                for (name, value) in &headers {
+                    request = request.header(*name, value);
+                }
+                match request.send().await {
+                    Ok(response) => {
+                        let status = response.status();
+                        let text = response.text().await.unwrap_or_default();
+                        (Some(status), Some(Self::truncate(text)), None)
+                    }
+                    Err(e) => (None, None, Some(e.to_string())),
+                }
+            }
+            Err(e) => (None, None, Some(e)),
+        };
+        let latency_ms = started.elapsed().as_millis().min(i32::MAX as u128) as i32;
+
+        webhook_delivery_attempt::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            delivery_id: Set(delivery.id),
+            attempt: Set(delivery.attempts),
+            request_url: Set(subscription.url.clone()),
+            request_headers: Set(json!(headers)),
+            request_body: Set(body),
+            response_status: Set(response_status.map(|status| status.as_u16() as i32)),
+            response_body: Set(response_body),
+            error: Set(request_error.clone()),
+            latency_ms: Set(latency_ms),
+            created_at: Set(Utc::now().into()),
+        }
+        .insert(&self.db)
+        .await?;
+
+        let error = match response_status {
+            Some(status) if status.is_success() => None,
+            Some(status) => Some(format!("Endpoint responded with {}", status)),
+            None => request_error,
+        };
+        Ok(AttemptOutcome {
+            response_status: response_status.map(|status| status.as_u16() as i32),
+            error,
+        })
+    }
+
+    async fn get_subscription(
+        &self,
+        organization_id: Uuid,
+        subscription_id: Uuid,
+    ) -> Result<webhook_subscription::Model, Box<dyn std::error::Error>> {
+        let subscription = WebhookSubscription::find_by_id(subscription_id)
+            .filter(webhook_subscription::Column::OrganizationId.eq(organization_id))
+            .one(&self.db)
+            .await?
+            .ok_or("Webhook subscription not found")?;
+        Ok(subscription)
+    }
+
+    /// Claim a batch of due deliveries, counting the attempt and leasing them so other
+    /// dispatchers skip them
+    async fn claim_due(
+        &self,
+    ) -> Result<Vec<(webhook_delivery::Model, Option<webhook_subscription::Model>)>, DbErr> {
+        let ids = claim_due_ids::<WebhookDelivery, _>(&self.db, self.config.batch_size).await?;
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        WebhookDelivery::find()
+            .filter(webhook_delivery::Column::Id.is_in(ids))
+            .find_also_related(WebhookSubscription)
+            .order_by_asc(webhook_delivery::Column::NextAttemptAt)
+            .all(&self.db)
+            .await
+    }
+
+    fn backoff(&self, attempts: i32) -> Duration {
+        retry_backoff(attempts, self.config.base_backoff_seconds, self.config.max_backoff_seconds)
+    }
+
+    async fn validate_url(&self, url: &str) -> Result<String, Box<dyn std::error::Error>> {
+        let url = url.trim();
+        let parsed = reqwest::Url::parse(url).map_err(|_| "URL is not valid")?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err("URL must use http or https".into());
+        }
+        self.resolve_endpoint(&parsed).await?;
+        Ok(url.to_string())
+    }
+
+    /// Addresses an endpoint resolves to, refusing it if any of them isn't public, so
+    /// webhooks can't be aimed at the server's own network or cloud metadata
+    async fn resolve_endpoint(&self, url: &reqwest::Url) -> Result<Vec<SocketAddr>, String> {
+        let host = url.host_str().ok_or("URL must have a host")?;
+        let port = url.port_or_known_default().ok_or("URL must have a port")?;
+        let addrs: Vec<SocketAddr> = match url.domain() {
+            Some(domain) => tokio::net::lookup_host((domain, port))
+                .await
+                .map_err(|_| format!("Could not resolve {}", host))?
+                .collect(),
+            // IPv6 literals keep their brackets in the host string
+            None => {
+                let ip: IpAddr = host
+                    .trim_start_matches('[')
+                    .trim_end_matches(']')
+                    .parse()
+                    .map_err(|_| "URL is not valid")?;
+                vec![SocketAddr::new(ip, port)]
+            }
+        };
+
+        if addrs.is_empty() {
+            return Err(format!("Could not resolve {}", host));
+        }
+        if !self.config.allow_private_networks
+            && let Some(addr) = addrs.iter().find(|addr| !is_public_address(addr.ip()))
+        {
+            return Err(format!("URL resolves to a non-public address ({})", addr.ip()));
+        }
+        Ok(addrs)
+    }
+
+    /// Client pinned to the endpoint's checked addresses, so a DNS change between the
+    /// check and the request can't send it elsewhere. Redirects are not followed.
+    async fn endpoint_client(&self, url: &str) -> Result<reqwest::Client, String> {
+        let parsed = reqwest::Url::parse(url).map_err(|_| "URL is not valid".to_string())?;
+        let addrs = self.resolve_endpoint(&parsed).await?;
+
+        let mut builder = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(self.config.timeout_seconds))
+            .redirect(reqwest::redirect::Policy::none());
+        if let Some(domain) = parsed.domain() {
+            builder = builder.resolve_to_addrs(domain, &addrs);
+        }
+        builder.build().map_err(|e| e.to_string())
+    }
+
+    fn generate_secret() -> String {
+        format!("whsec_{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+    }
+
+    fn event_names(event_types: Vec<WebhookEventType>) -> Vec<String> {
+        let mut names: Vec<String> = event_types.iter().map(|event_type| event_type.as_str().to_string()).collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    fn truncate(mut text: String) -> String {
+        if text.len() > MAX_LOGGED_RESPONSE_BYTES {
+            let mut end = MAX_LOGGED_RESPONSE_BYTES;
+            while !text.is_char_boundary(end) {
+                end -= 1;
+            }
+            text.truncate(end);
+        }
+        text
+    }
+}
+
+/// Whether an address is reachable on the public internet. Loopback, private,
+/// carrier-grade NAT, link-local (cloud metadata lives there), multicast, documentation
+/// and unspecified addresses are not.
+fn is_public_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, c, _] = ip.octets();
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_multicast()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_unspecified()
+                || a == 0
+                || (a == 100 && (64..128).contains(&b))
+                || (a == 192 && b == 0 && c == 0)
+                || (a == 198 && (18..20).contains(&b))
+                || a >= 240)
+        }
+        IpAddr::V6(ip) => {
+            if let Some(ip) = ip.to_ipv4_mapped() {
+                return is_public_address(IpAddr::V4(ip));
+            }
+            let first = ip.segments()[0];
+            !(ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                || (first & 0xfe00) == 0xfc00
+                || (first & 0xffc0) == 0xfe80
+                || (first == 0x2001 && ip.segments()[1] == 0x0db8))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn public(ip: &str) -> bool {
+        is_public_address(ip.parse().unwrap())
+    }
+
+    #[test]
+    fn rejects_internal_addresses() {
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "::",
+            "fd00:ec2::254",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+            "::ffff:169.254.169.254",
+        ] {
+            assert!(!public(ip), "{} should not be public", ip);
+        }
+    }
+
+    #[test]
+    fn accepts_public_addresses() {
+        for ip in ["93.184.216.34", "8.8.8.8", "2606:4700:4700::1111"] {
+            assert!(public(ip), "{} should be public", ip);
+        }
+    }
+
+    #[test]
+    fn signature_covers_timestamp_and_body() {
+        let signature = webhook_signature("secret", 1700000000, "{}");
+        assert_eq!(signature.len(), 64);
+        assert_ne!(signature, webhook_signature("secret", 1700000001, "{}"));
+        assert_ne!(signature, webhook_signature("secret", 1700000000, "{ }"));
+    }
+}